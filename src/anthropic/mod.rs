@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use reqwest::Client;
 use crate::workflow::models::{WorkflowEvent, RetryConfig};
 use crate::workflow::errors::{Result, SwissPipeError};
+use crate::utils::retry::{self, AttemptError};
 use std::time::Duration;
 use handlebars::{Handlebars, Helper, HelperResult, Output, RenderContext, RenderError};
 
@@ -100,88 +101,54 @@ impl AnthropicService {
             }],
         };
 
-        let mut attempts = 0;
-        let mut delay = Duration::from_millis(config.retry_config.initial_delay_ms);
-
-        loop {
-            attempts += 1;
-
+        let request = &request;
+        let api_key = api_key.as_str();
+        let anthropic_response = retry::run(config.retry_config, "Anthropic API call", || async move {
             let response = self
                 .client
                 .post("https://api.anthropic.com/v1/messages")
-                .header("x-api-key", &api_key)
+                .header("x-api-key", api_key)
                 .header("anthropic-version", "2023-06-01")
                 .header("content-type", "application/json")
                 .timeout(Duration::from_secs(config.timeout_seconds))
-                .json(&request)
+                .json(request)
                 .send()
-                .await;
-
-            match response {
-                Ok(resp) if resp.status().is_success() => {
-                    match resp.json::<AnthropicResponse>().await {
-                        Ok(anthropic_response) => {
-                            tracing::info!("Anthropic API call successful. Input tokens: {}, Output tokens: {}",
-                                anthropic_response.usage.input_tokens, anthropic_response.usage.output_tokens);
-
-                            let assistant_response = anthropic_response.content
-                                .first()
-                                .map(|c| c.text.as_str())
-                                .unwrap_or("")
-                                .to_string();
-
-                            let mut result_event = event.clone();
-                            result_event.data = serde_json::json!({
-                                "original_data": event.data,
-                                "anthropic_response": assistant_response,
-                                "usage": {
-                                    "input_tokens": anthropic_response.usage.input_tokens,
-                                    "output_tokens": anthropic_response.usage.output_tokens
-                                }
-                            });
-
-                            return Ok(result_event);
-                        }
-                        Err(e) => {
-                            tracing::error!("Failed to parse Anthropic response: {}", e);
-                            if attempts >= config.retry_config.max_attempts {
-                                return Err(SwissPipeError::Generic(
-                                    format!("Anthropic API parse error after {attempts} attempts: {e}")
-                                ));
-                            }
-                        }
-                    }
-                }
-                Ok(resp) => {
-                    let status = resp.status();
-                    let error_text = resp.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-                    tracing::error!("Anthropic API error {}: {}", status, error_text);
-
-                    if attempts >= config.retry_config.max_attempts {
-                        return Err(SwissPipeError::Generic(
-                            format!("Anthropic API error {status} after {attempts} attempts: {error_text}")
-                        ));
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Anthropic API request failed: {}", e);
-                    if attempts >= config.retry_config.max_attempts {
-                        return Err(SwissPipeError::Generic(
-                            format!("Anthropic API request failed after {attempts} attempts: {e}")
-                        ));
-                    }
-                }
+                .await
+                .map_err(|e| AttemptError::<String>::transport(&e, format!("Anthropic API request failed: {e}")))?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let headers = response.headers().clone();
+                let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                return Err(AttemptError::status(status.as_u16(), &headers, format!("Anthropic API error {status}: {error_text}")));
             }
 
-            if attempts < config.retry_config.max_attempts {
-                tracing::warn!("Anthropic API call failed, retrying in {:?} (attempt {} of {})",
-                    delay, attempts, config.retry_config.max_attempts);
-                tokio::time::sleep(delay).await;
-
-                let new_delay_ms = (delay.as_millis() as f64 * config.retry_config.backoff_multiplier) as u64;
-                delay = Duration::from_millis(new_delay_ms.min(config.retry_config.max_delay_ms));
+            response.json::<AnthropicResponse>().await
+                .map_err(|e| AttemptError::other(format!("Anthropic API parse error: {e}")))
+        })
+        .await
+        .map_err(|e| SwissPipeError::Generic(format!("{} (after {} attempts)", e.error, e.attempts)))?;
+
+        tracing::info!("Anthropic API call successful. Input tokens: {}, Output tokens: {}",
+            anthropic_response.usage.input_tokens, anthropic_response.usage.output_tokens);
+
+        let assistant_response = anthropic_response.content
+            .first()
+            .map(|c| c.text.as_str())
+            .unwrap_or("")
+            .to_string();
+
+        let mut result_event = event.clone();
+        result_event.data = serde_json::json!({
+            "original_data": event.data,
+            "anthropic_response": assistant_response,
+            "usage": {
+                "input_tokens": anthropic_response.usage.input_tokens,
+                "output_tokens": anthropic_response.usage.output_tokens
             }
-        }
+        });
+
+        Ok(result_event)
     }
 
     fn render_template(&self, template: &str, event: &WorkflowEvent) -> Result<String> {
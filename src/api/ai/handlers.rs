@@ -2,9 +2,12 @@ use axum::{extract::State, http::StatusCode, response::Json};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
-use crate::workflow::models::{WorkflowEvent, NodeType};
+use crate::workflow::models::{Node, NodeType, WorkflowEvent};
 use crate::anthropic::AnthropicCallConfig;
-use crate::api::workflows::types::{CreateWorkflowRequest, NodeRequest, EdgeRequest};
+use crate::api::workflows::{
+    operations::validate_workflow_references,
+    types::{CreateWorkflowRequest, NodeRequest, EdgeRequest},
+};
 use crate::AppState;
 
 // Input validation constants
@@ -49,6 +52,8 @@ pub async fn generate_code(
         metadata: std::collections::HashMap::new(),
         headers: std::collections::HashMap::new(),
         condition_results: std::collections::HashMap::new(),
+        switch_results: std::collections::HashMap::new(),
+        failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -64,6 +69,7 @@ pub async fn generate_code(
         initial_delay_ms: 1000,
         max_delay_ms: 5000,
         backoff_multiplier: 2.0,
+        ..Default::default()
     };
 
     match state.engine.anthropic_service
@@ -189,6 +195,8 @@ async fn generate_workflow_with_ai(
         metadata: std::collections::HashMap::new(),
         headers: std::collections::HashMap::new(),
         condition_results: std::collections::HashMap::new(),
+        switch_results: std::collections::HashMap::new(),
+        failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -199,6 +207,7 @@ async fn generate_workflow_with_ai(
         initial_delay_ms: 1000,
         max_delay_ms: 5000,
         backoff_multiplier: 2.0,
+        ..Default::default()
     };
 
     // Call Anthropic AI service
@@ -293,6 +302,9 @@ Your task is to generate a complete workflow specification based on user require
   }
 }
 ```
+Webhooks from Stripe, GitHub or Slack can add "verification": {"Stripe": {"secret_variable": "STRIPE_WEBHOOK_SECRET"}} (or "GitHub"/"Slack" with the same field) to reject unsigned requests.
+Partner APIs can add "api_key": {"secret_variable": "PARTNER_API_KEY"} to require an X-API-Key header, and "ip_filter": {"allow": ["203.0.113.0/24"]} to only accept requests from those networks.
+High-volume triggers can add "rate_limit": {"requests_per_minute": 120, "daily_quota": 10000} to answer HTTP 429 once a client exceeds them.
 
 ### 2. Transformer (Data Processing)
 ```json
@@ -325,9 +337,20 @@ Your task is to generate a complete workflow specification based on user require
       "max_attempts": 3,
       "initial_delay_ms": 100,
       "max_delay_ms": 5000,
-      "backoff_multiplier": 2
+      "backoff_multiplier": 2,
+      "retry_on": ["Connect", "Timeout", "ServerError", "TooManyRequests"], // Optional: failure classes to retry (default all four); other 4xx fail at once
+      "retry_status_codes": ["409"], // Optional: further status codes to retry
+      "jitter": "None", // Optional: "None" (default), "Full" or "Decorrelated"
+      "max_retry_duration_ms": 60000 // Optional: give up once retrying would take longer, including Retry-After waits
     },
-    "loop_config": { // Optional: For repeating HTTP requests
+    "body": {"Json": "{\"email\": {{json event.data.email}}}"}, // Optional: {"Json": template}, {"Form": {"field": "template"}} or {"Raw": {"template": "...", "content_type": "text/plain"}}; not allowed with "Get"
+    "query_params": {"tenant": "{{ event.data.tenant }}"}, // Optional: templated query parameters; on "Get" they replace the event data fields
+    "response_mapping": {"Pointer": "/crm_response"}, // Optional: store the response at a JSON pointer in event.data, or {"Template": "..."} rendered with event and response
+    "response_format": "Body", // Optional: "Body" (default) or "Full" for {"status", "headers", "body", "body_encoding"}; non-JSON bodies become text or base64
+    "success_status_codes": ["2xx", "404"], // Optional: status codes that count as success (default any 2xx); others fail the node
+    "auth": {"Bearer": {"token_variable": "CRM_TOKEN"}}, // Optional: {"Basic": {"username_variable", "password_variable"}}, {"ApiKey": {"key_variable", "header": "X-API-Key", "query_param": null}}, {"OAuth2ClientCredentials": {"token_url", "client_id_variable", "client_secret_variable", "scopes": []}} or {"AwsSigV4": {"access_key_id_variable", "secret_access_key_variable", "region", "service"}}; *_variable fields name environment variables
+    "forward_headers": "None", // Optional: trigger request headers to pass on; "None" (default), {"Allow": ["X-Request-Id"]} or "All"; node headers win
+    "loop_config": { // Optional: For repeating HTTP requests; cannot be combined with body, query_params, response_mapping, response_format, success_status_codes, auth or forward_headers
       "max_iterations": 10,
       "interval_seconds": 60,
       "backoff_strategy": {"Fixed": 60}, // or {"Exponential": {"base": 30, "multiplier": 1.5, "max": 300}}
@@ -423,14 +446,68 @@ Your task is to generate a complete workflow specification based on user require
 }
 ```
 
+### 10. Switch (Multi-way Routing)
+```json
+{
+  "Switch": {
+    "script": "function route(event) { return event.data.type; }",
+    "cases": ["track", "identify", "page"]
+  }
+}
+```
+Edges from a Switch set "source_handle_id" to a case label, or to "default" for events that match no case.
+
+### 11. ForEach (Fan-out over an Array)
+```json
+{
+  "ForEach": {
+    "selector": "$.data.items",
+    "concurrency": 5
+  }
+}
+```
+The selector is a JSONPath starting with "$" or a JavaScript "function select(event)" returning an array. The nodes between a ForEach and its Aggregate run once per item, with the item as event.data.
+
+### 12. Aggregate (Collect ForEach Results)
+```json
+{
+  "Aggregate": {}
+}
+```
+Every ForEach needs exactly one Aggregate; the Aggregate outputs the per-item results as an array in item order.
+
+### 13. SubWorkflow (Call Another Workflow)
+```json
+{
+  "SubWorkflow": {
+    "workflow_id": "existing-workflow-uuid",
+    "wait": true,
+    "timeout_seconds": 60
+  }
+}
+```
+With "wait": true the child workflow's output replaces event.data; with false the child is queued and the event continues unchanged. Only reference workflow IDs the user provides.
+
+### 14. Respond (Answer the Trigger Caller)
+```json
+{
+  "Respond": {
+    "status_code": 200,
+    "headers": {"Content-Type": "text/plain"},
+    "body_template": "{{ event.data.challenge }}"
+  }
+}
+```
+Answers a trigger called with ?wait=true as soon as it runs, while the rest of the workflow continues. Use it for webhook handshakes and custom status codes; use {{{ }}} for unescaped values.
+
 ## Design Guidelines:
 1. Always start with a Trigger node as the entry point
 2. Use meaningful node names and workflow descriptions
 3. Position nodes logically (x: 100-800, y: 50-400)
 4. Space nodes ~170px apart horizontally, ~150px vertically
 5. Use Transformer nodes to modify data structure
-6. Use Condition nodes for branching logic with conditional edges
-7. Include error handling via failure_action settings
+6. Use Condition nodes for branching logic with conditional edges, and a Switch node instead of chained Conditions when routing on one field with several values
+7. Include error handling via failure_action settings, or an edge with "source_handle_id": "error" from the failing node to nodes that receive event.data.error (message, node_id, node_name, attempts, input)
 8. Use template variables like {{data.field}} in prompts and messages
 9. Generate realistic UUIDs for all IDs
 10. Keep workflows focused and logical
@@ -692,6 +769,10 @@ fn extract_workflow_components(parsed: serde_json::Value) -> Result<CreateWorkfl
         name,
         description,
         start_node_id,
+        allowed_host_functions: Vec::new(),
+        error_workflow_id: None,
+        max_concurrent_executions: None,
+        scheduling_weight: None,
         nodes,
         edges,
     })
@@ -804,6 +885,27 @@ async fn create_workflow_from_ai_spec(
     let workflow_id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().timestamp_micros();
 
+    let workflow_nodes: Vec<Node> = spec.nodes.iter().map(|node| Node {
+        id: node.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string()),
+        workflow_id: workflow_id.clone(),
+        name: node.name.clone(),
+        node_type: node.node_type.clone(),
+        input_merge_strategy: None,
+        allowed_host_functions: Vec::new(),
+    }).collect();
+
+    // Same reference checks as workflows saved through the workflows API
+    validate_workflow_references(
+        &state.db,
+        &workflow_id,
+        &workflow_nodes,
+        spec.error_workflow_id.as_deref(),
+        spec.max_concurrent_executions,
+        spec.scheduling_weight,
+    )
+    .await
+    .map_err(|e| format!("Workflow validation failed: {e}"))?;
+
     // Create workflow entity
     let workflow = entities::ActiveModel {
         id: Set(workflow_id.clone()),
@@ -811,6 +913,10 @@ async fn create_workflow_from_ai_spec(
         description: Set(spec.description.clone()),
         start_node_id: Set(spec.start_node_id.clone()),
         enabled: Set(true),
+        allowed_host_functions: Set(None),
+        error_workflow_id: Set(spec.error_workflow_id.clone()),
+        max_concurrent_executions: Set(spec.max_concurrent_executions),
+        scheduling_weight: Set(spec.scheduling_weight),
         created_at: Set(now),
         updated_at: Set(now),
     };
@@ -821,14 +927,17 @@ async fn create_workflow_from_ai_spec(
         .map_err(|e| format!("Database error creating workflow: {e}"))?;
 
     // Insert nodes
-    for node in &spec.nodes {
-        let node_id = node.id.clone().unwrap_or_else(|| Uuid::new_v4().to_string());
-
+    for (node, workflow_node) in spec.nodes.iter().zip(&workflow_nodes) {
         // Convert NodeType to string for node_type field
         let node_type_str = match &node.node_type {
             NodeType::Trigger { .. } => "trigger".to_string(),
             NodeType::Transformer { .. } => "transformer".to_string(),
             NodeType::Condition { .. } => "condition".to_string(),
+            NodeType::Switch { .. } => "switch".to_string(),
+            NodeType::ForEach { .. } => "for_each".to_string(),
+            NodeType::Aggregate {} => "aggregate".to_string(),
+            NodeType::SubWorkflow { .. } => "sub_workflow".to_string(),
+            NodeType::Respond { .. } => "respond".to_string(),
             NodeType::HttpRequest { .. } => "http_request".to_string(),
             NodeType::Email { .. } => "email".to_string(),
             NodeType::Delay { .. } => "delay".to_string(),
@@ -842,7 +951,7 @@ async fn create_workflow_from_ai_spec(
             .map_err(|e| format!("Node config serialization error: {e}"))?;
 
         let node_entity = nodes::ActiveModel {
-            id: Set(node_id),
+            id: Set(workflow_node.id.clone()),
             workflow_id: Set(workflow_id.clone()),
             name: Set(node.name.clone()),
             node_type: Set(node_type_str),
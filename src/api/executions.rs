@@ -31,6 +31,7 @@ pub fn routes() -> Router<AppState> {
         .route("/:execution_id/cancel", axum::routing::post(cancel_execution))
         .route("/stats", get(get_worker_pool_stats))
         .route("/cleanup/stats", get(get_cleanup_stats))
+        .route("/rate-limits", get(get_rate_limits))
 }
 
 /// Get execution details by ID
@@ -52,25 +53,55 @@ pub async fn get_execution(
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
-    match execution {
-        Some(exec) => {
-            let response = serde_json::json!({
-                "id": exec.id,
-                "workflow_id": exec.workflow_id,
-                "status": exec.status,
-                "current_node_id": exec.current_node_id,
-                "input_data": exec.input_data.and_then(|d| serde_json::from_str::<Value>(&d).ok()),
-                "output_data": exec.output_data.and_then(|d| serde_json::from_str::<Value>(&d).ok()),
-                "error_message": exec.error_message,
-                "started_at": exec.started_at,
-                "completed_at": exec.completed_at,
-                "created_at": exec.created_at,
-                "updated_at": exec.updated_at
-            });
-            Ok(Json(response))
-        }
-        None => Err(StatusCode::NOT_FOUND),
-    }
+    let Some(exec) = execution else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let children = execution_service
+        .get_child_executions(&exec.id)
+        .await
+        .map_err(|e| {
+            tracing::error!(
+                error = %e,
+                execution_id = %execution_id,
+                "Failed to get child executions"
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let child_executions: Vec<Value> = children
+        .into_iter()
+        .map(|child| {
+            serde_json::json!({
+                "id": child.id,
+                "workflow_id": child.workflow_id,
+                "parent_node_id": child.parent_node_id,
+                "status": child.status,
+                "error_message": child.error_message,
+                "started_at": child.started_at,
+                "completed_at": child.completed_at,
+                "created_at": child.created_at
+            })
+        })
+        .collect();
+
+    let response = serde_json::json!({
+        "id": exec.id,
+        "workflow_id": exec.workflow_id,
+        "status": exec.status,
+        "current_node_id": exec.current_node_id,
+        "input_data": exec.input_data.and_then(|d| serde_json::from_str::<Value>(&d).ok()),
+        "output_data": exec.output_data.and_then(|d| serde_json::from_str::<Value>(&d).ok()),
+        "error_message": exec.error_message,
+        "parent_execution_id": exec.parent_execution_id,
+        "parent_node_id": exec.parent_node_id,
+        "failed_execution_id": exec.failed_execution_id,
+        "child_executions": child_executions,
+        "started_at": exec.started_at,
+        "completed_at": exec.completed_at,
+        "created_at": exec.created_at,
+        "updated_at": exec.updated_at
+    });
+    Ok(Json(response))
 }
 
 /// Get execution steps by execution ID
@@ -104,6 +135,8 @@ pub async fn get_execution_steps(
                 "input_data": step.input_data.and_then(|d| serde_json::from_str::<Value>(&d).ok()),
                 "output_data": step.output_data.and_then(|d| serde_json::from_str::<Value>(&d).ok()),
                 "error_message": step.error_message,
+                "logs": step.logs.and_then(|d| serde_json::from_str::<Value>(&d).ok()),
+                "item_index": step.item_index,
                 "started_at": step.started_at,
                 "completed_at": step.completed_at,
                 "created_at": step.created_at
@@ -156,6 +189,9 @@ pub async fn get_worker_pool_stats(
     // Get MPSC distributor metrics
     let mpsc_metrics = state.mpsc_distributor.get_metrics().await;
 
+    // Get JavaScript runtime pool and bytecode cache statistics
+    let javascript_stats = state.engine.js_executor.get_stats();
+
     // Get additional metrics
     let system_info = serde_json::json!({
         "timestamp": chrono::Utc::now().timestamp_micros(),
@@ -166,6 +202,8 @@ pub async fn get_worker_pool_stats(
     let response = serde_json::json!({
         "worker_pool": worker_stats,
         "mpsc_distributor": mpsc_metrics,
+        "javascript": javascript_stats,
+        "trigger_rejections": state.trigger_auth_metrics.snapshot(),
         "system": system_info,
         "health": "healthy"
     });
@@ -200,6 +238,8 @@ pub async fn get_execution_status(
                 "status": exec.status,
                 "current_node_id": exec.current_node_id,
                 "error_message": exec.error_message,
+                "parent_execution_id": exec.parent_execution_id,
+                "failed_execution_id": exec.failed_execution_id,
                 "started_at": exec.started_at,
                 "completed_at": exec.completed_at,
                 "created_at": exec.created_at,
@@ -323,6 +363,9 @@ pub async fn get_all_executions(
                 "input_data": exec.input_data.and_then(|d| serde_json::from_str::<Value>(&d).ok()),
                 "output_data": exec.output_data.and_then(|d| serde_json::from_str::<Value>(&d).ok()),
                 "error_message": exec.error_message,
+                "parent_execution_id": exec.parent_execution_id,
+                "parent_node_id": exec.parent_node_id,
+                "failed_execution_id": exec.failed_execution_id,
                 "started_at": exec.started_at,
                 "completed_at": exec.completed_at,
                 "duration_ms": duration_ms,
@@ -382,4 +425,14 @@ pub async fn get_cleanup_stats(
     });
 
     Ok(Json(response))
-}
\ No newline at end of file
+}
+
+/// Get the ingestion rate limit and daily quota state of each limited workflow
+pub async fn get_rate_limits(
+    State(state): State<AppState>,
+) -> Json<Value> {
+    Json(serde_json::json!({
+        "workflows": state.ingestion_limiter.snapshot(),
+        "timestamp": chrono::Utc::now().timestamp_micros()
+    }))
+}
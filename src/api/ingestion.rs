@@ -1,72 +1,369 @@
 #[allow(unused_imports)]
 use axum::{
-    extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
-    response::Json,
+    body::{Body, Bytes},
+    extract::{ConnectInfo, Path, Query, RawQuery, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post, put},
     Router,
 };
+use futures::StreamExt;
+use http_body_util::{LengthLimitError, Limited};
+use serde::{de::DeserializeOwned, Deserialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
 
 use crate::{
+    api::{
+        ingestion_limits::{IngestionLimiter, LimitExceeded},
+        middleware::{max_body_bytes, with_request_decompression},
+        trigger_auth,
+    },
     async_execution::ExecutionService,
+    database::workflow_executions::ExecutionStatus,
+    utils::record_stream::{CsvParser, NdjsonParser, ParsedRecord},
+    workflow::models::{TriggerResponse, Workflow},
     AppState,
     log_workflow_warn,
 };
 
 pub fn routes() -> Router<AppState> {
-    Router::new()
+    let router = Router::new()
         .route("/:workflow_id/trigger", get(trigger_workflow_get).post(trigger_workflow_post).put(trigger_workflow_put))
         .route("/:workflow_id/json_array", post(trigger_workflow_array))
+        .route("/:workflow_id/ndjson", post(trigger_workflow_ndjson))
+        .route("/:workflow_id/csv", post(trigger_workflow_csv));
+    with_request_decompression(router)
 }
 
 pub async fn trigger_workflow_get(
     State(state): State<AppState>,
     Path(workflow_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     headers: HeaderMap,
-    Query(params): Query<HashMap<String, String>>,
-) -> std::result::Result<(StatusCode, Json<Value>), StatusCode> {
+    RawQuery(query): RawQuery,
+    Query(mut params): Query<HashMap<String, String>>,
+) -> std::result::Result<Response, StatusCode> {
+    // Query parameters are the payload, except the reserved `wait` and `timeout`
+    let sync = SyncTriggerParams {
+        wait: params.remove("wait").is_some_and(|w| w == "true"),
+        timeout: params.remove("timeout"),
+    };
+
     let data = params
         .into_iter()
         .map(|(k, v)| (k, Value::String(v)))
         .collect::<serde_json::Map<_, _>>();
 
-    let event_headers = extract_headers(&headers);
-    execute_workflow_async(&state, &workflow_id, Value::Object(data), event_headers).await
+    // GET requests carry their payload in the query string, so that is what gets signed
+    let signed = query.clone().unwrap_or_default();
+    let request = TriggerRequest::new(&headers, query, connect_info, true);
+    trigger_workflow(&state, &workflow_id, Value::Object(data), request, signed.as_bytes(), sync).await
 }
 
 pub async fn trigger_workflow_post(
     State(state): State<AppState>,
     Path(workflow_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
+    Query(sync): Query<SyncTriggerParams>,
     headers: HeaderMap,
-    Json(data): Json<Value>,
-) -> std::result::Result<(StatusCode, Json<Value>), StatusCode> {
-    let event_headers = extract_headers(&headers);
-    execute_workflow_async(&state, &workflow_id, data, event_headers).await
+    body: Bytes,
+) -> std::result::Result<Response, StatusCode> {
+    let data: Value = match parse_json_body(&headers, &body) {
+        Ok(data) => data,
+        Err(rejection) => return Ok(rejection.into_response()),
+    };
+    let request = TriggerRequest::new(&headers, query, connect_info, false);
+    trigger_workflow(&state, &workflow_id, data, request, &body, sync).await
 }
 
 pub async fn trigger_workflow_put(
     State(state): State<AppState>,
     Path(workflow_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
+    Query(sync): Query<SyncTriggerParams>,
     headers: HeaderMap,
-    Json(data): Json<Value>,
-) -> std::result::Result<(StatusCode, Json<Value>), StatusCode> {
-    let event_headers = extract_headers(&headers);
-    execute_workflow_async(&state, &workflow_id, data, event_headers).await
+    body: Bytes,
+) -> std::result::Result<Response, StatusCode> {
+    let data: Value = match parse_json_body(&headers, &body) {
+        Ok(data) => data,
+        Err(rejection) => return Ok(rejection.into_response()),
+    };
+    let request = TriggerRequest::new(&headers, query, connect_info, false);
+    trigger_workflow(&state, &workflow_id, data, request, &body, sync).await
 }
 
 pub async fn trigger_workflow_array(
     State(state): State<AppState>,
     Path(workflow_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
+    Query(sync): Query<SyncTriggerParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> std::result::Result<Response, StatusCode> {
+    let data: Vec<Value> = match parse_json_body(&headers, &body) {
+        Ok(data) => data,
+        Err(rejection) => return Ok(rejection.into_response()),
+    };
+    let request = TriggerRequest::new(&headers, query, connect_info, false);
+    trigger_workflow(&state, &workflow_id, Value::Array(data), request, &body, sync).await
+}
+
+/// Run the workflow once per line of a newline-delimited JSON body
+pub async fn trigger_workflow_ndjson(
+    State(state): State<AppState>,
+    Path(workflow_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    body: Body,
+) -> std::result::Result<(StatusCode, Json<Value>), IngestionRejection> {
+    let workflow = validate_workflow_for_ingestion(&state, &workflow_id).await?;
+
+    let event_headers = TriggerRequest::new(&headers, query, connect_info, false)
+        .authorize(&state, &workflow)
+        .await?;
+    let body = match verify_bulk_body(&state, &workflow, &event_headers, body).await? {
+        Ok(body) => body,
+        Err(e) => return Ok(BulkIngestion::new(&state, &workflow_id, event_headers).finish(Some(e)).await),
+    };
+    // Executions are reserved from the daily quota per chunk as records are parsed
+    state.ingestion_limiter.admit(&state.db, &workflow, 0).await?;
+
+    let mut ingestion = BulkIngestion::new(&state, &workflow_id, event_headers);
+    let mut parser = NdjsonParser::new();
+    let mut stream = body.into_data_stream();
+
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) => ingestion.add(parser.push(&chunk)).await,
+            Err(e) => return Ok(ingestion.finish_unreadable(e).await),
+        }
+    }
+    ingestion.add(parser.finish()).await;
+
+    Ok(ingestion.finish(None).await)
+}
+
+/// Run the workflow once per row of a CSV body, keyed by its header row
+pub async fn trigger_workflow_csv(
+    State(state): State<AppState>,
+    Path(workflow_id): Path<String>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
     headers: HeaderMap,
-    Json(data): Json<Vec<Value>>,
-) -> std::result::Result<(StatusCode, Json<Value>), StatusCode> {
-    let event_headers = extract_headers(&headers);
-    execute_workflow_async(&state, &workflow_id, Value::Array(data), event_headers).await
+    body: Body,
+) -> std::result::Result<(StatusCode, Json<Value>), IngestionRejection> {
+    let workflow = validate_workflow_for_ingestion(&state, &workflow_id).await?;
+
+    let event_headers = TriggerRequest::new(&headers, query, connect_info, false)
+        .authorize(&state, &workflow)
+        .await?;
+    let body = match verify_bulk_body(&state, &workflow, &event_headers, body).await? {
+        Ok(body) => body,
+        Err(e) => return Ok(BulkIngestion::new(&state, &workflow_id, event_headers).finish(Some(e)).await),
+    };
+    // Executions are reserved from the daily quota per chunk as records are parsed
+    state.ingestion_limiter.admit(&state.db, &workflow, 0).await?;
+
+    let mut ingestion = BulkIngestion::new(&state, &workflow_id, event_headers);
+    let mut parser = CsvParser::new();
+    let mut stream = body.into_data_stream();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => return Ok(ingestion.finish_unreadable(e).await),
+        };
+        match parser.push(&chunk) {
+            Ok(records) => ingestion.add(records).await,
+            Err(e) => return Ok(ingestion.finish(Some(e)).await),
+        }
+    }
+    match parser.finish() {
+        Ok(records) => ingestion.add(records).await,
+        Err(e) => return Ok(ingestion.finish(Some(e)).await),
+    }
+
+    Ok(ingestion.finish(None).await)
+}
+
+/// Limit a bulk body to `max_body_bytes` once decompressed, buffering it so its
+/// signature can be checked when the workflow requires one
+///
+/// Unsigned workflows keep streaming the body and hit the limit while it is
+/// parsed. A body that cannot be read is reported as the inner error.
+async fn verify_bulk_body(
+    state: &AppState,
+    workflow: &Workflow,
+    headers: &HashMap<String, String>,
+    body: Body,
+) -> std::result::Result<std::result::Result<Body, String>, StatusCode> {
+    let body = Body::new(Limited::new(body, max_body_bytes()));
+    if workflow.trigger_verification().is_none() {
+        return Ok(Ok(body));
+    }
+
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) if is_length_limit(&e) => return Err(StatusCode::PAYLOAD_TOO_LARGE),
+        Err(e) => return Ok(Err(format!("Failed to read request body: {e}"))),
+    };
+    trigger_auth::verify_signature(state, workflow, headers, &body).await?;
+    Ok(Ok(Body::from(body)))
+}
+
+/// Whether reading a body failed because it grew past `max_body_bytes`
+fn is_length_limit(error: &axum::Error) -> bool {
+    let mut source = std::error::Error::source(error);
+    while let Some(error) = source {
+        if error.is::<LengthLimitError>() {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+/// Records queued per `ExecutionService::create_executions` transaction
+const BULK_CHUNK_SIZE: usize = 500;
+
+/// Queues parsed records in chunks and tracks which lines were accepted or rejected
+struct BulkIngestion<'a> {
+    execution_service: ExecutionService,
+    limiter: Arc<IngestionLimiter>,
+    workflow_id: &'a str,
+    headers: HashMap<String, String>,
+    pending: Vec<(usize, Value)>,
+    accepted: Vec<usize>,
+    rejected: Vec<(usize, String)>,
+}
+
+impl<'a> BulkIngestion<'a> {
+    fn new(state: &AppState, workflow_id: &'a str, headers: HashMap<String, String>) -> Self {
+        Self {
+            execution_service: ExecutionService::new(state.db.clone()),
+            limiter: state.ingestion_limiter.clone(),
+            workflow_id,
+            headers,
+            pending: Vec::with_capacity(BULK_CHUNK_SIZE),
+            accepted: Vec::new(),
+            rejected: Vec::new(),
+        }
+    }
+
+    async fn add(&mut self, records: impl IntoIterator<Item = ParsedRecord>) {
+        for (line, record) in records {
+            match record {
+                Ok(data) => {
+                    self.pending.push((line, data));
+                    if self.pending.len() >= BULK_CHUNK_SIZE {
+                        self.flush().await;
+                    }
+                }
+                Err(e) => self.rejected.push((line, e)),
+            }
+        }
+    }
+
+    async fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let (mut lines, mut inputs): (Vec<usize>, Vec<Value>) = std::mem::take(&mut self.pending).into_iter().unzip();
+        let reserved = self.limiter.reserve(self.workflow_id, inputs.len());
+        if reserved < inputs.len() {
+            inputs.truncate(reserved);
+            self.rejected.extend(lines.split_off(reserved).into_iter().map(|line| (line, "Daily execution quota exceeded".to_string())));
+            if reserved == 0 {
+                return;
+            }
+        }
+
+        match self.execution_service.create_executions(self.workflow_id, inputs, &self.headers, None).await {
+            Ok(results) => {
+                let mut failed = 0;
+                for (line, result) in lines.into_iter().zip(results) {
+                    match result {
+                        Ok(_) => self.accepted.push(line),
+                        Err(e) => {
+                            failed += 1;
+                            self.rejected.push((line, e.to_string()));
+                        }
+                    }
+                }
+                self.limiter.release(self.workflow_id, failed);
+            }
+            Err(e) => {
+                tracing::error!(
+                    error = %e,
+                    workflow_id = self.workflow_id,
+                    "Failed to queue bulk ingestion chunk"
+                );
+                self.limiter.release(self.workflow_id, reserved);
+                self.rejected.extend(lines.into_iter().map(|line| (line, format!("Failed to queue: {e}"))));
+            }
+        }
+    }
+
+    /// Stop at a body that could not be read, answering 413 when it grew past the size limit
+    async fn finish_unreadable(self, error: axum::Error) -> (StatusCode, Json<Value>) {
+        let too_large = is_length_limit(&error);
+        let (status_code, response) = self.finish(Some(format!("Failed to read request body: {error}"))).await;
+        (if too_large { StatusCode::PAYLOAD_TOO_LARGE } else { status_code }, response)
+    }
+
+    /// Queue any remaining records and build the summary response
+    async fn finish(mut self, error: Option<String>) -> (StatusCode, Json<Value>) {
+        self.flush().await;
+        self.rejected.sort_by_key(|(line, _)| *line);
+
+        tracing::info!(
+            "Bulk ingestion for workflow {}: {} accepted, {} rejected",
+            self.workflow_id, self.accepted.len(), self.rejected.len()
+        );
+
+        let status_code = if error.is_some() || self.accepted.is_empty() {
+            StatusCode::BAD_REQUEST
+        } else {
+            StatusCode::ACCEPTED
+        };
+        let status = match (&error, self.accepted.is_empty()) {
+            (Some(_), _) => "incomplete",
+            (None, true) => "rejected",
+            (None, false) => "accepted",
+        };
+
+        let rejected_lines: Vec<Value> = self.rejected
+            .into_iter()
+            .map(|(line, error)| serde_json::json!({ "line": line, "error": error }))
+            .collect();
+
+        let mut response = serde_json::json!({
+            "status": status,
+            "accepted": self.accepted.len(),
+            "rejected": rejected_lines.len(),
+            "accepted_lines": self.accepted,
+            "rejected_lines": rejected_lines,
+        });
+        if let Some(error) = error {
+            response["error"] = Value::String(error);
+        }
+
+        (status_code, Json(response))
+    }
 }
 
-fn extract_headers(headers: &HeaderMap) -> HashMap<String, String> {
+pub(crate) fn extract_headers(headers: &HeaderMap) -> HashMap<String, String> {
     let mut event_headers = HashMap::new();
     
     for (name, value) in headers.iter() {
@@ -78,14 +375,45 @@ fn extract_headers(headers: &HeaderMap) -> HashMap<String, String> {
     event_headers
 }
 
-async fn execute_workflow_async(
-    state: &AppState,
-    workflow_id: &str,
-    input_data: Value,
+/// Where a trigger request came from and the credentials it carried
+pub(crate) struct TriggerRequest {
     headers: HashMap<String, String>,
-) -> std::result::Result<(StatusCode, Json<Value>), StatusCode> {
-    tracing::info!("Executing workflow: {}", workflow_id);
+    query: Option<String>,
+    client_ip: Option<IpAddr>,
+    /// Whether the query parameters are the event data, as for GET triggers
+    payload_in_query: bool,
+}
+
+impl TriggerRequest {
+    pub(crate) fn new(
+        headers: &HeaderMap,
+        query: Option<String>,
+        connect_info: Option<ConnectInfo<SocketAddr>>,
+        payload_in_query: bool,
+    ) -> Self {
+        let headers = extract_headers(headers);
+        let client_ip = trigger_auth::client_ip(&headers, connect_info);
+        Self { headers, query, client_ip, payload_in_query }
+    }
+
+    /// Apply the Trigger node's IP filter and API key, returning the headers to record
+    /// on the execution without the API key
+    pub(crate) async fn authorize(self, state: &AppState, workflow: &Workflow) -> std::result::Result<HashMap<String, String>, StatusCode> {
+        trigger_auth::check_access(state, workflow, &self.headers, self.query.as_deref(), self.client_ip).await?;
 
+        let mut headers = self.headers;
+        if let Some(api_key) = workflow.trigger_api_key() {
+            headers.remove(&api_key.header.to_ascii_lowercase());
+        }
+        Ok(headers)
+    }
+}
+
+/// Check that a workflow exists and is enabled before queueing executions for it
+pub(crate) async fn validate_workflow_for_ingestion(
+    state: &AppState,
+    workflow_id: &str,
+) -> std::result::Result<Workflow, StatusCode> {
     // Load workflow from database to check for HIL nodes and get metadata
     tracing::debug!("Loading workflow {} from database for HIL detection", workflow_id);
     let workflow = state
@@ -116,18 +444,310 @@ async fn execute_workflow_async(
     let start_node_id = workflow.start_node_id.clone().unwrap();
     tracing::info!("Workflow {} validated successfully (start_node: {})", workflow_id, start_node_id);
 
-    // ALL workflows now execute in background workers - no special HIL routing
-    tracing::info!("Workflow {} queued for background worker execution (unified model)", workflow_id);
-
     // Cache the workflow metadata for future requests
     state.workflow_cache.put(workflow_id.to_string(), start_node_id.clone()).await;
     tracing::debug!("Cached workflow metadata for {}", workflow_id);
 
+    Ok(workflow)
+}
+
+/// Parse a JSON request body, answering like axum's `Json` extractor when it is not JSON
+fn parse_json_body<T: DeserializeOwned>(headers: &HeaderMap, body: &Bytes) -> std::result::Result<T, (StatusCode, String)> {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+        .is_some_and(|mime| mime == "application/json" || (mime.starts_with("application/") && mime.ends_with("+json")));
+    if !is_json {
+        let message = "Expected request with `Content-Type: application/json`".to_string();
+        return Err((StatusCode::UNSUPPORTED_MEDIA_TYPE, message));
+    }
+
+    Json::<T>::from_bytes(body)
+        .map(|Json(data)| data)
+        .map_err(|rejection| (rejection.status(), rejection.body_text()))
+}
+
+/// Query parameters that turn a trigger into a synchronous request/response call
+#[derive(Debug, Default, Deserialize)]
+pub struct SyncTriggerParams {
+    #[serde(default)]
+    pub wait: bool,
+    pub timeout: Option<String>,
+}
+
+/// How long a synchronous trigger waits when no `timeout` is given
+const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default for `SP_SYNC_TRIGGER_MAX_TIMEOUT_SECONDS`
+const DEFAULT_MAX_WAIT_TIMEOUT_SECONDS: u64 = 120;
+
+fn max_wait_timeout() -> Duration {
+    let seconds = std::env::var("SP_SYNC_TRIGGER_MAX_TIMEOUT_SECONDS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_MAX_WAIT_TIMEOUT_SECONDS);
+    Duration::from_secs(seconds)
+}
+
+/// Parse a wait timeout such as `10s`, `500ms`, `2m` or a bare number of seconds
+pub fn parse_wait_timeout(value: &str) -> std::result::Result<Duration, String> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid timeout '{value}': expected a duration such as 10s or 500ms"))?;
+
+    let timeout = match unit {
+        "ms" => Duration::from_millis(number),
+        "s" => Duration::from_secs(number),
+        "m" => Duration::from_secs(number.saturating_mul(60)),
+        _ => return Err(format!("Invalid timeout unit '{unit}': use ms, s or m")),
+    };
+
+    if timeout.is_zero() {
+        return Err("Timeout must be greater than zero".to_string());
+    }
+    let max = max_wait_timeout();
+    if timeout > max {
+        return Err(format!("Timeout must not exceed {}s", max.as_secs()));
+    }
+    Ok(timeout)
+}
+
+/// Queue an execution and, when `wait=true` was requested, answer with the workflow output
+///
+/// A Respond node in the workflow answers as soon as it runs, with the status, headers
+/// and body it rendered, while the rest of the workflow keeps running.
+async fn trigger_workflow(
+    state: &AppState,
+    workflow_id: &str,
+    mut input_data: Value,
+    request: TriggerRequest,
+    signed_payload: &[u8],
+    sync: SyncTriggerParams,
+) -> std::result::Result<Response, StatusCode> {
+    let timeout = if sync.wait {
+        match sync.timeout.as_deref().map(parse_wait_timeout).transpose() {
+            Ok(timeout) => Some(timeout.unwrap_or_else(|| DEFAULT_WAIT_TIMEOUT.min(max_wait_timeout()))),
+            Err(e) => {
+                let response = serde_json::json!({ "error": "invalid_timeout", "message": e });
+                return Ok((StatusCode::BAD_REQUEST, Json(response)).into_response());
+            }
+        }
+    } else {
+        None
+    };
+
+    let workflow = validate_workflow_for_ingestion(state, workflow_id).await?;
+    let payload_in_query = request.payload_in_query;
+    let headers = request.authorize(state, &workflow).await?;
+    trigger_auth::verify_signature(state, &workflow, &headers, signed_payload).await?;
+
+    // An API key passed as a GET query parameter would otherwise become part of the event
+    let query_param = workflow.trigger_api_key().and_then(|api_key| api_key.query_param.as_deref());
+    if let (true, Some(param), Value::Object(data)) = (payload_in_query, query_param, &mut input_data) {
+        data.remove(param);
+    }
+    if let Err(limited) = state.ingestion_limiter.admit(&state.db, &workflow, 1).await {
+        return Ok(limited.into_response());
+    }
+    let execution_id = queue_execution(state, workflow_id, input_data, headers).await?;
+
+    let Some(timeout) = timeout else {
+        return Ok(accepted_response(&execution_id, "Workflow execution has been queued").into_response());
+    };
+
+    let execution_service = ExecutionService::new(state.db.clone());
+    let execution = match execution_service.wait_for_response(&execution_id, timeout).await {
+        Ok(Some(execution)) => execution,
+        Ok(None) => {
+            tracing::info!(
+                "Execution {} did not finish within {:?}, responding asynchronously",
+                execution_id, timeout
+            );
+            let message = format!(
+                "Workflow execution did not finish within {}ms and continues in the background",
+                timeout.as_millis()
+            );
+            return Ok(accepted_response(&execution_id, &message).into_response());
+        }
+        Err(e) => {
+            // The execution is already queued, so report it as accepted rather than
+            // inviting the caller to retry and run it twice
+            tracing::error!(
+                error = %e,
+                execution_id = execution_id,
+                "Failed to wait for execution"
+            );
+            return Ok(accepted_response(&execution_id, "Workflow execution has been queued").into_response());
+        }
+    };
+
+    if let Some(response) = execution.http_response.as_deref() {
+        return Ok(respond_node_response(&execution_id, response));
+    }
+
+    let execution_header = [(HeaderName::from_static("x-execution-id"), execution_id.clone())];
+    let response = match ExecutionStatus::from(execution.status) {
+        ExecutionStatus::Completed => {
+            let output = execution
+                .output_data
+                .as_deref()
+                .and_then(|data| serde_json::from_str::<Value>(data).ok())
+                .unwrap_or(Value::Null);
+            (StatusCode::OK, execution_header, Json(output)).into_response()
+        }
+        ExecutionStatus::Failed => {
+            let response = serde_json::json!({
+                "status": "failed",
+                "execution_id": execution_id,
+                "error": execution.error_message,
+            });
+            (StatusCode::INTERNAL_SERVER_ERROR, execution_header, Json(response)).into_response()
+        }
+        ExecutionStatus::Cancelled => {
+            let response = serde_json::json!({
+                "status": "cancelled",
+                "execution_id": execution_id,
+            });
+            (StatusCode::CONFLICT, execution_header, Json(response)).into_response()
+        }
+        ExecutionStatus::PendingHumanInput => {
+            let response = serde_json::json!({
+                "status": "pending_human_input",
+                "execution_id": execution_id,
+                "message": "Workflow execution is waiting for human input",
+            });
+            (StatusCode::ACCEPTED, execution_header, Json(response)).into_response()
+        }
+        ExecutionStatus::Pending | ExecutionStatus::Running => {
+            accepted_response(&execution_id, "Workflow execution has been queued").into_response()
+        }
+    };
+
+    Ok(response)
+}
+
+/// Turn the response recorded by a Respond node into the HTTP response
+///
+/// JSON bodies default to `application/json` and anything else to `text/plain`
+/// unless the node set its own `Content-Type`.
+fn respond_node_response(execution_id: &str, response: &str) -> Response {
+    let response: TriggerResponse = match serde_json::from_str(response) {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::error!(
+                error = %e,
+                execution_id = execution_id,
+                "Failed to parse response set by Respond node"
+            );
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let status = StatusCode::from_u16(response.status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let is_json = serde_json::from_str::<Value>(&response.body).is_ok();
+    let mut http_response = (status, response.body).into_response();
+
+    let headers = http_response.headers_mut();
+    if is_json {
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    }
+    for (name, value) in &response.headers {
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(value)) {
+            (Ok(name), Ok(value)) => {
+                headers.insert(name, value);
+            }
+            _ => tracing::warn!(
+                "Ignoring invalid header '{}' set by Respond node for execution {}",
+                name, execution_id
+            ),
+        }
+    }
+    if let Ok(value) = HeaderValue::from_str(execution_id) {
+        headers.insert(HeaderName::from_static("x-execution-id"), value);
+    }
+
+    http_response
+}
+
+/// Queue an execution within the workflow's limits and return HTTP 202 with its ID
+///
+/// Used by the Segment API, whose clients cannot sign requests for signed triggers.
+pub(crate) async fn execute_workflow_async(
+    state: &AppState,
+    workflow_id: &str,
+    input_data: Value,
+    request: TriggerRequest,
+) -> std::result::Result<(StatusCode, Json<Value>), IngestionRejection> {
+    let workflow = validate_workflow_for_ingestion(state, workflow_id).await?;
+    let headers = request.authorize(state, &workflow).await?;
+    trigger_auth::require_unsigned_trigger(state, &workflow)?;
+    state.ingestion_limiter.admit(&state.db, &workflow, 1).await?;
+    let execution_id = queue_execution(state, workflow_id, input_data, headers).await?;
+    Ok(accepted_response(&execution_id, "Workflow execution has been queued"))
+}
+
+/// Why an ingestion request was turned away before anything was queued
+#[derive(Debug)]
+pub enum IngestionRejection {
+    Status(StatusCode),
+    Limited(LimitExceeded),
+}
+
+impl From<StatusCode> for IngestionRejection {
+    fn from(status: StatusCode) -> Self {
+        Self::Status(status)
+    }
+}
+
+impl From<LimitExceeded> for IngestionRejection {
+    fn from(limited: LimitExceeded) -> Self {
+        Self::Limited(limited)
+    }
+}
+
+impl IntoResponse for IngestionRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::Status(status) => status.into_response(),
+            Self::Limited(limited) => limited.into_response(),
+        }
+    }
+}
+
+fn accepted_response(execution_id: &str, message: &str) -> (StatusCode, Json<Value>) {
+    let response = serde_json::json!({
+        "status": "accepted",
+        "execution_id": execution_id,
+        "message": message
+    });
+
+    (StatusCode::ACCEPTED, Json(response))
+}
+
+/// Queue an execution admitted by the ingestion limiter, returning its reservation on failure
+async fn queue_execution(
+    state: &AppState,
+    workflow_id: &str,
+    input_data: Value,
+    headers: HashMap<String, String>,
+) -> std::result::Result<String, StatusCode> {
+    tracing::info!("Executing workflow: {}", workflow_id);
+
+    // ALL workflows now execute in background workers - no special HIL routing
+    tracing::info!("Workflow {} queued for background worker execution (unified model)", workflow_id);
+
     // Create execution service
     let execution_service = ExecutionService::new(state.db.clone());
 
     // Create execution and queue job
-    let execution_id = execution_service
+    execution_service
         .create_execution(
             workflow_id.to_string(),
             input_data,
@@ -141,16 +761,8 @@ async fn execute_workflow_async(
                 workflow_id = workflow_id,
                 "Failed to create execution"
             );
+            state.ingestion_limiter.release(workflow_id, 1);
             StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-    // Return HTTP 202 with execution details
-    let response = serde_json::json!({
-        "status": "accepted",
-        "execution_id": execution_id,
-        "message": "Workflow execution has been queued"
-    });
-
-    Ok((StatusCode::ACCEPTED, Json(response)))
+        })
 }
 
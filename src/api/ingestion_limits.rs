@@ -0,0 +1,292 @@
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{NaiveDate, Utc};
+use governor::{
+    clock::{Clock, DefaultClock},
+    middleware::StateInformationMiddleware,
+    state::{InMemoryState, NotKeyed},
+    Quota, RateLimiter,
+};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    num::NonZeroU32,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    database::workflow_executions,
+    workflow::models::{TriggerRateLimit, Workflow},
+};
+
+type DirectRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock, StateInformationMiddleware>;
+
+/// Which limit turned a request away
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LimitKind {
+    RateLimited,
+    QuotaExceeded,
+}
+
+/// A request over its workflow's limits, answered with HTTP 429 and `Retry-After`
+#[derive(Debug, Clone)]
+pub struct LimitExceeded {
+    pub kind: LimitKind,
+    pub retry_after: Duration,
+}
+
+impl IntoResponse for LimitExceeded {
+    fn into_response(self) -> Response {
+        // Round up so clients never retry before a token or the next day is available
+        let retry_after = self.retry_after.as_secs() + u64::from(self.retry_after.subsec_nanos() > 0);
+        let message = match self.kind {
+            LimitKind::RateLimited => "Workflow rate limit exceeded",
+            LimitKind::QuotaExceeded => "Workflow daily execution quota exceeded",
+        };
+        let body = serde_json::json!({
+            "error": self.kind,
+            "message": message,
+            "retry_after_seconds": retry_after,
+        });
+
+        let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        response
+    }
+}
+
+/// Limiter state of one workflow as shown by the admin API
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkflowLimitState {
+    pub requests_per_minute: Option<u32>,
+    pub burst: Option<u32>,
+    /// Requests that could be made right now before the rate limit applies
+    pub available_requests: Option<u32>,
+    pub daily_quota: Option<u32>,
+    pub executions_today: u64,
+    pub rate_limited_requests: u64,
+    pub quota_exceeded_requests: u64,
+}
+
+struct WorkflowLimits {
+    config: TriggerRateLimit,
+    limiter: Option<DirectRateLimiter>,
+    /// Burst capacity left after the last request and when that was
+    remaining: (u32, Instant),
+    quota_day: Option<NaiveDate>,
+    executions_today: u64,
+    rate_limited_requests: u64,
+    quota_exceeded_requests: u64,
+}
+
+impl WorkflowLimits {
+    fn new(config: &TriggerRateLimit) -> Self {
+        let limiter = config.requests_per_minute.and_then(NonZeroU32::new).map(|per_minute| {
+            let burst = config.burst.and_then(NonZeroU32::new).unwrap_or(per_minute);
+            RateLimiter::direct(Quota::per_minute(per_minute).allow_burst(burst))
+                .with_middleware::<StateInformationMiddleware>()
+        });
+        let burst = config.burst.or(config.requests_per_minute).unwrap_or(0);
+
+        Self {
+            config: config.clone(),
+            limiter,
+            remaining: (burst, Instant::now()),
+            quota_day: None,
+            executions_today: 0,
+            rate_limited_requests: 0,
+            quota_exceeded_requests: 0,
+        }
+    }
+
+    /// Start counting a new UTC day once the previous one is over
+    fn roll_over(&mut self, today: NaiveDate) {
+        if self.quota_day != Some(today) {
+            self.quota_day = Some(today);
+            self.executions_today = 0;
+        }
+    }
+
+    fn quota_left(&self) -> u64 {
+        match self.config.daily_quota {
+            Some(quota) => u64::from(quota).saturating_sub(self.executions_today),
+            None => u64::MAX,
+        }
+    }
+
+    fn take_token(&mut self) -> Result<(), Duration> {
+        let Some(limiter) = &self.limiter else {
+            return Ok(());
+        };
+        match limiter.check() {
+            Ok(snapshot) => {
+                self.remaining = (snapshot.remaining_burst_capacity(), Instant::now());
+                Ok(())
+            }
+            Err(not_until) => {
+                self.remaining = (0, Instant::now());
+                Err(not_until.wait_time_from(DefaultClock::default().now()))
+            }
+        }
+    }
+
+    fn state(&self) -> WorkflowLimitState {
+        // Tokens refill one every 60s / requests_per_minute since the last request
+        let available_requests = self.config.requests_per_minute.filter(|n| *n > 0).map(|per_minute| {
+            let burst = self.config.burst.unwrap_or(per_minute);
+            let (remaining, at) = self.remaining;
+            let refilled = at.elapsed().as_millis() * u128::from(per_minute) / 60_000;
+            u32::try_from(u128::from(remaining) + refilled).unwrap_or(u32::MAX).min(burst)
+        });
+        let counting_today = self.quota_day == Some(Utc::now().date_naive());
+
+        WorkflowLimitState {
+            requests_per_minute: self.config.requests_per_minute,
+            burst: self.config.burst.or(self.config.requests_per_minute),
+            available_requests,
+            daily_quota: self.config.daily_quota,
+            executions_today: if counting_today { self.executions_today } else { 0 },
+            rate_limited_requests: self.rate_limited_requests,
+            quota_exceeded_requests: self.quota_exceeded_requests,
+        }
+    }
+}
+
+/// Per-workflow request rate limits and daily execution quotas for ingestion routes
+///
+/// State lives in memory, so each server process limits independently. Daily counts are
+/// seeded from the executions already created that day the first time a workflow is seen.
+#[derive(Default)]
+pub struct IngestionLimiter {
+    workflows: Mutex<HashMap<String, WorkflowLimits>>,
+}
+
+impl IngestionLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Admit one request for `workflow`, reserving up to `executions` from its daily quota
+    ///
+    /// Returns how many executions were reserved. Requests are rejected while the
+    /// quota is used up even when they reserve nothing, as bulk requests do before
+    /// reserving per chunk.
+    pub async fn admit(&self, db: &DatabaseConnection, workflow: &Workflow, executions: usize) -> Result<usize, LimitExceeded> {
+        let Some(config) = workflow.trigger_rate_limit() else {
+            self.lock().remove(&workflow.id);
+            return Ok(executions);
+        };
+
+        let today = Utc::now().date_naive();
+        if config.daily_quota.is_some() && !self.counting(&workflow.id, config, today) {
+            let count = count_executions_since(db, &workflow.id, today).await;
+            let mut workflows = self.lock();
+            let limits = Self::entry(&mut workflows, &workflow.id, config);
+            if limits.quota_day != Some(today) {
+                limits.quota_day = Some(today);
+                limits.executions_today = count;
+            }
+        }
+
+        let mut workflows = self.lock();
+        let limits = Self::entry(&mut workflows, &workflow.id, config);
+        limits.roll_over(today);
+
+        if limits.quota_left() == 0 {
+            limits.quota_exceeded_requests += 1;
+            return Err(LimitExceeded { kind: LimitKind::QuotaExceeded, retry_after: until_tomorrow() });
+        }
+        if let Err(retry_after) = limits.take_token() {
+            limits.rate_limited_requests += 1;
+            return Err(LimitExceeded { kind: LimitKind::RateLimited, retry_after });
+        }
+
+        let reserved = (executions as u64).min(limits.quota_left()) as usize;
+        limits.executions_today += reserved as u64;
+        Ok(reserved)
+    }
+
+    /// Reserve up to `executions` more from the daily quota of an admitted request
+    pub fn reserve(&self, workflow_id: &str, executions: usize) -> usize {
+        let mut workflows = self.lock();
+        let Some(limits) = workflows.get_mut(workflow_id) else {
+            return executions;
+        };
+        limits.roll_over(Utc::now().date_naive());
+        let reserved = (executions as u64).min(limits.quota_left()) as usize;
+        limits.executions_today += reserved as u64;
+        reserved
+    }
+
+    /// Return reserved executions that were not queued after all
+    pub fn release(&self, workflow_id: &str, executions: usize) {
+        if let Some(limits) = self.lock().get_mut(workflow_id) {
+            limits.executions_today = limits.executions_today.saturating_sub(executions as u64);
+        }
+    }
+
+    /// Current limiter state of every workflow with limits that has received requests
+    pub fn snapshot(&self) -> HashMap<String, WorkflowLimitState> {
+        self.lock().iter().map(|(id, limits)| (id.clone(), limits.state())).collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, WorkflowLimits>> {
+        self.workflows.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether today's executions are already being counted for the workflow
+    fn counting(&self, workflow_id: &str, config: &TriggerRateLimit, today: NaiveDate) -> bool {
+        self.lock()
+            .get(workflow_id)
+            .is_some_and(|limits| limits.config == *config && limits.quota_day == Some(today))
+    }
+
+    /// The workflow's limits, starting afresh when its configuration changed
+    fn entry<'a>(
+        workflows: &'a mut HashMap<String, WorkflowLimits>,
+        workflow_id: &str,
+        config: &TriggerRateLimit,
+    ) -> &'a mut WorkflowLimits {
+        let limits = workflows
+            .entry(workflow_id.to_string())
+            .or_insert_with(|| WorkflowLimits::new(config));
+        if limits.config != *config {
+            let mut updated = WorkflowLimits::new(config);
+            updated.quota_day = limits.quota_day;
+            updated.executions_today = limits.executions_today;
+            *limits = updated;
+        }
+        limits
+    }
+}
+
+/// Executions of a workflow created since the start of `day`, or 0 if they cannot be counted
+async fn count_executions_since(db: &DatabaseConnection, workflow_id: &str, day: NaiveDate) -> u64 {
+    let start = day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp_micros();
+    workflow_executions::Entity::find()
+        .filter(workflow_executions::Column::WorkflowId.eq(workflow_id))
+        .filter(workflow_executions::Column::CreatedAt.gte(start))
+        .count(db)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!(
+                error = %e,
+                workflow_id = workflow_id,
+                "Failed to count today's executions for the daily quota"
+            );
+            0
+        })
+}
+
+fn until_tomorrow() -> Duration {
+    let now = Utc::now();
+    let tomorrow = now.date_naive().succ_opt().and_then(|day| day.and_hms_opt(0, 0, 0));
+    tomorrow
+        .and_then(|midnight| (midnight.and_utc() - now).to_std().ok())
+        .unwrap_or(Duration::from_secs(1))
+}
@@ -0,0 +1,26 @@
+use axum::{extract::DefaultBodyLimit, Router};
+use tower_http::decompression::RequestDecompressionLayer;
+
+use crate::AppState;
+
+/// Default cap on a request body after decompression (axum's own default)
+const DEFAULT_MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Maximum request body size on ingestion routes, measured after decompression
+pub fn max_body_bytes() -> usize {
+    std::env::var("SP_INGESTION_MAX_BODY_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_BODY_BYTES)
+}
+
+/// Transparently decompress `gzip` and `deflate` request bodies on public ingestion routes
+///
+/// The body limit is enforced while the decompressed stream is read, so a small
+/// compressed payload cannot expand past it. Unsupported encodings are rejected
+/// with 415 Unsupported Media Type.
+pub fn with_request_decompression(router: Router<AppState>) -> Router<AppState> {
+    router
+        .layer(DefaultBodyLimit::max(max_body_bytes()))
+        .layer(RequestDecompressionLayer::new())
+}
@@ -1,3 +1,5 @@
 pub mod admin_logging;
+pub mod decompression;
 
-pub use admin_logging::admin_api_logging_middleware;
\ No newline at end of file
+pub use admin_logging::admin_api_logging_middleware;
+pub use decompression::{max_body_bytes, with_request_decompression};
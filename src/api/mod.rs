@@ -3,6 +3,7 @@ pub mod executions;
 pub mod health;
 pub mod hil;
 pub mod ingestion;
+pub mod ingestion_limits;
 pub mod loops;
 pub mod middleware;
 pub mod schedules;
@@ -10,6 +11,7 @@ pub mod script;
 pub mod segment;
 pub mod settings;
 pub mod static_files;
+pub mod trigger_auth;
 pub mod variables;
 pub mod versions;
 pub mod workflows;
@@ -34,6 +36,7 @@ pub fn create_router() -> Router<AppState> {
         .nest("/api/admin/v1/settings", settings::routes())
         .nest("/api/admin/v1/hil", hil::routes())
         .nest("/api/admin/v1/variables", variables::routes())
+        .nest("/api/admin/v1/segment/write-keys", segment::write_keys::routes())
         .nest("/api/admin/v1", schedules::routes())
         .nest("/api/admin/v1", versions::routes::create_routes())
         .nest("/auth", auth_handlers::routes())
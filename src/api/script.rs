@@ -1,9 +1,10 @@
 use axum::{extract::State, http::StatusCode, Json, Router};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::utils::javascript::JavaScriptExecutor;
+use crate::utils::javascript::ScriptOptions;
+use crate::utils::javascript_console::{ConsoleCapture, ConsoleLog};
 use crate::workflow::{models::WorkflowEvent, errors::JavaScriptError};
 use crate::AppState;
 
@@ -11,7 +12,9 @@ use crate::AppState;
 pub struct ScriptExecuteRequest {
     pub script: String,
     pub input: Value,
-    pub script_type: Option<String>, // "transformer" or "condition"
+    pub script_type: Option<String>, // "transformer", "condition" or "switch"
+    #[serde(default)]
+    pub include_logs: bool, // Wrap the result as { result, logs } with captured console output
 }
 
 
@@ -19,6 +22,8 @@ pub struct ScriptExecuteRequest {
 pub struct ScriptExecuteError {
     pub error: String,
     pub details: Option<String>,
+    #[serde(skip_serializing_if = "ConsoleLog::is_empty")]
+    pub logs: ConsoleLog,
 }
 
 pub fn routes() -> Router<AppState> {
@@ -34,11 +39,16 @@ pub fn routes() -> Router<AppState> {
 /// The script_type parameter determines how the script is executed:
 /// - "transformer": Executes as transformer script, returns WorkflowEvent
 /// - "condition": Executes as condition script, returns boolean result
+/// - "switch": Executes as switch script, returns the selected case label or null
 /// - If not specified, defaults to "transformer" for backward compatibility
 ///
+/// When include_logs is set, the result is wrapped as `{ "result": ..., "logs": ... }`
+/// with the script's captured console output. Error responses always carry the
+/// console output captured before the failure.
+///
 /// Returns the script result or error details.
 pub async fn execute_script(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(request): Json<ScriptExecuteRequest>,
 ) -> Result<Json<Value>, (StatusCode, Json<ScriptExecuteError>)> {
     // Validate script is not empty
@@ -48,6 +58,7 @@ pub async fn execute_script(
             Json(ScriptExecuteError {
                 error: "Script cannot be empty".to_string(),
                 details: None,
+                logs: ConsoleLog::default(),
             }),
         ));
     }
@@ -64,18 +75,13 @@ pub async fn execute_script(
         },
     };
 
-    // Create JavaScript executor
-    let js_executor = match JavaScriptExecutor::new() {
-        Ok(executor) => executor,
-        Err(error) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ScriptExecuteError {
-                    error: "Failed to create JavaScript executor".to_string(),
-                    details: Some(error.to_string()),
-                }),
-            ));
-        }
+    // Use the engine's executor so scripts share its runtime pool and bytecode cache
+    let js_executor = state.engine.js_executor.clone();
+
+    let console = ConsoleCapture::new();
+    let options = ScriptOptions {
+        console: Some(&console),
+        ..ScriptOptions::default()
     };
 
     // Determine script type - default to "transformer" for backward compatibility
@@ -84,10 +90,10 @@ pub async fn execute_script(
     match script_type {
         "condition" => {
             // Execute as condition script
-            match js_executor.execute_condition(&request.script, &workflow_event).await {
+            match js_executor.execute_condition_with_options(&request.script, &workflow_event, options).await {
                 Ok(condition_result) => {
                     // Return boolean result
-                    Ok(Json(serde_json::json!(condition_result)))
+                    Ok(Json(with_logs(serde_json::json!(condition_result), request.include_logs, &console)))
                 },
                 Err(error) => {
                     let error_message = error.to_string();
@@ -96,6 +102,24 @@ pub async fn execute_script(
                         Json(ScriptExecuteError {
                             error: "Condition script execution failed".to_string(),
                             details: Some(error_message),
+                            logs: console.log(),
+                        }),
+                    ))
+                }
+            }
+        },
+        "switch" => {
+            // Execute as switch script and return the selected case label (null for default)
+            match js_executor.execute_switch_with_options(&request.script, &workflow_event, options).await {
+                Ok(label) => Ok(Json(with_logs(serde_json::json!(label), request.include_logs, &console))),
+                Err(error) => {
+                    let error_message = error.to_string();
+                    Err((
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ScriptExecuteError {
+                            error: "Switch script execution failed".to_string(),
+                            details: Some(error_message),
+                            logs: console.log(),
                         }),
                     ))
                 }
@@ -103,7 +127,7 @@ pub async fn execute_script(
         },
         "transformer" => {
             // Execute as transformer script
-            match js_executor.execute_transformer(&request.script, workflow_event).await {
+            match js_executor.execute_transformer_with_options(&request.script, workflow_event, options).await {
                 Ok(result_event) => {
                     // Convert the result event back to JSON
                     let result_json = serde_json::to_value(result_event)
@@ -113,24 +137,27 @@ pub async fn execute_script(
                                 Json(ScriptExecuteError {
                                     error: "Failed to serialize result".to_string(),
                                     details: Some(e.to_string()),
+                                    logs: console.log(),
                                 }),
                             )
                         })?;
 
-                    Ok(Json(result_json))
+                    Ok(Json(with_logs(result_json, request.include_logs, &console)))
                 },
                 Err(error) => {
                     let error_message = error.to_string();
 
                     // Check if it's an event dropped error (transformer returned null)
                     if matches!(error, JavaScriptError::EventDropped) {
-                        Ok(Json(serde_json::json!({ "dropped": true, "message": "Event was dropped (transformer returned null)" })))
+                        let dropped = serde_json::json!({ "dropped": true, "message": "Event was dropped (transformer returned null)" });
+                        Ok(Json(with_logs(dropped, request.include_logs, &console)))
                     } else {
                         Err((
                             StatusCode::INTERNAL_SERVER_ERROR,
                             Json(ScriptExecuteError {
                                 error: "Transformer script execution failed".to_string(),
                                 details: Some(error_message),
+                                logs: console.log(),
                             }),
                         ))
                     }
@@ -143,13 +170,23 @@ pub async fn execute_script(
                 StatusCode::BAD_REQUEST,
                 Json(ScriptExecuteError {
                     error: "Invalid script_type".to_string(),
-                    details: Some("script_type must be either 'transformer' or 'condition'".to_string()),
+                    details: Some("script_type must be 'transformer', 'condition' or 'switch'".to_string()),
+                    logs: ConsoleLog::default(),
                 }),
             ))
         }
     }
 }
 
+/// Wrap a result with the captured console output when the caller asked for it
+fn with_logs(result: Value, include_logs: bool, console: &ConsoleCapture) -> Value {
+    if include_logs {
+        serde_json::json!({ "result": result, "logs": console.log() })
+    } else {
+        result
+    }
+}
+
 fn create_workflow_event(input_data: Value) -> WorkflowEvent {
     // Create a workflow event structure similar to what's used in actual workflows
     WorkflowEvent {
@@ -157,6 +194,8 @@ fn create_workflow_event(input_data: Value) -> WorkflowEvent {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     }
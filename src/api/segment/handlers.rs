@@ -1,21 +1,25 @@
 use axum::{
-    extract::{State, Path},
+    extract::{ConnectInfo, RawQuery, State},
     http::{HeaderMap, StatusCode, Extensions},
     response::Json,
 };
-use futures::future::join_all;
 use sea_orm::{EntityTrait, ColumnTrait, QueryFilter};
 use serde::Serialize;
 use serde_json::{json, Value};
+use std::net::SocketAddr;
 use uuid::Uuid;
 
 use crate::{
-    api::ingestion::trigger_workflow_post,
+    api::{
+        ingestion::{execute_workflow_async, validate_workflow_for_ingestion, IngestionRejection, TriggerRequest},
+        trigger_auth,
+    },
+    async_execution::ExecutionService,
     database::entities,
     AppState,
 };
 
-use super::middleware::extract_write_key_from_request;
+use super::middleware::{resolve_workflow_from_request, SegmentAuthError};
 
 #[derive(Serialize)]
 pub struct SegmentResponse {
@@ -40,14 +44,18 @@ pub struct SegmentErrorDetails {
 /// Convert Segment.com request to SwissPipe format and trigger workflow
 async fn handle_segment_request(
     state: State<AppState>,
-    headers: HeaderMap,
+    request: TriggerRequest,
     extensions: Extensions,
     body_value: Value,
     segment_type: &str,
-) -> Result<Json<SegmentResponse>, StatusCode> {
-    // Extract write key (workflow UUID) using middleware or fallback
-    let workflow_id = match extract_write_key_from_request(&extensions, &body_value) {
-        Ok(write_key) => write_key,
+) -> Result<Json<SegmentResponse>, IngestionRejection> {
+    // Resolve the write key to a workflow using middleware or fallback
+    let workflow_id = match resolve_workflow_from_request(&state.db, &extensions, &body_value).await {
+        Ok(workflow_id) => workflow_id,
+        Err(SegmentAuthError::Database(e)) => {
+            tracing::error!("Segment API write key lookup failed: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
         Err(error) => {
             tracing::warn!("Segment API auth failed: {}", error);
             return Ok(Json(SegmentResponse {
@@ -93,7 +101,7 @@ async fn handle_segment_request(
                 }
                 Err(e) => {
                     tracing::error!("Database error checking workflow {}: {}", workflow_id, e);
-                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
                 }
             }
         }
@@ -130,11 +138,11 @@ async fn handle_segment_request(
     }
 
     // Call existing workflow trigger handler directly
-    match trigger_workflow_post(
-        state,
-        Path(workflow_id.clone()),
-        headers,
-        Json(swissipe_data.clone()),
+    match execute_workflow_async(
+        &state,
+        &workflow_id,
+        swissipe_data.clone(),
+        request,
     ).await {
         Ok((status_code, _response)) => {
             if status_code == StatusCode::ACCEPTED || status_code == StatusCode::OK {
@@ -164,14 +172,10 @@ async fn handle_segment_request(
                 }))
             }
         }
-        Err(status_code) => {
-            let (error_message, error_type) = match status_code {
-                StatusCode::NOT_FOUND => ("Workflow not found".to_string(), "workflow_not_found"),
-                StatusCode::UNAUTHORIZED => ("Unauthorized".to_string(), "unauthorized"),
-                StatusCode::BAD_REQUEST => ("Invalid request format".to_string(), "invalid_request"),
-                StatusCode::INTERNAL_SERVER_ERROR => ("Internal server error".to_string(), "internal_error"),
-                _ => ("Unknown error".to_string(), "unknown_error"),
-            };
+        // Over the workflow's limits, answer 429 so Segment clients back off and retry
+        Err(rejection @ IngestionRejection::Limited(_)) => Err(rejection),
+        Err(IngestionRejection::Status(status_code)) => {
+            let (error_message, error_type) = trigger_error(status_code);
 
             Ok(Json(SegmentResponse {
                 success: false,
@@ -189,69 +193,105 @@ async fn handle_segment_request(
     }
 }
 
+/// Map a workflow trigger failure to a Segment error message and type
+fn trigger_error(status_code: StatusCode) -> (String, &'static str) {
+    match status_code {
+        StatusCode::NOT_FOUND => ("Workflow not found".to_string(), "workflow_not_found"),
+        StatusCode::FORBIDDEN => ("Workflow is disabled or the client is not allowed".to_string(), "forbidden"),
+        StatusCode::UNAUTHORIZED => ("Unauthorized".to_string(), "unauthorized"),
+        StatusCode::BAD_REQUEST => ("Invalid request format".to_string(), "invalid_request"),
+        StatusCode::INTERNAL_SERVER_ERROR => ("Internal server error".to_string(), "internal_error"),
+        _ => ("Unknown error".to_string(), "unknown_error"),
+    }
+}
+
 pub async fn segment_track(
     state: State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
     headers: HeaderMap,
     extensions: Extensions,
     Json(data): Json<Value>,
-) -> Result<Json<SegmentResponse>, StatusCode> {
-    handle_segment_request(state, headers, extensions, data, "track").await
+) -> Result<Json<SegmentResponse>, IngestionRejection> {
+    let request = TriggerRequest::new(&headers, query, connect_info, false);
+    handle_segment_request(state, request, extensions, data, "track").await
 }
 
 pub async fn segment_identify(
     state: State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
     headers: HeaderMap,
     extensions: Extensions,
     Json(data): Json<Value>,
-) -> Result<Json<SegmentResponse>, StatusCode> {
-    handle_segment_request(state, headers, extensions, data, "identify").await
+) -> Result<Json<SegmentResponse>, IngestionRejection> {
+    let request = TriggerRequest::new(&headers, query, connect_info, false);
+    handle_segment_request(state, request, extensions, data, "identify").await
 }
 
 pub async fn segment_page(
     state: State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
     headers: HeaderMap,
     extensions: Extensions,
     Json(data): Json<Value>,
-) -> Result<Json<SegmentResponse>, StatusCode> {
-    handle_segment_request(state, headers, extensions, data, "page").await
+) -> Result<Json<SegmentResponse>, IngestionRejection> {
+    let request = TriggerRequest::new(&headers, query, connect_info, false);
+    handle_segment_request(state, request, extensions, data, "page").await
 }
 
 pub async fn segment_screen(
     state: State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
     headers: HeaderMap,
     extensions: Extensions,
     Json(data): Json<Value>,
-) -> Result<Json<SegmentResponse>, StatusCode> {
-    handle_segment_request(state, headers, extensions, data, "screen").await
+) -> Result<Json<SegmentResponse>, IngestionRejection> {
+    let request = TriggerRequest::new(&headers, query, connect_info, false);
+    handle_segment_request(state, request, extensions, data, "screen").await
 }
 
 pub async fn segment_group(
     state: State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
     headers: HeaderMap,
     extensions: Extensions,
     Json(data): Json<Value>,
-) -> Result<Json<SegmentResponse>, StatusCode> {
-    handle_segment_request(state, headers, extensions, data, "group").await
+) -> Result<Json<SegmentResponse>, IngestionRejection> {
+    let request = TriggerRequest::new(&headers, query, connect_info, false);
+    handle_segment_request(state, request, extensions, data, "group").await
 }
 
 pub async fn segment_alias(
     state: State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
     headers: HeaderMap,
     extensions: Extensions,
     Json(data): Json<Value>,
-) -> Result<Json<SegmentResponse>, StatusCode> {
-    handle_segment_request(state, headers, extensions, data, "alias").await
+) -> Result<Json<SegmentResponse>, IngestionRejection> {
+    let request = TriggerRequest::new(&headers, query, connect_info, false);
+    handle_segment_request(state, request, extensions, data, "alias").await
 }
 
 pub async fn segment_batch(
     state: State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    RawQuery(query): RawQuery,
     headers: HeaderMap,
     extensions: Extensions,
     Json(body_value): Json<Value>,
-) -> Result<Json<SegmentResponse>, StatusCode> {
-    // Extract write key using middleware
-    let workflow_id = match extract_write_key_from_request(&extensions, &body_value) {
-        Ok(write_key) => write_key,
+) -> Result<Json<SegmentResponse>, IngestionRejection> {
+    // Resolve the write key to a workflow using middleware or the batch's writeKey
+    let workflow_id = match resolve_workflow_from_request(&state.db, &extensions, &body_value).await {
+        Ok(workflow_id) => workflow_id,
+        Err(SegmentAuthError::Database(e)) => {
+            tracing::error!("Segment batch API write key lookup failed: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR.into());
+        }
         Err(error) => {
             tracing::warn!("Segment batch API auth failed: {}", error);
             return Ok(Json(SegmentResponse {
@@ -276,73 +316,109 @@ pub async fn segment_batch(
 
         tracing::info!("Processing batch of {} events for workflow {}", total_events, workflow_id);
 
-        // Prepare all events with metadata concurrently
-        let mut event_futures = Vec::new();
-
-        for (index, event) in batch_array.iter().enumerate() {
-            let mut event_data = event.clone();
-
-            // Add batch metadata
-            if let Some(obj) = event_data.as_object_mut() {
-                let event_type = obj.get("type").and_then(|t| t.as_str()).unwrap_or("unknown");
-                obj.insert("_segment_type".to_string(), json!(format!("batch_{}", event_type)));
-                obj.insert("_segment_original".to_string(), event.clone());
-                obj.insert("_batch_id".to_string(), json!(batch_message_id.clone()));
-                obj.insert("_batch_index".to_string(), json!(index));
-
-                if !obj.contains_key("messageId") {
-                    obj.insert("messageId".to_string(), json!(Uuid::new_v4().to_string()));
-                }
+        // Validate and authorize the workflow once for the whole batch
+        let authorized = async {
+            let workflow = validate_workflow_for_ingestion(&state, &workflow_id).await?;
+            let event_headers = TriggerRequest::new(&headers, query, connect_info, false)
+                .authorize(&state, &workflow)
+                .await?;
+            trigger_auth::require_unsigned_trigger(&state, &workflow)?;
+            Ok((workflow, event_headers))
+        };
+        let (workflow, event_headers) = match authorized.await {
+            Ok(authorized) => authorized,
+            Err(status_code) => {
+                let (error_message, error_type) = trigger_error(status_code);
+                return Ok(Json(SegmentResponse {
+                    success: false,
+                    message_id: Some(batch_message_id),
+                    error: Some(error_message),
+                    details: Some(SegmentErrorDetails {
+                        error_type: error_type.to_string(),
+                        workflow_id: Some(workflow_id),
+                        event_count: Some(total_events),
+                        failed_events: Some((0..total_events).collect()),
+                        validation_errors: None,
+                    }),
+                }));
             }
+        };
 
-            // Create future for concurrent execution
-            let state_clone = state.clone();
-            let workflow_id_clone = workflow_id.clone();
-            let headers_clone = headers.clone();
-
-            let future = async move {
-                let result = trigger_workflow_post(
-                    state_clone,
-                    Path(workflow_id_clone),
-                    headers_clone,
-                    Json(event_data),
-                ).await;
+        // Prepare all events with batch metadata; anything but an object is rejected up front
+        let mut failed_events = Vec::new();
+        let mut validation_errors = Vec::new();
+        let mut queued_indices = Vec::new();
+        let mut inputs = Vec::new();
 
-                (index, result)
+        for (index, event) in batch_array.iter().enumerate() {
+            let Some(original) = event.as_object() else {
+                failed_events.push(index);
+                validation_errors.push(format!("Event {index}: must be a JSON object"));
+                continue;
             };
 
-            event_futures.push(future);
+            let mut obj = original.clone();
+            let event_type = obj.get("type").and_then(|t| t.as_str()).unwrap_or("unknown");
+            obj.insert("_segment_type".to_string(), json!(format!("batch_{}", event_type)));
+            obj.insert("_segment_original".to_string(), event.clone());
+            obj.insert("_batch_id".to_string(), json!(batch_message_id.clone()));
+            obj.insert("_batch_index".to_string(), json!(index));
+
+            if !obj.contains_key("messageId") {
+                obj.insert("messageId".to_string(), json!(Uuid::new_v4().to_string()));
+            }
+
+            queued_indices.push(index);
+            inputs.push(Value::Object(obj));
         }
 
-        // Execute all events concurrently
-        let results = join_all(event_futures).await;
+        // Events beyond what is left of the daily quota are rejected individually
+        let reserved = state.ingestion_limiter.admit(&state.db, &workflow, inputs.len()).await?;
+        inputs.truncate(reserved);
+        for index in queued_indices.split_off(reserved) {
+            failed_events.push(index);
+            validation_errors.push(format!("Event {index}: daily execution quota exceeded"));
+        }
 
-        // Analyze results
-        let mut failed_events = Vec::new();
-        let mut success_count = 0;
-
-        for (index, result) in results {
-            match result {
-                Ok((status_code, _)) => {
-                    if status_code == StatusCode::ACCEPTED || status_code == StatusCode::OK {
-                        success_count += 1;
-                    } else {
+        // Queue every valid event in a single transaction
+        let execution_service = ExecutionService::new(state.db.clone());
+        match execution_service.create_executions(&workflow_id, inputs, &event_headers, None).await {
+            Ok(results) => {
+                let mut rejected = 0;
+                for (index, result) in queued_indices.into_iter().zip(results) {
+                    if let Err(e) = result {
+                        tracing::warn!("Batch event {} rejected: {}", index, e);
+                        rejected += 1;
                         failed_events.push(index);
-                        tracing::warn!("Batch event {} failed with status: {}", index, status_code);
+                        validation_errors.push(format!("Event {index}: {e}"));
                     }
                 }
-                Err(status_code) => {
-                    failed_events.push(index);
-                    tracing::error!("Batch event {} failed with error: {}", index, status_code);
-                }
+                state.ingestion_limiter.release(&workflow_id, rejected);
+            }
+            Err(e) => {
+                tracing::error!("Failed to queue batch for workflow {}: {}", workflow_id, e);
+                state.ingestion_limiter.release(&workflow_id, reserved);
+                return Ok(Json(SegmentResponse {
+                    success: false,
+                    message_id: Some(batch_message_id),
+                    error: Some("Internal server error".to_string()),
+                    details: Some(SegmentErrorDetails {
+                        error_type: "internal_error".to_string(),
+                        workflow_id: Some(workflow_id),
+                        event_count: Some(total_events),
+                        failed_events: Some((0..total_events).collect()),
+                        validation_errors: Some(vec![e.to_string()]),
+                    }),
+                }));
             }
         }
 
+        failed_events.sort_unstable();
         let all_success = failed_events.is_empty();
 
         tracing::info!(
             "Batch processing completed: {}/{} events successful, workflow_id={}",
-            success_count, total_events, workflow_id
+            total_events - failed_events.len(), total_events, workflow_id
         );
 
         Ok(Json(SegmentResponse {
@@ -361,7 +437,7 @@ pub async fn segment_batch(
                     workflow_id: Some(workflow_id),
                     event_count: Some(total_events),
                     failed_events: Some(failed_events),
-                    validation_errors: None,
+                    validation_errors: Some(validation_errors),
                 })
             },
         }))
@@ -383,10 +459,12 @@ pub async fn segment_batch(
 
 pub async fn segment_import(
     state: State<AppState>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    query: RawQuery,
     headers: HeaderMap,
     extensions: Extensions,
     data: Json<Value>,
-) -> Result<Json<SegmentResponse>, StatusCode> {
+) -> Result<Json<SegmentResponse>, IngestionRejection> {
     // Import is essentially the same as batch for our purposes
-    segment_batch(state, headers, extensions, data).await
+    segment_batch(state, connect_info, query, headers, extensions, data).await
 }
\ No newline at end of file
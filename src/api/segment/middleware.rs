@@ -1,31 +1,35 @@
 use axum::{
     extract::Request,
-    http::StatusCode,
+    http::{header::AUTHORIZATION, StatusCode},
     middleware::Next,
     response::Response,
 };
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use sea_orm::{DatabaseConnection, EntityTrait};
 use serde_json::Value;
 use uuid::Uuid;
 
-/// Extension to store extracted write key in request
+use crate::database::segment_write_keys;
+
+/// Extension to store the write key extracted from the Authorization header
 #[derive(Clone, Debug)]
 pub struct SegmentAuth {
-    pub workflow_id: String,
+    pub write_key: String,
 }
 
-/// Extract and validate Bearer token from Authorization header
+/// Extract the write key from the Authorization header
+///
+/// Accepts `Bearer <writeKey>` and the `Basic base64(writeKey:)` form sent by the
+/// official Segment libraries. Requests without an Authorization header are passed
+/// through so handlers can fall back to `writeKey` in the request body.
 pub async fn segment_auth_middleware(
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    // Get Authorization header
-    let auth_header = request
-        .headers()
-        .get("authorization")
-        .ok_or_else(|| {
-            tracing::warn!("Missing Authorization header for Segment API");
-            StatusCode::UNAUTHORIZED
-        })?;
+    let Some(auth_header) = request.headers().get(AUTHORIZATION) else {
+        tracing::debug!("No Authorization header for Segment API, deferring to request body writeKey");
+        return Ok(next.run(request).await);
+    };
 
     // Parse Authorization header
     let auth_str = auth_header.to_str().map_err(|_| {
@@ -33,61 +37,97 @@ pub async fn segment_auth_middleware(
         StatusCode::UNAUTHORIZED
     })?;
 
-    // Extract Bearer token
-    let bearer_token = auth_str.strip_prefix("Bearer ").ok_or_else(|| {
-        tracing::warn!("Authorization header must use Bearer scheme");
-        StatusCode::UNAUTHORIZED
-    })?;
-
-    // Validate UUID format and clone the token before using it
-    let workflow_id = bearer_token.to_string();
-    Uuid::parse_str(&workflow_id).map_err(|_| {
-        tracing::warn!("Bearer token is not a valid UUID: {}", workflow_id);
+    let write_key = parse_authorization_header(auth_str).map_err(|e| {
+        tracing::warn!("Segment API auth failed: {}", e);
         StatusCode::UNAUTHORIZED
     })?;
 
-    // Store workflow ID in request extensions for handlers to use
-    request.extensions_mut().insert(SegmentAuth {
-        workflow_id: workflow_id.clone(),
-    });
+    // Store write key in request extensions for handlers to resolve
+    request.extensions_mut().insert(SegmentAuth { write_key });
 
-    tracing::debug!("Segment API Bearer token validated: {}", workflow_id);
     Ok(next.run(request).await)
 }
 
-/// Extract write key from Segment.com request (either from extensions or request body)
-pub fn extract_write_key_from_request(
+/// Extract the write key from a `Bearer` or `Basic` Authorization header value
+///
+/// For Basic auth the write key is the username; the password is ignored, as
+/// Segment clients send it empty.
+pub fn parse_authorization_header(header: &str) -> Result<String, SegmentAuthError> {
+    let invalid = || SegmentAuthError::InvalidFormat {
+        source: "authorization_header".to_string(),
+        reason: "expected Bearer or Basic credentials carrying a write key".to_string(),
+    };
+
+    let (scheme, credentials) = header.trim().split_once(' ').ok_or_else(invalid)?;
+    let credentials = credentials.trim();
+
+    let write_key = if scheme.eq_ignore_ascii_case("Bearer") {
+        credentials.to_string()
+    } else if scheme.eq_ignore_ascii_case("Basic") {
+        let decoded = STANDARD.decode(credentials).map_err(|_| invalid())?;
+        let decoded = String::from_utf8(decoded).map_err(|_| invalid())?;
+        match decoded.split_once(':') {
+            Some((username, _password)) => username.to_string(),
+            None => decoded,
+        }
+    } else {
+        return Err(invalid());
+    };
+
+    if write_key.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(write_key)
+}
+
+/// Map a write key to a workflow ID
+///
+/// Workflow UUIDs are accepted directly; anything else must be a write key alias.
+pub async fn resolve_write_key(
+    db: &DatabaseConnection,
+    write_key: &str,
+    source: &str,
+) -> Result<String, SegmentAuthError> {
+    if Uuid::parse_str(write_key).is_ok() {
+        return Ok(write_key.to_string());
+    }
+
+    match segment_write_keys::Entity::find_by_id(write_key).one(db).await {
+        Ok(Some(alias)) => Ok(alias.workflow_id),
+        Ok(None) => Err(SegmentAuthError::UnknownWriteKey {
+            source: source.to_string(),
+            write_key: write_key.to_string(),
+        }),
+        Err(e) => Err(SegmentAuthError::Database(e.to_string())),
+    }
+}
+
+/// Resolve the workflow for a Segment.com request (either from extensions or request body)
+pub async fn resolve_workflow_from_request(
+    db: &DatabaseConnection,
     request_extensions: &axum::http::Extensions,
     body_value: &Value,
 ) -> Result<String, SegmentAuthError> {
     // Try request extensions first (set by middleware)
     if let Some(auth) = request_extensions.get::<SegmentAuth>() {
-        return Ok(auth.workflow_id.clone());
+        return resolve_write_key(db, &auth.write_key, "authorization_header").await;
     }
 
     // Fall back to writeKey in request body
-    if let Some(write_key) = body_value.get("writeKey") {
-        if let Some(write_key_str) = write_key.as_str() {
-            // Validate UUID format
-            if Uuid::parse_str(write_key_str).is_ok() {
-                return Ok(write_key_str.to_string());
-            } else {
-                return Err(SegmentAuthError::InvalidFormat {
-                    source: "request_body".to_string(),
-                    write_key: write_key_str.to_string(),
-                });
-            }
-        }
+    match body_value.get("writeKey").and_then(|w| w.as_str()) {
+        Some(write_key) if !write_key.is_empty() => resolve_write_key(db, write_key, "request_body").await,
+        _ => Err(SegmentAuthError::Missing),
     }
-
-    Err(SegmentAuthError::Missing)
 }
 
 /// Detailed authentication errors for better debugging
 #[derive(Debug)]
 pub enum SegmentAuthError {
     Missing,
-    InvalidFormat { source: String, write_key: String },
+    InvalidFormat { source: String, reason: String },
+    UnknownWriteKey { source: String, write_key: String },
+    Database(String),
 }
 
 impl std::fmt::Display for SegmentAuthError {
@@ -96,11 +136,17 @@ impl std::fmt::Display for SegmentAuthError {
             SegmentAuthError::Missing => {
                 write!(f, "No write key found in Authorization header or request body")
             }
-            SegmentAuthError::InvalidFormat { source, write_key } => {
-                write!(f, "Invalid write key format from {source}: '{write_key}' is not a valid UUID")
+            SegmentAuthError::InvalidFormat { source, reason } => {
+                write!(f, "Invalid write key format from {source}: {reason}")
+            }
+            SegmentAuthError::UnknownWriteKey { source, write_key } => {
+                write!(f, "Unknown write key from {source}: '{write_key}' is neither a workflow ID nor a registered alias")
+            }
+            SegmentAuthError::Database(e) => {
+                write!(f, "Failed to look up write key: {e}")
             }
         }
     }
 }
 
-impl std::error::Error for SegmentAuthError {}
\ No newline at end of file
+impl std::error::Error for SegmentAuthError {}
@@ -1,11 +1,12 @@
 pub mod handlers;
 pub mod middleware;
+pub mod write_keys;
 
 use axum::{routing::post, Router, middleware as axum_middleware};
-use crate::AppState;
+use crate::{api::middleware::with_request_decompression, AppState};
 
 pub fn create_segment_routes() -> Router<AppState> {
-    Router::new()
+    let router = Router::new()
         .route("/v1/track", post(handlers::segment_track))
         .route("/v1/identify", post(handlers::segment_identify))
         .route("/v1/page", post(handlers::segment_page))
@@ -14,5 +15,6 @@ pub fn create_segment_routes() -> Router<AppState> {
         .route("/v1/alias", post(handlers::segment_alias))
         .route("/v1/batch", post(handlers::segment_batch))
         .route("/v1/import", post(handlers::segment_import))
-        .route_layer(axum_middleware::from_fn(middleware::segment_auth_middleware))
+        .route_layer(axum_middleware::from_fn(middleware::segment_auth_middleware));
+    with_request_decompression(router)
 }
\ No newline at end of file
@@ -0,0 +1,156 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{delete, get},
+    Router,
+};
+use sea_orm::{ActiveModelBehavior, ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::Deserialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    database::{entities, segment_write_keys},
+    AppState,
+};
+
+const MAX_WRITE_KEY_LENGTH: usize = 128;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateWriteKeyRequest {
+    pub workflow_id: String,
+    pub write_key: Option<String>, // Generated when omitted
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListWriteKeysQuery {
+    pub workflow_id: Option<String>,
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_write_keys).post(create_write_key))
+        .route("/:write_key", delete(delete_write_key))
+}
+
+/// List write key aliases, optionally for a single workflow
+pub async fn list_write_keys(
+    State(state): State<AppState>,
+    Query(query): Query<ListWriteKeysQuery>,
+) -> std::result::Result<Json<Value>, StatusCode> {
+    let mut select = segment_write_keys::Entity::find()
+        .order_by_asc(segment_write_keys::Column::CreatedAt);
+    if let Some(workflow_id) = &query.workflow_id {
+        select = select.filter(segment_write_keys::Column::WorkflowId.eq(workflow_id));
+    }
+
+    let write_keys = select.all(&*state.db).await.map_err(|e| {
+        tracing::error!(error = %e, "Failed to list Segment write keys");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(serde_json::json!({
+        "write_keys": write_keys
+    })))
+}
+
+/// Register a write key alias for a workflow
+pub async fn create_write_key(
+    State(state): State<AppState>,
+    Json(req): Json<CreateWriteKeyRequest>,
+) -> std::result::Result<(StatusCode, Json<Value>), StatusCode> {
+    let write_key = match req.write_key {
+        Some(write_key) => {
+            validate_write_key_alias(&write_key).map_err(|e| {
+                tracing::warn!(write_key = %write_key, "Rejected Segment write key: {}", e);
+                StatusCode::BAD_REQUEST
+            })?;
+            write_key
+        }
+        None => generate_write_key(),
+    };
+
+    let workflow = entities::Entity::find_by_id(&req.workflow_id)
+        .one(&*state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, workflow_id = %req.workflow_id, "Failed to find workflow for write key");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if workflow.is_none() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let existing = segment_write_keys::Entity::find_by_id(&write_key)
+        .one(&*state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to check for existing Segment write key");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if existing.is_some() {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let model = segment_write_keys::ActiveModel {
+        write_key: Set(write_key),
+        workflow_id: Set(req.workflow_id),
+        description: Set(req.description),
+        ..segment_write_keys::ActiveModel::new()
+    }
+    .insert(&*state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, "Failed to create Segment write key");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    tracing::info!("Segment write key created for workflow {}", model.workflow_id);
+    let body = serde_json::to_value(model).map_err(|e| {
+        tracing::error!(error = %e, "Failed to serialize Segment write key");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    Ok((StatusCode::CREATED, Json(body)))
+}
+
+/// Remove a write key alias
+pub async fn delete_write_key(
+    State(state): State<AppState>,
+    Path(write_key): Path<String>,
+) -> std::result::Result<StatusCode, StatusCode> {
+    let result = segment_write_keys::Entity::delete_by_id(&write_key)
+        .exec(&*state.db)
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to delete Segment write key");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    if result.rows_affected == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Check that an alias can be sent by Segment clients and is not mistaken for a workflow ID
+pub fn validate_write_key_alias(write_key: &str) -> Result<(), String> {
+    if write_key.is_empty() || write_key.len() > MAX_WRITE_KEY_LENGTH {
+        return Err(format!("Write key must be between 1 and {MAX_WRITE_KEY_LENGTH} characters"));
+    }
+    // Basic auth carries the write key as the username, which cannot contain ':'
+    if !write_key.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        return Err("Write key may only contain letters, digits, '-', '_' and '.'".to_string());
+    }
+    if Uuid::parse_str(write_key).is_ok() {
+        return Err("Write key must not be a UUID, those are reserved for workflow IDs".to_string());
+    }
+    Ok(())
+}
+
+fn generate_write_key() -> String {
+    // The prefix keeps the key from parsing as a workflow UUID
+    format!("wk_{}", Uuid::new_v4().simple())
+}
@@ -0,0 +1,190 @@
+use axum::{extract::ConnectInfo, http::StatusCode};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Mutex,
+};
+
+use crate::{utils::webhook_signature, workflow::models::Workflow, AppState, log_workflow_warn};
+
+/// Why a trigger request was turned away
+#[derive(Debug, Clone, Copy)]
+pub enum RejectionReason {
+    IpDenied,
+    InvalidApiKey,
+    InvalidSignature,
+}
+
+/// Trigger requests rejected by IP filter, API key or signature checks
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RejectionCounts {
+    pub ip_denied: u64,
+    pub invalid_api_key: u64,
+    pub invalid_signature: u64,
+}
+
+impl RejectionCounts {
+    fn record(&mut self, reason: RejectionReason) {
+        match reason {
+            RejectionReason::IpDenied => self.ip_denied += 1,
+            RejectionReason::InvalidApiKey => self.invalid_api_key += 1,
+            RejectionReason::InvalidSignature => self.invalid_signature += 1,
+        }
+    }
+}
+
+/// Rejection counts since startup, in total and per workflow
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TriggerAuthStats {
+    pub total: RejectionCounts,
+    pub workflows: HashMap<String, RejectionCounts>,
+}
+
+/// Counts rejected trigger requests for the stats endpoint
+///
+/// Requests are only counted once their workflow has been loaded, so there is at most
+/// one entry per existing workflow.
+#[derive(Debug, Default)]
+pub struct TriggerAuthMetrics {
+    stats: Mutex<TriggerAuthStats>,
+}
+
+impl TriggerAuthMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, workflow_id: &str, reason: RejectionReason) {
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        stats.total.record(reason);
+        stats.workflows.entry(workflow_id.to_string()).or_default().record(reason);
+    }
+
+    pub fn snapshot(&self) -> TriggerAuthStats {
+        self.stats.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+/// Whether `X-Forwarded-For` names the client, set with `SP_TRUST_FORWARDED_FOR`
+fn trust_forwarded_for() -> bool {
+    std::env::var("SP_TRUST_FORWARDED_FOR").is_ok_and(|v| v == "true")
+}
+
+/// The address a trigger request came from
+///
+/// Behind a reverse proxy that appends to `X-Forwarded-For`, the last address in the
+/// header is the client the proxy saw; earlier ones are supplied by the client itself.
+pub fn client_ip(headers: &HashMap<String, String>, connect_info: Option<ConnectInfo<SocketAddr>>) -> Option<IpAddr> {
+    if trust_forwarded_for() {
+        let forwarded = headers
+            .get("x-forwarded-for")
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok());
+        if forwarded.is_some() {
+            return forwarded;
+        }
+    }
+    connect_info.map(|ConnectInfo(addr)| addr.ip())
+}
+
+/// Reject requests from networks the workflow's Trigger node does not accept, or
+/// without the API key it requires
+pub async fn check_access(
+    state: &AppState,
+    workflow: &Workflow,
+    headers: &HashMap<String, String>,
+    query: Option<&str>,
+    client_ip: Option<IpAddr>,
+) -> Result<(), StatusCode> {
+    if let Some(ip_filter) = workflow.trigger_ip_filter() {
+        // Without a known client address there is nothing to match, so fail closed
+        if !client_ip.is_some_and(|ip| ip_filter.allows(ip)) {
+            let client = client_ip.map(|ip| ip.to_string()).unwrap_or_else(|| "unknown".to_string());
+            return Err(reject(state, workflow, RejectionReason::IpDenied, &format!("client {client} is not allowed"), StatusCode::FORBIDDEN));
+        }
+    }
+
+    if let Some(api_key) = workflow.trigger_api_key() {
+        let presented = headers.get(&api_key.header.to_ascii_lowercase()).cloned().or_else(|| {
+            let param = api_key.query_param.as_deref()?;
+            url::form_urlencoded::parse(query?.as_bytes())
+                .find(|(name, _)| name == param)
+                .map(|(_, value)| value.into_owned())
+        });
+        let Some(presented) = presented else {
+            return Err(reject(state, workflow, RejectionReason::InvalidApiKey, "missing API key", StatusCode::UNAUTHORIZED));
+        };
+
+        let expected = load_secret(workflow, &api_key.secret_variable, state).await?;
+        if !keys_match(&presented, &expected) {
+            return Err(reject(state, workflow, RejectionReason::InvalidApiKey, "invalid API key", StatusCode::UNAUTHORIZED));
+        }
+    }
+
+    Ok(())
+}
+
+/// Reject requests without the signature the workflow's Trigger node requires
+pub async fn verify_signature(
+    state: &AppState,
+    workflow: &Workflow,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+) -> Result<(), StatusCode> {
+    let Some(verification) = workflow.trigger_verification() else {
+        return Ok(());
+    };
+
+    let secret = load_secret(workflow, verification.secret_variable(), state).await?;
+    if let Err(reason) = webhook_signature::verify(verification, &secret, headers, body, chrono::Utc::now().timestamp()) {
+        return Err(reject(state, workflow, RejectionReason::InvalidSignature, &reason, StatusCode::UNAUTHORIZED));
+    }
+    Ok(())
+}
+
+/// Reject requests to a workflow whose Trigger node requires a signature, on routes
+/// such as the Segment API whose clients cannot sign what they send
+pub fn require_unsigned_trigger(state: &AppState, workflow: &Workflow) -> Result<(), StatusCode> {
+    if workflow.trigger_verification().is_some() {
+        return Err(reject(state, workflow, RejectionReason::InvalidSignature, "signed triggers cannot be called through the Segment API", StatusCode::UNAUTHORIZED));
+    }
+    Ok(())
+}
+
+fn reject(state: &AppState, workflow: &Workflow, reason: RejectionReason, detail: &str, status: StatusCode) -> StatusCode {
+    log_workflow_warn!(&workflow.id, format!("Rejected trigger request: {detail}"));
+    state.trigger_auth_metrics.record(&workflow.id, reason);
+    status
+}
+
+async fn load_secret(workflow: &Workflow, variable: &str, state: &AppState) -> Result<String, StatusCode> {
+    match state.variable_service.get_value(variable).await {
+        Ok(Some(secret)) => Ok(secret),
+        Ok(None) => {
+            tracing::error!(
+                workflow_id = %workflow.id,
+                "Trigger secret variable '{}' not found, rejecting trigger request",
+                variable
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+        Err(e) => {
+            tracing::error!(
+                workflow_id = %workflow.id,
+                error = %e,
+                "Failed to load trigger secret variable '{}'",
+                variable
+            );
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Compare keys through their digests, so the time taken reveals nothing about either key
+fn keys_match(presented: &str, expected: &str) -> bool {
+    let presented = Sha256::digest(presented.as_bytes());
+    let expected = Sha256::digest(expected.as_bytes());
+    presented.iter().zip(expected.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+}
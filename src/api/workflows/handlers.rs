@@ -12,6 +12,7 @@ use tracing::{info, debug};
 use crate::{
     database::{edges, entities, nodes},
     workflow::{
+        errors::SwissPipeError,
         models::{Edge, Node, NodeType, HttpMethod, RetryConfig, FailureAction},
         validation::WorkflowValidator,
     },
@@ -20,6 +21,7 @@ use crate::{
 
 use super::{
     types::*,
+    operations::{serialize_host_functions, validate_workflow_references},
 };
 
 // Custom JSON extractor for better error handling
@@ -187,6 +189,10 @@ pub async fn list_workflows(
             WorkflowResponse {
                 endpoint_url: format!("/api/v1/{workflow_id}/trigger"),
                 id: workflow_id.clone(),
+                allowed_host_functions: w.host_functions(),
+                error_workflow_id: w.error_workflow_id,
+                max_concurrent_executions: w.max_concurrent_executions,
+                scheduling_weight: w.scheduling_weight,
                 name: w.name,
                 description: w.description,
                 start_node_id: w.start_node_id.unwrap_or_else(|| {
@@ -276,6 +282,7 @@ pub async fn create_workflow(
         name: n.name.clone(),
         node_type: n.node_type.clone(),
         input_merge_strategy: None,
+        allowed_host_functions: request.allowed_host_functions.clone(),
     }).collect();
 
     // Determine start node ID and validate
@@ -326,9 +333,14 @@ pub async fn create_workflow(
                 workflow_id: workflow_id.clone(),
                 name: "Start".to_string(),
                 node_type: NodeType::Trigger {
-                    methods: vec![HttpMethod::Get, HttpMethod::Post, HttpMethod::Put]
+                    methods: vec![HttpMethod::Get, HttpMethod::Post, HttpMethod::Put],
+                    verification: None,
+                    api_key: None,
+                    ip_filter: None,
+                    rate_limit: None,
                 },
                 input_merge_strategy: None,
+                allowed_host_functions: request.allowed_host_functions.clone(),
             };
             nodes.insert(0, start_node);
             auto_start_id
@@ -366,6 +378,37 @@ pub async fn create_workflow(
         })));
     }
 
+    // SubWorkflow nodes and the error workflow must point at existing workflows
+    let references = validate_workflow_references(
+        &state.db,
+        &workflow_id,
+        &nodes,
+        request.error_workflow_id.as_deref(),
+        request.max_concurrent_executions,
+        request.scheduling_weight,
+    );
+    if let Err(e) = references.await {
+        tracing::warn!(
+            workflow_name = %request.name,
+            error = %e,
+            error_type = "sub_workflow_validation_failed",
+            "Workflow creation validation failed"
+        );
+        let response = match e {
+            SwissPipeError::Config(_) => (StatusCode::BAD_REQUEST, Json(ErrorResponse {
+                error: "WORKFLOW_VALIDATION_FAILED".to_string(),
+                message: "Workflow validation failed".to_string(),
+                details: Some(e.to_string()),
+            })),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, Json(ErrorResponse {
+                error: "DATABASE_ERROR".to_string(),
+                message: "Failed to validate workflow references".to_string(),
+                details: Some(e.to_string()),
+            })),
+        };
+        return Err(response);
+    }
+
     // Check for warnings and log them
     let warnings = WorkflowValidator::validate_condition_completeness(&nodes, &edges);
     for warning in warnings {
@@ -382,6 +425,10 @@ pub async fn create_workflow(
         name: Set(request.name.clone()),
         description: Set(request.description.clone()),
         start_node_id: Set(Some(start_node_id.clone())),
+        allowed_host_functions: Set(serialize_host_functions(&request.allowed_host_functions)),
+        error_workflow_id: Set(request.error_workflow_id.clone()),
+        max_concurrent_executions: Set(request.max_concurrent_executions),
+        scheduling_weight: Set(request.scheduling_weight),
         ..Default::default()
     };
 
@@ -450,6 +497,11 @@ pub async fn create_workflow(
                 NodeType::Trigger { .. } => "trigger".to_string(),
                 NodeType::Condition { .. } => "condition".to_string(),
                 NodeType::Transformer { .. } => "transformer".to_string(),
+                NodeType::Switch { .. } => "switch".to_string(),
+                NodeType::ForEach { .. } => "for_each".to_string(),
+                NodeType::Aggregate {} => "aggregate".to_string(),
+                NodeType::SubWorkflow { .. } => "sub_workflow".to_string(),
+                NodeType::Respond { .. } => "respond".to_string(),
                 NodeType::HttpRequest { .. } => "http_request".to_string(),
                 NodeType::OpenObserve { .. } => "openobserve".to_string(),
                 NodeType::Email { .. } => "email".to_string(),
@@ -566,6 +618,13 @@ pub async fn create_workflow(
                     retry_config: RetryConfig::default(),
                     headers: HashMap::new(),
                     loop_config: None,
+                    body: None,
+                    query_params: HashMap::new(),
+                    response_mapping: None,
+                    response_format: Default::default(),
+                    success_status_codes: Vec::new(),
+                    auth: None,
+                    forward_headers: Default::default(),
                 });
             NodeResponse {
                 id: node.id,
@@ -591,6 +650,10 @@ pub async fn create_workflow(
 
     let response = WorkflowResponse {
         id: workflow.id.clone(),
+        allowed_host_functions: workflow.host_functions(),
+        error_workflow_id: workflow.error_workflow_id.clone(),
+        max_concurrent_executions: workflow.max_concurrent_executions,
+        scheduling_weight: workflow.scheduling_weight,
         name: workflow.name,
         description: workflow.description,
         start_node_id: start_node_id.clone(),
@@ -675,6 +738,13 @@ pub async fn get_workflow(
                     retry_config: RetryConfig::default(),
                     headers: HashMap::new(),
                     loop_config: None,
+                    body: None,
+                    query_params: HashMap::new(),
+                    response_mapping: None,
+                    response_format: Default::default(),
+                    success_status_codes: Vec::new(),
+                    auth: None,
+                    forward_headers: Default::default(),
                 });
             NodeResponse {
                 id: node.id,
@@ -700,6 +770,10 @@ pub async fn get_workflow(
 
     let response = WorkflowResponse {
         id: workflow.id.clone(),
+        allowed_host_functions: workflow.host_functions(),
+        error_workflow_id: workflow.error_workflow_id.clone(),
+        max_concurrent_executions: workflow.max_concurrent_executions,
+        scheduling_weight: workflow.scheduling_weight,
         name: workflow.name,
         description: workflow.description,
         start_node_id: workflow.start_node_id.clone().ok_or_else(|| {
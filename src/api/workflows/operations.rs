@@ -1,7 +1,12 @@
 use std::collections::{HashMap, HashSet};
 use super::types::{NodeRequest, EdgeRequest, NodeOperations, EdgeOperations, NodeResponse, EdgeResponse, WorkflowResponse};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 use crate::database::{nodes, edges, entities};
-use crate::workflow::models::{NodeType, HttpMethod, RetryConfig, FailureAction};
+use crate::workflow::{
+    errors::{Result, SwissPipeError},
+    models::{Node, NodeType, HttpMethod, RetryConfig, FailureAction, HostFunction},
+    validation::WorkflowValidator,
+};
 
 /// Check if a node needs updating by comparing existing vs new data
 pub fn node_needs_update(existing: &nodes::Model, new: &NodeRequest) -> bool {
@@ -135,6 +140,11 @@ pub fn node_type_to_string(node_type: &NodeType) -> String {
         NodeType::Trigger { .. } => "trigger".to_string(),
         NodeType::Condition { .. } => "condition".to_string(),
         NodeType::Transformer { .. } => "transformer".to_string(),
+        NodeType::Switch { .. } => "switch".to_string(),
+        NodeType::ForEach { .. } => "for_each".to_string(),
+        NodeType::Aggregate {} => "aggregate".to_string(),
+        NodeType::SubWorkflow { .. } => "sub_workflow".to_string(),
+        NodeType::Respond { .. } => "respond".to_string(),
         NodeType::HttpRequest { .. } => "http_request".to_string(),
         NodeType::OpenObserve { .. } => "openobserve".to_string(),
         NodeType::Email { .. } => "email".to_string(),
@@ -144,6 +154,15 @@ pub fn node_type_to_string(node_type: &NodeType) -> String {
     }
 }
 
+/// Serialize a host function allowlist for the workflows table (empty lists are stored as NULL)
+pub fn serialize_host_functions(host_functions: &[HostFunction]) -> Option<String> {
+    if host_functions.is_empty() {
+        None
+    } else {
+        serde_json::to_string(host_functions).ok()
+    }
+}
+
 /// Convert database nodes to response format
 pub fn nodes_to_response(nodes: Vec<nodes::Model>) -> Vec<NodeResponse> {
     nodes
@@ -160,6 +179,13 @@ pub fn nodes_to_response(nodes: Vec<nodes::Model>) -> Vec<NodeResponse> {
                         retry_config: RetryConfig::default(),
                         headers: HashMap::new(),
                         loop_config: None,
+                        body: None,
+                        query_params: HashMap::new(),
+                        response_mapping: None,
+                        response_format: Default::default(),
+                        success_status_codes: Vec::new(),
+                        auth: None,
+                        forward_headers: Default::default(),
                     }
                 });
             NodeResponse {
@@ -199,6 +225,10 @@ pub fn build_workflow_response(
 
     WorkflowResponse {
         id: workflow.id.clone(),
+        allowed_host_functions: workflow.host_functions(),
+        error_workflow_id: workflow.error_workflow_id.clone(),
+        max_concurrent_executions: workflow.max_concurrent_executions,
+        scheduling_weight: workflow.scheduling_weight,
         name: workflow.name,
         description: workflow.description,
         start_node_id,
@@ -209,4 +239,86 @@ pub fn build_workflow_response(
         nodes: node_responses,
         edges: edge_responses,
     }
-}
\ No newline at end of file
+}
+
+/// Check the SubWorkflow nodes of a workflow being saved against the saved workflows
+///
+/// Every target must exist, and no chain of SubWorkflow nodes may lead back to
+/// `workflow_id`. Validation failures are `SwissPipeError::Config`.
+pub async fn validate_sub_workflow_nodes(db: &DatabaseConnection, workflow_id: &str, nodes: &[Node]) -> Result<()> {
+    let targets: HashSet<&str> = nodes.iter()
+        .filter_map(|node| match &node.node_type {
+            NodeType::SubWorkflow { workflow_id: target, .. } => Some(target.as_str()),
+            _ => None,
+        })
+        .collect();
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    for target in targets.iter().filter(|target| **target != workflow_id) {
+        if entities::Entity::find_by_id(*target).one(db).await?.is_none() {
+            return Err(SwissPipeError::Config(format!("SubWorkflow target workflow '{target}' not found")));
+        }
+    }
+
+    let sub_workflow_nodes = nodes::Entity::find()
+        .filter(nodes::Column::NodeType.eq("sub_workflow"))
+        .filter(nodes::Column::WorkflowId.ne(workflow_id))
+        .all(db)
+        .await?;
+
+    let mut references: HashMap<String, Vec<String>> = HashMap::new();
+    for node in sub_workflow_nodes {
+        if let Ok(NodeType::SubWorkflow { workflow_id: target, .. }) = serde_json::from_str(&node.config) {
+            references.entry(node.workflow_id).or_default().push(target);
+        }
+    }
+
+    WorkflowValidator::validate_sub_workflow_references(workflow_id, nodes, &references)
+}
+
+/// Check that a workflow's error workflow exists and is a different workflow
+///
+/// Validation failures are `SwissPipeError::Config`.
+pub async fn validate_error_workflow(db: &DatabaseConnection, workflow_id: &str, error_workflow_id: Option<&str>) -> Result<()> {
+    let Some(error_workflow_id) = error_workflow_id else {
+        return Ok(());
+    };
+    if error_workflow_id == workflow_id {
+        return Err(SwissPipeError::Config("A workflow cannot be its own error workflow".to_string()));
+    }
+    if entities::Entity::find_by_id(error_workflow_id).one(db).await?.is_none() {
+        return Err(SwissPipeError::Config(format!("Error workflow '{error_workflow_id}' not found")));
+    }
+    Ok(())
+}
+
+/// Check a workflow's settings and the workflows it references before it is saved
+///
+/// Every path that saves a workflow runs this so they cannot drift apart.
+/// Validation failures are `SwissPipeError::Config`.
+pub async fn validate_workflow_references(
+    db: &DatabaseConnection,
+    workflow_id: &str,
+    nodes: &[Node],
+    error_workflow_id: Option<&str>,
+    max_concurrent_executions: Option<i32>,
+    scheduling_weight: Option<i32>,
+) -> Result<()> {
+    validate_concurrency(max_concurrent_executions, scheduling_weight)?;
+    validate_sub_workflow_nodes(db, workflow_id, nodes).await?;
+    validate_error_workflow(db, workflow_id, error_workflow_id).await
+}
+
+/// Check that a workflow's concurrency limit and scheduling weight are positive when set
+///
+/// Validation failures are `SwissPipeError::Config`.
+pub fn validate_concurrency(max_concurrent_executions: Option<i32>, scheduling_weight: Option<i32>) -> Result<()> {
+    for (name, value) in [("max_concurrent_executions", max_concurrent_executions), ("scheduling_weight", scheduling_weight)] {
+        if value.is_some_and(|value| value <= 0) {
+            return Err(SwissPipeError::Config(format!("{name} must be greater than 0")));
+        }
+    }
+    Ok(())
+}
@@ -5,6 +5,7 @@ use uuid::Uuid;
 use crate::{
     database::{edges, entities, nodes},
     workflow::{
+        errors::SwissPipeError,
         models::{Edge, Node, NodeType},
         validation::WorkflowValidator,
     },
@@ -14,7 +15,7 @@ use crate::{
 use super::{
     types::{CreateWorkflowRequest, UpdateContext, PlannedOperations, UpdateResult, WorkflowResponse},
     validation::validate_workflow_update_request,
-    operations::{categorize_node_changes, categorize_edge_changes, node_type_to_string, build_workflow_response},
+    operations::{categorize_node_changes, categorize_edge_changes, node_type_to_string, build_workflow_response, serialize_host_functions, validate_workflow_references},
 };
 
 
@@ -121,6 +122,7 @@ impl<'a> UpdateWorkflowService<'a> {
             name: existing_start_node.name.clone(),
             node_type: start_node_config,
             input_merge_strategy: None,
+            allowed_host_functions: self.request.allowed_host_functions.clone(),
         };
 
         // Convert request nodes to internal models and add start node
@@ -131,6 +133,7 @@ impl<'a> UpdateWorkflowService<'a> {
             name: n.name.clone(),
             node_type: n.node_type.clone(),
             input_merge_strategy: None,
+            allowed_host_functions: self.request.allowed_host_functions.clone(),
         }));
         
         let internal_edges: Vec<Edge> = self.request.edges.iter().map(|e| Edge {
@@ -181,6 +184,26 @@ impl<'a> UpdateWorkflowService<'a> {
             return Err(StatusCode::BAD_REQUEST);
         }
 
+        // SubWorkflow nodes and the error workflow must point at existing workflows
+        let references = validate_workflow_references(
+            &self.state.db,
+            &self.workflow_id,
+            &context.internal_nodes,
+            self.request.error_workflow_id.as_deref(),
+            self.request.max_concurrent_executions,
+            self.request.scheduling_weight,
+        );
+        if let Err(e) = references.await {
+            tracing::warn!(
+                "Workflow reference validation failed: workflow_id={}, error='{}'",
+                self.workflow_id, e
+            );
+            return Err(match e {
+                SwissPipeError::Config(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            });
+        }
+
         // Check for warnings and log them
         let warnings = WorkflowValidator::validate_condition_completeness(&context.internal_nodes, &context.internal_edges);
         for warning in warnings {
@@ -191,6 +214,10 @@ impl<'a> UpdateWorkflowService<'a> {
         let mut updated_workflow: entities::ActiveModel = context.workflow.clone().into();
         updated_workflow.name = Set(self.request.name.clone());
         updated_workflow.description = Set(self.request.description.clone());
+        updated_workflow.allowed_host_functions = Set(serialize_host_functions(&self.request.allowed_host_functions));
+        updated_workflow.error_workflow_id = Set(self.request.error_workflow_id.clone());
+        updated_workflow.max_concurrent_executions = Set(self.request.max_concurrent_executions);
+        updated_workflow.scheduling_weight = Set(self.request.scheduling_weight);
         // Keep existing start_node_id - don't update it
 
         let updated_workflow = updated_workflow
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::workflow::models::NodeType;
+use crate::workflow::models::{HostFunction, NodeType};
 
 #[derive(Serialize)]
 pub struct ErrorResponse {
@@ -13,6 +13,14 @@ pub struct CreateWorkflowRequest {
     pub name: String,
     pub description: Option<String>,
     pub start_node_id: Option<String>, // Optional start node ID - if None, first trigger node is used
+    #[serde(default)]
+    pub allowed_host_functions: Vec<HostFunction>, // Opt-in host functions for JavaScript nodes
+    #[serde(default)]
+    pub error_workflow_id: Option<String>, // Workflow started when an execution of this one fails
+    #[serde(default)]
+    pub max_concurrent_executions: Option<i32>, // Executions run at the same time, unlimited when None
+    #[serde(default)]
+    pub scheduling_weight: Option<i32>, // Share of free workers among busy workflows, 1 when None
     pub nodes: Vec<NodeRequest>,
     pub edges: Vec<EdgeRequest>,
 }
@@ -59,6 +67,10 @@ pub struct WorkflowResponse {
     pub description: Option<String>,
     pub start_node_id: String,   // Starting node ID
     pub endpoint_url: String,
+    pub allowed_host_functions: Vec<HostFunction>,
+    pub error_workflow_id: Option<String>,
+    pub max_concurrent_executions: Option<i32>,
+    pub scheduling_weight: Option<i32>,
     pub enabled: bool,
     pub created_at: i64, // Unix epoch microseconds
     pub updated_at: i64, // Unix epoch microseconds
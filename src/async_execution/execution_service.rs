@@ -7,15 +7,36 @@ use crate::database::{
 use crate::utils::validation;
 use crate::workflow::{
     errors::{Result, SwissPipeError},
+    models::TriggerResponse,
 };
 use sea_orm::{
     ActiveModelTrait, DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, Set,
-    QuerySelect, QueryOrder, TransactionTrait, PaginatorTrait,
+    QuerySelect, QueryOrder, TransactionTrait, PaginatorTrait, sea_query::Expr,
 };
 use serde_json::Value;
 use std::sync::Arc;
 use uuid::Uuid;
 
+const BULK_INSERT_CHUNK_SIZE: usize = 100;
+
+/// First and longest delay between status checks in `wait_for_execution`
+const WAIT_POLL_INITIAL_MS: u64 = 20;
+const WAIT_POLL_MAX_MS: u64 = 250;
+
+fn max_retries() -> i32 {
+    std::env::var("SP_WORKFLOW_MAX_RETRIES")
+        .ok()
+        .and_then(|v| v.parse::<i32>().ok())
+        .unwrap_or(0)
+}
+
+/// The execution and SubWorkflow node that start a child execution
+#[derive(Debug, Clone, Copy)]
+pub struct ParentExecution<'a> {
+    pub execution_id: &'a str,
+    pub node_id: &'a str,
+}
+
 #[derive(Clone)]
 pub struct ExecutionService {
     db: Arc<DatabaseConnection>,
@@ -40,9 +61,285 @@ impl ExecutionService {
         
         // Sanitize headers by removing dangerous ones instead of rejecting the request
         let sanitized_headers = validation::validate_and_sanitize_headers(&headers)?;
-        
-        let execution_id = Uuid::now_v7().to_string();
+
+        let now = chrono::Utc::now().timestamp_micros();
+        let (execution, job) = Self::build_execution_records(
+            &workflow_id, input_data, &sanitized_headers, priority, max_retries(), now,
+        )?;
+        let execution_id = job.execution_id.clone().unwrap();
+
+        // Create execution and job records in a single transaction
+        let txn = self.db.begin().await?;
+        execution.insert(&txn).await?;
+        job.insert(&txn).await?;
+
+        // Commit the transaction - both records are created atomically
+        txn.commit().await?;
+
+        tracing::info!("Created execution {} with job queued", execution_id);
+        Ok(execution_id)
+    }
+
+    /// Create executions and job queue entries for many inputs of one workflow
+    ///
+    /// Inputs are validated individually; the ones that pass are inserted in a
+    /// single transaction. The returned results line up with `inputs`, holding the
+    /// execution ID or the validation error for each one. An error is returned
+    /// only when nothing could be written.
+    pub async fn create_executions(
+        &self,
+        workflow_id: &str,
+        inputs: Vec<Value>,
+        headers: &std::collections::HashMap<String, String>,
+        priority: Option<i32>,
+    ) -> Result<Vec<Result<String>>> {
+        validation::validate_workflow_id(workflow_id)?;
+        validation::validate_priority(priority)?;
+        let sanitized_headers = validation::validate_and_sanitize_headers(headers)?;
+
+        let now = chrono::Utc::now().timestamp_micros();
+        let max_retries = max_retries();
+
+        let mut results = Vec::with_capacity(inputs.len());
+        let mut executions = Vec::new();
+        let mut jobs = Vec::new();
+        for input_data in inputs {
+            match Self::build_execution_records(workflow_id, input_data, &sanitized_headers, priority, max_retries, now) {
+                Ok((execution, job)) => {
+                    results.push(Ok(job.execution_id.clone().unwrap()));
+                    executions.push(execution);
+                    jobs.push(job);
+                }
+                Err(e) => results.push(Err(e)),
+            }
+        }
+
+        if executions.is_empty() {
+            return Ok(results);
+        }
+
+        let txn = self.db.begin().await?;
+        // Chunked to stay under the database's bind parameter limit
+        for chunk in executions.chunks(BULK_INSERT_CHUNK_SIZE) {
+            workflow_executions::Entity::insert_many(chunk.to_vec()).exec(&txn).await?;
+        }
+        for chunk in jobs.chunks(BULK_INSERT_CHUNK_SIZE) {
+            job_queue::Entity::insert_many(chunk.to_vec()).exec(&txn).await?;
+        }
+        txn.commit().await?;
+
+        tracing::info!(
+            "Created {} executions with jobs queued for workflow {} ({} rejected)",
+            executions.len(),
+            workflow_id,
+            results.len() - executions.len()
+        );
+        Ok(results)
+    }
+
+    /// Create an execution for a workflow started by a SubWorkflow node
+    ///
+    /// With `queue` the child gets a job like any other execution. Otherwise it is
+    /// created as running, for the parent to run inline and then record the outcome
+    /// with `complete_execution`.
+    pub async fn create_child_execution(
+        &self,
+        parent: ParentExecution<'_>,
+        workflow_id: &str,
+        input_data: Value,
+        headers: &std::collections::HashMap<String, String>,
+        queue: bool,
+    ) -> Result<String> {
+        validation::validate_workflow_id(workflow_id)?;
+        let sanitized_headers = validation::validate_and_sanitize_headers(headers)?;
+
+        let now = chrono::Utc::now().timestamp_micros();
+        let (mut execution, job) = Self::build_execution_records(
+            workflow_id, input_data, &sanitized_headers, None, max_retries(), now,
+        )?;
+        let execution_id = job.execution_id.clone().unwrap();
+        execution.parent_execution_id = Set(Some(parent.execution_id.to_string()));
+        execution.parent_node_id = Set(Some(parent.node_id.to_string()));
+        if !queue {
+            execution.status = Set(ExecutionStatus::Running.to_string());
+            execution.started_at = Set(Some(now));
+        }
+
+        let txn = self.db.begin().await?;
+        execution.insert(&txn).await?;
+        if queue {
+            job.insert(&txn).await?;
+        }
+        txn.commit().await?;
+
+        tracing::info!(
+            "Created child execution {} of execution {} for workflow {}",
+            execution_id, parent.execution_id, workflow_id
+        );
+        Ok(execution_id)
+    }
+
+    /// Record the outcome of an execution that was run inline rather than by a worker
+    pub async fn complete_execution(
+        &self,
+        execution_id: &str,
+        result: &Result<crate::workflow::models::WorkflowEvent>,
+    ) -> Result<()> {
+        let execution = workflow_executions::Entity::find_by_id(execution_id)
+            .one(self.db.as_ref())
+            .await?
+            .ok_or_else(|| SwissPipeError::Generic(format!("Execution not found: {execution_id}")))?;
+
+        let now = chrono::Utc::now().timestamp_micros();
+        let mut execution: workflow_executions::ActiveModel = execution.into();
+        match result {
+            Ok(output) => {
+                execution.status = Set(ExecutionStatus::Completed.to_string());
+                execution.output_data = Set(Some(serde_json::to_string(&output.data)?));
+                execution.error_message = Set(None);
+            }
+            Err(e) => {
+                execution.status = Set(ExecutionStatus::Failed.to_string());
+                execution.error_message = Set(Some(e.to_string()));
+            }
+        }
+        execution.current_node_id = Set(None);
+        execution.completed_at = Set(Some(now));
+        execution.updated_at = Set(now);
+        execution.update(self.db.as_ref()).await?;
+
+        if result.is_err() {
+            if let Err(e) = self.start_error_workflow(execution_id).await {
+                tracing::warn!("Failed to start error workflow for execution {}: {}", execution_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the executions started by an execution's SubWorkflow nodes, oldest first
+    pub async fn get_child_executions(&self, execution_id: &str) -> Result<Vec<workflow_executions::Model>> {
+        let children = workflow_executions::Entity::find()
+            .filter(workflow_executions::Column::ParentExecutionId.eq(execution_id))
+            .order_by_asc(workflow_executions::Column::CreatedAt)
+            .all(self.db.as_ref())
+            .await?;
+
+        Ok(children)
+    }
+
+    /// Record the HTTP response a Respond node rendered for the execution's caller
+    ///
+    /// Only the first response is kept; returns `false` if one was already recorded.
+    pub async fn set_http_response(&self, execution_id: &str, response: &TriggerResponse) -> Result<bool> {
+        let response_json = serde_json::to_string(response)?;
+        let result = workflow_executions::Entity::update_many()
+            .col_expr(workflow_executions::Column::HttpResponse, Expr::value(response_json))
+            .col_expr(workflow_executions::Column::UpdatedAt, Expr::value(chrono::Utc::now().timestamp_micros()))
+            .filter(workflow_executions::Column::Id.eq(execution_id))
+            .filter(workflow_executions::Column::HttpResponse.is_null())
+            .exec(self.db.as_ref())
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Queue the error workflow of a failed execution's workflow, if it names one
+    ///
+    /// The error workflow receives the failed execution's ID, its workflow, the node
+    /// that failed and the error. Executions of an error workflow never start another
+    /// one, so error workflows cannot trigger each other in a loop. A failed execution
+    /// is reported at most once, and disabled error workflows are skipped. Returns the
+    /// ID of the queued execution.
+    pub async fn start_error_workflow(&self, execution_id: &str) -> Result<Option<String>> {
+        let Some(execution) = workflow_executions::Entity::find_by_id(execution_id)
+            .one(self.db.as_ref())
+            .await? else {
+            return Ok(None);
+        };
+        if execution.status != ExecutionStatus::Failed.to_string() || execution.failed_execution_id.is_some() {
+            return Ok(None);
+        }
+
+        let Some(workflow) = entities::Entity::find_by_id(&execution.workflow_id)
+            .one(self.db.as_ref())
+            .await? else {
+            return Ok(None);
+        };
+        let Some(error_workflow_id) = workflow.error_workflow_id else {
+            return Ok(None);
+        };
+        let Some(error_workflow) = entities::Entity::find_by_id(&error_workflow_id).one(self.db.as_ref()).await? else {
+            tracing::warn!(
+                "Error workflow {} of workflow {} no longer exists, not reporting failed execution {}",
+                error_workflow_id, workflow.id, execution_id
+            );
+            return Ok(None);
+        };
+        if !error_workflow.enabled {
+            tracing::info!(
+                "Error workflow {} of workflow {} is disabled, not reporting failed execution {}",
+                error_workflow_id, workflow.id, execution_id
+            );
+            return Ok(None);
+        }
+
+        let already_reported = workflow_executions::Entity::find()
+            .filter(workflow_executions::Column::FailedExecutionId.eq(execution_id))
+            .one(self.db.as_ref())
+            .await?
+            .is_some();
+        if already_reported {
+            return Ok(None);
+        }
+
+        // The latest failed step is the node that failed the execution, if a node failed at all
+        let failed_step = workflow_execution_steps::Entity::find()
+            .filter(workflow_execution_steps::Column::ExecutionId.eq(execution_id))
+            .filter(workflow_execution_steps::Column::Status.eq(StepStatus::Failed.to_string()))
+            .order_by_desc(workflow_execution_steps::Column::CreatedAt)
+            .one(self.db.as_ref())
+            .await?;
+
+        let input_data = serde_json::json!({
+            "execution_id": execution.id,
+            "workflow_id": workflow.id,
+            "workflow_name": workflow.name,
+            "node_id": failed_step.as_ref().map(|step| &step.node_id),
+            "node_name": failed_step.as_ref().map(|step| &step.node_name),
+            "error": execution.error_message,
+            "failed_at": execution.completed_at,
+        });
+
         let now = chrono::Utc::now().timestamp_micros();
+        let (mut error_execution, job) = Self::build_execution_records(
+            &error_workflow_id, input_data, &std::collections::HashMap::new(), None, max_retries(), now,
+        )?;
+        let error_execution_id = job.execution_id.clone().unwrap();
+        error_execution.failed_execution_id = Set(Some(execution_id.to_string()));
+
+        let txn = self.db.begin().await?;
+        error_execution.insert(&txn).await?;
+        job.insert(&txn).await?;
+        txn.commit().await?;
+
+        tracing::info!(
+            "Queued error workflow {} as execution {} for failed execution {}",
+            error_workflow_id, error_execution_id, execution_id
+        );
+        Ok(Some(error_execution_id))
+    }
+
+    /// Build the execution and job rows for one input, validating its size and depth
+    fn build_execution_records(
+        workflow_id: &str,
+        input_data: Value,
+        sanitized_headers: &std::collections::HashMap<String, String>,
+        priority: Option<i32>,
+        max_retries: i32,
+        now: i64,
+    ) -> Result<(workflow_executions::ActiveModel, job_queue::ActiveModel)> {
+        let execution_id = Uuid::now_v7().to_string();
 
         // Create complete execution data structure
         let execution_data = serde_json::json!({
@@ -54,18 +351,10 @@ impl ExecutionService {
         // Validate and serialize execution data once (eliminates duplicate serialization)
         let serialized_execution_data = validation::validate_and_serialize_execution_data(&execution_data)?;
 
-        // Create execution and job records in a single transaction
-        let max_retries = std::env::var("SP_WORKFLOW_MAX_RETRIES")
-            .ok()
-            .and_then(|v| v.parse::<i32>().ok())
-            .unwrap_or(0);
-
-        let txn = self.db.begin().await?;
-
         // Create workflow execution record
         let execution = workflow_executions::ActiveModel {
             id: Set(execution_id.clone()),
-            workflow_id: Set(workflow_id),
+            workflow_id: Set(workflow_id.to_string()),
             status: Set(ExecutionStatus::Pending.to_string()),
             current_node_id: Set(None),
             input_data: Set(Some(serialized_execution_data)),
@@ -75,14 +364,16 @@ impl ExecutionService {
             completed_at: Set(None),
             created_at: Set(now),
             updated_at: Set(now),
+            parent_execution_id: Set(None),
+            parent_node_id: Set(None),
+            failed_execution_id: Set(None),
+            http_response: Set(None),
         };
 
-        execution.insert(&txn).await?;
-
         // Create job queue entry
         let job = job_queue::ActiveModel {
             id: Set(Uuid::now_v7().to_string()),
-            execution_id: Set(execution_id.clone()),
+            execution_id: Set(execution_id),
             priority: Set(priority.unwrap_or(0)),
             scheduled_at: Set(now),
             claimed_at: Set(None),
@@ -96,13 +387,7 @@ impl ExecutionService {
             updated_at: Set(now),
         };
 
-        job.insert(&txn).await?;
-
-        // Commit the transaction - both records are created atomically
-        txn.commit().await?;
-
-        tracing::info!("Created execution {} with job queued", execution_id);
-        Ok(execution_id)
+        Ok((execution, job))
     }
 
     /// Get execution details by ID
@@ -115,6 +400,57 @@ impl ExecutionService {
         Ok(execution)
     }
 
+    /// Wait until an execution stops running, polling its status with backoff
+    ///
+    /// Returns the execution once it has completed, failed, been cancelled or
+    /// paused for human input, or `None` if `timeout` elapses first.
+    pub async fn wait_for_execution(
+        &self,
+        execution_id: &str,
+        timeout: std::time::Duration,
+    ) -> Result<Option<workflow_executions::Model>> {
+        self.wait_until(execution_id, timeout, |_| false).await
+    }
+
+    /// Wait until an execution stops running or a Respond node has set its HTTP response
+    pub async fn wait_for_response(
+        &self,
+        execution_id: &str,
+        timeout: std::time::Duration,
+    ) -> Result<Option<workflow_executions::Model>> {
+        self.wait_until(execution_id, timeout, |execution| execution.http_response.is_some()).await
+    }
+
+    async fn wait_until(
+        &self,
+        execution_id: &str,
+        timeout: std::time::Duration,
+        ready: impl Fn(&workflow_executions::Model) -> bool,
+    ) -> Result<Option<workflow_executions::Model>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let mut interval = std::time::Duration::from_millis(WAIT_POLL_INITIAL_MS);
+
+        loop {
+            let execution = self.get_execution(execution_id).await?
+                .ok_or_else(|| SwissPipeError::Generic(format!("Execution not found: {execution_id}")))?;
+
+            if ready(&execution) {
+                return Ok(Some(execution));
+            }
+            match ExecutionStatus::from(execution.status.clone()) {
+                ExecutionStatus::Pending | ExecutionStatus::Running => {}
+                _ => return Ok(Some(execution)),
+            }
+
+            let now = tokio::time::Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            tokio::time::sleep(interval.min(deadline - now)).await;
+            interval = (interval * 2).min(std::time::Duration::from_millis(WAIT_POLL_MAX_MS));
+        }
+    }
+
     /// Get execution steps by execution ID
     pub async fn get_execution_steps(&self, execution_id: &str) -> Result<Vec<workflow_execution_steps::Model>> {
         validation::validate_execution_id(execution_id)?;
@@ -1,5 +1,5 @@
 use std::sync::Arc;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tokio::sync::RwLock;
 use chrono::Utc;
 use sea_orm::{entity::Set, ActiveModelTrait, EntityTrait};
@@ -512,6 +512,8 @@ impl HttpLoopScheduler {
                             metadata: std::collections::HashMap::new(),
                             headers: std::collections::HashMap::new(),
                             condition_results: std::collections::HashMap::new(),
+                            switch_results: std::collections::HashMap::new(),
+                            failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
                         });
@@ -522,6 +524,8 @@ impl HttpLoopScheduler {
                             metadata: std::collections::HashMap::new(),
                             headers: std::collections::HashMap::new(),
                             condition_results: std::collections::HashMap::new(),
+                            switch_results: std::collections::HashMap::new(),
+                            failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
                         });
@@ -944,6 +948,7 @@ impl HttpLoopScheduler {
             initial_delay_ms: 100,
             max_delay_ms: 1000,
             backoff_multiplier: 1.0,
+            ..Default::default()
         };
 
         match app_executor.execute_http_request(
@@ -953,13 +958,16 @@ impl HttpLoopScheduler {
             &retry_config,
             event.clone(),
             &config.headers,
+            &crate::utils::http_client::RequestContent::default(),
         ).await {
-            Ok(response_event) => {
+            Ok(response) => {
                 // Successful HTTP response (2xx status codes)
-                let status_code = response_event.metadata
-                    .get("http_status")
-                    .and_then(|s| s.parse::<u16>().ok())
-                    .unwrap_or(200);
+                let status_code = response.status;
+                let mut response_event = event.clone();
+                response_event.metadata.insert("http_status".to_string(), status_code.to_string());
+                if !response.body.is_null() {
+                    response_event.data = response.body;
+                }
 
                 // Check response size limit
                 let response_size = serde_json::to_string(&response_event.data)
@@ -1147,6 +1155,8 @@ impl HttpLoopScheduler {
                     metadata: enhanced_metadata,
                     headers: workflow_event.headers.clone(),
                     condition_results: workflow_event.condition_results.clone(),
+                    switch_results: workflow_event.switch_results.clone(),
+                    failed_nodes: workflow_event.failed_nodes.clone(),
         hil_task: None,
         sources: Vec::new(),
                 })
@@ -1178,6 +1188,8 @@ impl HttpLoopScheduler {
                     metadata,
                     headers: HashMap::new(),
                     condition_results: HashMap::new(),
+                    switch_results: HashMap::new(),
+                    failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
                 })
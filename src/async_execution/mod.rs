@@ -8,7 +8,7 @@ pub mod mpsc_worker_pool;
 pub mod async_hil_service;
 pub mod step_tracker;
 
-pub use execution_service::ExecutionService;
+pub use execution_service::{ExecutionService, ParentExecution};
 pub use resumption_service::ResumptionService;
 pub use delay_scheduler::DelayScheduler;
 pub use cleanup_service::CleanupService;
@@ -1,8 +1,10 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use tokio::sync::mpsc;
-use sea_orm::{DatabaseConnection, EntityTrait, ColumnTrait, QueryFilter, QueryOrder, QuerySelect, Set, ActiveModelTrait};
+use sea_orm::{DatabaseConnection, EntityTrait, ColumnTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set, ActiveModelTrait, JoinType};
 use chrono::Utc;
 
+use crate::database::{entities, workflow_executions};
 use crate::database::job_queue::{self, JobStatus};
 use crate::workflow::errors::{Result, SwissPipeError};
 
@@ -26,12 +28,82 @@ pub struct JobMessage {
 /// - Multiple producers (ingestion endpoints, HIL responses) send jobs via channels
 /// - Single consumer pulls jobs from database and distributes via channels
 /// - Workers receive jobs from channels instead of competing for database claims
+///
+/// Jobs are only claimed for idle workers. Free workers are shared between workflows
+/// with pending jobs by weighted round-robin, skipping workflows already running
+/// their `max_concurrent_executions`, so a backlog on one workflow cannot hold up
+/// the others.
 #[derive(Clone)]
 pub struct MpscJobDistributor {
     db: Arc<DatabaseConnection>,
     job_sender: mpsc::UnboundedSender<JobMessage>,
     shutdown_sender: tokio::sync::broadcast::Sender<()>,
     metrics: Arc<tokio::sync::RwLock<MpscMetrics>>,
+    dispatch: Arc<Mutex<FairDispatch>>,
+}
+
+/// A workflow with pending jobs, as seen by one distribution cycle
+struct DispatchCandidate {
+    workflow_id: String,
+    /// Priority of the workflow's next job
+    priority: i32,
+    max_concurrent: Option<usize>,
+    weight: i64,
+}
+
+/// Jobs handed to workers but not yet finished, and the round-robin credit of each workflow
+///
+/// In-flight jobs are tracked in memory rather than counted from claimed rows, so jobs
+/// left claimed by a crashed process do not take up capacity after a restart.
+#[derive(Default)]
+struct FairDispatch {
+    /// Workflow of every job sent to a worker, by job ID
+    in_flight: HashMap<String, String>,
+    credits: HashMap<String, i64>,
+}
+
+impl FairDispatch {
+    fn running(&self, workflow_id: &str) -> usize {
+        self.in_flight.values().filter(|id| *id == workflow_id).count()
+    }
+
+    /// Forget the credit of workflows that no longer have pending jobs
+    fn retain_credits(&mut self, candidates: &[DispatchCandidate]) {
+        self.credits.retain(|id, _| candidates.iter().any(|c| c.workflow_id == *id));
+    }
+
+    /// Pick the workflow whose next job should go to a free worker
+    ///
+    /// Workflows at their concurrency limit are skipped and only those whose next job
+    /// has the highest priority compete. Among them the smooth weighted round-robin
+    /// credit decides: every competitor earns its weight and the winner pays back the
+    /// total, so over time each gets a share of workers proportional to its weight.
+    fn pick(&mut self, candidates: &[DispatchCandidate]) -> Option<usize> {
+        let eligible: Vec<usize> = (0..candidates.len())
+            .filter(|&i| {
+                let candidate = &candidates[i];
+                candidate.max_concurrent.is_none_or(|limit| self.running(&candidate.workflow_id) < limit)
+            })
+            .collect();
+        let priority = eligible.iter().map(|&i| candidates[i].priority).max()?;
+        let competing: Vec<usize> = eligible.into_iter().filter(|&i| candidates[i].priority == priority).collect();
+
+        let total: i64 = competing.iter().map(|&i| candidates[i].weight).sum();
+        let mut winner = None;
+        let mut best = i64::MIN;
+        for &i in &competing {
+            let credit = self.credits.entry(candidates[i].workflow_id.clone()).or_insert(0);
+            *credit += candidates[i].weight;
+            if *credit > best {
+                best = *credit;
+                winner = Some(i);
+            }
+        }
+        if let Some(i) = winner {
+            *self.credits.entry(candidates[i].workflow_id.clone()).or_insert(0) -= total;
+        }
+        winner
+    }
 }
 
 /// Metrics for monitoring MPSC job distribution performance
@@ -59,6 +131,7 @@ impl MpscJobDistributor {
             job_sender,
             shutdown_sender,
             metrics: Arc::new(tokio::sync::RwLock::new(MpscMetrics::default())),
+            dispatch: Arc::new(Mutex::new(FairDispatch::default())),
         };
 
         (distributor, job_receiver)
@@ -66,17 +139,18 @@ impl MpscJobDistributor {
 
     /// Start the single consumer that polls database and distributes jobs
     /// This eliminates the "thundering herd" problem by having only one process claim jobs
-    pub async fn start_consumer(&self, polling_interval_ms: u64) -> Result<()> {
-        let db = self.db.clone();
-        let job_sender = self.job_sender.clone();
-        let metrics = self.metrics.clone();
+    ///
+    /// At most `worker_count` jobs are handed out and unfinished at any time.
+    pub async fn start_consumer(&self, polling_interval_ms: u64, worker_count: usize) -> Result<()> {
+        let distributor = self.clone();
         let mut shutdown_receiver = self.shutdown_sender.subscribe();
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(polling_interval_ms));
+            let metrics = distributor.metrics.clone();
 
-            tracing::debug!("MPSC_AUDIT: Job consumer started - polling_interval_ms: {}, channel_capacity: unbounded",
-                          polling_interval_ms);
+            tracing::debug!("MPSC_AUDIT: Job consumer started - polling_interval_ms: {}, worker_count: {}",
+                          polling_interval_ms, worker_count);
 
             loop {
                 tokio::select! {
@@ -88,7 +162,7 @@ impl MpscJobDistributor {
                         }
 
                         // Claim and distribute jobs in single transaction to prevent race conditions
-                        match Self::claim_and_distribute_jobs(&db, &job_sender, &metrics).await {
+                        match distributor.claim_and_distribute_jobs(worker_count).await {
                             Ok(distributed_count) => {
                                 if distributed_count > 0 {
                                     tracing::trace!("MPSC_AUDIT: Jobs distributed in cycle - count: {}", distributed_count);
@@ -117,15 +191,13 @@ impl MpscJobDistributor {
         Ok(())
     }
 
-    /// Single atomic operation to claim jobs and distribute via channels
+    /// Claim jobs for idle workers and distribute them via channels
     /// Eliminates race conditions by having only one consumer access the job queue
-    async fn claim_and_distribute_jobs(
-        db: &DatabaseConnection,
-        job_sender: &mpsc::UnboundedSender<JobMessage>,
-        metrics: &Arc<tokio::sync::RwLock<MpscMetrics>>,
-    ) -> Result<usize> {
+    async fn claim_and_distribute_jobs(&self, worker_count: usize) -> Result<usize> {
         use sea_orm::TransactionTrait;
 
+        let db = self.db.as_ref();
+
         // Acquire global lock to ensure single consumer pattern (with timeout to prevent deadlocks)
         let _lock = match tokio::time::timeout(
             tokio::time::Duration::from_secs(30), // 30 second timeout
@@ -145,26 +217,34 @@ impl MpscJobDistributor {
 
         tracing::trace!("MPSC_AUDIT: Acquired global job consumer lock");
 
-        // Find available jobs without holding a transaction
-        let pending_jobs = job_queue::Entity::find()
-            .filter(job_queue::Column::Status.eq(JobStatus::Pending.to_string()))
-            .filter(job_queue::Column::ClaimedAt.is_null())
-            .order_by_desc(job_queue::Column::Priority)
-            .order_by_asc(job_queue::Column::ScheduledAt)
-            .limit(10) // Reduced batch size for shorter transactions
-            .all(db)
-            .await
-            .map_err(|e| SwissPipeError::Generic(format!("Failed to query pending jobs: {e}")))?;
+        let free_workers = worker_count.saturating_sub(self.lock_dispatch().in_flight.len());
+        if free_workers == 0 {
+            return Ok(0);
+        }
 
-        if pending_jobs.is_empty() {
+        // Find workflows with available jobs without holding a transaction
+        let mut candidates = Self::dispatch_candidates(db).await?;
+        if candidates.is_empty() {
             return Ok(0);
         }
+        self.lock_dispatch().retain_credits(&candidates);
 
         let consumer_id = format!("mpsc-consumer-{}", std::process::id());
         let mut distributed_count = 0;
 
         // Claim jobs individually with short transactions
-        for job in pending_jobs {
+        while distributed_count < free_workers {
+            let Some(index) = self.lock_dispatch().pick(&candidates) else {
+                // Every workflow with pending jobs is at its concurrency limit
+                break;
+            };
+            let workflow_id = candidates[index].workflow_id.clone();
+
+            let Some(job) = Self::next_pending_job(db, &workflow_id).await? else {
+                candidates.remove(index);
+                continue;
+            };
+
             // Each job gets its own short transaction
             let txn = db.begin().await
                 .map_err(|e| SwissPipeError::Generic(format!("Failed to begin individual job claim transaction: {e}")))?;
@@ -186,6 +266,7 @@ impl MpscJobDistributor {
                             "MPSC_AUDIT: Failed to commit transaction for already claimed job"
                         );
                     }
+                    candidates.remove(index);
                     continue;
                 }
                 None => {
@@ -197,6 +278,7 @@ impl MpscJobDistributor {
                             "MPSC_AUDIT: Failed to commit transaction for non-existent job"
                         );
                     }
+                    candidates.remove(index);
                     continue;
                 }
             };
@@ -219,6 +301,8 @@ impl MpscJobDistributor {
                             error = %e,
                             "MPSC_AUDIT: Failed to commit job claim"
                         );
+                        // Leave the workflow for the next cycle rather than retrying it here
+                        candidates.remove(index);
                         continue;
                     }
                 }
@@ -232,6 +316,7 @@ impl MpscJobDistributor {
                             "MPSC_AUDIT: Failed to rollback transaction for job"
                         );
                     }
+                    candidates.remove(index);
                     continue;
                 }
             }
@@ -247,7 +332,7 @@ impl MpscJobDistributor {
             };
 
             // Send to worker channel (unbounded, so this won't block)
-            if let Err(e) = job_sender.send(job_message) {
+            if let Err(e) = self.job_sender.send(job_message) {
                 tracing::error!(
                     job_id = %current_job.id,
                     execution_id = %current_job.execution_id,
@@ -256,21 +341,22 @@ impl MpscJobDistributor {
                 );
 
                 // Increment failure metrics
-                let mut m = metrics.write().await;
+                let mut m = self.metrics.write().await;
                 m.jobs_failed += 1;
 
-                continue;
+                break;
             }
 
+            self.lock_dispatch().in_flight.insert(current_job.id.clone(), workflow_id.clone());
             distributed_count += 1;
 
-            tracing::debug!("MPSC_AUDIT: Job claimed and distributed - job_id: {}, execution_id: {}, priority: {}",
-                          current_job.id, current_job.execution_id, current_job.priority);
+            tracing::debug!("MPSC_AUDIT: Job claimed and distributed - job_id: {}, execution_id: {}, workflow_id: {}, priority: {}",
+                          current_job.id, current_job.execution_id, workflow_id, current_job.priority);
         }
 
         // Update success metrics
         if distributed_count > 0 {
-            let mut m = metrics.write().await;
+            let mut m = self.metrics.write().await;
             m.jobs_distributed += distributed_count as u64;
             m.active_jobs_in_channel = m.active_jobs_in_channel.saturating_add(distributed_count as u64);
             m.last_distribution_timestamp = Some(Utc::now().timestamp_micros());
@@ -282,6 +368,67 @@ impl MpscJobDistributor {
         Ok(distributed_count)
     }
 
+    /// Workflows with pending jobs, with the priority of their next job and their scheduling settings
+    async fn dispatch_candidates(db: &DatabaseConnection) -> Result<Vec<DispatchCandidate>> {
+        let pending: Vec<(String, i32)> = job_queue::Entity::find()
+            .select_only()
+            .column(workflow_executions::Column::WorkflowId)
+            .column_as(job_queue::Column::Priority.max(), "priority")
+            .join(JoinType::InnerJoin, job_queue::Relation::WorkflowExecution.def())
+            .filter(job_queue::Column::Status.eq(JobStatus::Pending.to_string()))
+            .filter(job_queue::Column::ClaimedAt.is_null())
+            .group_by(workflow_executions::Column::WorkflowId)
+            .order_by_asc(workflow_executions::Column::WorkflowId)
+            .into_tuple()
+            .all(db)
+            .await
+            .map_err(|e| SwissPipeError::Generic(format!("Failed to query pending jobs: {e}")))?;
+
+        if pending.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let settings: HashMap<String, (Option<i32>, Option<i32>)> = entities::Entity::find()
+            .filter(entities::Column::Id.is_in(pending.iter().map(|(id, _)| id.clone())))
+            .all(db)
+            .await
+            .map_err(|e| SwissPipeError::Generic(format!("Failed to load workflow scheduling settings: {e}")))?
+            .into_iter()
+            .map(|workflow| (workflow.id, (workflow.max_concurrent_executions, workflow.scheduling_weight)))
+            .collect();
+
+        Ok(pending
+            .into_iter()
+            .map(|(workflow_id, priority)| {
+                let (max_concurrent, weight) = settings.get(&workflow_id).copied().unwrap_or_default();
+                DispatchCandidate {
+                    workflow_id,
+                    priority,
+                    max_concurrent: max_concurrent.map(|limit| limit.max(1) as usize),
+                    weight: i64::from(weight.unwrap_or(1).max(1)),
+                }
+            })
+            .collect())
+    }
+
+    /// The pending job of a workflow to run next, by priority and then queue order
+    async fn next_pending_job(db: &DatabaseConnection, workflow_id: &str) -> Result<Option<job_queue::Model>> {
+        job_queue::Entity::find()
+            .join(JoinType::InnerJoin, job_queue::Relation::WorkflowExecution.def())
+            .filter(workflow_executions::Column::WorkflowId.eq(workflow_id))
+            .filter(job_queue::Column::Status.eq(JobStatus::Pending.to_string()))
+            .filter(job_queue::Column::ClaimedAt.is_null())
+            .order_by_desc(job_queue::Column::Priority)
+            .order_by_asc(job_queue::Column::ScheduledAt)
+            .one(db)
+            .await
+            .map_err(|e| SwissPipeError::Generic(format!("Failed to query pending jobs: {e}")))
+    }
+
+    fn lock_dispatch(&self) -> std::sync::MutexGuard<'_, FairDispatch> {
+        self.dispatch.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
     /// Queue a new job (called by job producers like ingestion endpoints, HIL responses)
     /// Jobs are inserted into database, then picked up by the single consumer
     pub async fn queue_job(
@@ -321,6 +468,8 @@ impl MpscJobDistributor {
 
     /// Mark job as completed (called by workers after successful processing)
     pub async fn complete_job(&self, job_id: &str) -> Result<()> {
+        self.lock_dispatch().in_flight.remove(job_id);
+
         let job = job_queue::Entity::find_by_id(job_id)
             .one(self.db.as_ref())
             .await
@@ -347,6 +496,8 @@ impl MpscJobDistributor {
 
     /// Mark job as failed with retry logic
     pub async fn fail_job(&self, job_id: &str, error_message: &str) -> Result<bool> {
+        self.lock_dispatch().in_flight.remove(job_id);
+
         let job = job_queue::Entity::find_by_id(job_id)
             .one(self.db.as_ref())
             .await
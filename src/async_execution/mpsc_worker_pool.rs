@@ -209,7 +209,7 @@ impl MpscWorkerPool {
 
         // Start the MPSC job consumer (single consumer that pulls from database)
         tracing::info!("Starting MPSC job consumer with {}ms polling interval", self.config.mpsc_polling_interval_ms);
-        self.mpsc_distributor.start_consumer(self.config.mpsc_polling_interval_ms).await?;
+        self.mpsc_distributor.start_consumer(self.config.mpsc_polling_interval_ms, self.config.worker_count).await?;
         tracing::info!("MPSC job consumer started successfully");
 
         // Spawn worker tasks that process jobs from MPSC channels
@@ -385,6 +385,8 @@ impl MpscWorkerPool {
                                     metadata: std::collections::HashMap::new(),
                                     headers: std::collections::HashMap::new(),
                                     condition_results: std::collections::HashMap::new(),
+                                    switch_results: std::collections::HashMap::new(),
+                                    failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
                                 };
@@ -523,6 +525,16 @@ impl MpscWorkerPool {
                                 "MPSC job failed permanently",
                                 err
                             );
+
+                            // Report the failure to the workflow's error workflow once no retry follows
+                            if let Err(e) = self.execution_service.start_error_workflow(&job_message.execution_id).await {
+                                log_workflow_error!(
+                                    &job_message.execution_id,
+                                    &job_message.execution_id,
+                                    "Failed to start error workflow",
+                                    e
+                                );
+                            }
                         }
                     }
                     Err(fail_err) => {
@@ -660,6 +672,8 @@ impl MpscWorkerPool {
             metadata: std::collections::HashMap::new(),
             headers: std::collections::HashMap::new(),
             condition_results: std::collections::HashMap::new(),
+            switch_results: std::collections::HashMap::new(),
+            failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
         };
@@ -1046,6 +1060,8 @@ impl MpscWorkerPool {
                             headers,
                             metadata,
                             condition_results: std::collections::HashMap::new(),
+                            switch_results: std::collections::HashMap::new(),
+                            failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
                         }
@@ -1057,6 +1073,8 @@ impl MpscWorkerPool {
                             headers: std::collections::HashMap::new(),
                             metadata: std::collections::HashMap::new(),
                             condition_results: std::collections::HashMap::new(),
+                            switch_results: std::collections::HashMap::new(),
+                            failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
                         }
@@ -1080,6 +1098,8 @@ impl MpscWorkerPool {
                 headers: std::collections::HashMap::new(),
                 metadata: std::collections::HashMap::new(),
                 condition_results: std::collections::HashMap::new(),
+                switch_results: std::collections::HashMap::new(),
+                failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
             }
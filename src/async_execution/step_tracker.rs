@@ -4,6 +4,7 @@ use serde_json::Value;
 use uuid::Uuid;
 
 use crate::database::workflow_execution_steps::{self, StepStatus};
+use crate::utils::javascript_console::ConsoleLog;
 use crate::workflow::errors::{Result, SwissPipeError};
 
 /// Service for tracking workflow execution steps with granular node-level visibility
@@ -18,12 +19,15 @@ impl StepTracker {
     }
 
     /// Create a new execution step when node starts
+    ///
+    /// `item_index` is set for nodes running inside a ForEach body.
     pub async fn create_step(
         &self,
         execution_id: &str,
         node_id: &str,
         node_name: &str,
         input_data: Option<&Value>,
+        item_index: Option<usize>,
     ) -> Result<String> {
         let step_id = Uuid::now_v7().to_string();
         let now = chrono::Utc::now().timestamp_micros();
@@ -53,6 +57,8 @@ impl StepTracker {
             completed_at: Set(None),
             created_at: Set(now),
             sources: Set(sources_json),
+            logs: Set(None),
+            item_index: Set(item_index.map(|i| i as i32)),
         };
 
         new_step.insert(&*self.db).await
@@ -83,11 +89,12 @@ impl StepTracker {
         Ok(())
     }
 
-    /// Mark step as completed with output data
+    /// Mark step as completed with output data and any captured console output
     pub async fn complete_step(
         &self,
         step_id: &str,
         output_data: Option<&Value>,
+        console_log: Option<&ConsoleLog>,
     ) -> Result<()> {
         let step = workflow_execution_steps::Entity::find_by_id(step_id)
             .one(&*self.db)
@@ -112,6 +119,7 @@ impl StepTracker {
         active_step.output_data = Set(output_data.map(|d| serde_json::to_string(d).unwrap_or_else(|_| "{}".to_string())));
         active_step.error_message = Set(None);
         active_step.sources = Set(sources_json);
+        active_step.logs = Set(serialize_console_log(console_log));
 
         active_step.update(&*self.db).await
             .map_err(|e| SwissPipeError::Generic(format!("Failed to complete step: {e}")))?;
@@ -120,12 +128,13 @@ impl StepTracker {
         Ok(())
     }
 
-    /// Mark step as failed with error message
+    /// Mark step as failed with error message and any captured console output
     pub async fn fail_step(
         &self,
         step_id: &str,
         error_message: &str,
         output_data: Option<&Value>,
+        console_log: Option<&ConsoleLog>,
     ) -> Result<()> {
         let step = workflow_execution_steps::Entity::find_by_id(step_id)
             .one(&*self.db)
@@ -138,6 +147,7 @@ impl StepTracker {
         active_step.completed_at = Set(Some(chrono::Utc::now().timestamp_micros()));
         active_step.error_message = Set(Some(error_message.to_string()));
         active_step.output_data = Set(output_data.map(|d| serde_json::to_string(d).unwrap_or_else(|_| "{}".to_string())));
+        active_step.logs = Set(serialize_console_log(console_log));
 
         active_step.update(&*self.db).await
             .map_err(|e| SwissPipeError::Generic(format!("Failed to fail step: {e}")))?;
@@ -200,6 +210,8 @@ impl StepTracker {
             completed_at: Set(if status == StepStatus::Completed { Some(now) } else { None }),
             created_at: Set(now),
             sources: Set("[]".to_string()),
+            logs: Set(None),
+            item_index: Set(None),
         };
 
         new_step.insert(&*self.db).await
@@ -208,4 +220,8 @@ impl StepTracker {
         tracing::debug!("Created workflow step '{}' for execution {}", step_name, execution_id);
         Ok(step_id)
     }
-}
\ No newline at end of file
+}
+
+fn serialize_console_log(console_log: Option<&ConsoleLog>) -> Option<String> {
+    console_log.and_then(|log| serde_json::to_string(log).ok())
+}
@@ -12,10 +12,26 @@ pub struct Model {
     pub description: Option<String>,
     pub start_node_id: Option<String>,
     pub enabled: bool,
+    pub allowed_host_functions: Option<String>, // JSON serialized Vec<HostFunction>
+    pub error_workflow_id: Option<String>, // Workflow started when an execution of this one fails
+    pub max_concurrent_executions: Option<i32>, // Executions run at the same time, unlimited when NULL
+    pub scheduling_weight: Option<i32>, // Share of free workers among busy workflows, 1 when NULL
     pub created_at: i64, // Unix epoch microseconds
     pub updated_at: i64, // Unix epoch microseconds
 }
 
+impl Model {
+    /// Host functions this workflow exposes to its JavaScript nodes
+    ///
+    /// Unknown or malformed entries fall back to an empty allowlist.
+    pub fn host_functions(&self) -> Vec<crate::workflow::models::HostFunction> {
+        self.allowed_host_functions
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::nodes::Entity")]
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add allowed_host_functions column (JSON array) for the JavaScript sandbox allowlist
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workflows::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Workflows::AllowedHostFunctions)
+                            .text()
+                            .null() // NULL means no host functions are exposed
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workflows::Table)
+                    .drop_column(Workflows::AllowedHostFunctions)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Workflows {
+    Table,
+    AllowedHostFunctions,
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add logs column (JSON) holding console output captured from script nodes
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutionSteps::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(WorkflowExecutionSteps::Logs)
+                            .text()
+                            .null() // NULL when the step produced no console output
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutionSteps::Table)
+                    .drop_column(WorkflowExecutionSteps::Logs)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WorkflowExecutionSteps {
+    Table,
+    Logs,
+}
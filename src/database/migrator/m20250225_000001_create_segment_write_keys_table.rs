@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SegmentWriteKeys::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SegmentWriteKeys::WriteKey)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SegmentWriteKeys::WorkflowId)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SegmentWriteKeys::Description).text())
+                    .col(
+                        ColumnDef::new(SegmentWriteKeys::CreatedAt)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_segment_write_keys_workflow_id")
+                            .from(SegmentWriteKeys::Table, SegmentWriteKeys::WorkflowId)
+                            .to(Workflows::Table, Workflows::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Create index on workflow_id for listing a workflow's write keys
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_segment_write_keys_workflow_id")
+                    .table(SegmentWriteKeys::Table)
+                    .col(SegmentWriteKeys::WorkflowId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SegmentWriteKeys::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SegmentWriteKeys {
+    Table,
+    WriteKey,
+    WorkflowId,
+    Description,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Workflows {
+    Table,
+    Id,
+}
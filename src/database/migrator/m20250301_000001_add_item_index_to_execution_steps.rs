@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Add item_index column identifying the ForEach item a step ran for
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutionSteps::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(WorkflowExecutionSteps::ItemIndex)
+                            .integer()
+                            .null() // NULL for steps outside a ForEach body
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutionSteps::Table)
+                    .drop_column(WorkflowExecutionSteps::ItemIndex)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WorkflowExecutionSteps {
+    Table,
+    ItemIndex,
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Link executions started by a SubWorkflow node to the execution and node that started them
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutions::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(WorkflowExecutions::ParentExecutionId)
+                            .string()
+                            .null() // NULL for executions started by a trigger
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutions::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(WorkflowExecutions::ParentNodeId)
+                            .string()
+                            .null()
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx_workflow_executions_parent_execution_id")
+                    .table(WorkflowExecutions::Table)
+                    .col(WorkflowExecutions::ParentExecutionId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_workflow_executions_parent_execution_id").to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutions::Table)
+                    .drop_column(WorkflowExecutions::ParentNodeId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutions::Table)
+                    .drop_column(WorkflowExecutions::ParentExecutionId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WorkflowExecutions {
+    Table,
+    ParentExecutionId,
+    ParentNodeId,
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Workflow started whenever an execution of this workflow fails
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workflows::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Workflows::ErrorWorkflowId)
+                            .string()
+                            .null() // NULL means failures only show up in the execution list
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Link error workflow executions to the failed execution they report
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutions::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(WorkflowExecutions::FailedExecutionId)
+                            .string()
+                            .null()
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutions::Table)
+                    .drop_column(WorkflowExecutions::FailedExecutionId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workflows::Table)
+                    .drop_column(Workflows::ErrorWorkflowId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Workflows {
+    Table,
+    ErrorWorkflowId,
+}
+
+#[derive(DeriveIden)]
+enum WorkflowExecutions {
+    Table,
+    FailedExecutionId,
+}
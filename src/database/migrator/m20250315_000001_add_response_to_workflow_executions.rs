@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // HTTP response set by a Respond node for the caller of a synchronous trigger
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutions::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(WorkflowExecutions::HttpResponse)
+                            .text()
+                            .null() // NULL until a Respond node runs
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(WorkflowExecutions::Table)
+                    .drop_column(WorkflowExecutions::HttpResponse)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WorkflowExecutions {
+    Table,
+    HttpResponse,
+}
@@ -0,0 +1,64 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Executions of a workflow the job distributor runs at the same time
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workflows::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Workflows::MaxConcurrentExecutions)
+                            .integer()
+                            .null() // NULL means no limit besides the worker count
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Share of free workers a workflow gets when several have pending jobs
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workflows::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Workflows::SchedulingWeight)
+                            .integer()
+                            .null() // NULL means a weight of 1
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workflows::Table)
+                    .drop_column(Workflows::SchedulingWeight)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Workflows::Table)
+                    .drop_column(Workflows::MaxConcurrentExecutions)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Workflows {
+    Table,
+    MaxConcurrentExecutions,
+    SchedulingWeight,
+}
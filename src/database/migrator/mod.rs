@@ -22,6 +22,14 @@ mod m20250130_000001_create_environment_variables_table;
 mod m20250131_000001_create_scheduled_triggers_table;
 mod m20250201_000001_create_workflow_versions_table;
 mod m20250210_000001_add_sources_to_execution_steps;
+mod m20250215_000001_add_allowed_host_functions_to_workflows;
+mod m20250220_000001_add_logs_to_execution_steps;
+mod m20250225_000001_create_segment_write_keys_table;
+mod m20250301_000001_add_item_index_to_execution_steps;
+mod m20250305_000001_add_parent_to_workflow_executions;
+mod m20250310_000001_add_error_workflows;
+mod m20250315_000001_add_response_to_workflow_executions;
+mod m20250320_000001_add_workflow_concurrency;
 
 pub struct Migrator;
 
@@ -51,6 +59,14 @@ impl MigratorTrait for Migrator {
             Box::new(m20250131_000001_create_scheduled_triggers_table::Migration),
             Box::new(m20250201_000001_create_workflow_versions_table::Migration),
             Box::new(m20250210_000001_add_sources_to_execution_steps::Migration),
+            Box::new(m20250215_000001_add_allowed_host_functions_to_workflows::Migration),
+            Box::new(m20250220_000001_add_logs_to_execution_steps::Migration),
+            Box::new(m20250225_000001_create_segment_write_keys_table::Migration),
+            Box::new(m20250301_000001_add_item_index_to_execution_steps::Migration),
+            Box::new(m20250305_000001_add_parent_to_workflow_executions::Migration),
+            Box::new(m20250310_000001_add_error_workflows::Migration),
+            Box::new(m20250315_000001_add_response_to_workflow_executions::Migration),
+            Box::new(m20250320_000001_add_workflow_concurrency::Migration),
         ]
     }
 }
\ No newline at end of file
@@ -0,0 +1,39 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::{ActiveModelTrait, Set};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "segment_write_keys")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub write_key: String, // Alias sent by Segment clients in place of the workflow UUID
+    pub workflow_id: String,
+    pub description: Option<String>,
+    pub created_at: i64, // Unix epoch microseconds
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::entities::Entity",
+        from = "Column::WorkflowId",
+        to = "super::entities::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Workflow,
+}
+
+impl Related<super::entities::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Workflow.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {
+    fn new() -> Self {
+        Self {
+            created_at: Set(chrono::Utc::now().timestamp_micros()),
+            ..ActiveModelTrait::default()
+        }
+    }
+}
@@ -20,6 +20,8 @@ pub struct Model {
     pub created_at: i64, // Unix epoch microseconds
     #[sea_orm(column_type = "Text", default_value = "[]")]
     pub sources: String, // JSON array of NodeSource objects
+    pub logs: Option<String>, // JSON ConsoleLog captured from script nodes
+    pub item_index: Option<i32>, // ForEach item this step ran for
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
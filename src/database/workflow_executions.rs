@@ -18,6 +18,10 @@ pub struct Model {
     pub completed_at: Option<i64>, // Unix epoch microseconds
     pub created_at: i64, // Unix epoch microseconds
     pub updated_at: i64, // Unix epoch microseconds
+    pub parent_execution_id: Option<String>, // Execution whose SubWorkflow node started this one
+    pub parent_node_id: Option<String>,
+    pub failed_execution_id: Option<String>, // Failed execution this error workflow run reports
+    pub http_response: Option<String>, // JSON, response set by a Respond node
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
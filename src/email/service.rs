@@ -8,7 +8,7 @@ use lettre::{
     transport::smtp::{authentication::Credentials, client::{Tls, TlsParameters}},
 };
 use sea_orm::{DatabaseConnection, EntityTrait, ActiveModelTrait, Set, QueryFilter, ColumnTrait, QueryOrder, PaginatorTrait};
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{collections::{HashMap, HashSet}, sync::Arc, time::Duration};
 use governor::{Quota, RateLimiter, state::NotKeyed, state::InMemoryState, clock::DefaultClock};
 use uuid::Uuid;
 use std::num::NonZeroU32;
@@ -268,18 +268,18 @@ impl EmailService {
         builder = builder.subject(&email_message.subject);
         
         // Build message body
-        let body = if email_message.html_body.is_some() && email_message.text_body.is_some() {
+        let body = if let (Some(html_body), Some(text_body)) = (&email_message.html_body, &email_message.text_body) {
             // Multipart message
             MultiPart::alternative()
                 .singlepart(
                     SinglePart::builder()
                         .header(ContentType::TEXT_PLAIN)
-                        .body(email_message.text_body.as_ref().unwrap().clone())
+                        .body(text_body.clone())
                 )
                 .singlepart(
                     SinglePart::builder()
                         .header(ContentType::TEXT_HTML)
-                        .body(email_message.html_body.as_ref().unwrap().clone())
+                        .body(html_body.clone())
                 )
         } else if let Some(ref html_body) = email_message.html_body {
             // HTML only
@@ -526,6 +526,8 @@ impl EmailService {
                 metadata: HashMap::new(),
                 headers: HashMap::new(),
                 condition_results: HashMap::new(),
+                switch_results: HashMap::new(),
+                failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
             };
@@ -32,4 +32,6 @@ pub struct AppState {
     pub template_engine: Arc<variables::TemplateEngine>,
     pub schedule_service: Arc<schedule::ScheduleService>,
     pub version_service: Arc<versions::VersionService>,
+    pub trigger_auth_metrics: Arc<api::trigger_auth::TriggerAuthMetrics>,
+    pub ingestion_limiter: Arc<api::ingestion_limits::IngestionLimiter>,
 }
\ No newline at end of file
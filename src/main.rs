@@ -1,5 +1,5 @@
 use axum::middleware;
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
 
@@ -88,7 +88,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Start MPSC consumer (after worker pool is ready)
     tracing::info!("Starting MPSC job consumer...");
-    match mpsc_distributor.start_consumer(100).await { // 100ms polling interval
+    match mpsc_distributor.start_consumer(100, config.worker_pool.worker_count).await { // 100ms polling interval
         Ok(()) => {
             tracing::info!("MPSC job consumer started successfully");
         }
@@ -457,6 +457,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         template_engine,
         schedule_service,
         version_service,
+        trigger_auth_metrics: Arc::new(api::trigger_auth::TriggerAuthMetrics::new()),
+        ingestion_limiter: Arc::new(api::ingestion_limits::IngestionLimiter::new()),
     };
 
     // Build application
@@ -504,8 +506,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         tracing::info!("Received shutdown signal");
     };
 
-    // Start server with graceful shutdown
-    axum::serve(listener, app)
+    // Start server with graceful shutdown, keeping peer addresses for trigger IP filters
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal)
         .await?;
 
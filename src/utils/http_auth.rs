@@ -0,0 +1,223 @@
+use crate::workflow::errors::{AppError, SwissPipeError};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{header::HeaderValue, Client, Request};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Tokens are refreshed this long before they expire
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+/// Lifetime assumed for tokens issued without `expires_in`
+const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(300);
+
+/// Credentials an outgoing request authenticates with, resolved from environment variables
+#[derive(Debug, Clone)]
+pub enum RequestAuth {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+    /// Sent in `header`, or as the `query_param` query parameter when set
+    ApiKey { key: String, header: String, query_param: Option<String> },
+    OAuth2(OAuth2Credentials),
+    AwsSigV4(AwsCredentials),
+}
+
+/// Client credentials exchanged for a bearer token at `token_url`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OAuth2Credentials {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: Vec<String>,
+    pub audience: Option<String>,
+    /// Send the client credentials as form fields instead of HTTP Basic auth
+    pub credentials_in_body: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    pub region: String,
+    pub service: String,
+}
+
+struct CachedToken {
+    access_token: String,
+    refresh_at: Instant,
+}
+
+/// OAuth2 client-credentials tokens shared by every execution
+///
+/// Each set of credentials has its own lock, so concurrent requests wait for a single
+/// token request instead of each fetching a token.
+#[derive(Default)]
+pub struct OAuthTokenCache {
+    tokens: Mutex<HashMap<OAuth2Credentials, Arc<tokio::sync::Mutex<Option<CachedToken>>>>>,
+}
+
+impl OAuthTokenCache {
+    fn slot(&self, credentials: &OAuth2Credentials) -> Arc<tokio::sync::Mutex<Option<CachedToken>>> {
+        self.tokens
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(credentials.clone())
+            .or_default()
+            .clone()
+    }
+
+    /// A valid access token, requesting a new one when none is cached or it is about to expire
+    pub async fn token(&self, client: &Client, credentials: &OAuth2Credentials, timeout: Duration) -> Result<String, SwissPipeError> {
+        let slot = self.slot(credentials);
+        let mut cached = slot.lock().await;
+        if let Some(token) = cached.as_ref().filter(|token| Instant::now() < token.refresh_at) {
+            return Ok(token.access_token.clone());
+        }
+
+        let token = Self::request_token(client, credentials, timeout).await?;
+        let access_token = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+
+    /// Forget the cached token, e.g. after the service rejected it
+    pub async fn invalidate(&self, credentials: &OAuth2Credentials) {
+        *self.slot(credentials).lock().await = None;
+    }
+
+    async fn request_token(client: &Client, credentials: &OAuth2Credentials, timeout: Duration) -> Result<CachedToken, SwissPipeError> {
+        let failed = |error: String| SwissPipeError::App(AppError::TokenRequestFailed { error });
+
+        let mut form = vec![("grant_type", "client_credentials".to_string())];
+        if !credentials.scopes.is_empty() {
+            form.push(("scope", credentials.scopes.join(" ")));
+        }
+        if let Some(audience) = &credentials.audience {
+            form.push(("audience", audience.clone()));
+        }
+        let mut request = client.post(&credentials.token_url).timeout(timeout);
+        if credentials.credentials_in_body {
+            form.push(("client_id", credentials.client_id.clone()));
+            form.push(("client_secret", credentials.client_secret.clone()));
+        } else {
+            request = request.basic_auth(&credentials.client_id, Some(&credentials.client_secret));
+        }
+
+        let requested_at = Instant::now();
+        let response = request.form(&form).send().await.map_err(|e| failed(e.to_string()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(failed(format!("token endpoint returned status {}", status.as_u16())));
+        }
+        let body: serde_json::Value = response.json().await.map_err(|e| failed(format!("invalid token response: {e}")))?;
+        let access_token = body["access_token"]
+            .as_str()
+            .ok_or_else(|| failed("token response has no access_token".to_string()))?
+            .to_string();
+
+        let lifetime = body["expires_in"].as_u64().map(Duration::from_secs).unwrap_or(DEFAULT_TOKEN_LIFETIME);
+        // Refresh early, but never use a token for less than half its lifetime
+        let refresh_after = lifetime.saturating_sub(TOKEN_EXPIRY_MARGIN).max(lifetime / 2);
+        tracing::debug!("Fetched OAuth2 token from {} valid for {:?}", credentials.token_url, lifetime);
+
+        Ok(CachedToken { access_token, refresh_at: requested_at + refresh_after })
+    }
+}
+
+/// Sign a request with AWS Signature Version 4, adding the `x-amz-*` and `Authorization` headers
+///
+/// The host, the content type and every `x-amz-*` header are signed. S3 requests also
+/// carry the payload hash in `x-amz-content-sha256`.
+pub fn sign_aws_v4(request: &mut Request, credentials: &AwsCredentials, now: DateTime<Utc>) -> Result<(), String> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex::encode(Sha256::digest(request.body().and_then(|body| body.as_bytes()).unwrap_or_default()));
+    let is_s3 = credentials.service == "s3";
+
+    let header = |value: &str| HeaderValue::from_str(value).map_err(|e| format!("invalid header value: {e}"));
+    let headers = request.headers_mut();
+    headers.insert("x-amz-date", header(&amz_date)?);
+    if let Some(token) = &credentials.session_token {
+        headers.insert("x-amz-security-token", header(token)?);
+    }
+    if is_s3 {
+        headers.insert("x-amz-content-sha256", header(&payload_hash)?);
+    }
+
+    let url = request.url();
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{host}:{port}"),
+        (Some(host), None) => host.to_string(),
+        (None, _) => return Err("URL has no host".to_string()),
+    };
+    let mut signed: Vec<(String, String)> = vec![("host".to_string(), host)];
+    for (name, value) in request.headers() {
+        let name = name.as_str();
+        if name == "content-type" || name.starts_with("x-amz-") {
+            let value = value.to_str().map_err(|e| format!("header '{name}' is not text: {e}"))?;
+            signed.push((name.to_string(), value.split_whitespace().collect::<Vec<_>>().join(" ")));
+        }
+    }
+    signed.sort();
+    let signed_headers = signed.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+    let canonical_headers: String = signed.iter().map(|(name, value)| format!("{name}:{value}\n")).collect();
+
+    // Paths arrive percent-encoded once; every service except S3 expects them encoded twice
+    let canonical_path = match url.path() {
+        "" => "/".to_string(),
+        path if is_s3 => path.to_string(),
+        path => path.split('/').map(uri_encode).collect::<Vec<_>>().join("/"),
+    };
+    let mut query: Vec<(String, String)> = url.query_pairs().map(|(k, v)| (uri_encode(&k), uri_encode(&v))).collect();
+    query.sort();
+    let canonical_query = query.iter().map(|(k, v)| format!("{k}={v}")).collect::<Vec<_>>().join("&");
+
+    let canonical_request = format!(
+        "{}\n{canonical_path}\n{canonical_query}\n{canonical_headers}\n{signed_headers}\n{payload_hash}",
+        request.method().as_str()
+    );
+    let scope = format!("{date}/{}/{}/aws4_request", credentials.region, credentials.service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = [date.as_str(), &credentials.region, &credentials.service, "aws4_request"]
+        .iter()
+        .fold(format!("AWS4{}", credentials.secret_access_key).into_bytes(), |key, part| hmac_sha256(&key, part.as_bytes()));
+    let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        credentials.access_key_id
+    );
+    request.headers_mut().insert(reqwest::header::AUTHORIZATION, header(&authorization)?);
+    Ok(())
+}
+
+/// `Authorization` value for HTTP Basic auth
+pub fn basic_authorization(username: &str, password: &str) -> String {
+    format!("Basic {}", STANDARD.encode(format!("{username}:{password}")))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
@@ -1,13 +1,105 @@
+use crate::utils::{
+    http_auth::{self, OAuthTokenCache, RequestAuth},
+    retry::{self, AttemptError},
+};
 use crate::workflow::{
     errors::{AppError, SwissPipeError},
     models::{HttpMethod, RetryConfig, WorkflowEvent},
 };
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use reqwest::Client;
-use std::time::Duration;
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, ops::RangeInclusive, time::Duration};
+
+/// Body of an outgoing request, rendered from an HttpRequest node's templates
+#[derive(Debug, Clone)]
+pub enum RequestBody {
+    Json(serde_json::Value),
+    Form(Vec<(String, String)>),
+    Raw { body: String, content_type: Option<String> },
+}
+
+/// What a request sends besides its headers when the node does not simply forward the event data,
+/// and how it authenticates
+#[derive(Debug, Clone, Default)]
+pub struct RequestContent {
+    /// Sent instead of the event data
+    pub body: Option<RequestBody>,
+    /// Query parameters; on GET they replace the event data fields
+    pub query: Option<Vec<(String, String)>>,
+    /// Status codes treated as success; any 2xx when empty
+    pub success_statuses: Vec<RangeInclusive<u16>>,
+    pub auth: Option<RequestAuth>,
+}
+
+impl RequestContent {
+    fn is_success(&self, status: u16) -> bool {
+        if self.success_statuses.is_empty() {
+            return (200..=299).contains(&status);
+        }
+        self.success_statuses.iter().any(|range| range.contains(&status))
+    }
+}
+
+/// How a response body was decoded into [`HttpResponse::body`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyEncoding {
+    Empty,
+    Json,
+    Text,
+    Base64,
+}
+
+impl BodyEncoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BodyEncoding::Empty => "empty",
+            BodyEncoding::Json => "json",
+            BodyEncoding::Text => "text",
+            BodyEncoding::Base64 => "base64",
+        }
+    }
+}
+
+/// Response to an outgoing request
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// Header names are lowercase; repeated headers are joined with ", "
+    pub headers: BTreeMap<String, String>,
+    /// Parsed JSON, the text of a UTF-8 body or base64 of a binary one; null when empty
+    pub body: Value,
+    pub body_encoding: BodyEncoding,
+}
 
+impl HttpResponse {
+    fn decode_body(bytes: &[u8]) -> (Value, BodyEncoding) {
+        if bytes.is_empty() {
+            return (Value::Null, BodyEncoding::Empty);
+        }
+        if let Ok(value) = serde_json::from_slice(bytes) {
+            return (value, BodyEncoding::Json);
+        }
+        match std::str::from_utf8(bytes) {
+            Ok(text) => (Value::String(text.to_string()), BodyEncoding::Text),
+            Err(_) => (Value::String(STANDARD.encode(bytes)), BodyEncoding::Base64),
+        }
+    }
+
+    /// The whole response as `{status, headers, body, body_encoding}`
+    pub fn to_value(&self) -> Value {
+        json!({
+            "status": self.status,
+            "headers": self.headers,
+            "body": self.body,
+            "body_encoding": self.body_encoding.as_str(),
+        })
+    }
+}
 
 pub struct AppExecutor {
     client: Client,
+    oauth_tokens: OAuthTokenCache,
 }
 
 impl Default for AppExecutor {
@@ -27,6 +119,7 @@ impl AppExecutor {
             
         Self {
             client,
+            oauth_tokens: OAuthTokenCache::default(),
         }
     }
     
@@ -39,48 +132,20 @@ impl AppExecutor {
         retry_config: &RetryConfig,
         event: WorkflowEvent,
         node_headers: &std::collections::HashMap<String, String>,
-    ) -> Result<WorkflowEvent, SwissPipeError> {
+        content: &RequestContent,
+    ) -> Result<HttpResponse, SwissPipeError> {
         tracing::info!("Starting HTTP request execution: url={}, method={:?}, timeout={}s, max_attempts={}", 
             url, method, timeout_seconds, retry_config.max_attempts);
         
-        let mut attempts = 0;
-        let mut delay = Duration::from_millis(retry_config.initial_delay_ms);
-        
-        loop {
-            attempts += 1;
-            tracing::info!("HTTP request execution attempt {} of {}", attempts, retry_config.max_attempts);
-            
-            let start_time = std::time::Instant::now();
-            match self.execute_http_request_internal(url, method, timeout_seconds, &event, node_headers).await {
-                Ok(response_event) => {
-                    let elapsed = start_time.elapsed();
-                    tracing::info!("HTTP request execution succeeded on attempt {} after {:?}", attempts, elapsed);
-                    return Ok(response_event);
-                },
-                Err(e) if attempts >= retry_config.max_attempts => {
-                    let elapsed = start_time.elapsed();
-                    tracing::error!("HTTP request execution failed after {} attempts. Final attempt took {:?}. Error: {}", 
-                        attempts, elapsed, e);
-                    return Err(SwissPipeError::App(AppError::HttpRequestFailed {
-                        attempts,
-                        error: e.to_string(),
-                    }));
-                }
-                Err(e) => {
-                    let elapsed = start_time.elapsed();
-                    tracing::warn!("HTTP request execution attempt {} failed after {:?}, retrying in {:?}. Error: {}", 
-                        attempts, elapsed, delay, e);
-                    
-                    // Wait before retry
-                    tokio::time::sleep(delay).await;
-                    
-                    // Exponential backoff
-                    delay = Duration::from_millis(
-                        ((delay.as_millis() as f64) * retry_config.backoff_multiplier) as u64
-                    ).min(Duration::from_millis(retry_config.max_delay_ms));
-                }
-            }
-        }
+        let event = &event;
+        retry::run(retry_config, "HTTP request execution", || {
+            self.execute_http_request_internal(url, method, timeout_seconds, event, node_headers, content)
+        })
+        .await
+        .map_err(|e| SwissPipeError::App(AppError::HttpRequestFailed {
+            attempts: e.attempts,
+            error: e.error.to_string(),
+        }))
     }
 
     pub async fn execute_openobserve(
@@ -94,44 +159,15 @@ impl AppExecutor {
         tracing::info!("Starting OpenObserve execution: url={}, timeout={}s, max_attempts={}", 
             url, timeout_seconds, retry_config.max_attempts);
         
-        let mut attempts = 0;
-        let mut delay = Duration::from_millis(retry_config.initial_delay_ms);
-        
-        loop {
-            attempts += 1;
-            tracing::info!("OpenObserve execution attempt {} of {}", attempts, retry_config.max_attempts);
-            
-            let start_time = std::time::Instant::now();
-            match self.execute_openobserve_request(url, authorization_header, timeout_seconds, &event).await {
-                Ok(response_event) => {
-                    let elapsed = start_time.elapsed();
-                    tracing::info!("OpenObserve execution succeeded on attempt {} after {:?}", attempts, elapsed);
-                    return Ok(response_event);
-                },
-                Err(e) if attempts >= retry_config.max_attempts => {
-                    let elapsed = start_time.elapsed();
-                    tracing::error!("OpenObserve execution failed after {} attempts. Final attempt took {:?}. Error: {}", 
-                        attempts, elapsed, e);
-                    return Err(SwissPipeError::App(AppError::HttpRequestFailed {
-                        attempts,
-                        error: e.to_string(),
-                    }));
-                }
-                Err(e) => {
-                    let elapsed = start_time.elapsed();
-                    tracing::warn!("OpenObserve execution attempt {} failed after {:?}, retrying in {:?}. Error: {}", 
-                        attempts, elapsed, delay, e);
-                    
-                    // Wait before retry
-                    tokio::time::sleep(delay).await;
-                    
-                    // Exponential backoff
-                    delay = Duration::from_millis(
-                        ((delay.as_millis() as f64) * retry_config.backoff_multiplier) as u64
-                    ).min(Duration::from_millis(retry_config.max_delay_ms));
-                }
-            }
-        }
+        let event = &event;
+        retry::run(retry_config, "OpenObserve execution", || {
+            self.execute_openobserve_request(url, authorization_header, timeout_seconds, event)
+        })
+        .await
+        .map_err(|e| SwissPipeError::App(AppError::HttpRequestFailed {
+            attempts: e.attempts,
+            error: e.error.to_string(),
+        }))
     }
     
     async fn execute_http_request_internal(
@@ -141,21 +177,37 @@ impl AppExecutor {
         timeout_seconds: u64,
         event: &WorkflowEvent,
         node_headers: &std::collections::HashMap<String, String>,
-    ) -> Result<WorkflowEvent, SwissPipeError> {
+        content: &RequestContent,
+    ) -> Result<HttpResponse, AttemptError> {
         let timeout = Duration::from_secs(timeout_seconds);
         tracing::info!("Executing HTTP request: url={}, timeout={:?}", url, timeout);
         
         {
-                let mut request = match method {
-                    HttpMethod::Post => self.client.post(url).json(&event.data),
-                    HttpMethod::Put => self.client.put(url).json(&event.data),
-                    HttpMethod::Delete => self.client.delete(url).json(&event.data),
-                    HttpMethod::Patch => self.client.patch(url).json(&event.data),
-                    HttpMethod::Get => {
-                        // For GET, convert data to query parameters
-                        let query_params = self.json_to_query_params(&event.data)?;
-                        self.client.get(url).query(&query_params)
+                let request = match method {
+                    HttpMethod::Post => self.client.post(url),
+                    HttpMethod::Put => self.client.put(url),
+                    HttpMethod::Delete => self.client.delete(url),
+                    HttpMethod::Patch => self.client.patch(url),
+                    HttpMethod::Get => self.client.get(url),
+                };
+                let request = match (&content.query, method) {
+                    (Some(query), _) => request.query(query),
+                    // For GET, convert data to query parameters
+                    (None, HttpMethod::Get) => request.query(&self.json_to_query_params(&event.data).map_err(AttemptError::other)?),
+                    (None, _) => request,
+                };
+                let mut request = match (&content.body, method) {
+                    (Some(RequestBody::Json(body)), _) => request.json(body),
+                    (Some(RequestBody::Form(fields)), _) => request.form(fields),
+                    (Some(RequestBody::Raw { body, content_type }), _) => {
+                        let request = request.body(body.clone());
+                        match content_type {
+                            Some(content_type) => request.header(reqwest::header::CONTENT_TYPE, content_type),
+                            None => request,
+                        }
                     }
+                    (None, HttpMethod::Get) => request,
+                    (None, _) => request.json(&event.data),
                 };
                 
                 // Headers that should not be forwarded as they can cause issues
@@ -165,22 +217,10 @@ impl AppExecutor {
                     "te", "trailer"
                 ];
                 
-                // Merge headers from both sources, with event headers taking precedence
-                let mut combined_headers = node_headers.clone();
-                for (key, value) in &event.headers {
-                    let key_lower = key.to_lowercase();
-                    // Filter out problematic headers
-                    if !forbidden_headers.contains(&key_lower.as_str()) {
-                        combined_headers.insert(key.clone(), value.clone());
-                    } else {
-                        tracing::debug!("Filtering out forbidden header: '{}': '{}'", key, value);
-                    }
-                }
-                
-                // Add all headers to the request, validating each one
-                for (key, value) in &combined_headers {
+                // Add the node's headers to the request, validating each one. Trigger headers
+                // are only included when the node's forwarding policy merged them in
+                for (key, value) in node_headers {
                     let key_lower = key.to_lowercase();
-                    // Double-check forbidden headers (in case they came from node_headers)
                     if forbidden_headers.contains(&key_lower.as_str()) {
                         tracing::debug!("Skipping forbidden header: '{}': '{}'", key, value);
                         continue;
@@ -201,50 +241,105 @@ impl AppExecutor {
                     }
                 }
                 
-                tracing::info!("Sending HTTP request with {} headers: {:?}", combined_headers.len(), combined_headers);
+                tracing::info!("Sending HTTP request with {} headers", node_headers.len());
                 let request_start = std::time::Instant::now();
                 
-                let response = request
-                    .timeout(timeout)
-                    .send()
+                let mut request = request.timeout(timeout).build().map_err(|e| {
+                    AttemptError::other(AppError::HttpRequestFailed { attempts: 1, error: format!("Invalid request: {e}") })
+                })?;
+                if let Some(auth) = &content.auth {
+                    self.authenticate(&mut request, auth, timeout).await.map_err(AttemptError::other)?;
+                }
+                
+                let response = self.client
+                    .execute(request)
                     .await
                     .map_err(|e| {
                         let elapsed = request_start.elapsed();
                         tracing::error!("HTTP request failed after {:?}: {}", elapsed, e);
-                        AppError::HttpRequestFailed { attempts: 1, error: e.to_string() }
+                        AttemptError::transport(&e, AppError::HttpRequestFailed { attempts: 1, error: e.to_string() })
                     })?;
                 
                 let request_elapsed = request_start.elapsed();
                 tracing::info!("HTTP request completed in {:?}, status: {}", request_elapsed, response.status());
                 
-                if !response.status().is_success() {
-                    return Err(SwissPipeError::App(AppError::InvalidStatus {
-                        status: response.status().as_u16(),
-                    }));
+                let status = response.status().as_u16();
+                if status == 401 {
+                    // The token may have been revoked before it expired
+                    if let Some(RequestAuth::OAuth2(credentials)) = &content.auth {
+                        self.oauth_tokens.invalidate(credentials).await;
+                    }
+                }
+                if !content.is_success(status) {
+                    return Err(AttemptError::status(status, response.headers(), AppError::InvalidStatus { status }));
+                }
+                
+                let mut headers = BTreeMap::<String, String>::new();
+                for (name, value) in response.headers() {
+                    let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                    headers
+                        .entry(name.as_str().to_string())
+                        .and_modify(|existing| {
+                            existing.push_str(", ");
+                            existing.push_str(&value);
+                        })
+                        .or_insert(value);
                 }
                 
-                // Try to parse response as JSON, fallback to original event
-                let response_data = response.json::<serde_json::Value>().await
-                    .unwrap_or(event.data.clone());
+                let bytes = response.bytes().await.map_err(|e| {
+                    AttemptError::transport(&e, AppError::HttpRequestFailed { attempts: 1, error: format!("Failed to read response body: {e}") })
+                })?;
+                let (body, body_encoding) = HttpResponse::decode_body(&bytes);
                 
-                Ok(WorkflowEvent {
-                    data: response_data,
-                    metadata: event.metadata.clone(),
-                    headers: event.headers.clone(),
-                    condition_results: event.condition_results.clone(),
-        hil_task: None,
-        sources: Vec::new(),
-                })
+                Ok(HttpResponse { status, headers, body, body_encoding })
             }
     }
     
+    /// Add the credentials to a built request, replacing any `Authorization` header it already has
+    async fn authenticate(&self, request: &mut reqwest::Request, auth: &RequestAuth, timeout: Duration) -> Result<(), SwissPipeError> {
+        let invalid = |error: String| SwissPipeError::App(AppError::HttpRequestFailed { attempts: 1, error });
+        let sensitive = |value: &str| {
+            let mut value = reqwest::header::HeaderValue::from_str(value)
+                .map_err(|e| invalid(format!("Invalid credentials: {e}")))?;
+            value.set_sensitive(true);
+            Ok::<_, SwissPipeError>(value)
+        };
+
+        match auth {
+            RequestAuth::Basic { username, password } => {
+                let value = sensitive(&http_auth::basic_authorization(username, password))?;
+                request.headers_mut().insert(reqwest::header::AUTHORIZATION, value);
+            }
+            RequestAuth::Bearer { token } => {
+                request.headers_mut().insert(reqwest::header::AUTHORIZATION, sensitive(&format!("Bearer {token}"))?);
+            }
+            RequestAuth::ApiKey { key, query_param: Some(param), .. } => {
+                request.url_mut().query_pairs_mut().append_pair(param, key);
+            }
+            RequestAuth::ApiKey { key, header, query_param: None } => {
+                let name = reqwest::header::HeaderName::from_bytes(header.as_bytes())
+                    .map_err(|e| invalid(format!("Invalid API key header '{header}': {e}")))?;
+                request.headers_mut().insert(name, sensitive(key)?);
+            }
+            RequestAuth::OAuth2(credentials) => {
+                let token = self.oauth_tokens.token(&self.client, credentials, timeout).await?;
+                request.headers_mut().insert(reqwest::header::AUTHORIZATION, sensitive(&format!("Bearer {token}"))?);
+            }
+            RequestAuth::AwsSigV4(credentials) => {
+                http_auth::sign_aws_v4(request, credentials, chrono::Utc::now())
+                    .map_err(|e| invalid(format!("AWS SigV4 signing failed: {e}")))?;
+            }
+        }
+        Ok(())
+    }
+    
     async fn execute_openobserve_request(
         &self,
         url: &str,
         authorization_header: &str,
         timeout_seconds: u64,
         event: &WorkflowEvent,
-    ) -> Result<WorkflowEvent, SwissPipeError> {
+    ) -> Result<WorkflowEvent, AttemptError> {
         let timeout = Duration::from_secs(timeout_seconds);
         tracing::info!("Executing OpenObserve request: url={}, timeout={:?}", url, timeout);
         
@@ -268,7 +363,7 @@ impl AppExecutor {
             .map_err(|e| {
                 let elapsed = request_start.elapsed();
                 tracing::error!("OpenObserve request failed after {:?}: {}", elapsed, e);
-                AppError::HttpRequestFailed { attempts: 1, error: e.to_string() }
+                AttemptError::transport(&e, AppError::HttpRequestFailed { attempts: 1, error: e.to_string() })
             })?;
         
         let request_elapsed = request_start.elapsed();
@@ -279,8 +374,8 @@ impl AppExecutor {
                 // OpenObserve success - return original event for further processing
                 Ok(event.clone())
             }
-            401 => Err(SwissPipeError::App(AppError::AuthenticationFailed)),
-            status => Err(SwissPipeError::App(AppError::InvalidStatus { status })),
+            401 => Err(AttemptError::status(401, response.headers(), AppError::AuthenticationFailed)),
+            status => Err(AttemptError::status(status, response.headers(), AppError::InvalidStatus { status })),
         }
     }
     
@@ -1,43 +1,382 @@
-use crate::workflow::{errors::JavaScriptError, models::WorkflowEvent};
-use rquickjs::{Context, Runtime, Value};
+use crate::utils::javascript_console::{self, ConsoleCapture, ConsoleSlot};
+use crate::utils::javascript_helpers;
+use crate::utils::javascript_pool::{self, EntryPoint, JavaScriptPoolConfig, JavaScriptPoolStats, SandboxPool, ScriptCache};
+use crate::workflow::{errors::JavaScriptError, models::{HostFunction, ScriptLimits, WorkflowEvent}};
+use rquickjs::{object::Filter, CaughtError, Coerced, Context, Ctx, Exception, Function, Object, Runtime, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+const DEFAULT_MAX_EXECUTION_TIME_MS: u64 = 5000;
+const DEFAULT_MEMORY_LIMIT_BYTES: usize = 64 * 1024 * 1024;
+const DEFAULT_MAX_STACK_SIZE_BYTES: usize = 512 * 1024;
+const MAX_SCRIPT_SIZE_BYTES: usize = 10_000;
+
+/// Hardens a pooled sandbox's context once, before any user code runs in it
+///
+/// Dynamic code evaluation is removed by replacing every function constructor
+/// reachable from a function prototype, and all intrinsics (plus the `swisspipe`
+/// host object) are deep-frozen so scripts cannot tamper with shared built-ins.
+/// Properties that ordinary code commonly shadows on instances, such as
+/// `toString` or `Error.prototype.name`, are turned into accessors first so
+/// assigning them on an object still creates an own property.
+const HARDEN_INTRINSICS_SCRIPT: &str = r"
+(function () {
+    'use strict';
+    const { defineProperty, getOwnPropertyDescriptor, getPrototypeOf, freeze, isFrozen } = Object;
+
+    const blocked = function Function() {
+        throw new EvalError('Dynamic code evaluation is not allowed in the sandbox');
+    };
+    const functionPrototypes = [
+        Function.prototype,
+        getPrototypeOf(function* () {}),
+        getPrototypeOf(async function () {}),
+        getPrototypeOf(async function* () {}),
+    ];
+    for (const prototype of functionPrototypes) {
+        defineProperty(prototype, 'constructor', { value: blocked, writable: false, enumerable: false, configurable: false });
+    }
+    blocked.prototype = Function.prototype;
+    globalThis.Function = blocked;
+    delete globalThis.eval;
+
+    const overridable = (target, names) => {
+        for (const name of names) {
+            const descriptor = getOwnPropertyDescriptor(target, name);
+            if (!descriptor || !('value' in descriptor)) {
+                continue;
+            }
+            const value = descriptor.value;
+            defineProperty(target, name, {
+                get() { return value; },
+                set(newValue) {
+                    if (this === target) {
+                        throw new TypeError('Cannot assign to read only property ' + String(name));
+                    }
+                    defineProperty(this, name, { value: newValue, writable: true, enumerable: true, configurable: true });
+                },
+                enumerable: descriptor.enumerable,
+                configurable: false,
+            });
+        }
+    };
+    overridable(Object.prototype, [
+        'constructor', 'toString', 'toLocaleString', 'valueOf',
+        'hasOwnProperty', 'isPrototypeOf', 'propertyIsEnumerable',
+    ]);
+    for (const ErrorType of [Error, EvalError, RangeError, ReferenceError, SyntaxError, TypeError, URIError]) {
+        overridable(ErrorType.prototype, ['constructor', 'name', 'message', 'toString']);
+    }
+    overridable(Function.prototype, ['toString']);
+
+    const visited = new Set();
+    const deepFreeze = (root) => {
+        const pending = [root];
+        while (pending.length > 0) {
+            const target = pending.pop();
+            if ((typeof target !== 'object' && typeof target !== 'function') || target === null || target === globalThis || visited.has(target)) {
+                continue;
+            }
+            visited.add(target);
+            pending.push(getPrototypeOf(target));
+            for (const key of Reflect.ownKeys(target)) {
+                const descriptor = getOwnPropertyDescriptor(target, key);
+                if ('value' in descriptor) {
+                    pending.push(descriptor.value);
+                } else {
+                    pending.push(descriptor.get, descriptor.set);
+                }
+            }
+            if (!isFrozen(target)) {
+                freeze(target);
+            }
+        }
+    };
+
+    for (const name of Object.getOwnPropertyNames(globalThis)) {
+        const descriptor = getOwnPropertyDescriptor(globalThis, name);
+        deepFreeze(descriptor.value);
+        if (descriptor.configurable || descriptor.writable) {
+            defineProperty(globalThis, name, { writable: false, configurable: false });
+        }
+    }
+})();
+";
+
+/// Checks that a script left the global object itself as hardening left it
+const GLOBAL_OBJECT_INTACT_SCRIPT: &str =
+    "Object.isExtensible(globalThis) && Object.getPrototypeOf(globalThis) === Object.prototype";
+
+/// Resource limits enforced by the QuickJS runtime while a script is running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JavaScriptLimits {
+    pub max_execution_time: Duration,
+    pub memory_limit_bytes: usize,
+    pub max_stack_size_bytes: usize,
+}
+
+impl Default for JavaScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_execution_time: Duration::from_millis(DEFAULT_MAX_EXECUTION_TIME_MS),
+            memory_limit_bytes: DEFAULT_MEMORY_LIMIT_BYTES,
+            max_stack_size_bytes: DEFAULT_MAX_STACK_SIZE_BYTES,
+        }
+    }
+}
+
+impl JavaScriptLimits {
+    /// Load global limits from SP_JS_* environment variables, falling back to defaults
+    pub fn from_env() -> Self {
+        let max_execution_time_ms = std::env::var("SP_JS_MAX_EXECUTION_TIME_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_MAX_EXECUTION_TIME_MS);
+
+        let memory_limit_bytes = std::env::var("SP_JS_MEMORY_LIMIT_BYTES")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_MEMORY_LIMIT_BYTES);
+
+        let max_stack_size_bytes = std::env::var("SP_JS_MAX_STACK_SIZE_BYTES")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_MAX_STACK_SIZE_BYTES);
+
+        Self {
+            max_execution_time: Duration::from_millis(max_execution_time_ms),
+            memory_limit_bytes,
+            max_stack_size_bytes,
+        }
+    }
+
+    /// Apply per-node overrides on top of these limits
+    pub fn with_overrides(&self, overrides: Option<&ScriptLimits>) -> Self {
+        let Some(overrides) = overrides else {
+            return *self;
+        };
+
+        Self {
+            max_execution_time: overrides
+                .timeout_ms
+                .map(Duration::from_millis)
+                .unwrap_or(self.max_execution_time),
+            memory_limit_bytes: overrides.memory_limit_bytes.unwrap_or(self.memory_limit_bytes),
+            max_stack_size_bytes: overrides.max_stack_size_bytes.unwrap_or(self.max_stack_size_bytes),
+        }
+    }
+}
+
+/// Per-execution sandbox options layered on top of the executor defaults
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScriptOptions<'a> {
+    /// Per-node overrides for the global resource limits
+    pub limits: Option<&'a ScriptLimits>,
+    /// Host functions the workflow has opted in to
+    pub host_functions: &'a [HostFunction],
+    /// Environment variables served by `swisspipe.env` when that host function is allowed
+    pub variables: Option<&'a HashMap<String, String>>,
+    /// Collector for `console.*` output; calls are only traced when absent
+    pub console: Option<&'a ConsoleCapture>,
+}
+
+/// Per-execution state read by the host functions of a pooled sandbox
+#[derive(Default)]
+struct Session {
+    host_functions: Vec<HostFunction>,
+    variables: Arc<HashMap<String, String>>,
+}
+
+impl Session {
+    /// The running execution's variables, or a `TypeError` when it has not opted in to `host_function`
+    fn enabled(session: &Mutex<Session>, ctx: &Ctx<'_>, host_function: HostFunction) -> rquickjs::Result<Arc<HashMap<String, String>>> {
+        match session.lock() {
+            Ok(session) if session.host_functions.contains(&host_function) => Ok(session.variables.clone()),
+            _ => Err(Exception::throw_type(
+                ctx,
+                &format!("Host function 'swisspipe.{}' is not enabled for this workflow", host_function.js_name()),
+            )),
+        }
+    }
+}
+
+/// A QuickJS runtime with a hardened context, reused across executions
+///
+/// Limits, console capture, host functions and variables are applied when an
+/// execution starts and cleared by [`Sandbox::reset`], which also checks that
+/// the script left nothing behind on the global object.
+struct Sandbox {
+    // Declared before the runtime so the context is dropped first
+    context: Context,
+    runtime: Runtime,
+    console: ConsoleSlot,
+    session: Arc<Mutex<Session>>,
+    /// Global names present once hardening has finished
+    baseline_globals: HashSet<String>,
+}
+
+impl Sandbox {
+    /// Prepare the sandbox for one execution
+    fn begin(&self, limits: &JavaScriptLimits, options: &ScriptOptions<'_>) {
+        // Cap heap and native stack usage; QuickJS raises an uncatchable error when exceeded
+        self.runtime.set_memory_limit(limits.memory_limit_bytes);
+        self.runtime.set_max_stack_size(limits.max_stack_size_bytes);
+
+        if let Ok(mut console) = self.console.lock() {
+            *console = options.console.cloned();
+        }
+        if let Ok(mut session) = self.session.lock() {
+            let variables = match options.variables {
+                Some(variables) if options.host_functions.contains(&HostFunction::Env) => Arc::new(variables.clone()),
+                _ => Arc::default(),
+            };
+            *session = Session { host_functions: options.host_functions.to_vec(), variables };
+        }
+    }
+
+    /// Clear the finished execution's state and report whether the sandbox can be reused
+    fn reset(&self) -> bool {
+        self.runtime.set_interrupt_handler(None);
+        self.runtime.set_memory_limit(usize::MAX);
+        if let Ok(mut console) = self.console.lock() {
+            *console = None;
+        }
+        if let Ok(mut session) = self.session.lock() {
+            *session = Session::default();
+        }
+
+        // Promise jobs left behind would otherwise run during a later execution
+        let clean = !self.runtime.is_job_pending() && self.context.with(|ctx| self.restore_globals(&ctx)).unwrap_or(false);
+        self.runtime.run_gc();
+        clean
+    }
+
+    /// Remove globals the script added, failing when one cannot be removed or the global object was altered
+    fn restore_globals(&self, ctx: &Ctx<'_>) -> rquickjs::Result<bool> {
+        let globals = ctx.globals();
+        let keys = globals
+            .own_keys::<Value>(Filter::new().string().symbol())
+            .collect::<rquickjs::Result<Vec<_>>>()?;
+
+        for key in keys {
+            let name = key.as_string().map(|name| name.to_string()).transpose()?;
+            if !name.is_some_and(|name| self.baseline_globals.contains(&name)) {
+                globals.remove(key)?;
+            }
+        }
+
+        ctx.eval(GLOBAL_OBJECT_INTACT_SCRIPT)
+    }
+}
+
 pub struct JavaScriptExecutor {
-    max_execution_time: Duration,
+    limits: JavaScriptLimits,
+    sandbox_pool: SandboxPool<Sandbox>,
+    script_cache: ScriptCache,
 }
 
 impl JavaScriptExecutor {
     pub fn new() -> Result<Self, JavaScriptError> {
-        Ok(JavaScriptExecutor {
-            max_execution_time: Duration::from_millis(5000), // 5 second timeout
-        })
+        Ok(Self::with_config(JavaScriptLimits::from_env(), JavaScriptPoolConfig::from_env()))
     }
 
-    /// Create a secure sandboxed JavaScript context
-    fn create_secure_context(&self) -> Result<(Runtime, Context), JavaScriptError> {
-        let runtime = Runtime::new()
-            .map_err(|e| JavaScriptError::RuntimeError(e.to_string()))?;
+    pub fn with_limits(limits: JavaScriptLimits) -> Self {
+        Self::with_config(limits, JavaScriptPoolConfig::default())
+    }
+
+    pub fn with_config(limits: JavaScriptLimits, pool_config: JavaScriptPoolConfig) -> Self {
+        JavaScriptExecutor {
+            limits,
+            sandbox_pool: SandboxPool::new(pool_config.runtime_pool_size, Self::create_sandbox),
+            script_cache: ScriptCache::new(pool_config.script_cache_size),
+        }
+    }
+
+    pub fn limits(&self) -> JavaScriptLimits {
+        self.limits
+    }
+
+    /// Get sandbox pool and bytecode cache statistics
+    pub fn get_stats(&self) -> JavaScriptPoolStats {
+        javascript_pool::pool_stats(&self.sandbox_pool, &self.script_cache)
+    }
+
+    /// Create a runtime with a secure, hardened context for the sandbox pool
+    fn create_sandbox() -> Result<Sandbox, JavaScriptError> {
+        let runtime = Runtime::new().map_err(|e| JavaScriptError::RuntimeError(e.to_string()))?;
 
         // Create a full context (temporarily using full instead of base)
         let context = Context::full(&runtime)
             .map_err(|e| JavaScriptError::RuntimeError(e.to_string()))?;
 
+        let console = ConsoleSlot::default();
+        let session = Arc::new(Mutex::new(Session::default()));
+
         // Set up the secure sandbox
-        context.with(|ctx| {
+        let baseline_globals = context.with(|ctx| {
             // Remove dangerous globals that might still be accessible
-            self.remove_dangerous_globals(&ctx)?;
+            Self::remove_dangerous_globals(&ctx)?;
+
+            // Set up safe math, basic operations and the helper library
+            Self::setup_safe_environment(&ctx)?;
+
+            // Capture console output for the execution step
+            javascript_console::install(&ctx, &console)?;
 
-            // Set up safe math and basic operations
-            self.setup_safe_environment(&ctx)?;
+            // Expose the host functions, which check the running execution's opt-ins
+            Self::install_host_functions(&ctx, &session)?;
 
-            Ok::<(), JavaScriptError>(())
+            // Block dynamic code evaluation and freeze intrinsics, after which no global setup is possible
+            let _: Value = ctx.eval(HARDEN_INTRINSICS_SCRIPT)?;
+
+            Ok::<_, JavaScriptError>(ctx.globals().own_keys::<String>(Filter::new().string()).collect::<rquickjs::Result<HashSet<_>>>()?)
         })?;
 
-        Ok((runtime, context))
+        Ok(Sandbox { context, runtime, console, session, baseline_globals })
+    }
+
+    /// Run a cached script in a pooled sandbox and hand its entry point to `executor`
+    ///
+    /// Sandboxes are returned to the pool only when the script ran inside its function
+    /// wrapper, was not aborted for exceeding a limit, and left the globals clean.
+    async fn run_sandboxed<T, F>(
+        &self,
+        script: &str,
+        entry: EntryPoint,
+        options: &ScriptOptions<'_>,
+        executor: F,
+    ) -> Result<T, JavaScriptError>
+    where
+        F: for<'js> FnOnce(&rquickjs::Ctx<'js>, Value<'js>) -> rquickjs::Result<T>,
+        T: 'static,
+    {
+        let limits = self.limits.with_overrides(options.limits);
+        let sandbox = self.sandbox_pool.acquire(Self::create_sandbox).await?;
+        sandbox.begin(&limits, options);
+
+        let result = self.execute_with_limits(&sandbox.runtime, &sandbox.context, &limits, |ctx| {
+            let bytecode = self.script_cache.get_or_compile(ctx, script, entry)?;
+            let value = javascript_pool::run_bytecode(ctx, &bytecode)?;
+            executor(ctx, value)
+        });
+
+        // Declarations of scripts run for their completion value stay on the global scope
+        let reusable = entry != EntryPoint::Completion
+            && !matches!(
+                result,
+                Err(JavaScriptError::Timeout | JavaScriptError::MemoryLimitExceeded | JavaScriptError::StackOverflow)
+            );
+        if sandbox.reset() && reusable {
+            sandbox.release();
+        }
+
+        result
     }
 
     /// Remove dangerous global objects and functions
-    fn remove_dangerous_globals(&self, ctx: &rquickjs::Ctx) -> Result<(), JavaScriptError> {
+    fn remove_dangerous_globals(ctx: &rquickjs::Ctx) -> Result<(), JavaScriptError> {
         let global = ctx.globals();
 
         // List of dangerous globals to remove/disable
@@ -54,18 +393,11 @@ impl JavaScriptExecutor {
             }
         }
 
-        // Remove constructor access that could be used for escaping
-        // Note: Temporarily disabled to allow JavaScript functions to work
-        // let object_prototype: Object = ctx.eval("Object.prototype")?;
-        // if object_prototype.contains_key("constructor")? {
-        //     object_prototype.set("constructor", Value::new_undefined(ctx.clone()))?;
-        // }
-
         Ok(())
     }
 
     /// Set up safe environment with allowed operations
-    fn setup_safe_environment(&self, ctx: &rquickjs::Ctx) -> Result<(), JavaScriptError> {
+    fn setup_safe_environment(ctx: &rquickjs::Ctx) -> Result<(), JavaScriptError> {
         // Keep safe Math operations
         let _: Value = ctx.eval(r"
             // Ensure Math object is available for legitimate calculations
@@ -79,141 +411,312 @@ impl JavaScriptExecutor {
             }
         ")?;
 
+        // Rust-backed helpers (hashing, ids, base64, URL parsing, dates) on the swisspipe global
+        let swisspipe = Object::new(ctx.clone())?;
+        javascript_helpers::install(ctx, &swisspipe)?;
+        ctx.globals().set("swisspipe", swisspipe)?;
+
+        Ok(())
+    }
+
+    /// Add the host functions to the `swisspipe` global
+    ///
+    /// Every function is present, but one the running execution's workflow has not
+    /// opted in to throws, so scripts get a clear error instead of `undefined is not a function`.
+    fn install_host_functions(ctx: &Ctx<'_>, session: &Arc<Mutex<Session>>) -> Result<(), JavaScriptError> {
+        let swisspipe: Object = ctx.globals().get("swisspipe")?;
+
+        for &host_function in HostFunction::all() {
+            let session = session.clone();
+            let function = match host_function {
+                HostFunction::Log => Function::new(ctx.clone(), move |ctx: Ctx<'_>, message: Coerced<String>| -> rquickjs::Result<()> {
+                    Session::enabled(&session, &ctx, host_function)?;
+                    tracing::info!(target: "swisspipe::script", "{}", message.0);
+                    Ok(())
+                })?,
+                HostFunction::Env => {
+                    javascript_helpers::env_function(ctx, move |ctx| Session::enabled(&session, ctx, host_function))?
+                }
+            };
+            swisspipe.set(host_function.js_name(), function)?;
+        }
+
         Ok(())
     }
 
     /// Execute JavaScript with timeout and memory limits
-    fn execute_with_limits<T, F>(&self, context: &Context, executor: F) -> Result<T, JavaScriptError>
+    ///
+    /// The deadline is enforced by a runtime interrupt handler, so long-running
+    /// scripts are aborted mid-execution instead of blocking the worker thread.
+    fn execute_with_limits<T, F>(
+        &self,
+        runtime: &Runtime,
+        context: &Context,
+        limits: &JavaScriptLimits,
+        executor: F,
+    ) -> Result<T, JavaScriptError>
     where
         F: FnOnce(&rquickjs::Ctx) -> rquickjs::Result<T>,
         T: 'static,
     {
-        let start_time = Instant::now();
-
-        let result = context.with(|ctx| {
-            // Check execution time limit
-            if start_time.elapsed() > self.max_execution_time {
-                return Err(rquickjs::Error::new_from_js("script", "Execution timeout exceeded"));
+        let deadline = Instant::now() + limits.max_execution_time;
+        let timed_out = Arc::new(AtomicBool::new(false));
+
+        let handler_flag = timed_out.clone();
+        runtime.set_interrupt_handler(Some(Box::new(move || {
+            if Instant::now() >= deadline {
+                handler_flag.store(true, Ordering::Relaxed);
+                true
+            } else {
+                false
             }
+        })));
 
-            executor(&ctx)
-        });
+        let result = context.with(|ctx| executor(&ctx));
+
+        runtime.set_interrupt_handler(None);
 
         match result {
             Ok(value) => Ok(value),
-            Err(e) => {
-                if start_time.elapsed() > self.max_execution_time {
-                    Err(JavaScriptError::ExecutionError("Execution timeout exceeded".to_string()))
+            Err(_) if timed_out.load(Ordering::Relaxed) => Err(JavaScriptError::Timeout),
+            Err(error) => {
+                // Lift the memory cap so the pending exception can be inspected safely
+                runtime.set_memory_limit(usize::MAX);
+
+                Err(context.with(|ctx| Self::classify_error(CaughtError::from_error(&ctx, error))))
+            }
+        }
+    }
+
+    /// Map QuickJS resource errors to their dedicated error variants
+    ///
+    /// When an allocation fails while QuickJS is already building the out-of-memory
+    /// error, the engine gives up and throws `null` instead, so that is treated as
+    /// memory exhaustion as well.
+    fn classify_error(caught: CaughtError<'_>) -> JavaScriptError {
+        match &caught {
+            CaughtError::Error(rquickjs::Error::Allocation) => JavaScriptError::MemoryLimitExceeded,
+            CaughtError::Value(value) if value.is_null() => JavaScriptError::MemoryLimitExceeded,
+            CaughtError::Exception(exception) => {
+                let message = exception.message().unwrap_or_default().to_lowercase();
+                if message.contains("out of memory") {
+                    JavaScriptError::MemoryLimitExceeded
+                } else if message.contains("stack overflow") {
+                    JavaScriptError::StackOverflow
                 } else {
-                    Err(JavaScriptError::ExecutionError(e.to_string()))
+                    JavaScriptError::ExecutionError(caught.to_string())
                 }
             }
+            _ => JavaScriptError::ExecutionError(caught.to_string()),
         }
     }
 
-    /// Validate script for basic security (pre-execution check)
+    /// Validate script before execution
+    ///
+    /// Only the size is checked here; everything else is enforced by the hardened
+    /// sandbox and the runtime limits.
     fn validate_script_security(&self, script: &str) -> Result<(), JavaScriptError> {
-        let script_lower = script.to_lowercase();
-
         // Check script length to prevent excessive memory usage
-        if script.len() > 10_000 {
+        if script.len() > MAX_SCRIPT_SIZE_BYTES {
             return Err(JavaScriptError::ValidationError("Script too long (max 10KB)".to_string()));
         }
 
-        // Check for suspicious patterns that might indicate injection attempts
-        let suspicious_patterns = [
-            "\\\\u", "\\\\x", "\\\\0", // Unicode/hex escaping
-            "string.fromcharcode", "fromcharcode", // Character code manipulation
-            "eval", "new\\s+function", "function\\s*constructor", // Dynamic code execution
-            "\\.constructor\\s*\\(", "__proto__", "prototype\\.constructor", // Prototype pollution
-            "import", "require", "module", "exports", // Module system
-            "while\\s*\\(\\s*true", "for\\s*\\(\\s*;\\s*;", // Infinite loops
-        ];
+        Ok(())
+    }
+
+    pub async fn execute_condition(&self, script: &str, event: &WorkflowEvent) -> Result<bool, JavaScriptError> {
+        self.execute_condition_with_limits(script, event, None).await
+    }
+
+    /// Execute a condition script with optional per-node limit overrides
+    pub async fn execute_condition_with_limits(
+        &self,
+        script: &str,
+        event: &WorkflowEvent,
+        overrides: Option<&ScriptLimits>,
+    ) -> Result<bool, JavaScriptError> {
+        let options = ScriptOptions {
+            limits: overrides,
+            ..ScriptOptions::default()
+        };
+        self.execute_condition_with_options(script, event, options).await
+    }
+
+    /// Execute a condition script with per-node limits and the workflow's host functions
+    pub async fn execute_condition_with_options(
+        &self,
+        script: &str,
+        event: &WorkflowEvent,
+        options: ScriptOptions<'_>,
+    ) -> Result<bool, JavaScriptError> {
+        // Validate script security before execution
+        self.validate_script_security(script)?;
 
-        for pattern in &suspicious_patterns {
-            if regex::Regex::new(pattern)
-                .map_err(|e| JavaScriptError::ValidationError(format!("Regex error: {e}")))?
-                .is_match(&script_lower)
-            {
-                return Err(JavaScriptError::SecurityError(
-                    format!("Suspicious pattern detected: {pattern}")
-                ));
+        let event_json = serde_json::to_string(event)
+            .map_err(|e| JavaScriptError::SerializationError(e.to_string()))?;
+
+        tracing::info!("Executing secure JavaScript condition (first 200 chars): {}", script.chars().take(200).collect::<String>());
+        tracing::debug!("Full condition script:\n{}", script);
+        tracing::debug!("Event JSON (first 500 chars): {}", event_json.chars().take(500).collect::<String>());
+
+        let result = self.run_sandboxed(script, EntryPoint::Function("condition"), &options, |ctx, condition| {
+            // User provides the complete function implementation
+            let condition: Function = condition.get()?;
+            let event = ctx.json_parse(event_json.as_str())?;
+            condition.call((event,))
+        }).await;
+
+        match &result {
+            Ok(val) => tracing::info!("JavaScript condition result: {}", val),
+            Err(e) => {
+                tracing::error!("JavaScript condition error: {}", e);
+                tracing::error!("Failed script (first 500 chars): {}", script.chars().take(500).collect::<String>());
             }
         }
 
-        Ok(())
+        result
     }
 
-    pub async fn execute_condition(&self, script: &str, event: &WorkflowEvent) -> Result<bool, JavaScriptError> {
+    /// Execute a switch script's `route(event)` function and return the case label it picked
+    ///
+    /// Strings and numbers are returned as the label; `null` or `undefined` selects
+    /// no case (the default branch).
+    pub async fn execute_switch_with_options(
+        &self,
+        script: &str,
+        event: &WorkflowEvent,
+        options: ScriptOptions<'_>,
+    ) -> Result<Option<String>, JavaScriptError> {
         // Validate script security before execution
         self.validate_script_security(script)?;
 
         let event_json = serde_json::to_string(event)
             .map_err(|e| JavaScriptError::SerializationError(e.to_string()))?;
 
-        // Create secure sandbox
-        let (_runtime, context) = self.create_secure_context()?;
+        tracing::info!("Executing secure JavaScript switch (first 200 chars): {}", script.chars().take(200).collect::<String>());
+        tracing::debug!("Full switch script:\n{}", script);
+
+        let result = self.run_sandboxed(script, EntryPoint::Function("route"), &options, |ctx, route| {
+            let route: Function = route.get()?;
+            let event = ctx.json_parse(event_json.as_str())?;
+            let label: Value = route.call((event,))?;
+            if label.is_null() || label.is_undefined() {
+                Ok(None)
+            } else if label.is_string() || label.is_number() {
+                label.get::<rquickjs::Coerced<String>>().map(|label| Some(label.0))
+            } else {
+                Err(rquickjs::Error::new_from_js_message(
+                    label.type_name(),
+                    "string",
+                    "route() must return a case label string, null or undefined",
+                ))
+            }
+        }).await;
 
-        let result = self.execute_with_limits(&context, |ctx| {
-            // User provides the complete function implementation
-            let full_script = format!(
-                r"
-                {script}
-                condition({event_json});
-                "
-            );
+        match &result {
+            Ok(label) => tracing::info!("JavaScript switch result: {:?}", label),
+            Err(e) => {
+                tracing::error!("JavaScript switch error: {}", e);
+                tracing::error!("Failed script (first 500 chars): {}", script.chars().take(500).collect::<String>());
+            }
+        }
 
-            tracing::info!("Executing secure JavaScript condition (first 200 chars): {}", script.chars().take(200).collect::<String>());
-            tracing::debug!("Full condition script:\n{}", script);
-            tracing::debug!("Event JSON (first 500 chars): {}", event_json.chars().take(500).collect::<String>());
+        result
+    }
 
-            let result: rquickjs::Result<bool> = ctx.eval(full_script.as_bytes());
-            match &result {
-                Ok(val) => tracing::info!("JavaScript condition result: {}", val),
-                Err(e) => {
-                    tracing::error!("JavaScript condition error: {}", e);
-                    tracing::error!("Failed script (first 500 chars): {}", full_script.chars().take(500).collect::<String>());
-                }
+    /// Execute a ForEach selector script's `select(event)` function and return its value as JSON
+    ///
+    /// `undefined` is returned as `null`.
+    pub async fn execute_selector_with_options(
+        &self,
+        script: &str,
+        event: &WorkflowEvent,
+        options: ScriptOptions<'_>,
+    ) -> Result<serde_json::Value, JavaScriptError> {
+        // Validate script security before execution
+        self.validate_script_security(script)?;
+
+        let event_json = serde_json::to_string(event)
+            .map_err(|e| JavaScriptError::SerializationError(e.to_string()))?;
+
+        tracing::info!("Executing secure JavaScript selector (first 200 chars): {}", script.chars().take(200).collect::<String>());
+        tracing::debug!("Full selector script:\n{}", script);
+
+        let result = self.run_sandboxed(script, EntryPoint::Function("select"), &options, |ctx, select| {
+            let select: Function = select.get()?;
+            let event = ctx.json_parse(event_json.as_str())?;
+            let output: Value = select.call((event,))?;
+            match ctx.json_stringify(output)? {
+                Some(json) => json.to_string(),
+                None => Ok("null".to_string()),
             }
-            result
+        }).await;
+
+        let result = result.inspect_err(|e| {
+            tracing::error!("JavaScript selector error: {}", e);
+            tracing::error!("Failed script (first 500 chars): {}", script.chars().take(500).collect::<String>());
         })?;
 
-        Ok(result)
+        serde_json::from_str(&result).map_err(|e| JavaScriptError::SerializationError(e.to_string()))
     }
-    
+
     pub async fn execute_transformer(&self, script: &str, event: WorkflowEvent) -> Result<WorkflowEvent, JavaScriptError> {
+        self.execute_transformer_with_limits(script, event, None).await
+    }
+
+    /// Execute a transformer script with optional per-node limit overrides
+    pub async fn execute_transformer_with_limits(
+        &self,
+        script: &str,
+        event: WorkflowEvent,
+        overrides: Option<&ScriptLimits>,
+    ) -> Result<WorkflowEvent, JavaScriptError> {
+        let options = ScriptOptions {
+            limits: overrides,
+            ..ScriptOptions::default()
+        };
+        self.execute_transformer_with_options(script, event, options).await
+    }
+
+    /// Execute a transformer script with per-node limits and the workflow's host functions
+    pub async fn execute_transformer_with_options(
+        &self,
+        script: &str,
+        event: WorkflowEvent,
+        options: ScriptOptions<'_>,
+    ) -> Result<WorkflowEvent, JavaScriptError> {
         // Validate script security before execution
         self.validate_script_security(script)?;
 
         let event_json = serde_json::to_string(&event)
             .map_err(|e| JavaScriptError::SerializationError(e.to_string()))?;
 
-        // Create secure sandbox
-        let (_runtime, context) = self.create_secure_context()?;
+        tracing::info!("Executing secure JavaScript transformer (first 200 chars): {}", script.chars().take(200).collect::<String>());
+        tracing::debug!("Full transformer script:\n{}", script);
 
-        let result = self.execute_with_limits(&context, |ctx| {
+        let result = self.run_sandboxed(script, EntryPoint::Function("transformer"), &options, |ctx, transformer| {
             // User provides the complete function implementation
-            let full_script = format!(
-                r"
-                {script}
-                JSON.stringify(transformer({event_json}));
-                "
-            );
-
-            tracing::info!("Executing secure JavaScript transformer (first 200 chars): {}", script.chars().take(200).collect::<String>());
-            tracing::debug!("Full transformer script:\n{}", script);
+            let transformer: Function = transformer.get()?;
+            let event = ctx.json_parse(event_json.as_str())?;
+            let output: Value = transformer.call((event,))?;
+            match ctx.json_stringify(output)? {
+                Some(json) => json.to_string(),
+                None => Err(rquickjs::Error::new_from_js("undefined", "string")),
+            }
+        }).await;
 
-            let result: rquickjs::Result<String> = ctx.eval(full_script.as_bytes());
-            match &result {
-                Ok(val) => tracing::info!("JavaScript transformer result length: {}", val.len()),
-                Err(e) => {
-                    tracing::error!("JavaScript transformer error: {}", e);
-                    tracing::error!("Failed script (first 500 chars): {}", full_script.chars().take(500).collect::<String>());
-                }
+        let result = match result {
+            Ok(val) => {
+                tracing::info!("JavaScript transformer result length: {}", val.len());
+                val
             }
-            result
-        })?;
-        
+            Err(e) => {
+                tracing::error!("JavaScript transformer error: {}", e);
+                tracing::error!("Failed script (first 500 chars): {}", script.chars().take(500).collect::<String>());
+                return Err(e);
+            }
+        };
+
         // Handle null return (drop event case)
         if result == "null" {
             return Err(JavaScriptError::EventDropped);
@@ -232,6 +735,8 @@ impl JavaScriptExecutor {
                     metadata: event.metadata,
                     headers: event.headers,
                     condition_results: event.condition_results,
+                    switch_results: event.switch_results,
+                    failed_nodes: event.failed_nodes,
                     hil_task: event.hil_task,
                     sources: event.sources,
                 })
@@ -243,41 +748,29 @@ impl JavaScriptExecutor {
         // Validate script security before execution
         self.validate_script_security(script)?;
 
-        // Create secure sandbox
-        let (_runtime, context) = self.create_secure_context()?;
+        tracing::debug!("Executing secure raw JavaScript: {}", script);
 
-        let result = self.execute_with_limits(&context, |ctx| {
-            tracing::debug!("Executing secure raw JavaScript: {}", script);
-
-            let result: rquickjs::Result<bool> = ctx.eval(script.as_bytes());
-            match &result {
-                Ok(val) => tracing::debug!("Raw JavaScript result: {}", val),
-                Err(e) => tracing::error!("Raw JavaScript error: {}", e),
-            }
-            result
-        })?;
+        let result = self.run_sandboxed(script, EntryPoint::Completion, &ScriptOptions::default(), |_, value| value.get::<bool>()).await;
+        match &result {
+            Ok(val) => tracing::debug!("Raw JavaScript result: {}", val),
+            Err(e) => tracing::error!("Raw JavaScript error: {}", e),
+        }
 
-        Ok(result)
+        result
     }
 
     pub async fn execute_numeric(&self, script: &str) -> Result<f64, JavaScriptError> {
         // Validate script security before execution
         self.validate_script_security(script)?;
 
-        // Create secure sandbox
-        let (_runtime, context) = self.create_secure_context()?;
-
-        let result = self.execute_with_limits(&context, |ctx| {
-            tracing::debug!("Executing secure numeric JavaScript: {}", script);
+        tracing::debug!("Executing secure numeric JavaScript: {}", script);
 
-            let result: rquickjs::Result<f64> = ctx.eval(script.as_bytes());
-            match &result {
-                Ok(val) => tracing::debug!("Numeric JavaScript result: {}", val),
-                Err(e) => tracing::error!("Numeric JavaScript error: {}", e),
-            }
-            result
-        })?;
+        let result = self.run_sandboxed(script, EntryPoint::Completion, &ScriptOptions::default(), |_, value| value.get::<f64>()).await;
+        match &result {
+            Ok(val) => tracing::debug!("Numeric JavaScript result: {}", val),
+            Err(e) => tracing::error!("Numeric JavaScript error: {}", e),
+        }
 
-        Ok(result)
+        result
     }
-}
\ No newline at end of file
+}
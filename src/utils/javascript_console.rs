@@ -0,0 +1,167 @@
+use rquickjs::function::Rest;
+use rquickjs::{Coerced, Ctx, Function, Object, Value};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+const MAX_CONSOLE_ENTRIES: usize = 100;
+const MAX_MESSAGE_BYTES: usize = 2 * 1024;
+const MAX_TOTAL_BYTES: usize = 32 * 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleLevel {
+    Debug,
+    Info,
+    Log,
+    Warn,
+    Error,
+}
+
+impl ConsoleLevel {
+    fn method_name(&self) -> &'static str {
+        match self {
+            ConsoleLevel::Debug => "debug",
+            ConsoleLevel::Info => "info",
+            ConsoleLevel::Log => "log",
+            ConsoleLevel::Warn => "warn",
+            ConsoleLevel::Error => "error",
+        }
+    }
+}
+
+/// A single console call made by a script
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConsoleEntry {
+    pub level: ConsoleLevel,
+    pub message: String,
+    pub timestamp: i64, // Unix epoch microseconds
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool, // Message was cut to the per-message size cap
+}
+
+/// Console output captured from one script execution
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ConsoleLog {
+    pub entries: Vec<ConsoleEntry>,
+    /// Number of console calls discarded after the entry or size caps were reached
+    #[serde(default)]
+    pub dropped: usize,
+}
+
+impl ConsoleLog {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.dropped == 0
+    }
+
+    fn total_bytes(&self) -> usize {
+        self.entries.iter().map(|entry| entry.message.len()).sum()
+    }
+}
+
+/// Collects `console.*` calls from a sandboxed script, bounded by size caps
+///
+/// The capture is shared with the script's context, so anything logged before a
+/// script fails or times out is still available to the caller.
+#[derive(Debug, Clone, Default)]
+pub struct ConsoleCapture {
+    log: Arc<Mutex<ConsoleLog>>,
+}
+
+impl ConsoleCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snapshot of everything captured so far
+    pub fn log(&self) -> ConsoleLog {
+        self.log.lock().map(|log| log.clone()).unwrap_or_default()
+    }
+
+    fn record(&self, level: ConsoleLevel, mut message: String) {
+        let Ok(mut log) = self.log.lock() else {
+            return;
+        };
+
+        let truncated = message.len() > MAX_MESSAGE_BYTES;
+        if truncated {
+            let mut end = MAX_MESSAGE_BYTES;
+            while !message.is_char_boundary(end) {
+                end -= 1;
+            }
+            message.truncate(end);
+        }
+
+        if log.entries.len() >= MAX_CONSOLE_ENTRIES || log.total_bytes() + message.len() > MAX_TOTAL_BYTES {
+            log.dropped += 1;
+            return;
+        }
+
+        log.entries.push(ConsoleEntry {
+            level,
+            message,
+            timestamp: chrono::Utc::now().timestamp_micros(),
+            truncated,
+        });
+    }
+}
+
+/// Where a pooled sandbox records console output for the execution it is running
+pub type ConsoleSlot = Arc<Mutex<Option<ConsoleCapture>>>;
+
+/// Install a `console` global whose calls are recorded into the capture in `slot`
+///
+/// While the slot is empty the calls are only forwarded to debug tracing.
+pub fn install<'js>(ctx: &Ctx<'js>, slot: &ConsoleSlot) -> rquickjs::Result<()> {
+    let console = Object::new(ctx.clone())?;
+
+    for level in [ConsoleLevel::Debug, ConsoleLevel::Info, ConsoleLevel::Log, ConsoleLevel::Warn, ConsoleLevel::Error] {
+        let slot = slot.clone();
+        let function = Function::new(ctx.clone(), move |ctx: Ctx<'js>, args: Rest<Value<'js>>| {
+            let message = args
+                .0
+                .into_iter()
+                .map(|value| format_value(&ctx, value))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            tracing::debug!(target: "swisspipe::script", "console.{}: {}", level.method_name(), message);
+            if let Some(capture) = slot.lock().ok().and_then(|capture| capture.clone()) {
+                capture.record(level, message);
+            }
+        })?;
+        console.set(level.method_name(), function)?;
+    }
+
+    ctx.globals().set("console", console)?;
+    Ok(())
+}
+
+/// Render a console argument: strings verbatim, errors via `toString`, objects as JSON
+fn format_value<'js>(ctx: &Ctx<'js>, value: Value<'js>) -> String {
+    if let Some(string) = value.as_string() {
+        return string.to_string().unwrap_or_default();
+    }
+
+    if value.is_object() && !value.is_error() && !value.is_function() {
+        match ctx.json_stringify(value.clone()) {
+            Ok(Some(json)) => {
+                if let Ok(json) = json.to_string() {
+                    return json;
+                }
+            }
+            Ok(None) => {}
+            Err(_) => {
+                // Discard the pending exception (e.g. a cyclic structure) and fall back to toString
+                let _ = ctx.catch();
+            }
+        }
+    }
+
+    match value.get::<Coerced<String>>() {
+        Ok(coerced) => coerced.0,
+        Err(_) => {
+            let _ = ctx.catch();
+            format!("[{}]", value.type_name())
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use base64::prelude::*;
+use chrono::format::StrftimeItems;
+use chrono::{DateTime, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use rquickjs::function::Opt;
+use rquickjs::{Coerced, Ctx, Exception, Function, Object, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3f%:z";
+
+/// Install the Rust-backed helper library on the `swisspipe` global
+///
+/// These helpers are pure functions of their arguments and are available to
+/// every script. Capabilities that reach outside the sandbox are opt-in host
+/// functions instead.
+pub fn install<'js>(ctx: &Ctx<'js>, swisspipe: &Object<'js>) -> rquickjs::Result<()> {
+    swisspipe.set("sha256", Function::new(ctx.clone(), |input: Coerced<String>| sha256_hex(&input.0))?)?;
+    swisspipe.set(
+        "hmacSha256",
+        Function::new(ctx.clone(), |key: Coerced<String>, message: Coerced<String>| {
+            hmac_sha256_hex(&key.0, &message.0)
+        })?,
+    )?;
+    swisspipe.set("uuid", Function::new(ctx.clone(), || uuid::Uuid::new_v4().to_string())?)?;
+    swisspipe.set("uuidv7", Function::new(ctx.clone(), || uuid::Uuid::now_v7().to_string())?)?;
+    swisspipe.set(
+        "base64Encode",
+        Function::new(ctx.clone(), |input: Coerced<String>, url_safe: Opt<bool>| {
+            base64_encode(&input.0, url_safe.0.unwrap_or(false))
+        })?,
+    )?;
+    swisspipe.set(
+        "base64Decode",
+        Function::new(ctx.clone(), |ctx: Ctx<'js>, input: Coerced<String>, url_safe: Opt<bool>| {
+            base64_decode(&input.0, url_safe.0.unwrap_or(false)).map_err(|e| Exception::throw_type(&ctx, &e))
+        })?,
+    )?;
+    swisspipe.set(
+        "parseUrl",
+        Function::new(ctx.clone(), |ctx: Ctx<'js>, input: Coerced<String>| -> rquickjs::Result<Value<'js>> {
+            let parsed = parse_url(&input.0).map_err(|e| Exception::throw_type(&ctx, &e))?;
+            ctx.json_parse(parsed.to_string())
+        })?,
+    )?;
+    swisspipe.set(
+        "formatDate",
+        Function::new(
+            ctx.clone(),
+            |ctx: Ctx<'js>, value: Value<'js>, format: Opt<String>, timezone: Opt<String>| -> rquickjs::Result<String> {
+                let timestamp = date_from_value(&value).map_err(|e| Exception::throw_type(&ctx, &e))?;
+                format_date(timestamp, format.0.as_deref(), timezone.0.as_deref())
+                    .map_err(|e| Exception::throw_range(&ctx, &e))
+            },
+        )?,
+    )?;
+
+    Ok(())
+}
+
+/// Build `swisspipe.env(name)`, a read-only lookup into the environment variables `variables` returns
+///
+/// The lookup runs on every call, so a pooled sandbox always reads the variables
+/// of the execution it is currently running.
+pub fn env_function<'js, F>(ctx: &Ctx<'js>, variables: F) -> rquickjs::Result<Function<'js>>
+where
+    F: Fn(&Ctx<'js>) -> rquickjs::Result<Arc<HashMap<String, String>>> + 'js,
+{
+    Function::new(ctx.clone(), move |ctx: Ctx<'js>, name: Coerced<String>| -> rquickjs::Result<Option<String>> {
+        Ok(variables(&ctx)?.get(&name.0).cloned())
+    })
+}
+
+pub fn sha256_hex(input: &str) -> String {
+    hex::encode(Sha256::digest(input.as_bytes()))
+}
+
+pub fn hmac_sha256_hex(key: &str, message: &str) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(message.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+pub fn base64_encode(input: &str, url_safe: bool) -> String {
+    if url_safe {
+        BASE64_URL_SAFE_NO_PAD.encode(input)
+    } else {
+        BASE64_STANDARD.encode(input)
+    }
+}
+
+pub fn base64_decode(input: &str, url_safe: bool) -> Result<String, String> {
+    let bytes = if url_safe {
+        BASE64_URL_SAFE_NO_PAD.decode(input.trim_end_matches('='))
+    } else {
+        BASE64_STANDARD.decode(input)
+    }
+    .map_err(|e| format!("Invalid base64 input: {e}"))?;
+
+    String::from_utf8(bytes).map_err(|_| "Decoded base64 data is not valid UTF-8".to_string())
+}
+
+/// Split a URL into its components, including the query string as an object
+pub fn parse_url(input: &str) -> Result<serde_json::Value, String> {
+    let url = url::Url::parse(input).map_err(|e| format!("Invalid URL '{input}': {e}"))?;
+
+    let mut query = serde_json::Map::new();
+    for (key, value) in url.query_pairs() {
+        query.insert(key.into_owned(), serde_json::Value::String(value.into_owned()));
+    }
+
+    Ok(serde_json::json!({
+        "href": url.as_str(),
+        "protocol": format!("{}:", url.scheme()),
+        "username": url.username(),
+        "hostname": url.host_str().unwrap_or_default(),
+        "port": url.port().map(|p| p.to_string()).unwrap_or_default(),
+        "host": match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{host}:{port}"),
+            (Some(host), None) => host.to_string(),
+            _ => String::new(),
+        },
+        "pathname": url.path(),
+        "search": url.query().map(|q| format!("?{q}")).unwrap_or_default(),
+        "hash": url.fragment().map(|f| format!("#{f}")).unwrap_or_default(),
+        "query": query,
+    }))
+}
+
+/// Accept epoch milliseconds, an RFC 3339 string or a `Date` object
+fn date_from_value(value: &Value<'_>) -> Result<DateTime<Utc>, String> {
+    if let Some(millis) = value.as_number() {
+        return Utc
+            .timestamp_millis_opt(millis as i64)
+            .single()
+            .ok_or_else(|| format!("Timestamp out of range: {millis}"));
+    }
+
+    if let Some(object) = value.as_object() {
+        let get_time: Function = object
+            .get("getTime")
+            .map_err(|_| "Expected a Date, epoch milliseconds or an RFC 3339 string".to_string())?;
+        let millis: f64 = get_time
+            .call((rquickjs::function::This(object.clone()),))
+            .map_err(|e| format!("Invalid Date: {e}"))?;
+        if millis.is_nan() {
+            return Err("Invalid Date".to_string());
+        }
+        return Utc
+            .timestamp_millis_opt(millis as i64)
+            .single()
+            .ok_or_else(|| format!("Timestamp out of range: {millis}"));
+    }
+
+    let text = value
+        .as_string()
+        .and_then(|s| s.to_string().ok())
+        .ok_or_else(|| "Expected a Date, epoch milliseconds or an RFC 3339 string".to_string())?;
+    DateTime::parse_from_rfc3339(&text)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|e| format!("Invalid date '{text}': {e}"))
+}
+
+/// Format a timestamp with a strftime pattern in an IANA timezone (UTC by default)
+pub fn format_date(timestamp: DateTime<Utc>, format: Option<&str>, timezone: Option<&str>) -> Result<String, String> {
+    let items = StrftimeItems::new(format.unwrap_or(DEFAULT_DATE_FORMAT))
+        .parse()
+        .map_err(|_| format!("Invalid date format '{}'", format.unwrap_or_default()))?;
+
+    let timezone: chrono_tz::Tz = timezone
+        .unwrap_or("UTC")
+        .parse()
+        .map_err(|_| format!("Unknown timezone '{}'", timezone.unwrap_or_default()))?;
+
+    Ok(timestamp.with_timezone(&timezone).format_with_items(items.iter()).to_string())
+}
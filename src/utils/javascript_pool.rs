@@ -0,0 +1,358 @@
+use crate::workflow::errors::JavaScriptError;
+use rquickjs::{qjs, Ctx, Value};
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::ffi::CString;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+const DEFAULT_RUNTIME_POOL_SIZE: usize = 8;
+const DEFAULT_SCRIPT_CACHE_SIZE: usize = 1000;
+
+/// Sizing for the runtime pool and bytecode cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JavaScriptPoolConfig {
+    pub runtime_pool_size: usize,
+    pub script_cache_size: usize,
+}
+
+impl Default for JavaScriptPoolConfig {
+    fn default() -> Self {
+        Self {
+            runtime_pool_size: DEFAULT_RUNTIME_POOL_SIZE,
+            script_cache_size: DEFAULT_SCRIPT_CACHE_SIZE,
+        }
+    }
+}
+
+impl JavaScriptPoolConfig {
+    /// Load pool sizing from SP_JS_* environment variables, falling back to defaults
+    pub fn from_env() -> Self {
+        let runtime_pool_size = std::env::var("SP_JS_RUNTIME_POOL_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_RUNTIME_POOL_SIZE);
+
+        let script_cache_size = std::env::var("SP_JS_SCRIPT_CACHE_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_SCRIPT_CACHE_SIZE);
+
+        Self {
+            runtime_pool_size,
+            script_cache_size,
+        }
+    }
+}
+
+/// Pool and cache statistics exposed through the admin stats endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct JavaScriptPoolStats {
+    pub pool_size: usize,
+    pub idle_runtimes: usize,
+    pub runtime_hits: u64,
+    pub runtime_misses: u64,
+    pub runtime_hit_rate: f64,
+    pub cached_scripts: usize,
+    pub script_cache_size: usize,
+    pub script_cache_hits: u64,
+    pub script_cache_misses: u64,
+    pub script_cache_hit_rate: f64,
+}
+
+fn hit_rate(hits: u64, misses: u64) -> f64 {
+    let total = hits + misses;
+    if total == 0 {
+        0.0
+    } else {
+        hits as f64 / total as f64
+    }
+}
+
+/// Bounded pool of pre-warmed, hardened QuickJS sandboxes
+///
+/// At most `max_size` sandboxes exist at once; callers wait for one to be released
+/// when all of them are in use. A size of zero disables pooling, so every execution
+/// builds and drops its own sandbox.
+pub struct SandboxPool<T> {
+    idle: Mutex<Vec<T>>,
+    slots: Option<Semaphore>,
+    max_size: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<T> SandboxPool<T> {
+    pub fn new(max_size: usize, create: impl Fn() -> Result<T, JavaScriptError>) -> Self {
+        let mut idle = Vec::with_capacity(max_size);
+        for _ in 0..max_size {
+            match create() {
+                Ok(sandbox) => idle.push(sandbox),
+                Err(e) => {
+                    tracing::warn!("Failed to pre-warm JavaScript sandbox: {}", e);
+                    break;
+                }
+            }
+        }
+
+        Self {
+            idle: Mutex::new(idle),
+            slots: (max_size > 0).then(|| Semaphore::new(max_size)),
+            max_size,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Take an idle sandbox, waiting while the pool is exhausted and creating one to replace a discarded sandbox
+    pub async fn acquire(&self, create: impl FnOnce() -> Result<T, JavaScriptError>) -> Result<PooledSandbox<'_, T>, JavaScriptError> {
+        let slot = match &self.slots {
+            Some(slots) => Some(
+                slots
+                    .acquire()
+                    .await
+                    .map_err(|e| JavaScriptError::RuntimeError(e.to_string()))?,
+            ),
+            None => None,
+        };
+
+        let pooled = self.idle.lock().ok().and_then(|mut idle| idle.pop());
+        let sandbox = match pooled {
+            Some(sandbox) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                sandbox
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                create()?
+            }
+        };
+
+        Ok(PooledSandbox { pool: self, sandbox: Some(sandbox), _slot: slot })
+    }
+
+    fn idle_count(&self) -> usize {
+        self.idle.lock().map(|idle| idle.len()).unwrap_or(0)
+    }
+}
+
+/// A sandbox checked out of the pool
+///
+/// Dropping it discards the sandbox; [`PooledSandbox::release`] hands it back for reuse.
+/// Either way its slot is freed for the next caller.
+pub struct PooledSandbox<'a, T> {
+    pool: &'a SandboxPool<T>,
+    sandbox: Option<T>,
+    _slot: Option<SemaphorePermit<'a>>,
+}
+
+impl<T> PooledSandbox<'_, T> {
+    /// Return the sandbox to the pool
+    pub fn release(mut self) {
+        if let (Some(sandbox), Ok(mut idle)) = (self.sandbox.take(), self.pool.idle.lock()) {
+            if idle.len() < self.pool.max_size {
+                idle.push(sandbox);
+            }
+        }
+    }
+}
+
+impl<T> Deref for PooledSandbox<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.sandbox.as_ref().expect("sandbox is present until released")
+    }
+}
+
+/// What running a script hands back to the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryPoint {
+    /// The function the script defines under this name, such as `transformer`
+    ///
+    /// The script runs inside a function body, so its declarations stay local to
+    /// one execution instead of landing on the pooled sandbox's global scope.
+    Function(&'static str),
+    /// The script's completion value, for scripts that are a single expression
+    Completion,
+}
+
+struct CompiledScript {
+    source: String,
+    entry: EntryPoint,
+    bytecode: Arc<Vec<u8>>,
+}
+
+/// Bytecode cache keyed by script hash
+pub struct ScriptCache {
+    entries: Mutex<ScriptCacheEntries>,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+#[derive(Default)]
+struct ScriptCacheEntries {
+    scripts: HashMap<u64, CompiledScript>,
+    insertion_order: VecDeque<u64>,
+}
+
+impl ScriptCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(ScriptCacheEntries::default()),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn hash_source(source: &str, entry: EntryPoint) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        entry.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Return cached bytecode for a script, compiling it in `ctx` on a miss
+    pub fn get_or_compile(&self, ctx: &Ctx<'_>, source: &str, entry: EntryPoint) -> rquickjs::Result<Arc<Vec<u8>>> {
+        let key = Self::hash_source(source, entry);
+
+        if let Ok(entries) = self.entries.lock() {
+            // Compare the source as well so a hash collision can never run the wrong script
+            if let Some(compiled) = entries.scripts.get(&key).filter(|c| c.source == source && c.entry == entry) {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(compiled.bytecode.clone());
+            }
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let bytecode = Arc::new(compile_entry_point(ctx, source, entry)?);
+
+        if self.capacity > 0 {
+            if let Ok(mut entries) = self.entries.lock() {
+                while entries.scripts.len() >= self.capacity {
+                    match entries.insertion_order.pop_front() {
+                        Some(oldest) => {
+                            entries.scripts.remove(&oldest);
+                        }
+                        None => break,
+                    }
+                }
+                let previous = entries.scripts.insert(key, CompiledScript {
+                    source: source.to_string(),
+                    entry,
+                    bytecode: bytecode.clone(),
+                });
+                if previous.is_none() {
+                    entries.insertion_order.push_back(key);
+                }
+            }
+        }
+
+        Ok(bytecode)
+    }
+
+    fn len(&self) -> usize {
+        self.entries.lock().map(|entries| entries.scripts.len()).unwrap_or(0)
+    }
+}
+
+/// Build combined statistics for a sandbox pool and its script cache
+pub fn pool_stats<T>(pool: &SandboxPool<T>, cache: &ScriptCache) -> JavaScriptPoolStats {
+    let runtime_hits = pool.hits.load(Ordering::Relaxed);
+    let runtime_misses = pool.misses.load(Ordering::Relaxed);
+    let script_cache_hits = cache.hits.load(Ordering::Relaxed);
+    let script_cache_misses = cache.misses.load(Ordering::Relaxed);
+
+    JavaScriptPoolStats {
+        pool_size: pool.max_size,
+        idle_runtimes: pool.idle_count(),
+        runtime_hits,
+        runtime_misses,
+        runtime_hit_rate: hit_rate(runtime_hits, runtime_misses),
+        cached_scripts: cache.len(),
+        script_cache_size: cache.capacity,
+        script_cache_hits,
+        script_cache_misses,
+        script_cache_hit_rate: hit_rate(script_cache_hits, script_cache_misses),
+    }
+}
+
+/// Compile a script so that running it evaluates to its entry point
+fn compile_entry_point(ctx: &Ctx<'_>, source: &str, entry: EntryPoint) -> rquickjs::Result<Vec<u8>> {
+    match entry {
+        EntryPoint::Completion => compile_script(ctx, source),
+        EntryPoint::Function(name) => {
+            // The script must parse on its own, so it cannot close the wrapper and declare globals after it
+            compile_script(ctx, source)?;
+            compile_script(ctx, &format!("(function () {{{source}\n;return {name};\n}})()"))
+        }
+    }
+}
+
+/// Compile a global script to QuickJS bytecode without running it
+fn compile_script(ctx: &Ctx<'_>, source: &str) -> rquickjs::Result<Vec<u8>> {
+    let source = CString::new(source)?;
+    let filename = CString::new("script")?;
+    let flags = (qjs::JS_EVAL_TYPE_GLOBAL | qjs::JS_EVAL_FLAG_COMPILE_ONLY) as i32;
+
+    // SAFETY: `ctx` is a live context for the duration of this call, the source is
+    // NUL-terminated as JS_Eval requires, and every value and buffer QuickJS hands
+    // back is freed exactly once before returning.
+    unsafe {
+        let raw_ctx = ctx.as_raw().as_ptr();
+        let function = qjs::JS_Eval(
+            raw_ctx,
+            source.as_ptr(),
+            source.as_bytes().len() as _,
+            filename.as_ptr(),
+            flags,
+        );
+        if qjs::JS_IsException(function) {
+            return Err(rquickjs::Error::Exception);
+        }
+
+        let mut length = 0;
+        let buffer = qjs::JS_WriteObject(raw_ctx, &mut length, function, qjs::JS_WRITE_OBJ_BYTECODE as i32);
+        qjs::JS_FreeValue(raw_ctx, function);
+        if buffer.is_null() {
+            return Err(rquickjs::Error::Exception);
+        }
+
+        let bytecode = std::slice::from_raw_parts(buffer, length as usize).to_vec();
+        qjs::js_free(raw_ctx, buffer as *mut _);
+        Ok(bytecode)
+    }
+}
+
+/// Run bytecode produced by the script cache and return the script's completion value
+pub fn run_bytecode<'js>(ctx: &Ctx<'js>, bytecode: &[u8]) -> rquickjs::Result<Value<'js>> {
+    // SAFETY: the bytecode was produced by `compile_script` with the same QuickJS
+    // build, so it is trusted input for JS_ReadObject. JS_EvalFunction takes
+    // ownership of the function object, and the returned value is handed over
+    // to `Value` which frees it on drop.
+    unsafe {
+        let raw_ctx = ctx.as_raw().as_ptr();
+        let function = qjs::JS_ReadObject(
+            raw_ctx,
+            bytecode.as_ptr(),
+            bytecode.len() as _,
+            qjs::JS_READ_OBJ_BYTECODE as i32,
+        );
+        if qjs::JS_IsException(function) {
+            return Err(rquickjs::Error::Exception);
+        }
+
+        let result = qjs::JS_EvalFunction(raw_ctx, function);
+        if qjs::JS_IsException(result) {
+            return Err(rquickjs::Error::Exception);
+        }
+
+        Ok(Value::from_raw(ctx.clone(), result))
+    }
+}
@@ -0,0 +1,127 @@
+use serde_json::Value;
+
+/// Resolve a JSONPath expression against a value
+///
+/// Supports the subset needed to point at a single value: the `$` root followed by
+/// `.field`, `['field']` / `["field"]` and `[index]` segments. Missing fields and
+/// out-of-range indexes resolve to `Value::Null`.
+pub fn resolve<'a>(value: &'a Value, path: &str) -> Result<&'a Value, String> {
+    let rest = path
+        .trim()
+        .strip_prefix('$')
+        .ok_or_else(|| format!("JSONPath '{path}' must start with '$'"))?;
+
+    let mut current = value;
+    let mut chars = rest.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let segment = match c {
+            '.' => {
+                let from = start + 1;
+                let mut end = rest.len();
+                while let Some(&(i, c)) = chars.peek() {
+                    if c == '.' || c == '[' {
+                        end = i;
+                        break;
+                    }
+                    chars.next();
+                }
+                let field = &rest[from..end];
+                if field.is_empty() {
+                    return Err(format!("JSONPath '{path}' has an empty field name"));
+                }
+                Segment::Field(field)
+            }
+            '[' => {
+                let close = rest[start..]
+                    .find(']')
+                    .map(|i| start + i)
+                    .ok_or_else(|| format!("JSONPath '{path}' has an unclosed '['"))?;
+                let inner = rest[start + 1..close].trim();
+                while chars.peek().is_some_and(|&(i, _)| i <= close) {
+                    chars.next();
+                }
+
+                let quoted = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+                match quoted {
+                    Some(field) => Segment::Field(field),
+                    None => Segment::Index(
+                        inner
+                            .parse()
+                            .map_err(|_| format!("JSONPath '{path}' has an invalid index '{inner}'"))?,
+                    ),
+                }
+            }
+            _ => return Err(format!("JSONPath '{path}' has an unexpected '{c}'")),
+        };
+
+        current = match segment {
+            Segment::Field(field) => current.get(field),
+            Segment::Index(index) => current.get(index),
+        }
+        .unwrap_or(&Value::Null);
+    }
+
+    Ok(current)
+}
+
+enum Segment<'a> {
+    Field(&'a str),
+    Index(usize),
+}
+
+/// Store `new_value` at a JSON pointer (RFC 6901) inside `value`
+///
+/// The empty pointer replaces the whole value. Missing objects along the way are
+/// created, and anything that is neither an object nor an array is replaced by one.
+/// `-` or the length of an array appends to it.
+pub fn set_pointer(value: &mut Value, pointer: &str, new_value: Value) -> Result<(), String> {
+    if pointer.is_empty() {
+        *value = new_value;
+        return Ok(());
+    }
+    let rest = pointer
+        .strip_prefix('/')
+        .ok_or_else(|| format!("JSON pointer '{pointer}' must be empty or start with '/'"))?;
+
+    let tokens: Vec<String> = rest.split('/').map(|t| t.replace("~1", "/").replace("~0", "~")).collect();
+    let mut current = value;
+    for token in tokens {
+        if !current.is_object() && !current.is_array() {
+            *current = Value::Object(serde_json::Map::new());
+        }
+        current = match current {
+            Value::Array(items) => {
+                let index = match token.as_str() {
+                    "-" => items.len(),
+                    index => index
+                        .parse()
+                        .ok()
+                        .filter(|index| *index <= items.len())
+                        .ok_or_else(|| format!("JSON pointer '{pointer}' has an invalid array index '{token}'"))?,
+                };
+                if index == items.len() {
+                    items.push(Value::Null);
+                }
+                &mut items[index]
+            }
+            Value::Object(map) => map.entry(token).or_insert(Value::Null),
+            _ => unreachable!("replaced by an object above"),
+        };
+    }
+
+    *current = new_value;
+    Ok(())
+}
+
+/// Check that a JSON pointer can be used with [`set_pointer`]
+pub fn validate_pointer(pointer: &str) -> Result<(), String> {
+    if pointer.is_empty() || pointer.starts_with('/') {
+        Ok(())
+    } else {
+        Err(format!("JSON pointer '{pointer}' must be empty or start with '/'"))
+    }
+}
@@ -1,4 +1,12 @@
 pub mod javascript;
+pub mod javascript_pool;
+pub mod javascript_helpers;
+pub mod javascript_console;
 pub mod http_client;
+pub mod http_auth;
 pub mod validation;
-pub mod logging;
\ No newline at end of file
+pub mod logging;
+pub mod record_stream;
+pub mod json_path;
+pub mod retry;
+pub mod webhook_signature;
@@ -0,0 +1,243 @@
+use csv_core::{ReadRecordResult, Reader};
+use serde_json::{Map, Value};
+
+/// Largest single NDJSON line or CSV row accepted, matching the execution input limit
+pub const MAX_RECORD_BYTES: usize = 1_048_576;
+
+/// Most columns accepted in a CSV row
+const MAX_FIELDS: usize = 16_384;
+
+/// One parsed record and the 1-based line (NDJSON) or row (CSV) it came from
+pub type ParsedRecord = (usize, Result<Value, String>);
+
+/// Incremental NDJSON parser fed with arbitrary body chunks
+///
+/// Blank lines are skipped. A line longer than `MAX_RECORD_BYTES` is rejected
+/// without being buffered in full.
+#[derive(Default)]
+pub struct NdjsonParser {
+    buffer: Vec<u8>,
+    line_number: usize,
+    oversized: bool,
+}
+
+impl NdjsonParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of the body and return every line it completed
+    pub fn push(&mut self, mut chunk: &[u8]) -> Vec<ParsedRecord> {
+        let mut records = Vec::new();
+
+        while let Some(newline) = chunk.iter().position(|&b| b == b'\n') {
+            self.append(&chunk[..newline]);
+            if let Some(record) = self.complete_line() {
+                records.push(record);
+            }
+            chunk = &chunk[newline + 1..];
+        }
+        self.append(chunk);
+
+        records
+    }
+
+    /// Flush the final line when the body does not end with a newline
+    pub fn finish(mut self) -> Option<ParsedRecord> {
+        if self.buffer.is_empty() && !self.oversized {
+            return None;
+        }
+        self.complete_line()
+    }
+
+    fn append(&mut self, bytes: &[u8]) {
+        if self.oversized {
+            return;
+        }
+        if self.buffer.len() + bytes.len() > MAX_RECORD_BYTES {
+            self.oversized = true;
+            self.buffer.clear();
+            return;
+        }
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    fn complete_line(&mut self) -> Option<ParsedRecord> {
+        self.line_number += 1;
+        let line = std::mem::take(&mut self.buffer);
+
+        if std::mem::take(&mut self.oversized) {
+            return Some((self.line_number, Err(format!("Line exceeds {MAX_RECORD_BYTES} bytes"))));
+        }
+
+        let line = line.strip_suffix(b"\r").unwrap_or(&line);
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return None;
+        }
+
+        let result = serde_json::from_slice::<Value>(line).map_err(|e| format!("Invalid JSON: {e}"));
+        Some((self.line_number, result))
+    }
+}
+
+/// Incremental CSV parser that turns each row into an object keyed by the header row
+///
+/// Row numbers count the header as row 1. Quoted fields may span lines, so a
+/// row number only equals the line number when no field contains a newline.
+pub struct CsvParser {
+    reader: Reader,
+    headers: Option<Vec<String>>,
+    row_number: usize,
+    output: Vec<u8>,
+    output_len: usize,
+    ends: Vec<usize>,
+    ends_len: usize,
+    oversized: bool,
+}
+
+impl Default for CsvParser {
+    fn default() -> Self {
+        Self {
+            reader: Reader::new(),
+            headers: None,
+            row_number: 0,
+            output: vec![0; 4096],
+            output_len: 0,
+            ends: vec![0; 64],
+            ends_len: 0,
+            oversized: false,
+        }
+    }
+}
+
+impl CsvParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of the body and return every row it completed
+    ///
+    /// Returns an error if the header row itself cannot be used.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<Vec<ParsedRecord>, String> {
+        if chunk.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.read(chunk)
+    }
+
+    /// Flush the final row when the body does not end with a newline
+    pub fn finish(mut self) -> Result<Vec<ParsedRecord>, String> {
+        // An empty input tells csv-core the stream has ended
+        self.read(&[])
+    }
+
+    fn read(&mut self, mut input: &[u8]) -> Result<Vec<ParsedRecord>, String> {
+        let mut records = Vec::new();
+
+        loop {
+            let (result, consumed, written, ends_written) = self.reader.read_record(
+                input,
+                &mut self.output[self.output_len..],
+                &mut self.ends[self.ends_len..],
+            );
+            input = &input[consumed..];
+            self.output_len += written;
+            self.ends_len += ends_written;
+
+            match result {
+                ReadRecordResult::InputEmpty => return Ok(records),
+                ReadRecordResult::OutputFull => self.grow_output(),
+                ReadRecordResult::OutputEndsFull => self.grow_ends(),
+                ReadRecordResult::Record => {
+                    if let Some(record) = self.complete_row()? {
+                        records.push(record);
+                    }
+                }
+                ReadRecordResult::End => return Ok(records),
+            }
+        }
+    }
+
+    fn grow_output(&mut self) {
+        if self.output.len() >= MAX_RECORD_BYTES {
+            // Keep consuming the row into the same buffer; it is rejected once complete
+            self.oversized = true;
+            self.output_len = 0;
+            self.ends_len = 0;
+            return;
+        }
+        let len = self.output.len();
+        self.output.resize((len * 2).min(MAX_RECORD_BYTES), 0);
+    }
+
+    fn grow_ends(&mut self) {
+        if self.ends.len() >= MAX_FIELDS {
+            self.oversized = true;
+            self.ends_len = 0;
+            return;
+        }
+        let len = self.ends.len();
+        self.ends.resize((len * 2).min(MAX_FIELDS), 0);
+    }
+
+    fn complete_row(&mut self) -> Result<Option<ParsedRecord>, String> {
+        self.row_number += 1;
+        let row_number = self.row_number;
+        let oversized = std::mem::take(&mut self.oversized);
+        let fields = self.take_fields();
+
+        if self.headers.is_none() {
+            if oversized {
+                return Err(format!("Header row exceeds {MAX_RECORD_BYTES} bytes or {MAX_FIELDS} fields"));
+            }
+            let mut headers = fields.map_err(|_| "Header row is not valid UTF-8".to_string())?;
+            if let Some(first) = headers.first_mut() {
+                // Spreadsheet exports often start with a byte order mark
+                *first = first.trim_start_matches('\u{feff}').to_string();
+            }
+            if headers.iter().all(|h| h.is_empty()) {
+                return Err("Header row is empty".to_string());
+            }
+            self.headers = Some(headers);
+            return Ok(None);
+        }
+
+        if oversized {
+            return Ok(Some((row_number, Err(format!("Row exceeds {MAX_RECORD_BYTES} bytes or {MAX_FIELDS} fields")))));
+        }
+
+        let headers = self.headers.as_ref().expect("headers are set above");
+        let result = fields
+            .map_err(|_| "Row is not valid UTF-8".to_string())
+            .and_then(|fields| {
+                if fields.len() != headers.len() {
+                    return Err(format!("Expected {} fields, found {}", headers.len(), fields.len()));
+                }
+                let object: Map<String, Value> = headers
+                    .iter()
+                    .cloned()
+                    .zip(fields.into_iter().map(Value::String))
+                    .collect();
+                Ok(Value::Object(object))
+            });
+
+        Ok(Some((row_number, result)))
+    }
+
+    fn take_fields(&mut self) -> Result<Vec<String>, std::str::Utf8Error> {
+        let output = &self.output[..self.output_len];
+        let mut start = 0;
+        let fields = self.ends[..self.ends_len]
+            .iter()
+            .map(|&end| {
+                let field = std::str::from_utf8(&output[start..end]).map(str::to_string);
+                start = end;
+                field
+            })
+            .collect();
+
+        self.output_len = 0;
+        self.ends_len = 0;
+        fields
+    }
+}
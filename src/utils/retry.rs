@@ -0,0 +1,191 @@
+use crate::workflow::{
+    errors::SwissPipeError,
+    models::{parse_status_codes, RetryCondition, RetryConfig, RetryJitter},
+};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::{
+    fmt::Display,
+    future::Future,
+    ops::RangeInclusive,
+    time::{Duration, Instant},
+};
+
+/// Longest `Retry-After` wait honored when the retry config sets no `max_retry_duration_ms`
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(300);
+
+/// What went wrong with one attempt, as far as deciding whether to retry it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    Connect,
+    Timeout,
+    Status(u16),
+    /// Failures a retry cannot fix, such as an invalid request or an unparseable response
+    Other,
+}
+
+/// A failed attempt: the error to report and what kind of failure it was
+#[derive(Debug)]
+pub struct AttemptError<E = SwissPipeError> {
+    pub kind: FailureKind,
+    /// Wait the server asked for with `Retry-After`
+    pub retry_after: Option<Duration>,
+    pub error: E,
+}
+
+impl<E> AttemptError<E> {
+    pub fn other(error: impl Into<E>) -> Self {
+        Self { kind: FailureKind::Other, retry_after: None, error: error.into() }
+    }
+
+    /// Classify an error reqwest returned while sending a request or reading its response
+    pub fn transport(source: &reqwest::Error, error: impl Into<E>) -> Self {
+        let kind = if source.is_timeout() {
+            FailureKind::Timeout
+        } else if source.is_connect() || source.is_request() || source.is_body() {
+            FailureKind::Connect
+        } else {
+            FailureKind::Other
+        };
+        Self { kind, retry_after: None, error: error.into() }
+    }
+
+    /// An unsuccessful response, with the wait a 429 or 503 response asks for in `Retry-After`
+    pub fn status(status: u16, headers: &HeaderMap, error: impl Into<E>) -> Self {
+        let retry_after = match status {
+            429 | 503 => retry_after(headers, Utc::now()),
+            _ => None,
+        };
+        Self { kind: FailureKind::Status(status), retry_after, error: error.into() }
+    }
+}
+
+/// Parse a `Retry-After` header given as delay seconds or as an HTTP date
+pub fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&Utc) - now).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// The last error of an operation that was given up on, after `attempts` attempts
+#[derive(Debug)]
+pub struct RetryExhausted<E = SwissPipeError> {
+    pub attempts: u32,
+    pub error: E,
+}
+
+/// Decides whether and when to retry failed attempts according to a [`RetryConfig`]
+pub struct RetryPolicy<'a> {
+    config: &'a RetryConfig,
+    retry_statuses: Vec<RangeInclusive<u16>>,
+    started: Instant,
+    /// Exponential backoff delay for the next retry
+    backoff: Duration,
+    /// Last delay, which decorrelated jitter grows from
+    previous: Duration,
+}
+
+impl<'a> RetryPolicy<'a> {
+    pub fn new(config: &'a RetryConfig) -> Self {
+        let initial = Duration::from_millis(config.initial_delay_ms);
+        Self {
+            config,
+            // Patterns are validated when the workflow is saved
+            retry_statuses: config.retry_status_codes.iter().filter_map(|pattern| parse_status_codes(pattern)).collect(),
+            started: Instant::now(),
+            backoff: initial.min(Duration::from_millis(config.max_delay_ms)),
+            previous: initial,
+        }
+    }
+
+    pub fn is_retryable(&self, kind: FailureKind) -> bool {
+        let retry_on = |condition| self.config.retry_on.contains(&condition);
+        match kind {
+            FailureKind::Connect => retry_on(RetryCondition::Connect),
+            FailureKind::Timeout => retry_on(RetryCondition::Timeout),
+            FailureKind::Status(status) => {
+                (status == 429 && retry_on(RetryCondition::TooManyRequests))
+                    || ((500..=599).contains(&status) && retry_on(RetryCondition::ServerError))
+                    || self.retry_statuses.iter().any(|range| range.contains(&status))
+            }
+            FailureKind::Other => false,
+        }
+    }
+
+    /// How long to wait before the next attempt after `attempts` failed ones, or `None` to give up
+    pub fn next_delay<E>(&mut self, attempts: u32, failure: &AttemptError<E>) -> Option<Duration> {
+        if attempts >= self.config.max_attempts || !self.is_retryable(failure.kind) {
+            return None;
+        }
+
+        let max = Duration::from_millis(self.config.max_delay_ms);
+        let initial = Duration::from_millis(self.config.initial_delay_ms);
+        let delay = match self.config.jitter {
+            RetryJitter::None => self.backoff,
+            RetryJitter::Full => random_between(Duration::ZERO, self.backoff),
+            RetryJitter::Decorrelated => random_between(initial, self.previous * 3).min(max),
+        };
+        self.previous = delay;
+        self.backoff = Duration::from_millis((self.backoff.as_millis() as f64 * self.config.backoff_multiplier) as u64).min(max);
+
+        // A server asking for a longer wait is obeyed, even beyond max_delay_ms, within the total retry budget
+        let honored = |wait: Duration| match self.config.max_retry_duration_ms {
+            Some(_) => wait,
+            None => wait.min(MAX_RETRY_AFTER),
+        };
+        let delay = failure.retry_after.map_or(delay, |wait| delay.max(honored(wait)));
+        if let Some(limit) = self.config.max_retry_duration_ms {
+            if self.started.elapsed() + delay > Duration::from_millis(limit) {
+                return None;
+            }
+        }
+        Some(delay)
+    }
+}
+
+fn random_between(low: Duration, high: Duration) -> Duration {
+    if high <= low {
+        return low;
+    }
+    Duration::from_millis(rand::thread_rng().gen_range(low.as_millis() as u64..=high.as_millis() as u64))
+}
+
+/// Run `attempt` until it succeeds or `config` says to stop retrying
+pub async fn run<T, E, F, Fut>(config: &RetryConfig, name: &str, mut attempt: F) -> Result<T, RetryExhausted<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, AttemptError<E>>>,
+    E: Display,
+{
+    let mut policy = RetryPolicy::new(config);
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        tracing::info!("{} attempt {} of {}", name, attempts, config.max_attempts);
+
+        let start_time = Instant::now();
+        match attempt().await {
+            Ok(value) => {
+                tracing::info!("{} succeeded on attempt {} after {:?}", name, attempts, start_time.elapsed());
+                return Ok(value);
+            }
+            Err(failure) => match policy.next_delay(attempts, &failure) {
+                Some(delay) => {
+                    tracing::warn!("{} attempt {} failed after {:?}, retrying in {:?}. Error: {}",
+                        name, attempts, start_time.elapsed(), delay, failure.error);
+                    tokio::time::sleep(delay).await;
+                }
+                None => {
+                    tracing::error!("{} failed after {} attempts. Final attempt took {:?}. Error: {}",
+                        name, attempts, start_time.elapsed(), failure.error);
+                    return Err(RetryExhausted { attempts, error: failure.error });
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+
+use crate::workflow::models::TriggerVerification;
+
+/// How far a signed timestamp may be from the current time when a scheme sets no tolerance
+pub const DEFAULT_TOLERANCE_SECONDS: u64 = 300;
+
+/// Check that a request was signed according to a Trigger node's verification scheme
+///
+/// `headers` are keyed by lowercase name and `now` is the current Unix time in
+/// seconds. Signatures are compared in constant time.
+pub fn verify(
+    verification: &TriggerVerification,
+    secret: &str,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+    now: i64,
+) -> Result<(), String> {
+    match verification {
+        TriggerVerification::Hmac { header: name, prefix, .. } => {
+            let value = header(headers, &name.to_ascii_lowercase())?;
+            let signature = match prefix.as_deref().filter(|p| !p.is_empty()) {
+                Some(prefix) => value
+                    .strip_prefix(prefix)
+                    .ok_or_else(|| format!("{name} header does not start with '{prefix}'"))?,
+                None => value,
+            };
+            check_hmac(secret, &[body], signature)
+        }
+        TriggerVerification::GitHub { .. } => {
+            let signature = header(headers, "x-hub-signature-256")?
+                .strip_prefix("sha256=")
+                .ok_or("X-Hub-Signature-256 header does not start with 'sha256='")?;
+            check_hmac(secret, &[body], signature)
+        }
+        TriggerVerification::Stripe { tolerance_seconds, .. } => {
+            let mut timestamp = None;
+            let mut signatures = Vec::new();
+            for (key, value) in header(headers, "stripe-signature")?.split(',').filter_map(|p| p.trim().split_once('=')) {
+                match key {
+                    "t" => timestamp = Some(value),
+                    "v1" => signatures.push(value),
+                    _ => {}
+                }
+            }
+            let timestamp = timestamp.ok_or("Stripe-Signature header has no timestamp")?;
+            check_timestamp(timestamp, tolerance_seconds.unwrap_or(DEFAULT_TOLERANCE_SECONDS), now)?;
+
+            // Stripe sends one v1 signature per active secret while secrets are rolled
+            let payload = [timestamp.as_bytes(), b".", body];
+            if signatures.iter().any(|signature| check_hmac(secret, &payload, signature).is_ok()) {
+                Ok(())
+            } else {
+                Err("Stripe-Signature header has no matching v1 signature".to_string())
+            }
+        }
+        TriggerVerification::Slack { tolerance_seconds, .. } => {
+            let timestamp = header(headers, "x-slack-request-timestamp")?;
+            check_timestamp(timestamp, tolerance_seconds.unwrap_or(DEFAULT_TOLERANCE_SECONDS), now)?;
+            let signature = header(headers, "x-slack-signature")?
+                .strip_prefix("v0=")
+                .ok_or("X-Slack-Signature header does not start with 'v0='")?;
+            check_hmac(secret, &[b"v0:", timestamp.as_bytes(), b":", body], signature)
+        }
+    }
+}
+
+fn header<'a>(headers: &'a HashMap<String, String>, name: &str) -> Result<&'a str, String> {
+    headers
+        .get(name)
+        .map(|value| value.trim())
+        .ok_or_else(|| format!("Missing {name} header"))
+}
+
+/// Compare a hex HMAC-SHA256 signature against the signature of the concatenated `parts`
+fn check_hmac(secret: &str, parts: &[&[u8]], signature: &str) -> Result<(), String> {
+    let signature = hex::decode(signature.trim()).map_err(|_| "Signature is not valid hex".to_string())?;
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    for part in parts {
+        mac.update(part);
+    }
+    mac.verify_slice(&signature).map_err(|_| "Signature does not match".to_string())
+}
+
+/// Reject signed timestamps too far from `now`, which would allow replaying old requests
+fn check_timestamp(timestamp: &str, tolerance_seconds: u64, now: i64) -> Result<(), String> {
+    let timestamp: i64 = timestamp
+        .parse()
+        .map_err(|_| format!("Invalid signature timestamp '{timestamp}'"))?;
+    if now.abs_diff(timestamp) > tolerance_seconds {
+        return Err(format!("Signature timestamp is more than {tolerance_seconds}s from the current time"));
+    }
+    Ok(())
+}
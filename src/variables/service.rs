@@ -4,12 +4,23 @@ use crate::workflow::errors::{Result, SwissPipeError};
 use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, Set};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 #[derive(Clone)]
 pub struct VariableService {
     db: Arc<DatabaseConnection>,
     encryption: EncryptionService,
+    cache: Arc<Mutex<VariableCache>>,
+}
+
+/// Decrypted variables shared by every clone of the service
+///
+/// The map is dropped whenever a variable is written. The generation counts those
+/// writes, so a load that raced with one is not cached.
+#[derive(Default)]
+struct VariableCache {
+    generation: u64,
+    variables: Option<Arc<HashMap<String, String>>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,7 +50,7 @@ pub struct VariableResponse {
 
 impl VariableService {
     pub fn new(db: Arc<DatabaseConnection>, encryption: EncryptionService) -> Self {
-        Self { db, encryption }
+        Self { db, encryption, cache: Arc::default() }
     }
 
     /// Validate variable name format (A-Z, 0-9, _)
@@ -98,6 +109,7 @@ impl VariableService {
         };
 
         let inserted = variable.insert(self.db.as_ref()).await?;
+        self.invalidate_cache();
 
         Ok(self.model_to_response(inserted))
     }
@@ -144,6 +156,7 @@ impl VariableService {
         active.updated_at = Set(now);
 
         let updated = active.update(self.db.as_ref()).await?;
+        self.invalidate_cache();
 
         Ok(self.model_to_response(updated))
     }
@@ -157,6 +170,7 @@ impl VariableService {
 
         let active: environment_variables::ActiveModel = variable.into();
         active.delete(self.db.as_ref()).await?;
+        self.invalidate_cache();
 
         Ok(())
     }
@@ -184,6 +198,51 @@ impl VariableService {
         Ok(map)
     }
 
+    /// All variables with secrets decrypted, loaded from the database only after a variable changed
+    pub async fn variables(&self) -> Result<Arc<HashMap<String, String>>> {
+        let generation = match self.cache.lock() {
+            Ok(cache) => match &cache.variables {
+                Some(variables) => return Ok(variables.clone()),
+                None => Some(cache.generation),
+            },
+            Err(_) => None,
+        };
+
+        let variables = Arc::new(self.load_variables_map().await?);
+        if let Ok(mut cache) = self.cache.lock() {
+            if Some(cache.generation) == generation {
+                cache.variables = Some(variables.clone());
+            }
+        }
+        Ok(variables)
+    }
+
+    fn invalidate_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            cache.generation += 1;
+            cache.variables = None;
+        }
+    }
+
+    /// Load one variable's value by name, decrypting secrets
+    pub async fn get_value(&self, name: &str) -> Result<Option<String>> {
+        let Some(var) = environment_variables::Entity::find()
+            .filter(environment_variables::Column::Name.eq(name))
+            .one(self.db.as_ref())
+            .await? else {
+            return Ok(None);
+        };
+
+        if var.value_type == "secret" {
+            let value = self.encryption
+                .decrypt(&var.value)
+                .map_err(|e| SwissPipeError::InternalError(format!("Decryption failed: {e}")))?;
+            Ok(Some(value))
+        } else {
+            Ok(Some(var.value))
+        }
+    }
+
     /// Convert model to response (mask secrets)
     fn model_to_response(&self, model: environment_variables::Model) -> VariableResponse {
         let value = if model.value_type == "secret" {
@@ -38,6 +38,17 @@ impl TemplateEngine {
         template: &str,
         variables: &HashMap<String, String>,
         event_data: Option<&Value>
+    ) -> Result<String, String> {
+        let values: Vec<(&str, &Value)> = event_data.map(|event| ("event", event)).into_iter().collect();
+        self.resolve_with_values(template, variables, &values)
+    }
+
+    /// Resolve template with variables and named values such as `event` and `response`
+    pub fn resolve_with_values(
+        &self,
+        template: &str,
+        variables: &HashMap<String, String>,
+        values: &[(&str, &Value)],
     ) -> Result<String, String> {
         // Quick check: if no template markers, return as-is
         if !template.contains("{{") {
@@ -53,9 +64,8 @@ impl TemplateEngine {
 
         context.insert("env".to_string(), serde_json::Value::Object(env_map));
 
-        // Add event data if provided
-        if let Some(event) = event_data {
-            context.insert("event".to_string(), event.clone());
+        for (name, value) in values {
+            context.insert(name.to_string(), (*value).clone());
         }
 
         self.handlebars
@@ -90,6 +100,24 @@ impl Default for TemplateEngine {
     }
 }
 
+// Handlebars helper function for JSON serialization
+fn json_helper(
+    h: &Helper,
+    _: &Handlebars,
+    _: &handlebars::Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let value = h.param(0)
+        .ok_or_else(|| RenderError::new("json helper requires a parameter"))?;
+
+    let json_str = serde_json::to_string_pretty(value.value())
+        .map_err(|e| RenderError::new(format!("Failed to serialize to JSON: {e}")))?;
+
+    out.write(&json_str)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -247,21 +275,3 @@ mod tests {
         assert!(result_bracket.is_ok() || result_dot.is_ok());
     }
 }
-
-// Handlebars helper function for JSON serialization
-fn json_helper(
-    h: &Helper,
-    _: &Handlebars,
-    _: &handlebars::Context,
-    _: &mut RenderContext,
-    out: &mut dyn Output,
-) -> HelperResult {
-    let value = h.param(0)
-        .ok_or_else(|| RenderError::new("json helper requires a parameter"))?;
-
-    let json_str = serde_json::to_string_pretty(value.value())
-        .map_err(|e| RenderError::new(format!("Failed to serialize to JSON: {e}")))?;
-
-    out.write(&json_str)?;
-    Ok(())
-}
@@ -67,6 +67,7 @@ impl WorkflowEngine {
             node_executor.clone(),
             input_sync_service.clone(),
         ));
+        node_executor.set_dag_executor(&dag_executor)?;
 
         Ok(Self {
             workflow_loader,
@@ -1,24 +1,31 @@
 use crate::{
     workflow::{
-        errors::{Result, SwissPipeError},
-        models::{Node, Workflow, WorkflowEvent, InputMergeStrategy, NodeOutput, HilMultiPathResult, NodeType},
+        errors::{AppError, Result, SwissPipeError},
+        models::{Edge, Node, Workflow, WorkflowEvent, InputMergeStrategy, NodeOutput, HilMultiPathResult, NodeType, FailureAction, ERROR_HANDLE, SWITCH_DEFAULT_CASE},
         input_sync::InputSyncService,
+        validation::{ForEachBody, WorkflowValidator},
     },
     log_workflow_error, log_workflow_warn,
 };
+use futures::{future::BoxFuture, StreamExt, TryStreamExt};
 use std::{collections::{HashMap, HashSet}, sync::Arc};
 use tokio::task::JoinSet;
 
-use super::node_executor::NodeExecutor;
+use super::node_executor::{json_type_name, NodeExecutor};
 
+/// Items a ForEach node runs at once when it doesn't set `concurrency`
+const DEFAULT_FOREACH_CONCURRENCY: u32 = 10;
+
+#[derive(Clone)]
 pub struct DagExecutor {
     node_executor: Arc<NodeExecutor>,
     _input_sync_service: Arc<InputSyncService>,
 }
 
 struct ExecutionContext<'a> {
+    original_workflow: &'a Arc<Workflow>, // Full workflow, including the ForEach bodies hidden from the run
+    item_index: Option<usize>, // ForEach item this run executes, if any
     predecessors: &'a HashMap<String, Vec<String>>,
-    successors: &'a HashMap<String, Vec<(String, Option<bool>)>>,
     completed_nodes: &'a HashSet<String>,
     node_outputs: &'a HashMap<String, WorkflowEvent>,
     pending_executions: &'a mut JoinSet<Result<(String, NodeOutput)>>,
@@ -28,6 +35,25 @@ struct ExecutionContext<'a> {
     pending_nodes: &'a mut HashSet<String>, // Nodes currently pending execution
 }
 
+/// How a DAG run ended
+enum DagOutcome {
+    Finished {
+        completed_nodes: HashSet<String>,
+        node_outputs: HashMap<String, WorkflowEvent>,
+    },
+    /// A HIL node is waiting for a human response
+    Blocked(Box<WorkflowEvent>),
+}
+
+/// A ForEach node's body, ready to be run once per item
+struct ForEachRun<'a> {
+    original_workflow: &'a Arc<Workflow>,
+    item_view: Workflow,
+    foreach_id: &'a str,
+    aggregate_id: &'a str,
+    execution_id: &'a str,
+}
+
 struct HilExecutionParams<'a> {
     workflow: &'a Workflow,
     hil_node_id: &'a str,
@@ -56,27 +82,11 @@ impl DagExecutor {
     pub async fn execute_workflow(&self, workflow: &Workflow, event: WorkflowEvent, execution_id: &str) -> Result<WorkflowEvent> {
         tracing::info!("Starting DAG execution for workflow '{}' with execution_id '{}'", workflow.name, execution_id);
 
-        // Build graph structures for DAG traversal
-        let node_map: HashMap<String, &Node> = workflow.nodes
-            .iter()
-            .map(|node| (node.id.clone(), node))
-            .collect();
-
-        let predecessors = self.build_predecessor_map(workflow);
-        let successors = self.build_successor_map(workflow);
-
-        // Initialize execution state
-        let mut completed_nodes: HashSet<String> = HashSet::new();
-        let mut node_outputs: HashMap<String, WorkflowEvent> = HashMap::new();
-        let mut pending_executions: JoinSet<Result<(String, NodeOutput)>> = JoinSet::new();
-        let mut executed_hil_handles: HashMap<String, HashSet<String>> = HashMap::new(); // Track executed HIL handles
-        let mut multipath_scheduled_nodes: HashSet<String> = HashSet::new(); // Track nodes scheduled by MultiPath execution
-        let mut pending_nodes: HashSet<String> = HashSet::new(); // Track nodes currently pending execution
-
         // Start with the trigger node
         let start_node_id = workflow.start_node_id.as_ref()
             .ok_or_else(|| SwissPipeError::Config("Workflow missing start_node_id".to_string()))?;
-        let start_node = node_map.get(start_node_id)
+        let start_node = workflow.nodes.iter()
+            .find(|node| node.id == *start_node_id)
             .ok_or_else(|| SwissPipeError::NodeNotFound(start_node_id.clone()))?;
 
         // Execute trigger node first
@@ -85,7 +95,7 @@ impl DagExecutor {
             NodeOutput::Continue(event) => event,
             NodeOutput::Complete => {
                 tracing::info!("Trigger node completed workflow immediately");
-                return self.get_final_output(workflow, &completed_nodes, &node_outputs);
+                return self.get_final_output(workflow, &HashSet::new(), &HashMap::new());
             },
             NodeOutput::MultiPath(_) => {
                 return Err(SwissPipeError::Generic("Trigger nodes cannot return MultiPath".to_string()));
@@ -95,15 +105,48 @@ impl DagExecutor {
                 return Ok(event); // Return the event, async processing will handle the rest
             }
         };
-        completed_nodes.insert(start_node_id.clone());
-        node_outputs.insert(start_node_id.clone(), trigger_event.clone());
 
         tracing::info!("Completed trigger node '{}' (id: {}), looking for next nodes", start_node.name, start_node_id);
 
+        // ForEach bodies run once per item, so the top-level run only sees each ForEach feeding its Aggregate
+        let original_workflow = Arc::new(workflow.clone());
+        let view = Self::collapse_foreach_bodies(workflow, None)?;
+
+        match self.run_dag(&view, &original_workflow, start_node_id, trigger_event, execution_id, None).await? {
+            DagOutcome::Blocked(blocking_event) => Ok(*blocking_event),
+            DagOutcome::Finished { completed_nodes, node_outputs } => {
+                tracing::info!("DAG execution completed successfully for execution_id '{}'", execution_id);
+
+                // Return the final output (last completed node or aggregate)
+                self.get_final_output(&view, &completed_nodes, &node_outputs)
+            }
+        }
+    }
+
+    /// Run every node reachable from an already completed start node
+    async fn run_dag(
+        &self,
+        workflow: &Workflow,
+        original_workflow: &Arc<Workflow>,
+        start_node_id: &str,
+        start_event: WorkflowEvent,
+        execution_id: &str,
+        item_index: Option<usize>,
+    ) -> Result<DagOutcome> {
+        let predecessors = self.build_predecessor_map(workflow);
+        // Initialize execution state
+        let mut completed_nodes: HashSet<String> = HashSet::from([start_node_id.to_string()]);
+        let mut node_outputs: HashMap<String, WorkflowEvent> = HashMap::from([(start_node_id.to_string(), start_event)]);
+        let mut pending_executions: JoinSet<Result<(String, NodeOutput)>> = JoinSet::new();
+        let mut executed_hil_handles: HashMap<String, HashSet<String>> = HashMap::new(); // Track executed HIL handles
+        let mut multipath_scheduled_nodes: HashSet<String> = HashSet::new(); // Track nodes scheduled by MultiPath execution
+        let mut pending_nodes: HashSet<String> = HashSet::new(); // Track nodes currently pending execution
+
         // Schedule immediately ready nodes for concurrent execution
         let mut execution_context = ExecutionContext {
+            original_workflow,
+            item_index,
             predecessors: &predecessors,
-            successors: &successors,
             completed_nodes: &completed_nodes,
             node_outputs: &node_outputs,
             pending_executions: &mut pending_executions,
@@ -170,10 +213,12 @@ impl DagExecutor {
                                         metadata: HashMap::new(),
                                         headers: HashMap::new(),
                                         condition_results: HashMap::new(),
+                                        switch_results: HashMap::new(),
+                                        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
                                     };
-                                    return Ok(blocking_event);
+                                    return Ok(DagOutcome::Blocked(Box::new(blocking_event)));
                                 }
                             },
                             NodeOutput::Complete => {
@@ -191,8 +236,9 @@ impl DagExecutor {
 
                         // Schedule any newly ready nodes
                         let mut execution_context = ExecutionContext {
+                            original_workflow,
+                            item_index,
                             predecessors: &predecessors,
-                            successors: &successors,
                             completed_nodes: &completed_nodes,
                             node_outputs: &node_outputs,
                             pending_executions: &mut pending_executions,
@@ -217,10 +263,7 @@ impl DagExecutor {
             }
         }
 
-        tracing::info!("DAG execution completed successfully for execution_id '{}'", execution_id);
-
-        // Return the final output (last completed node or aggregate)
-        self.get_final_output(workflow, &completed_nodes, &node_outputs)
+        Ok(DagOutcome::Finished { completed_nodes, node_outputs })
     }
 
     fn build_predecessor_map(&self, workflow: &Workflow) -> HashMap<String, Vec<String>> {
@@ -293,9 +336,9 @@ impl DagExecutor {
 
                 // Collect inputs from predecessors
                 let inputs = self.collect_node_inputs(
+                    workflow,
                     &node.id,
                     &node_predecessors,
-                    execution_context.successors,
                     execution_context.node_outputs,
                     execution_context.completed_nodes,
                 )?;
@@ -309,6 +352,30 @@ impl DagExecutor {
                     // Clone necessary data for the async task
                     let node_clone = node.clone();
                     let execution_id = execution_context.execution_id.to_string();
+                    let item_index = execution_context.item_index;
+                    // Nodes with an error edge keep their inputs to report what they failed on
+                    let error_inputs = workflow.edges.iter()
+                        .any(|e| e.from_node_id == node.id && e.source_handle_id.as_deref() == Some(ERROR_HANDLE))
+                        .then(|| inputs.clone());
+
+                    if matches!(node.node_type, NodeType::ForEach { .. }) {
+                        // Fan out over the selected items; the results feed the Aggregate
+                        let dag_executor = self.clone();
+                        let original_workflow = execution_context.original_workflow.clone();
+                        execution_context.pending_executions.spawn(async move {
+                            let result = dag_executor.execute_foreach(
+                                &original_workflow,
+                                &node_clone,
+                                inputs,
+                                &execution_id,
+                                item_index,
+                            ).await;
+                            let output = Self::catch_node_error(&node_clone, result, error_inputs, &execution_id)?;
+                            Ok((node_clone.id, output))
+                        });
+                        continue;
+                    }
+
                     let node_executor = self.node_executor.clone();
 
                     // Spawn async execution
@@ -318,11 +385,10 @@ impl DagExecutor {
                             &node_clone,
                             inputs,
                             &execution_id,
+                            item_index,
                         ).await;
-                        match result {
-                            Ok(output) => Ok((node_clone.id, output)),
-                            Err(e) => Err(e),
-                        }
+                        let output = Self::catch_node_error(&node_clone, result, error_inputs, &execution_id)?;
+                        Ok((node_clone.id, output))
                     });
                 } else {
                     tracing::debug!("Skipping node '{}' - no valid inputs after condition filtering", node.name);
@@ -364,6 +430,7 @@ impl DagExecutor {
                         &node_clone,
                         vec![event_clone],
                         &execution_id_clone,
+                        None,
                     ).await;
                     match result {
                         Ok(output) => Ok((node_clone.id, output)),
@@ -462,6 +529,8 @@ impl DagExecutor {
     /// 2. It has a conditional edge where the condition path was taken
     ///
     /// Predecessors that haven't executed and won't execute (due to conditional paths) are skipped.
+    /// A node joined by a normal edge to a failed node whose error was caught never becomes
+    /// ready, as only the failed node's error edges carry on.
     fn check_predecessors_ready(
         &self,
         workflow: &Workflow,
@@ -472,11 +541,45 @@ impl DagExecutor {
         node_outputs: &HashMap<String, WorkflowEvent>,
     ) -> Result<bool> {
         for pred_id in predecessors {
+            let pred_failed = node_outputs.get(pred_id).is_some_and(|output| output.failed_nodes.contains(pred_id));
+            let error_edge = workflow.edges.iter().any(|e|
+                e.from_node_id == *pred_id && e.to_node_id == node_id && e.source_handle_id.as_deref() == Some(ERROR_HANDLE)
+            );
+            if pred_failed && !error_edge {
+                tracing::debug!("Node '{}' skipped - predecessor '{}' failed and only follows its error edges", node_id, pred_id);
+                return Ok(false);
+            }
+
             // Check if this edge is conditional and if the condition path should be followed
             let should_wait_for_pred = if let Some(edge) = workflow.edges.iter().find(|e|
                 e.from_node_id == *pred_id && e.to_node_id == node_id
             ) {
-                if let Some(expected_result) = edge.condition_result {
+                let pred_is_switch = workflow.nodes.iter()
+                    .any(|n| n.id == *pred_id && matches!(n.node_type, NodeType::Switch { .. }));
+
+                if pred_is_switch {
+                    // Switch edge - wait only if one of the edges to this node leaves the selected case
+                    match node_outputs.get(pred_id).and_then(|output| output.switch_results.get(pred_id)) {
+                        Some(selected_case) => {
+                            let path_taken = workflow.edges.iter()
+                                .filter(|e| e.from_node_id == *pred_id && e.to_node_id == node_id)
+                                .any(|e| Self::switch_edge_taken(e, selected_case));
+                            tracing::debug!(
+                                "Node '{}' switch predecessor '{}': selected='{}', path_taken={}",
+                                node_id, pred_id, selected_case, path_taken
+                            );
+                            path_taken
+                        }
+                        None => {
+                            // Switch hasn't executed - it's on an untaken path
+                            tracing::debug!(
+                                "Node '{}' skipping switch predecessor '{}' - not executed (unreachable path)",
+                                node_id, pred_id
+                            );
+                            false
+                        }
+                    }
+                } else if let Some(expected_result) = edge.condition_result {
                     // This is a conditional edge - check if the condition was evaluated
                     if let Some(pred_output) = node_outputs.get(pred_id) {
                         // Predecessor has completed - check if condition matches
@@ -608,40 +711,31 @@ impl DagExecutor {
 
     fn collect_node_inputs(
         &self,
+        workflow: &Workflow,
         node_id: &str,
         predecessors: &[String],
-        successors: &HashMap<String, Vec<(String, Option<bool>)>>,
         node_outputs: &HashMap<String, WorkflowEvent>,
         _completed_nodes: &HashSet<String>,
     ) -> Result<Vec<WorkflowEvent>> {
         let mut inputs = Vec::new();
 
+        // Predecessors appear once per edge, so only visit each of them once
+        let mut visited = HashSet::new();
         for pred_id in predecessors {
+            if !visited.insert(pred_id) {
+                continue;
+            }
             if let Some(pred_output) = node_outputs.get(pred_id) {
                 // Check if this edge should be followed based on conditions
-                if let Some(pred_successors) = successors.get(pred_id) {
-                    for (succ_id, condition_result) in pred_successors {
-                        if succ_id == node_id {
-                            if let Some(expected_result) = condition_result {
-                                // Check if condition matches - use node ID as key
-                                let actual_result = pred_output.condition_results
-                                    .get(pred_id)
-                                    .copied()
-                                    .unwrap_or(false);
-
-                                tracing::debug!(
-                                    "Condition edge: pred_id='{}', expected={}, actual={}, follow={}, condition_results={:?}",
-                                    pred_id, expected_result, actual_result, actual_result == *expected_result, pred_output.condition_results
-                                );
-
-                                if actual_result == *expected_result {
-                                    inputs.push(pred_output.clone());
-                                }
-                            } else {
-                                // Unconditional edge (no condition_result or source_handle_id specific routing)
-                                inputs.push(pred_output.clone());
-                            }
-                        }
+                for edge in workflow.edges.iter().filter(|e| e.from_node_id == *pred_id && e.to_node_id == node_id) {
+                    let follow = Self::edge_followed(edge, pred_output);
+                    tracing::debug!(
+                        "Edge '{}' -> '{}': handle={:?}, condition={:?}, follow={}",
+                        pred_id, node_id, edge.source_handle_id, edge.condition_result, follow
+                    );
+
+                    if follow {
+                        inputs.push(pred_output.clone());
                     }
                 }
             }
@@ -650,6 +744,36 @@ impl DagExecutor {
         Ok(inputs)
     }
 
+    /// Whether an edge carries its source node's output, given the switch case or condition it produced
+    ///
+    /// A failed node's output only follows its `error` edges, and those edges carry nothing else.
+    fn edge_followed(edge: &Edge, source_output: &WorkflowEvent) -> bool {
+        let source_failed = source_output.failed_nodes.contains(&edge.from_node_id);
+        if edge.source_handle_id.as_deref() == Some(ERROR_HANDLE) || source_failed {
+            edge.source_handle_id.as_deref() == Some(ERROR_HANDLE) && source_failed
+        } else if let Some(selected_case) = source_output.switch_results.get(&edge.from_node_id) {
+            // Switch edge - follow it when its handle is the selected case
+            Self::switch_edge_taken(edge, selected_case)
+        } else if let Some(expected_result) = edge.condition_result {
+            // Check if condition matches - use node ID as key
+            let actual_result = source_output.condition_results
+                .get(&edge.from_node_id)
+                .copied()
+                .unwrap_or(false);
+            actual_result == expected_result
+        } else {
+            // Unconditional edge (no condition_result or source_handle_id specific routing)
+            true
+        }
+    }
+
+    /// Whether a Switch node's edge leads out of the case it selected
+    ///
+    /// Edges without a `source_handle_id` belong to the default case.
+    fn switch_edge_taken(edge: &Edge, selected_case: &str) -> bool {
+        edge.source_handle_id.as_deref().unwrap_or(SWITCH_DEFAULT_CASE) == selected_case
+    }
+
     /// Execute a single node with input merging
     async fn execute_single_node(
         &self,
@@ -667,9 +791,217 @@ impl DagExecutor {
         node: &Node,
         inputs: Vec<WorkflowEvent>,
         execution_id: &str,
+        item_index: Option<usize>,
     ) -> Result<NodeOutput> {
         let merged_input = Self::merge_inputs_static(inputs, node)?;
-        node_executor.execute_node_with_output(node, merged_input, execution_id).await
+        node_executor.execute_node_for_item(node, merged_input, execution_id, item_index).await
+    }
+
+    /// Send a failed node's error down its `error` edges instead of failing the execution
+    ///
+    /// `error_inputs` holds the node's inputs when it has error edges; otherwise the error is returned.
+    fn catch_node_error(
+        node: &Node,
+        result: Result<NodeOutput>,
+        error_inputs: Option<Vec<WorkflowEvent>>,
+        execution_id: &str,
+    ) -> Result<NodeOutput> {
+        let (error, inputs) = match (result, error_inputs) {
+            (Err(error), Some(inputs)) => (error, inputs),
+            (result, _) => return result,
+        };
+        log_workflow_warn!(&node.workflow_id, execution_id, &node.id,
+            format!("Node '{}' failed, following its error edges: {}", node.name, error));
+
+        let mut event = Self::merge_inputs_static(inputs, node)?;
+        let input = std::mem::take(&mut event.data);
+        event.data = serde_json::json!({
+            "error": {
+                "message": error.to_string(),
+                "node_id": node.id,
+                "node_name": node.name,
+                "attempts": Self::failed_attempts(node, &error),
+                "input": input,
+            }
+        });
+        event.failed_nodes.insert(node.id.clone());
+        Ok(NodeOutput::Continue(event))
+    }
+
+    /// How many times a node tried before giving up with `error`
+    fn failed_attempts(node: &Node, error: &SwissPipeError) -> u32 {
+        match (error, &node.node_type) {
+            (SwissPipeError::App(AppError::HttpRequestFailed { attempts, .. }), _) => *attempts,
+            (_, NodeType::Anthropic { failure_action: FailureAction::Retry, retry_config, .. }) => retry_config.max_attempts,
+            _ => 1,
+        }
+    }
+
+    /// Run a ForEach node's body once per selected item and collect the results in item order
+    ///
+    /// Items run `concurrency` at a time. The first failing item fails the node and
+    /// stops the items that haven't started yet.
+    async fn execute_foreach(
+        &self,
+        original_workflow: &Arc<Workflow>,
+        node: &Node,
+        inputs: Vec<WorkflowEvent>,
+        execution_id: &str,
+        item_index: Option<usize>,
+    ) -> Result<NodeOutput> {
+        let concurrency = match &node.node_type {
+            NodeType::ForEach { concurrency, .. } => concurrency.unwrap_or(DEFAULT_FOREACH_CONCURRENCY).max(1) as usize,
+            _ => 1,
+        };
+
+        let mut selected = match Self::execute_single_node_static(
+            self.node_executor.clone(),
+            node,
+            inputs,
+            execution_id,
+            item_index,
+        ).await? {
+            NodeOutput::Continue(event) => event,
+            other => return Ok(other),
+        };
+        let items = match std::mem::take(&mut selected.data) {
+            serde_json::Value::Array(items) => items,
+            other => {
+                return Err(SwissPipeError::Generic(format!(
+                    "ForEach node '{}' selector must return an array, got {}",
+                    node.name,
+                    json_type_name(&other)
+                )));
+            }
+        };
+
+        let body = WorkflowValidator::foreach_body(&original_workflow.nodes, &original_workflow.edges, &node.id)?;
+        let item_workflow = Self::foreach_item_workflow(original_workflow, node, &body);
+        let run = ForEachRun {
+            original_workflow,
+            item_view: Self::collapse_foreach_bodies(&item_workflow, Some(&node.id))?,
+            foreach_id: &node.id,
+            aggregate_id: &body.aggregate_id,
+            execution_id,
+        };
+
+        tracing::info!(
+            "ForEach node '{}' running {} items with concurrency {}",
+            node.name, items.len(), concurrency
+        );
+
+        let results: Vec<Option<serde_json::Value>> = futures::stream::iter(items.into_iter().enumerate())
+            .map(|(index, item)| {
+                let event = WorkflowEvent { data: item, ..selected.clone() };
+                self.run_foreach_item(&run, event, index)
+            })
+            .buffered(concurrency)
+            .try_collect()
+            .await?;
+
+        // Items whose body produced nothing for the Aggregate are dropped
+        selected.data = serde_json::Value::Array(results.into_iter().flatten().collect());
+        Ok(NodeOutput::Continue(selected))
+    }
+
+    /// Run one item through a ForEach body and return what reached the Aggregate
+    ///
+    /// Several edges into the Aggregate yield an array; none yields `None`. Boxed because
+    /// a nested ForEach recurses back into `run_dag`.
+    fn run_foreach_item<'a>(
+        &'a self,
+        run: &'a ForEachRun<'a>,
+        event: WorkflowEvent,
+        index: usize,
+    ) -> BoxFuture<'a, Result<Option<serde_json::Value>>> {
+        Box::pin(async move {
+            let outcome = self.run_dag(
+                &run.item_view,
+                run.original_workflow,
+                run.foreach_id,
+                event,
+                run.execution_id,
+                Some(index),
+            ).await.map_err(|e| SwissPipeError::Generic(format!("ForEach item {index} failed: {e}")))?;
+
+            let DagOutcome::Finished { node_outputs, .. } = outcome else {
+                return Err(SwissPipeError::Generic(format!("ForEach item {index} cannot wait for human input")));
+            };
+
+            let mut results: Vec<serde_json::Value> = run.original_workflow.edges.iter()
+                .filter(|edge| edge.to_node_id == run.aggregate_id)
+                .filter_map(|edge| {
+                    node_outputs.get(&edge.from_node_id)
+                        .filter(|output| Self::edge_followed(edge, output))
+                        .map(|output| output.data.clone())
+                })
+                .collect();
+
+            Ok(match results.len() {
+                0 => None,
+                1 => results.pop(),
+                _ => Some(serde_json::Value::Array(results)),
+            })
+        })
+    }
+
+    /// The part of a workflow a ForEach runs per item: the ForEach itself and its body
+    fn foreach_item_workflow(workflow: &Workflow, foreach: &Node, body: &ForEachBody) -> Workflow {
+        let in_item = |id: &str| id == foreach.id || body.nodes.contains(id);
+        Workflow {
+            id: workflow.id.clone(),
+            name: workflow.name.clone(),
+            description: workflow.description.clone(),
+            start_node_id: Some(foreach.id.clone()),
+            enabled: workflow.enabled,
+            allowed_host_functions: workflow.allowed_host_functions.clone(),
+            nodes: workflow.nodes.iter().filter(|n| in_item(&n.id)).cloned().collect(),
+            edges: workflow.edges.iter()
+                .filter(|e| in_item(&e.from_node_id) && in_item(&e.to_node_id))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Hide ForEach bodies so that each ForEach feeds its Aggregate directly
+    ///
+    /// `expanded` names the ForEach whose body an item run executes; it stays visible.
+    fn collapse_foreach_bodies(workflow: &Workflow, expanded: Option<&str>) -> Result<Workflow> {
+        let mut hidden = HashSet::new();
+        let mut shortcuts = Vec::new();
+        for node in &workflow.nodes {
+            if !matches!(node.node_type, NodeType::ForEach { .. }) || expanded == Some(node.id.as_str()) {
+                continue;
+            }
+            let body = WorkflowValidator::foreach_body(&workflow.nodes, &workflow.edges, &node.id)?;
+            hidden.extend(body.nodes);
+            shortcuts.push((node.id.clone(), body.aggregate_id));
+        }
+
+        let mut view = workflow.clone();
+        if shortcuts.is_empty() {
+            return Ok(view);
+        }
+
+        view.nodes.retain(|n| !hidden.contains(&n.id));
+        view.edges.retain(|e| !hidden.contains(&e.from_node_id) && !hidden.contains(&e.to_node_id));
+        for (foreach_id, aggregate_id) in shortcuts {
+            // Nested ForEach nodes went away with their parent's body
+            let already_linked = view.edges.iter().any(|e| e.from_node_id == foreach_id && e.to_node_id == aggregate_id);
+            if hidden.contains(&foreach_id) || already_linked {
+                continue;
+            }
+            view.edges.push(Edge {
+                id: format!("{foreach_id}->{aggregate_id}"),
+                workflow_id: workflow.id.clone(),
+                from_node_id: foreach_id,
+                to_node_id: aggregate_id,
+                condition_result: None,
+                source_handle_id: None,
+            });
+        }
+
+        Ok(view)
     }
 
     /// Merge multiple inputs based on node's input merge strategy
@@ -688,6 +1020,8 @@ impl DagExecutor {
                 metadata: HashMap::new(),
                 headers: HashMap::new(),
                 condition_results: HashMap::new(),
+                switch_results: HashMap::new(),
+                failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
             })
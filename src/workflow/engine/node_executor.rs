@@ -1,14 +1,14 @@
 use crate::{
     anthropic::{AnthropicService, AnthropicCallConfig},
-    async_execution::{HttpLoopScheduler, StepTracker},
+    async_execution::{ExecutionService, HttpLoopScheduler, ParentExecution, StepTracker},
     database::human_in_loop_tasks,
     email::{service::EmailService, EmailConfig},
     hil::{HilService, service::HilTaskParams},
-    utils::{http_client::AppExecutor, javascript::JavaScriptExecutor},
+    utils::{http_auth::{AwsCredentials, OAuth2Credentials, RequestAuth}, http_client::{AppExecutor, HttpResponse, RequestBody, RequestContent}, javascript::{JavaScriptExecutor, ScriptOptions}, javascript_console::ConsoleCapture, json_path},
     variables::{VariableService, TemplateEngine},
     workflow::{
         errors::{Result, SwissPipeError},
-        models::{Node, NodeType, WorkflowEvent, FailureAction, RetryConfig, NodeOutput},
+        models::{Node, NodeType, WorkflowEvent, FailureAction, RetryConfig, NodeOutput, HostFunction, ScriptLimits, TriggerResponse, HeaderForwarding, HttpAuth, HttpRequestBody, HttpResponseFormat, HttpResponseMapping, SWITCH_DEFAULT_CASE},
     },
     log_workflow_error, log_workflow_warn,
 };
@@ -37,23 +37,36 @@ struct ExecuteNodeParams<'a> {
     node_name: &'a str,
     workflow_id: &'a str,
     node_id: &'a str,
+    host_functions: &'a [HostFunction],
+    console: Option<&'a ConsoleCapture>,
 }
-use std::sync::{Arc, OnceLock};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, Weak};
+use futures::future::BoxFuture;
 use sea_orm::{DatabaseConnection, ActiveModelTrait, Set};
 use uuid::Uuid;
 
+use super::{dag_executor::DagExecutor, workflow_loader::WorkflowLoader};
+
+/// How long a SubWorkflow node waits for its child when it sets no `timeout_seconds`
+const DEFAULT_SUB_WORKFLOW_TIMEOUT_SECONDS: u64 = 300;
+
+/// Event metadata key holding the child execution started by a SubWorkflow node
+pub const SUB_WORKFLOW_EXECUTION_ID_KEY: &str = "sub_workflow_execution_id";
+
 pub struct NodeExecutor {
     js_executor: Arc<JavaScriptExecutor>,
     app_executor: Arc<AppExecutor>,
     email_service: Option<Arc<EmailService>>,
     anthropic_service: Arc<AnthropicService>,
-    #[allow(dead_code)] // May be used in future for direct database operations
     db: Arc<DatabaseConnection>,
     http_loop_scheduler: Arc<OnceLock<Arc<HttpLoopScheduler>>>,
     hil_service: Arc<OnceLock<Arc<HilService>>>,
     step_tracker: Arc<StepTracker>,
     variable_service: Arc<OnceLock<Arc<VariableService>>>,
     template_engine: Arc<OnceLock<Arc<TemplateEngine>>>,
+    // Weak because the DAG executor owns this node executor
+    dag_executor: Arc<OnceLock<Weak<DagExecutor>>>,
 }
 
 impl NodeExecutor {
@@ -76,6 +89,7 @@ impl NodeExecutor {
             step_tracker,
             variable_service: Arc::new(OnceLock::new()),
             template_engine: Arc::new(OnceLock::new()),
+            dag_executor: Arc::new(OnceLock::new()),
         }
     }
 
@@ -139,6 +153,12 @@ impl NodeExecutor {
             .map_err(|_| SwissPipeError::Generic("Template engine already initialized".to_string()))
     }
 
+    /// Set the DAG executor that runs the child workflows of SubWorkflow nodes
+    pub fn set_dag_executor(&self, dag_executor: &Arc<DagExecutor>) -> Result<()> {
+        self.dag_executor.set(Arc::downgrade(dag_executor))
+            .map_err(|_| SwissPipeError::Generic("DAG executor already initialized".to_string()))
+    }
+
     /// Execute a single node based on its type
     pub async fn execute_node(
         &self,
@@ -153,6 +173,8 @@ impl NodeExecutor {
             node_name: &node.name,
             workflow_id: &node.workflow_id,
             node_id: &node.id,
+            host_functions: &node.allowed_host_functions,
+            console: None,
         };
         self.execute_node_by_type(params, event).await
     }
@@ -163,6 +185,17 @@ impl NodeExecutor {
         node: &Node,
         event: WorkflowEvent,
         execution_id: &str,
+    ) -> Result<NodeOutput> {
+        self.execute_node_for_item(node, event, execution_id, None).await
+    }
+
+    /// Execute a single node, recording the ForEach item it runs for on its execution step
+    pub async fn execute_node_for_item(
+        &self,
+        node: &Node,
+        event: WorkflowEvent,
+        execution_id: &str,
+        item_index: Option<usize>,
     ) -> Result<NodeOutput> {
         // Create execution step for tracking - store complete event with data, metadata, headers, etc.
         let event_json = serde_json::to_value(&event).map_err(|e| {
@@ -176,6 +209,7 @@ impl NodeExecutor {
             &node.id,
             &node.name,
             input_data,
+            item_index,
         ).await.map_err(|e| {
             log_workflow_warn!(&node.workflow_id, execution_id, &node.id,
                 format!("Failed to create execution step: {}", e));
@@ -188,7 +222,8 @@ impl NodeExecutor {
                 format!("Failed to mark step as running: {}", e));
         }
 
-        // Execute the node
+        // Execute the node, capturing console output from script nodes
+        let console = ConsoleCapture::new();
         let result = if let NodeType::HumanInLoop { .. } = node.node_type {
             // For HIL nodes, call the special HIL handler that returns NodeOutput::MultiPath
             let params = ExecuteNodeParams {
@@ -197,16 +232,30 @@ impl NodeExecutor {
                 node_name: &node.name,
                 workflow_id: &node.workflow_id,
                 node_id: &node.id,
+                host_functions: &node.allowed_host_functions,
+                console: None,
             };
             self.execute_hil_node_with_output(params, event).await
         } else {
             // For all other node types, execute normally and wrap in Continue
-            match self.execute_node(node, event, execution_id).await {
+            let params = ExecuteNodeParams {
+                node_type: &node.node_type,
+                execution_id,
+                node_name: &node.name,
+                workflow_id: &node.workflow_id,
+                node_id: &node.id,
+                host_functions: &node.allowed_host_functions,
+                console: Some(&console),
+            };
+            match self.execute_node_by_type(params, event).await {
                 Ok(result_event) => Ok(NodeOutput::Continue(result_event)),
                 Err(e) => Err(e),
             }
         };
 
+        // Console output is kept even when the script failed, it is usually what explains the failure
+        let console_log = Some(console.log()).filter(|log| !log.is_empty());
+
         // Update step based on result
         match &result {
             Ok(node_output) => {
@@ -217,14 +266,14 @@ impl NodeExecutor {
                     NodeOutput::AsyncPending(event) => Some(&event.data),
                 };
 
-                if let Err(e) = self.step_tracker.complete_step(&step_id, output_data).await {
+                if let Err(e) = self.step_tracker.complete_step(&step_id, output_data, console_log.as_ref()).await {
                     log_workflow_warn!(&node.workflow_id, execution_id, &node.id,
                         format!("Failed to complete execution step: {}", e));
                 }
             }
             Err(error) => {
                 let error_message = error.to_string();
-                if let Err(e) = self.step_tracker.fail_step(&step_id, &error_message, None).await {
+                if let Err(e) = self.step_tracker.fail_step(&step_id, &error_message, None, console_log.as_ref()).await {
                     log_workflow_warn!(&node.workflow_id, execution_id, &node.id,
                         format!("Failed to mark step as failed: {}", e));
                 }
@@ -249,6 +298,8 @@ impl NodeExecutor {
             node_name: &node.name,
             workflow_id: &node.workflow_id,
             node_id: &node.id,
+            host_functions: &node.allowed_host_functions,
+            console: None,
         };
         self.execute_node_by_type(params, event).await
     }
@@ -261,13 +312,47 @@ impl NodeExecutor {
     ) -> Result<WorkflowEvent> {
         match params.node_type {
             NodeType::Trigger { .. } => Ok(event),
-            NodeType::Condition { script } => {
-                self.execute_condition_node(script, event, params.node_name, params.node_id).await
+            NodeType::Condition { script, limits } => {
+                let variables = self.load_script_variables(params.host_functions).await?;
+                let options = ScriptOptions {
+                    limits: limits.as_ref(),
+                    host_functions: params.host_functions,
+                    variables: variables.as_deref(),
+                    console: params.console,
+                };
+                self.execute_condition_node(script, options, event, params.node_name, params.node_id).await
+            }
+            NodeType::Switch { script, cases, limits } => {
+                let variables = self.load_script_variables(params.host_functions).await?;
+                let options = ScriptOptions {
+                    limits: limits.as_ref(),
+                    host_functions: params.host_functions,
+                    variables: variables.as_deref(),
+                    console: params.console,
+                };
+                self.execute_switch_node(script, cases, options, event, params.node_name, params.node_id).await
+            }
+            NodeType::ForEach { selector, limits, .. } => {
+                self.execute_foreach_node(selector, limits.as_ref(), &params, event).await
+            }
+            NodeType::Aggregate {} => Ok(event),
+            NodeType::SubWorkflow { workflow_id, wait, timeout_seconds } => {
+                self.execute_sub_workflow_node(workflow_id, *wait, *timeout_seconds, &params, event).await
             }
-            NodeType::Transformer { script } => {
-                self.execute_transformer_node(script, event, params.node_name, params.node_id).await
+            NodeType::Respond { status_code, headers, body_template } => {
+                self.execute_respond_node(*status_code, headers, body_template, &params, event).await
             }
-            NodeType::HttpRequest { url, method, timeout_seconds, failure_action, retry_config, headers, loop_config } => {
+            NodeType::Transformer { script, limits } => {
+                let variables = self.load_script_variables(params.host_functions).await?;
+                let options = ScriptOptions {
+                    limits: limits.as_ref(),
+                    host_functions: params.host_functions,
+                    variables: variables.as_deref(),
+                    console: params.console,
+                };
+                self.execute_transformer_node(script, options, event, params.node_name, params.node_id).await
+            }
+            NodeType::HttpRequest { url, method, timeout_seconds, failure_action, retry_config, headers, loop_config, body, query_params, response_mapping, response_format, success_status_codes, auth, forward_headers } => {
                 let config = HttpRequestConfig {
                     url,
                     method,
@@ -275,6 +360,13 @@ impl NodeExecutor {
                     failure_action,
                     retry_config,
                     headers,
+                    body: body.as_deref(),
+                    query_params,
+                    response_mapping: response_mapping.as_ref(),
+                    response_format: *response_format,
+                    success_status_codes,
+                    auth: auth.as_deref(),
+                    forward_headers,
                     node_name: params.node_name,
                     loop_config,
                     workflow_id: params.workflow_id,
@@ -340,11 +432,12 @@ impl NodeExecutor {
     async fn execute_condition_node(
         &self,
         script: &str,
+        options: ScriptOptions<'_>,
         mut event: WorkflowEvent,
         node_name: &str,
         node_id: &str,
     ) -> Result<WorkflowEvent> {
-        let condition_result = self.js_executor.execute_condition(script, &event).await?;
+        let condition_result = self.js_executor.execute_condition_with_options(script, &event, options).await?;
         tracing::info!("Condition node '{}' evaluated to: {}", node_name, condition_result);
 
         // Store condition result in event for edge routing using node ID as key
@@ -352,10 +445,213 @@ impl NodeExecutor {
         Ok(event)
     }
 
+    /// Execute switch node
+    ///
+    /// A label that is not one of the node's cases selects the default branch.
+    async fn execute_switch_node(
+        &self,
+        script: &str,
+        cases: &[String],
+        options: ScriptOptions<'_>,
+        mut event: WorkflowEvent,
+        node_name: &str,
+        node_id: &str,
+    ) -> Result<WorkflowEvent> {
+        let label = self.js_executor.execute_switch_with_options(script, &event, options).await?;
+
+        let selected = match label {
+            Some(label) if cases.contains(&label) => label,
+            Some(label) => {
+                tracing::info!("Switch node '{}' returned unknown case '{}', using default branch", node_name, label);
+                SWITCH_DEFAULT_CASE.to_string()
+            }
+            None => SWITCH_DEFAULT_CASE.to_string(),
+        };
+        tracing::info!("Switch node '{}' selected case: {}", node_name, selected);
+
+        // Store the selected case in the event for edge routing using node ID as key
+        event.switch_results.insert(node_id.to_string(), selected);
+        Ok(event)
+    }
+
+    /// Execute ForEach node, replacing the event data with the array of items to iterate
+    ///
+    /// The DAG executor runs the node's body once per item. A selector resolving to
+    /// `null` yields no items.
+    async fn execute_foreach_node(
+        &self,
+        selector: &str,
+        limits: Option<&ScriptLimits>,
+        params: &ExecuteNodeParams<'_>,
+        event: WorkflowEvent,
+    ) -> Result<WorkflowEvent> {
+        use crate::workflow::models::node_type_names;
+
+        let mut event = self.append_source(event, params.node_id, params.node_name, node_type_names::FOR_EACH);
+
+        let selected = if selector.trim_start().starts_with('$') {
+            let event_json = serde_json::to_value(&event)?;
+            json_path::resolve(&event_json, selector)
+                .map_err(SwissPipeError::Config)?
+                .clone()
+        } else {
+            let variables = self.load_script_variables(params.host_functions).await?;
+            let options = ScriptOptions {
+                limits,
+                host_functions: params.host_functions,
+                variables: variables.as_deref(),
+                console: params.console,
+            };
+            self.js_executor.execute_selector_with_options(selector, &event, options).await?
+        };
+
+        let items = match selected {
+            serde_json::Value::Array(items) => items,
+            serde_json::Value::Null => Vec::new(),
+            other => {
+                return Err(SwissPipeError::Generic(format!(
+                    "ForEach node '{}' selector must return an array, got {}",
+                    params.node_name,
+                    json_type_name(&other)
+                )));
+            }
+        };
+        tracing::info!("ForEach node '{}' selected {} items", params.node_name, items.len());
+
+        event.data = serde_json::Value::Array(items);
+        Ok(event)
+    }
+
+    /// Execute SubWorkflow node, starting the referenced workflow as a child execution
+    ///
+    /// Waited-on children run inline so that parents never hold a worker while their
+    /// child sits in the queue behind them. Boxed because the child's nodes run through
+    /// this executor again.
+    fn execute_sub_workflow_node<'a>(
+        &'a self,
+        workflow_id: &'a str,
+        wait: bool,
+        timeout_seconds: Option<u64>,
+        params: &'a ExecuteNodeParams<'a>,
+        event: WorkflowEvent,
+    ) -> BoxFuture<'a, Result<WorkflowEvent>> {
+        Box::pin(async move {
+            use crate::workflow::models::node_type_names;
+
+            let mut event = self.append_source(event, params.node_id, params.node_name, node_type_names::SUB_WORKFLOW);
+
+            let child_workflow = WorkflowLoader::new(self.db.clone()).load_workflow(workflow_id).await?;
+            if !child_workflow.enabled {
+                return Err(SwissPipeError::Generic(format!(
+                    "SubWorkflow node '{}' cannot start disabled workflow '{}'",
+                    params.node_name, child_workflow.name
+                )));
+            }
+
+            let execution_service = ExecutionService::new(self.db.clone());
+            let parent = ParentExecution { execution_id: params.execution_id, node_id: params.node_id };
+
+            if !wait {
+                let child_execution_id = execution_service
+                    .create_child_execution(parent, workflow_id, event.data.clone(), &event.headers, true)
+                    .await?;
+                tracing::info!(
+                    "SubWorkflow node '{}' queued execution {} of workflow '{}'",
+                    params.node_name, child_execution_id, child_workflow.name
+                );
+                event.metadata.insert(SUB_WORKFLOW_EXECUTION_ID_KEY.to_string(), child_execution_id);
+                return Ok(event);
+            }
+
+            if child_workflow.requires_sync_execution() {
+                return Err(SwissPipeError::Config(format!(
+                    "SubWorkflow node '{}' cannot wait for workflow '{}': it contains HumanInLoop, Delay or looping HttpRequest nodes",
+                    params.node_name, child_workflow.name
+                )));
+            }
+            let dag_executor = self.dag_executor.get()
+                .and_then(Weak::upgrade)
+                .ok_or_else(|| SwissPipeError::Generic("DAG executor not initialized".to_string()))?;
+
+            let child_execution_id = execution_service
+                .create_child_execution(parent, workflow_id, event.data.clone(), &event.headers, false)
+                .await?;
+            let child_event = WorkflowEvent {
+                data: event.data.clone(),
+                headers: event.headers.clone(),
+                ..WorkflowEvent::default()
+            };
+
+            let timeout_seconds = timeout_seconds.unwrap_or(DEFAULT_SUB_WORKFLOW_TIMEOUT_SECONDS);
+            let run = dag_executor.execute_workflow(&child_workflow, child_event, &child_execution_id);
+            let result = tokio::time::timeout(std::time::Duration::from_secs(timeout_seconds), run)
+                .await
+                .unwrap_or_else(|_| Err(SwissPipeError::Generic(format!("timed out after {timeout_seconds} seconds"))));
+
+            if let Err(e) = execution_service.complete_execution(&child_execution_id, &result).await {
+                log_workflow_warn!(params.workflow_id, params.execution_id, params.node_id,
+                    format!("Failed to record result of child execution {}: {}", child_execution_id, e));
+            }
+
+            let output = result.map_err(|e| SwissPipeError::Generic(format!(
+                "Sub-workflow '{}' (execution {}) failed: {}",
+                child_workflow.name, child_execution_id, e
+            )))?;
+            tracing::info!(
+                "SubWorkflow node '{}' completed execution {} of workflow '{}'",
+                params.node_name, child_execution_id, child_workflow.name
+            );
+
+            event.data = output.data;
+            event.metadata.insert(SUB_WORKFLOW_EXECUTION_ID_KEY.to_string(), child_execution_id);
+            Ok(event)
+        })
+    }
+
+    /// Execute Respond node, recording the HTTP response for the execution's waiting caller
+    ///
+    /// The event passes through unchanged. Only the first Respond node to run in an
+    /// execution is answered with; later ones are logged and ignored.
+    async fn execute_respond_node(
+        &self,
+        status_code: u16,
+        headers: &HashMap<String, String>,
+        body_template: &str,
+        params: &ExecuteNodeParams<'_>,
+        event: WorkflowEvent,
+    ) -> Result<WorkflowEvent> {
+        use crate::workflow::models::node_type_names;
+
+        let event = self.append_source(event, params.node_id, params.node_name, node_type_names::RESPOND);
+
+        let mut resolved_headers = HashMap::new();
+        for (name, value) in headers {
+            resolved_headers.insert(name.clone(), self.resolve_template(value, Some(&event)).await?);
+        }
+        let response = TriggerResponse {
+            status_code,
+            headers: resolved_headers,
+            body: self.resolve_template(body_template, Some(&event)).await?,
+        };
+
+        let recorded = ExecutionService::new(self.db.clone())
+            .set_http_response(params.execution_id, &response)
+            .await?;
+        if recorded {
+            tracing::info!("Respond node '{}' set HTTP {} response", params.node_name, status_code);
+        } else {
+            log_workflow_warn!(params.workflow_id, params.execution_id, params.node_id,
+                format!("Respond node '{}' ignored: the execution already has a response", params.node_name));
+        }
+
+        Ok(event)
+    }
+
     /// Execute transformer node
     async fn execute_transformer_node(
         &self,
         script: &str,
+        options: ScriptOptions<'_>,
         event: WorkflowEvent,
         node_name: &str,
         node_id: &str,
@@ -366,33 +662,64 @@ impl NodeExecutor {
         // Takes ownership to avoid cloning
         let event_with_source = self.append_source(event, node_id, node_name, node_type_names::TRANSFORMER);
 
-        let mut transformed_event = self.js_executor.execute_transformer(script, event_with_source.clone()).await
+        let mut transformed_event = self.js_executor.execute_transformer_with_options(script, event_with_source.clone(), options).await
             .map_err(SwissPipeError::JavaScript)?;
 
-        // Preserve condition results and sources from the event with source
+        // Preserve condition/switch results and sources from the event with source
         transformed_event.condition_results = event_with_source.condition_results;
+        transformed_event.switch_results = event_with_source.switch_results;
         transformed_event.sources = event_with_source.sources;
 
         tracing::debug!("Transformer node '{}' completed transformation", node_name);
         Ok(transformed_event)
     }
 
+    /// Load environment variables for scripts, but only when the workflow allows `swisspipe.env`
+    async fn load_script_variables(&self, host_functions: &[HostFunction]) -> Result<Option<Arc<HashMap<String, String>>>> {
+        if !host_functions.contains(&HostFunction::Env) {
+            return Ok(None);
+        }
+        let Some(variable_service) = self.variable_service.get() else {
+            return Ok(None);
+        };
+
+        let variables = variable_service.variables().await
+            .map_err(|e| SwissPipeError::Generic(format!("Failed to load variables: {e}")))?;
+        Ok(Some(variables))
+    }
+
     /// Resolve environment variable and event data templates in a string
     async fn resolve_template(&self, template: &str, event: Option<&WorkflowEvent>) -> Result<String> {
-        // Check if template engine and variable service are available
-        let Some(template_engine) = self.template_engine.get() else {
-            // If not configured, return original string
-            return Ok(template.to_string());
-        };
+        let variables = self.template_variables().await?;
+        self.render_template(template, &variables, event, None)
+    }
+
+    /// Load the environment variables templates are resolved with
+    ///
+    /// Nodes with several templates load them once and render each with [`Self::render_template`].
+    async fn template_variables(&self) -> Result<Arc<HashMap<String, String>>> {
         let Some(variable_service) = self.variable_service.get() else {
+            return Ok(Arc::default());
+        };
+
+        variable_service.variables().await
+            .map_err(|e| SwissPipeError::Generic(format!("Failed to load variables: {e}")))
+    }
+
+    /// Resolve a template with loaded variables; it can also refer to an HTTP response as `response`
+    fn render_template(
+        &self,
+        template: &str,
+        variables: &HashMap<String, String>,
+        event: Option<&WorkflowEvent>,
+        response: Option<&serde_json::Value>,
+    ) -> Result<String> {
+        // Check if template engine and variable service are available
+        let (Some(template_engine), Some(_)) = (self.template_engine.get(), self.variable_service.get()) else {
             // If not configured, return original string
             return Ok(template.to_string());
         };
 
-        // Load all variables as a HashMap
-        let variables = variable_service.load_variables_map().await
-            .map_err(|e| SwissPipeError::Generic(format!("Failed to load variables: {e}")))?;
-
         // Create event data context if event is provided
         let event_data = event.map(|e| {
             serde_json::json!({
@@ -403,8 +730,11 @@ impl NodeExecutor {
             })
         });
 
-        // Resolve the template with both environment variables and event data
-        template_engine.resolve_with_event(template, &variables, event_data.as_ref())
+        // Resolve the template with environment variables, event data and the response
+        let values: Vec<(&str, &serde_json::Value)> = event_data.as_ref().map(|event| ("event", event)).into_iter()
+            .chain(response.map(|response| ("response", response)))
+            .collect();
+        template_engine.resolve_with_values(template, variables, &values)
             .map_err(|e| SwissPipeError::Generic(format!("Template resolution failed: {e}")))
     }
 
@@ -418,15 +748,24 @@ impl NodeExecutor {
     ) -> Result<WorkflowEvent> {
         tracing::debug!("Executing HTTP request node: loop_config_present={}", config.loop_config.is_some());
 
+        // Load the variables once for every template of the node
+        let variables = self.template_variables().await?;
+
         // Resolve templates in URL and trim whitespace
-        let resolved_url = self.resolve_template(config.url, Some(&event)).await?.trim().to_string();
+        let resolved_url = self.render_template(config.url, &variables, Some(&event), None)?.trim().to_string();
 
         // Resolve templates in headers
         let mut resolved_headers = std::collections::HashMap::new();
         for (key, value) in config.headers {
-            let resolved_value = self.resolve_template(value, Some(&event)).await?;
+            let resolved_value = self.render_template(value, &variables, Some(&event), None)?;
             resolved_headers.insert(key.clone(), resolved_value);
         }
+        // Add the trigger headers the node forwards, without letting them override its own.
+        // Loops store their headers in the database, so they never carry forwarded ones.
+        let resolved_headers = match config.loop_config {
+            None => config.forward_headers.merge(&event.headers, resolved_headers),
+            Some(_) => resolved_headers,
+        };
 
         // Create resolved config
         let resolved_config = HttpRequestConfig {
@@ -436,6 +775,13 @@ impl NodeExecutor {
             failure_action: config.failure_action,
             retry_config: config.retry_config,
             headers: &resolved_headers,
+            body: config.body,
+            query_params: config.query_params,
+            response_mapping: config.response_mapping,
+            response_format: config.response_format,
+            success_status_codes: config.success_status_codes,
+            auth: config.auth,
+            forward_headers: config.forward_headers,
             node_name: config.node_name,
             loop_config: config.loop_config,
             workflow_id: config.workflow_id,
@@ -445,8 +791,8 @@ impl NodeExecutor {
         match resolved_config.loop_config {
             None => {
                 tracing::debug!("Taking single HTTP request path (no loop)");
-                // Standard HTTP request (existing behavior)
-                self.execute_single_http_request(&resolved_config, event, execution_id).await
+                let content = self.render_request_content(&resolved_config, &event, &variables)?;
+                self.execute_single_http_request(&resolved_config, &content, &variables, event, execution_id).await
             }
             Some(loop_config) => {
                 tracing::debug!("Taking HTTP loop path");
@@ -456,10 +802,143 @@ impl NodeExecutor {
         }
     }
 
+    /// Render the body and query parameter templates of an HttpRequest node
+    fn render_request_content(
+        &self,
+        config: &HttpRequestConfig<'_>,
+        event: &WorkflowEvent,
+        variables: &HashMap<String, String>,
+    ) -> Result<RequestContent> {
+        let body = match config.body {
+            None => None,
+            Some(HttpRequestBody::Json(template)) => {
+                let rendered = self.render_template(template, variables, Some(event), None)?;
+                let json = serde_json::from_str(&rendered).map_err(|e| SwissPipeError::Generic(format!(
+                    "HTTP request node '{}' body is not valid JSON after rendering: {e}", config.node_name
+                )))?;
+                Some(RequestBody::Json(json))
+            }
+            Some(HttpRequestBody::Form(fields)) => {
+                let mut rendered = Vec::with_capacity(fields.len());
+                for (name, template) in fields {
+                    rendered.push((name.clone(), self.render_template(template, variables, Some(event), None)?));
+                }
+                rendered.sort();
+                Some(RequestBody::Form(rendered))
+            }
+            Some(HttpRequestBody::Raw { template, content_type }) => Some(RequestBody::Raw {
+                body: self.render_template(template, variables, Some(event), None)?,
+                content_type: content_type.clone(),
+            }),
+        };
+
+        let query = if config.query_params.is_empty() {
+            None
+        } else {
+            let mut rendered = Vec::with_capacity(config.query_params.len());
+            for (name, template) in config.query_params {
+                rendered.push((name.clone(), self.render_template(template, variables, Some(event), None)?));
+            }
+            rendered.sort();
+            Some(rendered)
+        };
+
+        let success_statuses = config.success_status_codes.iter()
+            .map(|pattern| crate::workflow::models::parse_status_codes(pattern).ok_or_else(|| SwissPipeError::Config(format!(
+                "HTTP request node '{}' has an invalid success status code '{pattern}'", config.node_name
+            ))))
+            .collect::<Result<_>>()?;
+
+        let auth = match config.auth {
+            Some(auth) => Some(Self::resolve_http_auth(config, auth, variables)?),
+            None => None,
+        };
+
+        Ok(RequestContent { body, query, success_statuses, auth })
+    }
+
+    /// Take the credentials an HttpRequest node authenticates with from the environment variables
+    fn resolve_http_auth(config: &HttpRequestConfig<'_>, auth: &HttpAuth, variables: &HashMap<String, String>) -> Result<RequestAuth> {
+        if let Some(name) = auth.variables().into_iter().find(|name| !variables.contains_key(*name)) {
+            return Err(SwissPipeError::Generic(format!(
+                "HTTP request node '{}' auth variable '{name}' is not set", config.node_name
+            )));
+        }
+        let value = |name: &str| variables[name].clone();
+
+        Ok(match auth {
+            HttpAuth::Basic { username_variable, password_variable } => RequestAuth::Basic {
+                username: value(username_variable),
+                password: value(password_variable),
+            },
+            HttpAuth::Bearer { token_variable } => RequestAuth::Bearer { token: value(token_variable) },
+            HttpAuth::ApiKey { key_variable, header, query_param } => RequestAuth::ApiKey {
+                key: value(key_variable),
+                header: header.clone(),
+                query_param: query_param.clone(),
+            },
+            HttpAuth::OAuth2ClientCredentials { token_url, client_id_variable, client_secret_variable, scopes, audience, credentials_in_body } => {
+                RequestAuth::OAuth2(OAuth2Credentials {
+                    token_url: token_url.clone(),
+                    client_id: value(client_id_variable),
+                    client_secret: value(client_secret_variable),
+                    scopes: scopes.clone(),
+                    audience: audience.clone(),
+                    credentials_in_body: *credentials_in_body,
+                })
+            }
+            HttpAuth::AwsSigV4 { access_key_id_variable, secret_access_key_variable, session_token_variable, region, service } => {
+                RequestAuth::AwsSigV4(AwsCredentials {
+                    access_key_id: value(access_key_id_variable),
+                    secret_access_key: value(secret_access_key_variable),
+                    session_token: session_token_variable.as_deref().map(value),
+                    region: region.clone(),
+                    service: service.clone(),
+                })
+            }
+        })
+    }
+
+    /// Place an HTTP response in the event according to the node's response format and mapping
+    fn map_http_response(
+        &self,
+        config: &HttpRequestConfig<'_>,
+        variables: &HashMap<String, String>,
+        request_event: &WorkflowEvent,
+        response: HttpResponse,
+    ) -> Result<WorkflowEvent> {
+        let mut response_event = request_event.clone();
+        response_event.hil_task = None;
+        response_event.metadata.insert("http_status".to_string(), response.status.to_string());
+
+        let response = match config.response_format {
+            HttpResponseFormat::Body => response.body,
+            HttpResponseFormat::Full => response.to_value(),
+        };
+        match config.response_mapping {
+            // An empty body (such as a 204) leaves the data as it was
+            None if response.is_null() => {}
+            None => response_event.data = response,
+            Some(HttpResponseMapping::Pointer(pointer)) => {
+                json_path::set_pointer(&mut response_event.data, pointer, response)
+                    .map_err(|e| SwissPipeError::Generic(format!("HTTP request node '{}' response mapping failed: {e}", config.node_name)))?;
+            }
+            Some(HttpResponseMapping::Template(template)) => {
+                let rendered = self.render_template(template, variables, Some(request_event), Some(&response))?;
+                response_event.data = serde_json::from_str(&rendered).map_err(|e| SwissPipeError::Generic(format!(
+                    "HTTP request node '{}' response mapping is not valid JSON after rendering: {e}", config.node_name
+                )))?;
+            }
+        }
+        Ok(response_event)
+    }
+
     /// Execute a single HTTP request (existing behavior)
     async fn execute_single_http_request(
         &self,
         config: &HttpRequestConfig<'_>,
+        content: &RequestContent,
+        variables: &HashMap<String, String>,
         event: WorkflowEvent,
         execution_id: &str,
     ) -> Result<WorkflowEvent> {
@@ -471,17 +950,17 @@ impl NodeExecutor {
 
         match config.failure_action {
             FailureAction::Retry => {
-                let mut result = self.app_executor.execute_http_request(
+                let result = self.app_executor.execute_http_request(
                     config.url,
                     config.method,
                     config.timeout_seconds,
                     config.retry_config,
                     event_with_source.clone(),
                     config.headers,
+                    content,
                 ).await?;
                 // Preserve sources in the result
-                result.sources = event_with_source.sources;
-                Ok(result)
+                self.map_http_response(config, variables, &event_with_source, result)
             }
             FailureAction::Continue => {
                 let single_attempt_config = RetryConfig {
@@ -495,11 +974,9 @@ impl NodeExecutor {
                     &single_attempt_config,
                     event_with_source.clone(),
                     config.headers,
+                    content,
                 ).await {
-                    Ok(mut result) => {
-                        result.sources = event_with_source.sources;
-                        Ok(result)
-                    },
+                    Ok(result) => self.map_http_response(config, variables, &event_with_source, result),
                     Err(e) => {
                         log_workflow_warn!(config.workflow_id, execution_id, config.node_id,
                             format!("HTTP request node '{}' failed but continuing: {}", config.node_name, e));
@@ -512,16 +989,16 @@ impl NodeExecutor {
                     max_attempts: 1,
                     ..config.retry_config.clone()
                 };
-                let mut result = self.app_executor.execute_http_request(
+                let result = self.app_executor.execute_http_request(
                     config.url,
                     config.method,
                     config.timeout_seconds,
                     &single_attempt_config,
                     event_with_source.clone(),
                     config.headers,
+                    content,
                 ).await?;
-                result.sources = event_with_source.sources;
-                Ok(result)
+                self.map_http_response(config, variables, &event_with_source, result)
             }
         }
     }
@@ -1068,6 +1545,13 @@ struct HttpRequestConfig<'a> {
     failure_action: &'a FailureAction,
     retry_config: &'a RetryConfig,
     headers: &'a std::collections::HashMap<String, String>,
+    body: Option<&'a HttpRequestBody>,
+    query_params: &'a std::collections::HashMap<String, String>,
+    response_mapping: Option<&'a HttpResponseMapping>,
+    response_format: HttpResponseFormat,
+    success_status_codes: &'a [String],
+    auth: Option<&'a HttpAuth>,
+    forward_headers: &'a HeaderForwarding,
     node_name: &'a str,
     loop_config: &'a Option<crate::workflow::models::LoopConfig>,
     workflow_id: &'a str,
@@ -1097,4 +1581,15 @@ struct AnthropicNodeConfig<'a> {
     node_name: &'a str,
     workflow_id: &'a str,
     node_id: &'a str,
-}
\ No newline at end of file
+}
+
+pub(crate) fn json_type_name(value: &serde_json::Value) -> &'static str {
+    match value {
+        serde_json::Value::Null => "null",
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::Number(_) => "number",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Array(_) => "array",
+        serde_json::Value::Object(_) => "object",
+    }
+}
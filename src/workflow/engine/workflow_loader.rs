@@ -2,7 +2,7 @@ use crate::{
     database::{edges, entities, nodes},
     workflow::{
         errors::{Result, SwissPipeError},
-        models::{Edge, Node, NodeType, Workflow},
+        models::{Edge, HostFunction, Node, NodeType, Workflow},
     },
 };
 use sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait};
@@ -26,7 +26,8 @@ impl WorkflowLoader {
             .ok_or_else(|| SwissPipeError::WorkflowNotFound(workflow_id.to_string()))?;
 
         // Load all nodes for this workflow
-        let nodes = self.load_nodes(workflow_id).await?;
+        let allowed_host_functions = workflow_model.host_functions();
+        let nodes = self.load_nodes(workflow_id, &allowed_host_functions).await?;
 
         // Load all edges for this workflow
         let edges = self.load_edges(workflow_id).await?;
@@ -37,6 +38,7 @@ impl WorkflowLoader {
             description: workflow_model.description,
             start_node_id: workflow_model.start_node_id,
             enabled: workflow_model.enabled,
+            allowed_host_functions,
             nodes,
             edges,
         })
@@ -52,7 +54,7 @@ impl WorkflowLoader {
     }
 
     /// Load all nodes for a workflow
-    async fn load_nodes(&self, workflow_id: &str) -> Result<Vec<Node>> {
+    async fn load_nodes(&self, workflow_id: &str, allowed_host_functions: &[HostFunction]) -> Result<Vec<Node>> {
         let node_models = nodes::Entity::find()
             .filter(nodes::Column::WorkflowId.eq(workflow_id))
             .all(self.db.as_ref())
@@ -69,6 +71,7 @@ impl WorkflowLoader {
                 input_merge_strategy: node_model.input_merge_strategy
                     .as_deref()
                     .and_then(|s| serde_json::from_str(s).ok()),
+                allowed_host_functions: allowed_host_functions.to_vec(),
             });
         }
 
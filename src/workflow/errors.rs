@@ -71,6 +71,9 @@ pub enum JavaScriptError {
     #[error("JavaScript memory limit exceeded")]
     MemoryLimitExceeded,
 
+    #[error("JavaScript stack size limit exceeded")]
+    StackOverflow,
+
     #[error("JavaScript validation error: {0}")]
     ValidationError(String),
 
@@ -98,6 +101,9 @@ pub enum AppError {
     #[error("Authentication failed for OpenObserve")]
     AuthenticationFailed,
     
+    #[error("OAuth2 token request failed: {error}")]
+    TokenRequestFailed { error: String },
+    
     #[error("Unsupported app type for operation")]
     UnsupportedOperation,
 }
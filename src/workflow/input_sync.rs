@@ -7,7 +7,7 @@ use crate::{
 };
 use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
 use serde_json;
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::{HashMap, HashSet}, sync::Arc};
 use uuid::Uuid;
 
 pub struct InputSyncService {
@@ -229,6 +229,7 @@ impl InputSyncService {
         let mut merged_metadata = HashMap::new();
         let mut merged_headers = HashMap::new();
         let mut merged_condition_results = HashMap::new();
+        let mut merged_switch_results = HashMap::new();
 
         // Add metadata about the merge operation for traceability  
         merged_metadata.insert("merge_info".to_string(), "multiple_inputs_merged".to_string());
@@ -258,8 +259,16 @@ impl InputSyncService {
             for (key, value) in &input.condition_results {
                 merged_condition_results.insert(format!("{input_key}_{key}"), *value);
             }
+            for (key, value) in &input.switch_results {
+                merged_switch_results.insert(format!("{input_key}_{key}"), value.clone());
+            }
         }
 
+        // Failed nodes are identified by node ID, so they need no prefix
+        let merged_failed_nodes: HashSet<String> = inputs.iter()
+            .flat_map(|input| input.failed_nodes.iter().cloned())
+            .collect();
+
         // Merge sources from all inputs, deduplicating by node_id and keeping the most recent
         let mut merged_sources_map: std::collections::HashMap<String, crate::workflow::models::NodeSource> = std::collections::HashMap::new();
         for input in inputs.iter() {
@@ -283,6 +292,8 @@ impl InputSyncService {
             metadata: merged_metadata,
             headers: merged_headers,
             condition_results: merged_condition_results,
+            switch_results: merged_switch_results,
+            failed_nodes: merged_failed_nodes,
             hil_task: None,
             sources: merged_sources,
         })
@@ -320,6 +331,8 @@ mod tests {
             },
             headers: HashMap::new(),
             condition_results: HashMap::new(),
+            switch_results: HashMap::new(),
+            failed_nodes: HashSet::new(),
             hil_task: None,
             sources: Vec::new(),
         };
@@ -333,6 +346,8 @@ mod tests {
             },
             headers: HashMap::new(),
             condition_results: HashMap::new(),
+            switch_results: HashMap::new(),
+            failed_nodes: HashSet::new(),
             hil_task: None,
             sources: Vec::new(),
         };
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 /// Source node information - tracks which node produced data in the workflow execution path
@@ -33,6 +33,10 @@ pub mod node_type_names {
     pub const TRIGGER: &str = "Trigger";
     pub const CONDITION: &str = "Condition";
     pub const TRANSFORMER: &str = "Transformer";
+    pub const SWITCH: &str = "Switch";
+    pub const FOR_EACH: &str = "ForEach";
+    pub const SUB_WORKFLOW: &str = "SubWorkflow";
+    pub const RESPOND: &str = "Respond";
     pub const HTTP_REQUEST: &str = "HttpRequest";
     pub const OPEN_OBSERVE: &str = "OpenObserve";
     pub const EMAIL: &str = "Email";
@@ -50,6 +54,12 @@ pub struct WorkflowEvent {
     pub headers: HashMap<String, String>,
     #[serde(default)]
     pub condition_results: HashMap<String, bool>, // Store condition results by node ID
+    /// Case label chosen by each Switch node, keyed by node ID
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub switch_results: HashMap<String, String>,
+    /// Nodes whose failure this event carries down their `error` handle
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub failed_nodes: HashSet<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub hil_task: Option<serde_json::Value>, // HIL task metadata
 
@@ -65,6 +75,8 @@ impl Default for WorkflowEvent {
             metadata: HashMap::new(),
             headers: HashMap::new(),
             condition_results: HashMap::new(),
+            switch_results: HashMap::new(),
+            failed_nodes: HashSet::new(),
             hil_task: None,
             sources: Vec::new(),
         }
@@ -174,6 +186,19 @@ pub struct RetryConfig {
     pub initial_delay_ms: u64,
     pub max_delay_ms: u64,
     pub backoff_multiplier: f64,
+    /// Failures worth another attempt; anything else, such as a 400, fails immediately
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<RetryCondition>,
+    /// Further status code patterns to retry, such as `409` or `4xx`
+    #[serde(default)]
+    pub retry_status_codes: Vec<String>,
+    #[serde(default)]
+    pub jitter: RetryJitter,
+    /// Give up once the next attempt would start this long after the first one,
+    /// including waits requested by `Retry-After`. Without it those waits are
+    /// capped at [`crate::utils::retry::MAX_RETRY_AFTER`]
+    #[serde(default)]
+    pub max_retry_duration_ms: Option<u64>,
 }
 
 impl Default for RetryConfig {
@@ -183,10 +208,76 @@ impl Default for RetryConfig {
             initial_delay_ms: 100,
             max_delay_ms: 5000,
             backoff_multiplier: 2.0,
+            retry_on: default_retry_on(),
+            retry_status_codes: Vec::new(),
+            jitter: RetryJitter::default(),
+            max_retry_duration_ms: None,
         }
     }
 }
 
+/// A class of failure that a retry may get past
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RetryCondition {
+    /// The connection could not be made or broke before the response arrived
+    Connect,
+    Timeout,
+    /// Any 5xx status
+    ServerError,
+    /// A 429 status
+    TooManyRequests,
+}
+
+fn default_retry_on() -> Vec<RetryCondition> {
+    vec![RetryCondition::Connect, RetryCondition::Timeout, RetryCondition::ServerError, RetryCondition::TooManyRequests]
+}
+
+/// Randomization of the delay between attempts, so that clients do not retry in lockstep
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RetryJitter {
+    /// Exactly the exponential backoff delay
+    #[default]
+    None,
+    /// A random delay between zero and the backoff delay
+    Full,
+    /// A random delay between `initial_delay_ms` and three times the previous delay
+    Decorrelated,
+}
+
+
+/// Per-node overrides for the JavaScript sandbox limits.
+/// Unset fields fall back to the global `SP_JS_*` settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScriptLimits {
+    pub timeout_ms: Option<u64>,
+    pub memory_limit_bytes: Option<usize>,
+    pub max_stack_size_bytes: Option<usize>,
+}
+
+/// Host-backed functions a workflow can opt in to expose to its JavaScript nodes.
+/// Allowed functions are installed on the `swisspipe` global of the sandbox.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HostFunction {
+    /// `swisspipe.log(message)` writes a line to the server log
+    Log,
+    /// `swisspipe.env(name)` reads an environment variable, including decrypted secrets
+    Env,
+}
+
+impl HostFunction {
+    /// Name of the function on the `swisspipe` global
+    pub fn js_name(&self) -> &'static str {
+        match self {
+            HostFunction::Log => "log",
+            HostFunction::Env => "env",
+        }
+    }
+
+    pub fn all() -> &'static [HostFunction] {
+        &[HostFunction::Log, HostFunction::Env]
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum FailureAction {
@@ -203,6 +294,151 @@ pub enum DelayUnit {
     Days,
 }
 
+/// Body an HttpRequest node sends in place of the event data
+///
+/// Templates are rendered with the workflow's template engine, so they can use
+/// `{{ env.NAME }}` and `{{ event.data.field }}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum HttpRequestBody {
+    /// Template rendering a JSON document, sent as `application/json`
+    Json(String),
+    /// Field templates, sent as `application/x-www-form-urlencoded`
+    Form(HashMap<String, String>),
+    /// Template sent as rendered, with an optional `Content-Type`
+    Raw {
+        template: String,
+        #[serde(default)]
+        content_type: Option<String>,
+    },
+}
+
+/// Which headers of the triggering request an HttpRequest node passes on
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub enum HeaderForwarding {
+    /// Send only the node's own headers
+    #[default]
+    None,
+    /// Forward these headers, matched case-insensitively
+    Allow(Vec<String>),
+    All,
+}
+
+impl HeaderForwarding {
+    /// The inbound headers to send along with `node_headers`, which take precedence
+    pub fn merge(&self, inbound: &HashMap<String, String>, node_headers: HashMap<String, String>) -> HashMap<String, String> {
+        let forwarded = inbound.iter().filter(|(name, _)| {
+            let overridden = node_headers.keys().any(|node_name| node_name.eq_ignore_ascii_case(name));
+            !overridden && match self {
+                Self::None => false,
+                Self::Allow(allowed) => allowed.iter().any(|allowed| allowed.eq_ignore_ascii_case(name)),
+                Self::All => true,
+            }
+        });
+        let mut headers: HashMap<String, String> = forwarded.map(|(name, value)| (name.clone(), value.clone())).collect();
+        headers.extend(node_headers);
+        headers
+    }
+}
+
+/// How an HttpRequest node authenticates to the service it calls
+///
+/// Fields ending in `_variable` name the environment variables holding the credentials,
+/// so they can be stored as encrypted secrets.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum HttpAuth {
+    Basic {
+        username_variable: String,
+        password_variable: String,
+    },
+    Bearer {
+        token_variable: String,
+    },
+    /// Sent in `header`, or as the `query_param` query parameter when set
+    ApiKey {
+        key_variable: String,
+        #[serde(default = "default_api_key_header")]
+        header: String,
+        #[serde(default)]
+        query_param: Option<String>,
+    },
+    /// Client-credentials grant; tokens are cached until shortly before they expire
+    OAuth2ClientCredentials {
+        token_url: String,
+        client_id_variable: String,
+        client_secret_variable: String,
+        #[serde(default)]
+        scopes: Vec<String>,
+        #[serde(default)]
+        audience: Option<String>,
+        /// Send the client credentials as form fields instead of HTTP Basic auth
+        #[serde(default)]
+        credentials_in_body: bool,
+    },
+    AwsSigV4 {
+        access_key_id_variable: String,
+        secret_access_key_variable: String,
+        #[serde(default)]
+        session_token_variable: Option<String>,
+        region: String,
+        service: String,
+    },
+}
+
+impl HttpAuth {
+    /// Names of the environment variables holding the credentials
+    pub fn variables(&self) -> Vec<&str> {
+        match self {
+            Self::Basic { username_variable, password_variable } => vec![username_variable, password_variable],
+            Self::Bearer { token_variable } => vec![token_variable],
+            Self::ApiKey { key_variable, .. } => vec![key_variable],
+            Self::OAuth2ClientCredentials { client_id_variable, client_secret_variable, .. } => {
+                vec![client_id_variable, client_secret_variable]
+            }
+            Self::AwsSigV4 { access_key_id_variable, secret_access_key_variable, session_token_variable, .. } => {
+                let mut variables = vec![access_key_id_variable.as_str(), secret_access_key_variable];
+                variables.extend(session_token_variable.as_deref());
+                variables
+            }
+        }
+    }
+}
+
+/// What an HttpRequest node treats as its response
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum HttpResponseFormat {
+    /// The body alone; an empty body leaves `event.data` unchanged
+    #[default]
+    Body,
+    /// `{status, headers, body, body_encoding}`
+    Full,
+}
+
+/// Parse a success status code pattern: `404`, `4xx` or `400-404`
+pub fn parse_status_codes(pattern: &str) -> Option<std::ops::RangeInclusive<u16>> {
+    let pattern = pattern.trim();
+    let valid = |code: u16| (100..=599).contains(&code);
+    if let Some(class) = pattern.strip_suffix("xx").or_else(|| pattern.strip_suffix("XX")) {
+        let class: u16 = class.parse().ok().filter(|c| (1..=5).contains(c))?;
+        return Some(class * 100..=class * 100 + 99);
+    }
+    if let Some((start, end)) = pattern.split_once('-') {
+        let start: u16 = start.trim().parse().ok().filter(|c| valid(*c))?;
+        let end: u16 = end.trim().parse().ok().filter(|c| valid(*c) && *c >= start)?;
+        return Some(start..=end);
+    }
+    let code: u16 = pattern.parse().ok().filter(|c| valid(*c))?;
+    Some(code..=code)
+}
+
+/// Where an HttpRequest node puts the response instead of replacing `event.data`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum HttpResponseMapping {
+    /// JSON pointer into `event.data` such as `/crm_response`; the rest of the data is kept
+    Pointer(String),
+    /// Template rendering the new `event.data` as JSON, with `response` next to `event`
+    Template(String),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoopConfig {
     pub max_iterations: Option<u32>,
@@ -247,16 +483,198 @@ pub enum InputMergeStrategy {
     TimeoutBased(u64),
 }
 
+/// How a Trigger node checks that a request was signed by the expected sender
+///
+/// `secret_variable` names the environment variable holding the signing secret.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TriggerVerification {
+    /// Hex HMAC-SHA256 of the raw body in `header`, after an optional `prefix` such as `sha256=`
+    Hmac {
+        secret_variable: String,
+        header: String,
+        #[serde(default)]
+        prefix: Option<String>,
+    },
+    /// Stripe `Stripe-Signature` header
+    Stripe {
+        secret_variable: String,
+        #[serde(default)]
+        tolerance_seconds: Option<u64>,
+    },
+    /// GitHub `X-Hub-Signature-256` header
+    GitHub {
+        secret_variable: String,
+    },
+    /// Slack `X-Slack-Signature` and `X-Slack-Request-Timestamp` headers
+    Slack {
+        secret_variable: String,
+        #[serde(default)]
+        tolerance_seconds: Option<u64>,
+    },
+}
+
+impl TriggerVerification {
+    pub fn secret_variable(&self) -> &str {
+        match self {
+            Self::Hmac { secret_variable, .. }
+            | Self::Stripe { secret_variable, .. }
+            | Self::GitHub { secret_variable }
+            | Self::Slack { secret_variable, .. } => secret_variable,
+        }
+    }
+}
+
+/// API key a Trigger node requires, compared against the value of `secret_variable`
+///
+/// The key is read from `header`, or from the `query_param` query parameter when the
+/// header is absent and a parameter name is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerApiKey {
+    pub secret_variable: String,
+    #[serde(default = "default_api_key_header")]
+    pub header: String,
+    #[serde(default)]
+    pub query_param: Option<String>,
+}
+
+fn default_api_key_header() -> String {
+    "X-API-Key".to_string()
+}
+
+/// Client networks a Trigger node accepts, as CIDRs such as `10.0.0.0/8` or single addresses
+///
+/// Denied networks win over allowed ones, and an empty `allow` list allows every
+/// network that is not denied.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TriggerIpFilter {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+impl TriggerIpFilter {
+    /// Check every entry parses, returning the first that does not
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        self.allow.iter().chain(&self.deny).try_for_each(|entry| parse_network(entry).map(|_| ()))
+    }
+
+    pub fn allows(&self, ip: std::net::IpAddr) -> bool {
+        let contains = |entries: &[String]| {
+            entries.iter().filter_map(|entry| parse_network(entry).ok()).any(|network| network.contains(&ip))
+        };
+        !contains(&self.deny) && (self.allow.is_empty() || contains(&self.allow))
+    }
+}
+
+fn parse_network(entry: &str) -> std::result::Result<ipnet::IpNet, String> {
+    let entry = entry.trim();
+    entry
+        .parse::<ipnet::IpNet>()
+        .or_else(|_| entry.parse::<std::net::IpAddr>().map(ipnet::IpNet::from))
+        .map_err(|_| format!("'{entry}' is not an IP address or CIDR network"))
+}
+
+/// Ingestion limits a Trigger node applies to its workflow across the trigger and Segment routes
+///
+/// `requests_per_minute` refills a token bucket holding up to `burst` requests, which
+/// defaults to `requests_per_minute`. `daily_quota` caps the executions queued per UTC day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriggerRateLimit {
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    #[serde(default)]
+    pub burst: Option<u32>,
+    #[serde(default)]
+    pub daily_quota: Option<u32>,
+}
+
+impl TriggerRateLimit {
+    /// Check the limit sets something and every count is positive
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.burst.is_some() && self.requests_per_minute.is_none() {
+            return Err("burst requires requests_per_minute".to_string());
+        }
+        if self.requests_per_minute.is_none() && self.daily_quota.is_none() {
+            return Err("set requests_per_minute, daily_quota or both".to_string());
+        }
+        for (name, value) in [
+            ("requests_per_minute", self.requests_per_minute),
+            ("burst", self.burst),
+            ("daily_quota", self.daily_quota),
+        ] {
+            if value == Some(0) {
+                return Err(format!("{name} must be greater than 0"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Switch node handle taken when `route(event)` returns no known case
+pub const SWITCH_DEFAULT_CASE: &str = "default";
+
+/// Output handle that receives a node's error once it has failed, instead of the execution aborting
+pub const ERROR_HANDLE: &str = "error";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NodeType {
     Trigger { 
-        methods: Vec<HttpMethod> 
+        methods: Vec<HttpMethod>,
+        /// Signature that requests must carry before an execution is queued
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        verification: Option<TriggerVerification>,
+        /// API key that requests must present
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        api_key: Option<TriggerApiKey>,
+        /// Client networks that may or may not call the trigger
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ip_filter: Option<TriggerIpFilter>,
+        /// Request rate and daily execution limits for the workflow
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rate_limit: Option<TriggerRateLimit>,
     },
     Condition { 
-        script: String 
+        script: String,
+        limits: Option<ScriptLimits>,
     },
     Transformer { 
-        script: String 
+        script: String,
+        limits: Option<ScriptLimits>,
+    },
+    /// Routes the event to the outgoing edges whose `source_handle_id` matches the
+    /// case label returned by `route(event)`, or to the `default` handle
+    Switch {
+        script: String,
+        cases: Vec<String>,
+        limits: Option<ScriptLimits>,
+    },
+    /// Runs the nodes between this node and its Aggregate once per element of the
+    /// array picked by `selector`, either a JSONPath such as `$.data.items` or a
+    /// script defining `select(event)`
+    ForEach {
+        selector: String,
+        concurrency: Option<u32>,
+        limits: Option<ScriptLimits>,
+    },
+    /// Collects the per-item results of the matching ForEach node into an array
+    Aggregate {},
+    /// Starts another workflow as a child execution. With `wait` the child runs to
+    /// completion and its output becomes this node's output; otherwise the child is
+    /// queued and the event continues unchanged.
+    SubWorkflow {
+        workflow_id: String,
+        wait: bool,
+        timeout_seconds: Option<u64>,
+    },
+    /// Answers the caller of a synchronous trigger with a custom status, headers and
+    /// body rendered from templates, while the rest of the workflow keeps running
+    Respond {
+        status_code: u16,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        #[serde(default)]
+        body_template: String,
     },
     HttpRequest {
         url: String,
@@ -266,6 +684,24 @@ pub enum NodeType {
         retry_config: RetryConfig,
         headers: HashMap<String, String>,
         loop_config: Option<LoopConfig>,
+        /// Sent instead of the event data
+        #[serde(default)]
+        body: Option<Box<HttpRequestBody>>,
+        /// Query parameter templates; on GET they replace the event data fields
+        #[serde(default)]
+        query_params: HashMap<String, String>,
+        #[serde(default)]
+        response_mapping: Option<HttpResponseMapping>,
+        #[serde(default)]
+        response_format: HttpResponseFormat,
+        /// Status code patterns treated as success, such as `2xx` or `404`; any 2xx when empty
+        #[serde(default)]
+        success_status_codes: Vec<String>,
+        #[serde(default)]
+        auth: Option<Box<HttpAuth>>,
+        /// Headers of the triggering request to pass on; none unless configured
+        #[serde(default)]
+        forward_headers: HeaderForwarding,
     },
     OpenObserve {
         url: String,
@@ -301,6 +737,14 @@ pub enum NodeType {
     },
 }
 
+/// HTTP response rendered by a Respond node for the caller of a synchronous trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerResponse {
+    pub status_code: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct Node {
     pub id: String,
@@ -308,6 +752,7 @@ pub struct Node {
     pub name: String,
     pub node_type: NodeType,
     pub input_merge_strategy: Option<InputMergeStrategy>,
+    pub allowed_host_functions: Vec<HostFunction>, // Inherited from the parent workflow
 }
 
 #[derive(Debug, Clone)]
@@ -327,11 +772,54 @@ pub struct Workflow {
     pub description: Option<String>,
     pub start_node_id: Option<String>, // New: node ID reference
     pub enabled: bool,
+    pub allowed_host_functions: Vec<HostFunction>,
     pub nodes: Vec<Node>,
     pub edges: Vec<Edge>,
 }
 
 impl Workflow {
+    /// The start Trigger node's type, holding its request checks
+    fn start_trigger(&self) -> Option<&NodeType> {
+        let start_node_id = self.start_node_id.as_deref()?;
+        self.nodes
+            .iter()
+            .find(|node| node.id == start_node_id)
+            .map(|node| &node.node_type)
+            .filter(|node_type| matches!(node_type, NodeType::Trigger { .. }))
+    }
+
+    /// Signature scheme of the start Trigger node, if requests must be signed
+    pub fn trigger_verification(&self) -> Option<&TriggerVerification> {
+        match self.start_trigger()? {
+            NodeType::Trigger { verification, .. } => verification.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// API key the start Trigger node requires, if any
+    pub fn trigger_api_key(&self) -> Option<&TriggerApiKey> {
+        match self.start_trigger()? {
+            NodeType::Trigger { api_key, .. } => api_key.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Client networks the start Trigger node accepts, if restricted
+    pub fn trigger_ip_filter(&self) -> Option<&TriggerIpFilter> {
+        match self.start_trigger()? {
+            NodeType::Trigger { ip_filter, .. } => ip_filter.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Request rate and daily execution limits of the start Trigger node, if any
+    pub fn trigger_rate_limit(&self) -> Option<&TriggerRateLimit> {
+        match self.start_trigger()? {
+            NodeType::Trigger { rate_limit, .. } => rate_limit.as_ref(),
+            _ => None,
+        }
+    }
+
     /// Check if workflow contains Human-in-Loop nodes that require synchronous execution
     pub fn contains_hil_nodes(&self) -> bool {
         self.nodes.iter().any(|node| {
@@ -1,11 +1,20 @@
+use crate::utils::json_path;
 use crate::workflow::{
     errors::{Result, SwissPipeError},
-    models::{Edge, Node, NodeType},
+    models::{Edge, HeaderForwarding, HttpAuth, HttpMethod, HttpRequestBody, HttpResponseFormat, HttpResponseMapping, Node, NodeType, TriggerVerification, ERROR_HANDLE, SWITCH_DEFAULT_CASE, parse_status_codes},
 };
 use std::collections::{HashMap, HashSet, VecDeque};
 
 pub struct WorkflowValidator;
 
+/// Nodes run once per item by a ForEach node
+#[derive(Debug, Clone)]
+pub struct ForEachBody {
+    /// Node IDs between the ForEach and its Aggregate, excluding both
+    pub nodes: HashSet<String>,
+    pub aggregate_id: String,
+}
+
 impl WorkflowValidator {
     /// Validate the entire workflow structure
     pub fn validate_workflow(
@@ -26,12 +35,422 @@ impl WorkflowValidator {
         // 4. Validate connectivity (all nodes reachable from start)
         Self::validate_connectivity(start_node_id, nodes, edges)?;
         
-        // 5. Validate conditional edges have condition nodes
+        // 5. Validate conditional edges have condition nodes and switch edges have known cases
         Self::validate_conditional_edges(nodes, edges)?;
+
+        // 6. Validate ForEach bodies and their Aggregate nodes
+        Self::validate_foreach_nodes(nodes, edges)?;
+
+        // 7. Validate error edges leave nodes that can report a failure
+        Self::validate_error_edges(nodes, edges)?;
+
+        // 8. Validate Respond nodes describe a valid HTTP response
+        Self::validate_respond_nodes(nodes)?;
+
+        // 9. Validate Trigger signature verification settings
+        Self::validate_trigger_verification(nodes)?;
+
+        // 10. Validate Trigger API key, IP filter and rate limit settings
+        Self::validate_trigger_access(nodes)?;
+
+        // 11. Validate HttpRequest body, query parameter and response mapping settings
+        Self::validate_http_request_templates(nodes)?;
         
+        // 12. Validate retry status codes of nodes calling external services
+        Self::validate_retry_configs(nodes)?;
+        
+        Ok(())
+    }
+
+    /// Find the nodes a ForEach runs per item and the Aggregate that collects the results
+    ///
+    /// The body is everything reachable from the ForEach up to its Aggregate. Nested
+    /// ForEach nodes, their bodies and their Aggregates belong to the outer body. The
+    /// ForEach's own error edges lead out of the body.
+    pub fn foreach_body(nodes: &[Node], edges: &[Edge], foreach_id: &str) -> Result<ForEachBody> {
+        let node_map: HashMap<&str, &Node> = nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+        let successors = |id: &str| -> Vec<String> {
+            edges.iter().filter(|e| e.from_node_id == id).map(|e| e.to_node_id.clone()).collect()
+        };
+
+        let mut body = HashSet::new();
+        let mut aggregates = HashSet::new();
+        let mut queue: VecDeque<String> = edges.iter()
+            .filter(|e| e.from_node_id == foreach_id && e.source_handle_id.as_deref() != Some(ERROR_HANDLE))
+            .map(|e| e.to_node_id.clone())
+            .collect();
+
+        while let Some(node_id) = queue.pop_front() {
+            if body.contains(&node_id) || aggregates.contains(&node_id) {
+                continue;
+            }
+            match node_map.get(node_id.as_str()).map(|n| &n.node_type) {
+                Some(NodeType::Aggregate {}) => {
+                    aggregates.insert(node_id);
+                }
+                Some(NodeType::ForEach { .. }) => {
+                    let nested = Self::foreach_body(nodes, edges, &node_id)?;
+                    queue.extend(successors(&nested.aggregate_id));
+                    body.extend(nested.nodes);
+                    body.insert(nested.aggregate_id);
+                    body.insert(node_id);
+                }
+                _ => {
+                    queue.extend(successors(&node_id));
+                    body.insert(node_id);
+                }
+            }
+        }
+
+        let foreach_name = node_map.get(foreach_id).map(|n| n.name.as_str()).unwrap_or("unknown");
+        let mut aggregates = aggregates.into_iter();
+        match (aggregates.next(), aggregates.next()) {
+            (Some(aggregate_id), None) => Ok(ForEachBody { nodes: body, aggregate_id }),
+            (None, _) => Err(SwissPipeError::Config(format!(
+                "ForEach node '{foreach_name}' (id: {foreach_id}) must lead to an Aggregate node"
+            ))),
+            (Some(_), Some(_)) => Err(SwissPipeError::Config(format!(
+                "ForEach node '{foreach_name}' (id: {foreach_id}) leads to more than one Aggregate node"
+            ))),
+        }
+    }
+
+    /// Validate that SubWorkflow nodes never lead a workflow back to itself
+    ///
+    /// `references` maps saved workflows to the workflows their SubWorkflow nodes start.
+    /// Any entry for `workflow_id` itself is ignored in favour of `nodes`.
+    pub fn validate_sub_workflow_references(
+        workflow_id: &str,
+        nodes: &[Node],
+        references: &HashMap<String, Vec<String>>,
+    ) -> Result<()> {
+        for node in nodes {
+            let NodeType::SubWorkflow { workflow_id: target, .. } = &node.node_type else {
+                continue;
+            };
+            if target.trim().is_empty() {
+                return Err(SwissPipeError::Config(format!(
+                    "SubWorkflow node '{}' (id: {}) requires a workflow_id",
+                    node.name, node.id
+                )));
+            }
+
+            // Depth-first search keeping the path taken, to report the whole cycle
+            let mut visited = HashSet::new();
+            let mut stack = vec![vec![workflow_id, target.as_str()]];
+            while let Some(path) = stack.pop() {
+                let current = path[path.len() - 1];
+                if current == workflow_id {
+                    return Err(SwissPipeError::Config(format!(
+                        "SubWorkflow node '{}' (id: {}) creates a cycle between workflows: {}",
+                        node.name, node.id, path.join(" -> ")
+                    )));
+                }
+                if !visited.insert(current) {
+                    continue;
+                }
+                for next in references.get(current).into_iter().flatten() {
+                    let mut next_path = path.clone();
+                    next_path.push(next);
+                    stack.push(next_path);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate that every ForEach has a self-contained body ending in its own Aggregate
+    fn validate_foreach_nodes(nodes: &[Node], edges: &[Edge]) -> Result<()> {
+        let mut aggregate_owners: HashMap<String, &Node> = HashMap::new();
+
+        for node in nodes {
+            let NodeType::ForEach { selector, concurrency, .. } = &node.node_type else {
+                continue;
+            };
+            if selector.trim().is_empty() {
+                return Err(SwissPipeError::Config(format!(
+                    "ForEach node '{}' (id: {}) requires a selector",
+                    node.name, node.id
+                )));
+            }
+            if *concurrency == Some(0) {
+                return Err(SwissPipeError::Config(format!(
+                    "ForEach node '{}' (id: {}) concurrency must be at least 1",
+                    node.name, node.id
+                )));
+            }
+
+            let body = Self::foreach_body(nodes, edges, &node.id)?;
+            if let Some(other) = aggregate_owners.insert(body.aggregate_id.clone(), node) {
+                return Err(SwissPipeError::Config(format!(
+                    "ForEach nodes '{}' and '{}' share the same Aggregate node",
+                    other.name, node.name
+                )));
+            }
+
+            for body_node in nodes.iter().filter(|n| body.nodes.contains(&n.id)) {
+                let unsupported = match &body_node.node_type {
+                    NodeType::Trigger { .. } => Some("Trigger"),
+                    NodeType::HumanInLoop { .. } => Some("HumanInLoop"),
+                    NodeType::Delay { .. } => Some("Delay"),
+                    NodeType::Respond { .. } => Some("Respond"),
+                    NodeType::HttpRequest { loop_config: Some(_), .. } => Some("looping HttpRequest"),
+                    _ => None,
+                };
+                if let Some(kind) = unsupported {
+                    return Err(SwissPipeError::Config(format!(
+                        "{kind} node '{}' (id: {}) cannot run inside ForEach node '{}'",
+                        body_node.name, body_node.id, node.name
+                    )));
+                }
+            }
+
+            // Items only enter the body through the ForEach and leave it through the Aggregate
+            let inside = |id: &str| id == node.id || body.nodes.contains(id);
+            for edge in edges {
+                let enters_body = body.nodes.contains(&edge.to_node_id) || edge.to_node_id == body.aggregate_id;
+                if enters_body && !inside(&edge.from_node_id) {
+                    return Err(SwissPipeError::Config(format!(
+                        "Edge into the body of ForEach node '{}' (id: {}) must come from inside it",
+                        node.name, node.id
+                    )));
+                }
+            }
+        }
+
+        for node in nodes {
+            if matches!(node.node_type, NodeType::Aggregate {}) && !aggregate_owners.contains_key(&node.id) {
+                return Err(SwissPipeError::Config(format!(
+                    "Aggregate node '{}' (id: {}) is not reached from a ForEach node",
+                    node.name, node.id
+                )));
+            }
+        }
+
         Ok(())
     }
     
+    /// Validate that error edges are unconditional and leave nodes whose failures can be caught
+    ///
+    /// Triggers run before the DAG starts and Human-in-Loop nodes already route by their own handles.
+    fn validate_error_edges(nodes: &[Node], edges: &[Edge]) -> Result<()> {
+        let node_map: HashMap<&str, &Node> = nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+
+        for edge in edges.iter().filter(|e| e.source_handle_id.as_deref() == Some(ERROR_HANDLE)) {
+            let Some(node) = node_map.get(edge.from_node_id.as_str()) else {
+                continue;
+            };
+            let unsupported = match &node.node_type {
+                NodeType::Trigger { .. } => Some("Trigger"),
+                NodeType::HumanInLoop { .. } => Some("HumanInLoop"),
+                _ => None,
+            };
+            if let Some(kind) = unsupported {
+                return Err(SwissPipeError::Config(format!(
+                    "{kind} node '{}' (id: {}) cannot have an error edge",
+                    node.name, node.id
+                )));
+            }
+            if edge.condition_result.is_some() {
+                return Err(SwissPipeError::Config(format!(
+                    "Error edge from node '{}' (id: {}) cannot also set condition_result",
+                    node.name, node.id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate that Respond nodes use a real HTTP status code and header names
+    ///
+    /// Header values and the body are templates, so they are only checked once rendered.
+    fn validate_respond_nodes(nodes: &[Node]) -> Result<()> {
+        for node in nodes {
+            let NodeType::Respond { status_code, headers, .. } = &node.node_type else {
+                continue;
+            };
+            if !(100..=599).contains(status_code) {
+                return Err(SwissPipeError::Config(format!(
+                    "Respond node '{}' (id: {}) status_code must be between 100 and 599",
+                    node.name, node.id
+                )));
+            }
+            if let Some(name) = headers.keys().find(|name| axum::http::HeaderName::from_bytes(name.as_bytes()).is_err()) {
+                return Err(SwissPipeError::Config(format!(
+                    "Respond node '{}' (id: {}) has an invalid header name '{}'",
+                    node.name, node.id, name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate the request templates and response handling of HttpRequest nodes
+    ///
+    /// Looping requests are sent by the HTTP loop scheduler, which always forwards the
+    /// event data and decides success itself, so they cannot use these settings. Their
+    /// headers are also stored with the loop, which must never hold caller credentials.
+    fn validate_http_request_templates(nodes: &[Node]) -> Result<()> {
+        for node in nodes {
+            let NodeType::HttpRequest {
+                method, loop_config, body, query_params, response_mapping, response_format, success_status_codes, auth, forward_headers, ..
+            } = &node.node_type else {
+                continue;
+            };
+            let invalid = |reason: String| SwissPipeError::Config(format!(
+                "HttpRequest node '{}' (id: {}) {}", node.name, node.id, reason
+            ));
+
+            let templated = body.is_some() || !query_params.is_empty() || response_mapping.is_some()
+                || *response_format != HttpResponseFormat::Body || !success_status_codes.is_empty() || auth.is_some()
+                || *forward_headers != HeaderForwarding::None;
+            if loop_config.is_some() && templated {
+                return Err(invalid(
+                    "cannot use body, query_params, response_mapping, response_format, success_status_codes, auth or forward_headers together with loop_config".to_string()
+                ));
+            }
+            if let HeaderForwarding::Allow(allowed) = forward_headers {
+                if let Some(name) = allowed.iter().find(|name| axum::http::HeaderName::from_bytes(name.as_bytes()).is_err()) {
+                    return Err(invalid(format!("forwards an invalid header '{name}'")));
+                }
+            }
+            if let Some(auth) = auth {
+                Self::validate_http_auth(auth).map_err(|reason| invalid(format!("auth {reason}")))?;
+            }
+            if let Some(pattern) = success_status_codes.iter().find(|p| parse_status_codes(p).is_none()) {
+                return Err(invalid(format!("has an invalid success status code '{pattern}'")));
+            }
+            if body.is_some() && matches!(method, HttpMethod::Get) {
+                return Err(invalid("cannot send a body with a GET request".to_string()));
+            }
+            if let Some(HttpRequestBody::Raw { content_type: Some(content_type), .. }) = body.as_deref() {
+                if axum::http::HeaderValue::from_str(content_type).is_err() {
+                    return Err(invalid(format!("has an invalid content_type '{content_type}'")));
+                }
+            }
+            if let Some(HttpResponseMapping::Pointer(pointer)) = response_mapping {
+                json_path::validate_pointer(pointer).map_err(|e| invalid(format!("has an invalid response mapping: {e}")))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate the status code patterns HttpRequest, OpenObserve and Anthropic nodes retry on
+    fn validate_retry_configs(nodes: &[Node]) -> Result<()> {
+        for node in nodes {
+            let retry_config = match &node.node_type {
+                NodeType::HttpRequest { retry_config, .. }
+                | NodeType::OpenObserve { retry_config, .. }
+                | NodeType::Anthropic { retry_config, .. } => retry_config,
+                _ => continue,
+            };
+            if let Some(pattern) = retry_config.retry_status_codes.iter().find(|p| parse_status_codes(p).is_none()) {
+                return Err(SwissPipeError::Config(format!(
+                    "Node '{}' (id: {}) has an invalid retry status code '{}'",
+                    node.name, node.id, pattern
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate the auth settings of an HttpRequest node, returning the reason they are invalid
+    fn validate_http_auth(auth: &HttpAuth) -> std::result::Result<(), String> {
+        if auth.variables().iter().any(|name| name.trim().is_empty()) {
+            return Err("requires every credential variable to be named".to_string());
+        }
+        match auth {
+            HttpAuth::ApiKey { query_param: Some(param), .. } if param.trim().is_empty() => {
+                Err("query_param cannot be empty".to_string())
+            }
+            HttpAuth::ApiKey { header, query_param: None, .. } if axum::http::HeaderName::from_bytes(header.as_bytes()).is_err() => {
+                Err(format!("has an invalid header '{header}'"))
+            }
+            HttpAuth::OAuth2ClientCredentials { token_url, .. }
+                if !url::Url::parse(token_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) =>
+            {
+                Err(format!("has an invalid token_url '{token_url}'"))
+            }
+            HttpAuth::AwsSigV4 { region, service, .. } if region.trim().is_empty() || service.trim().is_empty() => {
+                Err("requires a region and a service".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Validate that Trigger nodes verifying signatures name their secret and header
+    fn validate_trigger_verification(nodes: &[Node]) -> Result<()> {
+        for node in nodes {
+            let NodeType::Trigger { verification: Some(verification), .. } = &node.node_type else {
+                continue;
+            };
+            if verification.secret_variable().trim().is_empty() {
+                return Err(SwissPipeError::Config(format!(
+                    "Trigger node '{}' (id: {}) verification requires a secret_variable",
+                    node.name, node.id
+                )));
+            }
+            if let TriggerVerification::Hmac { header, .. } = verification {
+                if axum::http::HeaderName::from_bytes(header.as_bytes()).is_err() {
+                    return Err(SwissPipeError::Config(format!(
+                        "Trigger node '{}' (id: {}) has an invalid signature header '{}'",
+                        node.name, node.id, header
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validate that Trigger API keys name their secret and header, IP filters parse and rate limits are positive
+    fn validate_trigger_access(nodes: &[Node]) -> Result<()> {
+        for node in nodes {
+            let NodeType::Trigger { api_key, ip_filter, rate_limit, .. } = &node.node_type else {
+                continue;
+            };
+            if let Some(api_key) = api_key {
+                if api_key.secret_variable.trim().is_empty() {
+                    return Err(SwissPipeError::Config(format!(
+                        "Trigger node '{}' (id: {}) API key requires a secret_variable",
+                        node.name, node.id
+                    )));
+                }
+                if axum::http::HeaderName::from_bytes(api_key.header.as_bytes()).is_err() {
+                    return Err(SwissPipeError::Config(format!(
+                        "Trigger node '{}' (id: {}) has an invalid API key header '{}'",
+                        node.name, node.id, api_key.header
+                    )));
+                }
+                if api_key.query_param.as_deref().is_some_and(|param| param.trim().is_empty()) {
+                    return Err(SwissPipeError::Config(format!(
+                        "Trigger node '{}' (id: {}) has an empty API key query_param",
+                        node.name, node.id
+                    )));
+                }
+            }
+            if let Some(Err(e)) = ip_filter.as_ref().map(|filter| filter.validate()) {
+                return Err(SwissPipeError::Config(format!(
+                    "Trigger node '{}' (id: {}) has an invalid IP filter: {}",
+                    node.name, node.id, e
+                )));
+            }
+            if let Some(Err(e)) = rate_limit.as_ref().map(|limit| limit.validate()) {
+                return Err(SwissPipeError::Config(format!(
+                    "Trigger node '{}' (id: {}) has an invalid rate limit: {}",
+                    node.name, node.id, e
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Validate that start_node_id exists in the workflow's nodes
     fn validate_start_node_exists(start_node_id: &str, nodes: &[Node]) -> Result<()> {
         let node_exists = nodes.iter().any(|node| node.id == start_node_id);
@@ -203,7 +622,8 @@ impl WorkflowValidator {
         Ok(())
     }
     
-    /// Validate that conditional edges originate from condition nodes
+    /// Validate that conditional edges originate from condition nodes and that
+    /// switch node edges bind to one of the node's cases
     fn validate_conditional_edges(nodes: &[Node], edges: &[Edge]) -> Result<()> {
         let condition_node_ids: HashSet<String> = nodes
             .iter()
@@ -212,8 +632,38 @@ impl WorkflowValidator {
                 _ => None,
             })
             .collect();
-        
+
+        let switch_nodes: HashMap<&str, (&Node, &Vec<String>)> = nodes
+            .iter()
+            .filter_map(|node| match &node.node_type {
+                NodeType::Switch { cases, .. } => Some((node.id.as_str(), (node, cases))),
+                _ => None,
+            })
+            .collect();
+
+        for (node, cases) in switch_nodes.values() {
+            Self::validate_switch_cases(node, cases)?;
+        }
+
         for edge in edges {
+            if let Some((node, cases)) = switch_nodes.get(edge.from_node_id.as_str()) {
+                if edge.condition_result.is_some() {
+                    return Err(SwissPipeError::Config(format!(
+                        "Edge from Switch node '{}' (id: {}) must route by case handle, not condition_result",
+                        node.name, node.id
+                    )));
+                }
+                if let Some(handle) = edge.source_handle_id.as_deref() {
+                    if handle != SWITCH_DEFAULT_CASE && handle != ERROR_HANDLE && !cases.iter().any(|c| c == handle) {
+                        return Err(SwissPipeError::Config(format!(
+                            "Edge from Switch node '{}' (id: {}) uses unknown case '{}'",
+                            node.name, node.id, handle
+                        )));
+                    }
+                }
+                continue;
+            }
+
             if edge.condition_result.is_some()
                 && !condition_node_ids.contains(&edge.from_node_id) {
                     // Get node name for better error message
@@ -231,6 +681,33 @@ impl WorkflowValidator {
         Ok(())
     }
     
+    /// Validate that a switch node's case labels are non-empty and unique
+    fn validate_switch_cases(node: &Node, cases: &[String]) -> Result<()> {
+        let mut seen = HashSet::new();
+        for case in cases {
+            if case.trim().is_empty() {
+                return Err(SwissPipeError::Config(format!(
+                    "Switch node '{}' (id: {}) has an empty case label",
+                    node.name, node.id
+                )));
+            }
+            if case == SWITCH_DEFAULT_CASE || case == ERROR_HANDLE {
+                return Err(SwissPipeError::Config(format!(
+                    "Switch node '{}' (id: {}) cannot declare a case named '{}'",
+                    node.name, node.id, case
+                )));
+            }
+            if !seen.insert(case.as_str()) {
+                return Err(SwissPipeError::Config(format!(
+                    "Switch node '{}' (id: {}) declares case '{}' more than once",
+                    node.name, node.id, case
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
     /// Validate that condition nodes have both true and false edges (warning only)
     pub fn validate_condition_completeness(nodes: &[Node], edges: &[Edge]) -> Vec<String> {
         let mut warnings = Vec::new();
@@ -263,6 +740,20 @@ impl WorkflowValidator {
             }
         }
         
+        for node in nodes.iter().filter(|node| matches!(&node.node_type, NodeType::Switch { .. })) {
+            let has_default_edge = edges.iter().any(|e| {
+                e.from_node_id == node.id
+                    && e.source_handle_id.as_deref().unwrap_or(SWITCH_DEFAULT_CASE) == SWITCH_DEFAULT_CASE
+            });
+
+            if !has_default_edge {
+                warnings.push(format!(
+                    "Switch node '{}' (id: {}) has no default edge - unmatched events will be dropped",
+                    node.name, node.id
+                ));
+            }
+        }
+
         warnings
     }
 }
\ No newline at end of file
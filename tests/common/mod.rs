@@ -0,0 +1,151 @@
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
+use swisspipe::{
+    api::{ingestion_limits::IngestionLimiter, trigger_auth::TriggerAuthMetrics},
+    async_execution::{DelayScheduler, HttpLoopScheduler, MpscJobDistributor, MpscWorkerPool},
+    cache::WorkflowCache,
+    config::{Config, HttpLoopConfig, WorkerPoolConfig},
+    database::{entities, establish_connection, nodes},
+    hil::HilService,
+    schedule::ScheduleService,
+    variables::{EncryptionService, TemplateEngine, VariableService},
+    versions::VersionService,
+    workflow::{engine::WorkflowEngine, models::{HttpMethod, NodeType, Workflow}},
+    AppState,
+};
+use uuid::Uuid;
+
+/// Build an `AppState` backed by an in-memory database, without starting background workers
+pub async fn test_state() -> AppState {
+    let db = Arc::new(establish_connection("sqlite::memory:").await.unwrap());
+    let config = test_config();
+
+    let engine = Arc::new(WorkflowEngine::new(db.clone()).unwrap());
+    let variable_service = Arc::new(VariableService::new(db.clone(), EncryptionService::new(&[0u8; 32])));
+    let template_engine = Arc::new(TemplateEngine::new());
+    engine.set_variable_service(variable_service.clone()).unwrap();
+    engine.set_template_engine(template_engine.clone()).unwrap();
+    let (mpsc_distributor, job_receiver) = MpscJobDistributor::new(db.clone(), 1000);
+    let mpsc_distributor = Arc::new(mpsc_distributor);
+    let worker_pool = Arc::new(MpscWorkerPool::with_distributor(
+        db.clone(),
+        engine.clone(),
+        mpsc_distributor.clone(),
+        job_receiver,
+        None,
+    ));
+    let http_loop_scheduler = Arc::new(HttpLoopScheduler::new(db.clone(), config.http_loop.clone()).await.unwrap());
+
+    AppState {
+        db: db.clone(),
+        engine,
+        config: Arc::new(config),
+        hil_service: Arc::new(HilService::new(db.clone())),
+        worker_pool,
+        mpsc_distributor,
+        workflow_cache: Arc::new(WorkflowCache::new(Some(300))),
+        delay_scheduler: Arc::new(DelayScheduler::new(db.clone()).await.unwrap()),
+        http_loop_scheduler,
+        variable_service,
+        template_engine,
+        schedule_service: Arc::new(ScheduleService::new(db.clone()).unwrap()),
+        version_service: Arc::new(VersionService::new(db)),
+        trigger_auth_metrics: Arc::new(TriggerAuthMetrics::new()),
+        ingestion_limiter: Arc::new(IngestionLimiter::new()),
+    }
+}
+
+fn test_config() -> Config {
+    Config {
+        username: "admin".to_string(),
+        password: "admin".to_string(),
+        database_url: "sqlite::memory:".to_string(),
+        port: 3700,
+        worker_pool: WorkerPoolConfig {
+            worker_count: 1,
+            job_poll_interval_ms: 100,
+            job_claim_timeout_seconds: 60,
+            worker_health_check_interval_seconds: 30,
+            job_claim_cleanup_interval_seconds: 60,
+        },
+        google_oauth: None,
+        execution_retention_count: 100,
+        cleanup_interval_minutes: 60,
+        http_loop: HttpLoopConfig {
+            scheduler_interval_seconds: 5,
+            max_history_entries: 100,
+            default_timeout_seconds: 30,
+            max_response_size_bytes: 1024 * 1024,
+            max_iteration_timeout_seconds: 60,
+        },
+    }
+}
+
+/// Create an enabled workflow containing only a POST trigger and return its ID
+pub async fn create_trigger_workflow(db: &DatabaseConnection) -> String {
+    let workflow_id = Uuid::new_v4().to_string();
+    let trigger_node_id = Uuid::new_v4().to_string();
+    let now = Utc::now().timestamp_micros();
+
+    entities::ActiveModel {
+        id: Set(workflow_id.clone()),
+        name: Set("Test workflow".to_string()),
+        description: Set(None),
+        start_node_id: Set(Some(trigger_node_id.clone())),
+        enabled: Set(true),
+        allowed_host_functions: Set(None),
+        error_workflow_id: Set(None),
+        max_concurrent_executions: Set(None),
+        scheduling_weight: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(db)
+    .await
+    .unwrap();
+
+    let trigger = NodeType::Trigger { methods: vec![HttpMethod::Post], verification: None, api_key: None, ip_filter: None, rate_limit: None };
+    nodes::ActiveModel {
+        id: Set(trigger_node_id),
+        workflow_id: Set(workflow_id.clone()),
+        name: Set("Trigger".to_string()),
+        node_type: Set("trigger".to_string()),
+        config: Set(serde_json::to_string(&trigger).unwrap()),
+        position_x: Set(100.0),
+        position_y: Set(100.0),
+        created_at: Set(now),
+        input_merge_strategy: Set(None),
+    }
+    .insert(db)
+    .await
+    .unwrap();
+
+    workflow_id
+}
+
+/// Persist the nodes of an in-memory workflow so that execution steps can reference them
+pub async fn insert_nodes(db: &DatabaseConnection, workflow: &Workflow) {
+    let now = Utc::now().timestamp_micros();
+    for node in &workflow.nodes {
+        let config = serde_json::to_value(&node.node_type).unwrap();
+        let node_type = config.as_object().and_then(|o| o.keys().next().cloned()).unwrap_or_default();
+        nodes::ActiveModel {
+            id: Set(node.id.clone()),
+            workflow_id: Set(workflow.id.clone()),
+            name: Set(node.name.clone()),
+            node_type: Set(node_type.to_lowercase()),
+            config: Set(config.to_string()),
+            position_x: Set(100.0),
+            position_y: Set(100.0),
+            created_at: Set(now),
+            input_merge_strategy: Set(None),
+        }
+        .insert(db)
+        .await
+        .unwrap();
+    }
+}
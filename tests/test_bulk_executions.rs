@@ -0,0 +1,96 @@
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
+use std::collections::HashMap;
+use std::sync::Arc;
+use swisspipe::async_execution::ExecutionService;
+use swisspipe::database::{entities, establish_connection, job_queue, workflow_executions};
+
+const WORKFLOW_ID: &str = "0192c3a0-7b1e-7c4d-9e2f-1a2b3c4d5e6f";
+
+async fn setup_db() -> Arc<sea_orm::DatabaseConnection> {
+    let db = establish_connection("sqlite::memory:").await.unwrap();
+
+    let now = chrono::Utc::now().timestamp_micros();
+    entities::ActiveModel {
+        id: Set(WORKFLOW_ID.to_string()),
+        name: Set("Batch workflow".to_string()),
+        description: Set(None),
+        start_node_id: Set(None),
+        enabled: Set(true),
+        allowed_host_functions: Set(None),
+        error_workflow_id: Set(None),
+        max_concurrent_executions: Set(None),
+        scheduling_weight: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    Arc::new(db)
+}
+
+fn deeply_nested(depth: usize) -> serde_json::Value {
+    (0..depth).fold(serde_json::json!("leaf"), |inner, _| serde_json::json!({ "nested": inner }))
+}
+
+#[tokio::test]
+async fn test_create_executions_queues_every_valid_input() {
+    let db = setup_db().await;
+    let service = ExecutionService::new(db.clone());
+
+    let inputs: Vec<serde_json::Value> = (0..250).map(|i| serde_json::json!({ "event": "Signed Up", "index": i })).collect();
+    let headers = HashMap::from([("user-agent".to_string(), "analytics-node".to_string())]);
+
+    let results = service.create_executions(WORKFLOW_ID, inputs, &headers, None).await.unwrap();
+    assert_eq!(results.len(), 250);
+    assert!(results.iter().all(|r| r.is_ok()));
+
+    let executions = workflow_executions::Entity::find()
+        .filter(workflow_executions::Column::WorkflowId.eq(WORKFLOW_ID))
+        .count(&*db)
+        .await
+        .unwrap();
+    let jobs = job_queue::Entity::find().count(&*db).await.unwrap();
+    assert_eq!(executions, 250);
+    assert_eq!(jobs, 250);
+
+    // Execution IDs line up with the inputs
+    let third = results[2].as_ref().unwrap();
+    let execution = workflow_executions::Entity::find_by_id(third).one(&*db).await.unwrap().unwrap();
+    let input: serde_json::Value = serde_json::from_str(&execution.input_data.unwrap()).unwrap();
+    assert_eq!(input["data"]["index"], 2);
+    assert_eq!(input["headers"]["user-agent"], "analytics-node");
+}
+
+#[tokio::test]
+async fn test_create_executions_reports_invalid_inputs_individually() {
+    let db = setup_db().await;
+    let service = ExecutionService::new(db.clone());
+
+    let inputs = vec![
+        serde_json::json!({ "event": "first" }),
+        deeply_nested(20),
+        serde_json::json!({ "event": "third" }),
+    ];
+
+    let results = service.create_executions(WORKFLOW_ID, inputs, &HashMap::new(), None).await.unwrap();
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+    assert!(results[2].is_ok());
+
+    let executions = workflow_executions::Entity::find().count(&*db).await.unwrap();
+    assert_eq!(executions, 2);
+}
+
+#[tokio::test]
+async fn test_create_executions_rejects_invalid_workflow_id() {
+    let db = setup_db().await;
+    let service = ExecutionService::new(db.clone());
+
+    let result = service
+        .create_executions("not-a-workflow-id", vec![serde_json::json!({})], &HashMap::new(), None)
+        .await;
+    assert!(result.is_err());
+    assert_eq!(job_queue::Entity::find().count(&*db).await.unwrap(), 0);
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use sea_orm::{EntityTrait, PaginatorTrait};
+use swisspipe::api;
+use swisspipe::database::workflow_executions;
+use swisspipe::utils::record_stream::{CsvParser, NdjsonParser, ParsedRecord, MAX_RECORD_BYTES};
+
+/// Feed `body` to the parser in chunks of `chunk_size` bytes
+fn parse_ndjson(body: &[u8], chunk_size: usize) -> Vec<ParsedRecord> {
+    let mut parser = NdjsonParser::new();
+    let mut records: Vec<ParsedRecord> = body.chunks(chunk_size).flat_map(|chunk| parser.push(chunk)).collect();
+    records.extend(parser.finish());
+    records
+}
+
+fn parse_csv(body: &[u8], chunk_size: usize) -> Result<Vec<ParsedRecord>, String> {
+    let mut parser = CsvParser::new();
+    let mut records = Vec::new();
+    for chunk in body.chunks(chunk_size) {
+        records.extend(parser.push(chunk)?);
+    }
+    records.extend(parser.finish()?);
+    Ok(records)
+}
+
+#[test]
+fn test_ndjson_parser_handles_chunk_boundaries() {
+    let body = b"{\"id\":1}\r\n\n{\"id\":2}\nnot json\n  \n{\"id\":3}";
+
+    for chunk_size in [1, 3, 7, body.len()] {
+        let records = parse_ndjson(body, chunk_size);
+        let lines: Vec<usize> = records.iter().map(|(line, _)| *line).collect();
+        assert_eq!(lines, vec![1, 3, 4, 6], "chunk size {chunk_size}");
+
+        assert_eq!(records[0].1.as_ref().unwrap()["id"], 1);
+        assert_eq!(records[1].1.as_ref().unwrap()["id"], 2);
+        assert!(records[2].1.as_ref().unwrap_err().starts_with("Invalid JSON"));
+        assert_eq!(records[3].1.as_ref().unwrap()["id"], 3);
+    }
+}
+
+#[test]
+fn test_ndjson_parser_rejects_oversized_lines() {
+    let mut body = format!("{{\"big\":\"{}\"}}\n", "x".repeat(MAX_RECORD_BYTES)).into_bytes();
+    body.extend_from_slice(b"{\"id\":2}\n");
+
+    let records = parse_ndjson(&body, 64 * 1024);
+    assert_eq!(records.len(), 2);
+    assert!(records[0].1.as_ref().unwrap_err().contains("exceeds"));
+    assert_eq!(records[1].0, 2);
+    assert_eq!(records[1].1.as_ref().unwrap()["id"], 2);
+}
+
+#[test]
+fn test_csv_parser_maps_rows_to_header_fields() {
+    let body = "\u{feff}email,name,note\r\na@example.com,Ann,\"multi\nline, quoted\"\nb@example.com,Bob\nc@example.com,Cy,plain";
+
+    for chunk_size in [1, 5, body.len()] {
+        let records = parse_csv(body.as_bytes(), chunk_size).unwrap();
+        let rows: Vec<usize> = records.iter().map(|(row, _)| *row).collect();
+        assert_eq!(rows, vec![2, 3, 4], "chunk size {chunk_size}");
+
+        let first = records[0].1.as_ref().unwrap();
+        assert_eq!(first["email"], "a@example.com");
+        assert_eq!(first["note"], "multi\nline, quoted");
+        assert_eq!(records[1].1.as_ref().unwrap_err(), "Expected 3 fields, found 2");
+        assert_eq!(records[2].1.as_ref().unwrap()["note"], "plain");
+    }
+}
+
+#[test]
+fn test_csv_parser_requires_header_row() {
+    assert!(parse_csv(b",,\n1,2,3\n", 4).is_err());
+    assert!(parse_csv(b"", 4).unwrap().is_empty());
+}
+
+async fn setup() -> (TestServer, swisspipe::AppState, String) {
+    let state = common::test_state().await;
+    let workflow_id = common::create_trigger_workflow(&state.db).await;
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state, workflow_id)
+}
+
+#[tokio::test]
+async fn test_ndjson_endpoint_creates_one_execution_per_line() {
+    let (server, state, workflow_id) = setup().await;
+
+    let mut body: String = (0..1200).map(|i| format!("{{\"index\":{i}}}\n")).collect();
+    body.push_str("{broken\n");
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/ndjson"))
+        .add_header(header::CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"))
+        .bytes(body.into())
+        .await;
+
+    response.assert_status(StatusCode::ACCEPTED);
+    let summary: serde_json::Value = response.json();
+    assert_eq!(summary["status"], "accepted");
+    assert_eq!(summary["accepted"], 1200);
+    assert_eq!(summary["rejected"], 1);
+    assert_eq!(summary["accepted_lines"][0], 1);
+    assert_eq!(summary["rejected_lines"][0]["line"], 1201);
+
+    let executions = workflow_executions::Entity::find().count(&*state.db).await.unwrap();
+    assert_eq!(executions, 1200);
+}
+
+#[tokio::test]
+async fn test_csv_endpoint_creates_one_execution_per_row() {
+    let (server, state, workflow_id) = setup().await;
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/csv"))
+        .add_header(header::CONTENT_TYPE, HeaderValue::from_static("text/csv"))
+        .bytes("email,plan\na@example.com,pro\nb@example.com\nc@example.com,free\n".into())
+        .await;
+
+    response.assert_status(StatusCode::ACCEPTED);
+    let summary: serde_json::Value = response.json();
+    assert_eq!(summary["accepted_lines"], serde_json::json!([2, 4]));
+    assert_eq!(summary["rejected_lines"][0]["line"], 3);
+
+    let executions = workflow_executions::Entity::find().count(&*state.db).await.unwrap();
+    assert_eq!(executions, 2);
+}
+
+#[tokio::test]
+async fn test_bulk_endpoint_rejects_body_without_valid_records() {
+    let (server, _state, workflow_id) = setup().await;
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/ndjson"))
+        .bytes("not json\n".into())
+        .await;
+
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let summary: serde_json::Value = response.json();
+    assert_eq!(summary["status"], "rejected");
+    assert_eq!(summary["accepted"], 0);
+}
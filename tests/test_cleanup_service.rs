@@ -43,6 +43,10 @@ async fn test_cleanup_service_retention() {
             completed_at: Set(Some(created_at + 1000)),
             created_at: Set(created_at),
             updated_at: Set(created_at),
+            parent_execution_id: Set(None),
+            parent_node_id: Set(None),
+            failed_execution_id: Set(None),
+            http_response: Set(None),
         };
 
         let saved = execution.insert(db.as_ref()).await.unwrap();
@@ -135,6 +139,10 @@ async fn test_cleanup_service_multiple_workflows() {
             completed_at: Set(Some(created_at + 1000)),
             created_at: Set(created_at),
             updated_at: Set(created_at),
+            parent_execution_id: Set(None),
+            parent_node_id: Set(None),
+            failed_execution_id: Set(None),
+            http_response: Set(None),
         };
 
         execution.insert(db.as_ref()).await.unwrap();
@@ -156,6 +164,10 @@ async fn test_cleanup_service_multiple_workflows() {
             completed_at: Set(Some(created_at + 1000)),
             created_at: Set(created_at),
             updated_at: Set(created_at),
+            parent_execution_id: Set(None),
+            parent_node_id: Set(None),
+            failed_execution_id: Set(None),
+            http_response: Set(None),
         };
 
         execution.insert(db.as_ref()).await.unwrap();
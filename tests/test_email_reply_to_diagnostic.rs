@@ -1,12 +1,12 @@
-/// Diagnostic test to help troubleshoot Reply-To header issues
-/// This test will print detailed information about how the reply_to field flows through the system
+//! Diagnostic test to help troubleshoot Reply-To header issues
+//! This test will print detailed information about how the reply_to field flows through the system
 
 use swisspipe::email::template::TemplateEngine;
 use swisspipe::email::{EmailConfig, EmailAddress};
 use swisspipe::workflow::models::WorkflowEvent;
 use swisspipe::email::models::SmtpConfig;
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 fn create_test_smtp_config() -> SmtpConfig {
     SmtpConfig {
@@ -29,6 +29,8 @@ fn create_test_event(data: serde_json::Value) -> WorkflowEvent {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     }
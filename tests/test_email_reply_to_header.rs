@@ -3,7 +3,7 @@ use swisspipe::email::{EmailConfig, EmailAddress, EmailMessage};
 use swisspipe::workflow::models::WorkflowEvent;
 use swisspipe::email::models::SmtpConfig;
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Helper to create a basic SMTP config for testing
 fn create_test_smtp_config() -> SmtpConfig {
@@ -28,6 +28,8 @@ fn create_test_event(data: serde_json::Value) -> WorkflowEvent {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     }
@@ -226,6 +226,33 @@ async fn test_load_variables_map() {
     assert_eq!(map.get("SECRET_VAR").unwrap(), "secret_value"); // Secrets decrypted in map
 }
 
+/// Test the cached variables map is reused until a variable is written
+#[tokio::test]
+async fn test_variables_cache_is_invalidated_on_writes() {
+    let db = Arc::new(establish_connection("sqlite::memory:").await.unwrap());
+    let service = VariableService::new(db, EncryptionService::new(&[0u8; 32]));
+
+    let created = service.create_variable(CreateVariableRequest {
+        name: "API_KEY".to_string(),
+        value_type: "secret".to_string(),
+        value: "key_1".to_string(),
+        description: None,
+    }).await.unwrap();
+
+    let first = service.variables().await.unwrap();
+    assert_eq!(first.get("API_KEY").unwrap(), "key_1");
+    assert!(Arc::ptr_eq(&first, &service.clone().variables().await.unwrap()), "cached map was not reused");
+
+    service.update_variable(&created.id, UpdateVariableRequest {
+        value: "key_2".to_string(),
+        description: None,
+    }).await.unwrap();
+    assert_eq!(service.variables().await.unwrap().get("API_KEY").unwrap(), "key_2");
+
+    service.delete_variable(&created.id).await.unwrap();
+    assert!(service.variables().await.unwrap().is_empty());
+}
+
 /// Test template engine resolves simple variables
 #[tokio::test]
 async fn test_template_simple_resolution() {
@@ -0,0 +1,241 @@
+mod common;
+
+use std::collections::HashMap;
+
+use swisspipe::async_execution::ExecutionService;
+use swisspipe::workflow::{
+    models::{
+        Edge, FailureAction, HttpMethod, Node, NodeType, RetryConfig, Workflow, WorkflowEvent, ERROR_HANDLE,
+    },
+    validation::WorkflowValidator,
+};
+
+fn node(id: &str, node_type: NodeType) -> Node {
+    Node {
+        id: id.to_string(),
+        workflow_id: "wf".to_string(),
+        name: id.to_string(),
+        node_type,
+        input_merge_strategy: None,
+        allowed_host_functions: Vec::new(),
+    }
+}
+
+fn edge(from: &str, to: &str, handle: Option<&str>) -> Edge {
+    Edge {
+        id: format!("{from}-{to}"),
+        workflow_id: "wf".to_string(),
+        from_node_id: from.to_string(),
+        to_node_id: to.to_string(),
+        condition_result: None,
+        source_handle_id: handle.map(str::to_string),
+    }
+}
+
+fn transformer(id: &str, script: &str) -> Node {
+    node(id, NodeType::Transformer { script: script.to_string(), limits: None })
+}
+
+/// Tag the event with the node that handled it
+fn tag(id: &str) -> Node {
+    transformer(id, &format!("function transformer(event) {{ event.data.handled_by = '{id}'; return event; }}"))
+}
+
+const FAIL: &str = "function transformer(event) { throw new Error('CRM unavailable'); }";
+
+/// trigger -> sync -> done, with sync -error-> alert
+fn workflow(workflow_id: &str, sync: Node) -> Workflow {
+    Workflow {
+        id: workflow_id.to_string(),
+        name: "CRM sync".to_string(),
+        description: None,
+        start_node_id: Some("trigger".to_string()),
+        enabled: true,
+        allowed_host_functions: Vec::new(),
+        nodes: vec![
+            node("trigger", NodeType::Trigger { methods: vec![HttpMethod::Post], verification: None, api_key: None, ip_filter: None, rate_limit: None }),
+            sync,
+            tag("done"),
+            tag("alert"),
+        ],
+        edges: vec![
+            edge("trigger", "sync", None),
+            edge("sync", "done", None),
+            edge("sync", "alert", Some(ERROR_HANDLE)),
+        ],
+    }
+}
+
+async fn run(workflow: impl FnOnce(&str) -> Workflow) -> swisspipe::workflow::errors::Result<WorkflowEvent> {
+    let state = common::test_state().await;
+    let workflow_id = common::create_trigger_workflow(&state.db).await;
+    let execution_id = ExecutionService::new(state.db.clone())
+        .create_execution(workflow_id.clone(), serde_json::json!({}), HashMap::new(), None)
+        .await
+        .unwrap();
+
+    let event = WorkflowEvent {
+        data: serde_json::json!({ "email": "user@example.com" }),
+        ..WorkflowEvent::default()
+    };
+    state.engine.execute_workflow(&workflow(&workflow_id), event, &execution_id).await
+}
+
+#[tokio::test]
+async fn test_failed_node_follows_error_edge() {
+    let output = run(|id| workflow(id, transformer("sync", FAIL))).await.unwrap();
+
+    assert_eq!(output.data["handled_by"], "alert");
+    let error = &output.data["error"];
+    assert!(error["message"].as_str().unwrap().contains("CRM unavailable"), "{error}");
+    assert_eq!(error["node_id"], "sync");
+    assert_eq!(error["node_name"], "sync");
+    assert_eq!(error["attempts"], 1);
+    assert_eq!(error["input"], serde_json::json!({ "email": "user@example.com" }));
+}
+
+#[tokio::test]
+async fn test_successful_node_skips_error_edge() {
+    let output = run(|id| workflow(id, tag("sync"))).await.unwrap();
+    assert_eq!(output.data["handled_by"], "done");
+    assert!(output.data.get("error").is_none());
+}
+
+#[tokio::test]
+async fn test_node_without_error_edge_fails_execution() {
+    let result = run(|id| {
+        let mut workflow = workflow(id, transformer("sync", FAIL));
+        workflow.edges.retain(|e| e.source_handle_id.is_none());
+        workflow.nodes.retain(|n| n.id != "alert");
+        workflow
+    })
+    .await;
+    assert!(result.unwrap_err().to_string().contains("CRM unavailable"));
+}
+
+#[tokio::test]
+async fn test_other_branches_keep_running() {
+    let output = run(|id| {
+        let mut workflow = workflow(id, transformer("sync", FAIL));
+        workflow.nodes.push(tag("audit"));
+        workflow.edges.push(edge("trigger", "audit", None));
+        workflow
+    })
+    .await
+    .unwrap();
+
+    // Both leaves ran and their outputs were merged
+    let handled_by: Vec<_> = output.data.as_array().unwrap().iter().map(|d| d["handled_by"].clone()).collect();
+    assert!(handled_by.contains(&serde_json::json!("alert")), "{handled_by:?}");
+    assert!(handled_by.contains(&serde_json::json!("audit")), "{handled_by:?}");
+}
+
+#[tokio::test]
+async fn test_join_after_failed_node_is_skipped() {
+    let output = run(|id| {
+        // done joins sync and audit, so it only runs when sync succeeds
+        let mut workflow = workflow(id, transformer("sync", FAIL));
+        workflow.nodes.push(tag("audit"));
+        workflow.edges.push(edge("trigger", "audit", None));
+        workflow.edges.push(edge("audit", "done", None));
+        workflow
+    })
+    .await
+    .unwrap();
+
+    let outputs = match &output.data {
+        serde_json::Value::Array(outputs) => outputs.clone(),
+        single => vec![single.clone()],
+    };
+    let handled_by: Vec<_> = outputs.iter().map(|d| d["handled_by"].clone()).collect();
+    assert!(handled_by.contains(&serde_json::json!("alert")), "{handled_by:?}");
+    assert!(!handled_by.contains(&serde_json::json!("done")), "{handled_by:?}");
+}
+
+#[tokio::test]
+async fn test_error_reports_http_retry_attempts() {
+    let http = node(
+        "sync",
+        NodeType::HttpRequest {
+            url: "http://127.0.0.1:1/contacts".to_string(),
+            method: HttpMethod::Post,
+            timeout_seconds: 5,
+            failure_action: FailureAction::Retry,
+            retry_config: RetryConfig { max_attempts: 2, initial_delay_ms: 1, max_delay_ms: 1, backoff_multiplier: 1.0, ..RetryConfig::default() },
+            headers: HashMap::new(),
+            loop_config: None,
+            body: None,
+            query_params: HashMap::new(),
+            response_mapping: None,
+            response_format: Default::default(),
+            success_status_codes: Vec::new(),
+            auth: None,
+            forward_headers: Default::default(),
+        },
+    );
+    let output = run(|id| workflow(id, http)).await.unwrap();
+    assert_eq!(output.data["handled_by"], "alert");
+    assert_eq!(output.data["error"]["attempts"], 2);
+}
+
+/// trigger -> sync (ForEach) -> upsert -> aggregate -> done, with sync -error-> alert
+fn foreach_workflow(workflow_id: &str, selector: &str) -> Workflow {
+    let mut workflow = workflow(
+        workflow_id,
+        node("sync", NodeType::ForEach { selector: selector.to_string(), concurrency: None, limits: None }),
+    );
+    workflow.nodes.push(tag("upsert"));
+    workflow.nodes.push(node("aggregate", NodeType::Aggregate {}));
+    workflow.edges.retain(|e| e.to_node_id != "done");
+    workflow.edges.push(edge("sync", "upsert", None));
+    workflow.edges.push(edge("upsert", "aggregate", None));
+    workflow.edges.push(edge("aggregate", "done", None));
+    WorkflowValidator::validate_workflow("wf", "trigger", &workflow.nodes, &workflow.edges).unwrap();
+    workflow
+}
+
+#[tokio::test]
+async fn test_failed_foreach_follows_error_edge() {
+    // A scalar and an object both fail the node instead of running no items
+    for selector in ["$.data.email", "$.data"] {
+        let output = run(|id| foreach_workflow(id, selector)).await.unwrap();
+        assert_eq!(output.data["handled_by"], "alert", "{selector}");
+        assert!(output.data["error"]["message"].as_str().unwrap().contains("must return an array"), "{selector}");
+    }
+}
+
+#[test]
+fn test_validator_checks_error_edges() {
+    let valid = workflow("wf", transformer("sync", FAIL));
+    WorkflowValidator::validate_workflow("wf", "trigger", &valid.nodes, &valid.edges).unwrap();
+
+    let validate = |workflow: &Workflow| {
+        WorkflowValidator::validate_workflow("wf", "trigger", &workflow.nodes, &workflow.edges)
+            .unwrap_err()
+            .to_string()
+    };
+
+    let mut from_trigger = workflow("wf", transformer("sync", FAIL));
+    from_trigger.edges.push(edge("trigger", "alert", Some(ERROR_HANDLE)));
+    assert!(validate(&from_trigger).contains("cannot have an error edge"));
+
+    // Switch nodes accept an error edge but cannot name a case after it
+    let switch = |cases: Vec<&str>| {
+        let mut workflow = workflow(
+            "wf",
+            node(
+                "sync",
+                NodeType::Switch {
+                    script: "function route(event) { return 'done'; }".to_string(),
+                    cases: cases.into_iter().map(str::to_string).collect(),
+                    limits: None,
+                },
+            ),
+        );
+        workflow.edges[1].source_handle_id = Some("done".to_string());
+        workflow
+    };
+    let valid = switch(vec!["done"]);
+    WorkflowValidator::validate_workflow("wf", "trigger", &valid.nodes, &valid.edges).unwrap();
+    assert!(validate(&switch(vec!["done", "error"])).contains("cannot declare a case named 'error'"));
+}
@@ -0,0 +1,175 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde_json::{json, Value};
+use swisspipe::api;
+use swisspipe::async_execution::ExecutionService;
+use swisspipe::database::{entities, workflow_executions};
+use uuid::Uuid;
+
+const FAIL: &str = "function transformer(event) { throw new Error('CRM unavailable'); }";
+const PASS: &str = "function transformer(event) { return event; }";
+
+async fn setup() -> (TestServer, swisspipe::AppState) {
+    let state = common::test_state().await;
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state)
+}
+
+/// A trigger followed by one transformer, optionally reporting failures to `error_workflow_id`
+async fn create_workflow(server: &TestServer, name: &str, script: &str, error_workflow_id: Option<&str>) -> String {
+    let trigger_id = Uuid::new_v4().to_string();
+    let node_id = Uuid::new_v4().to_string();
+    let response = server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({
+            "name": name,
+            "error_workflow_id": error_workflow_id,
+            "nodes": [
+                { "id": trigger_id, "name": "Trigger", "node_type": { "Trigger": { "methods": ["Post"] } } },
+                { "id": node_id, "name": "Sync contact", "node_type": { "Transformer": { "script": script, "limits": null } } }
+            ],
+            "edges": [{ "from_node_id": trigger_id, "to_node_id": node_id }]
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+/// Trigger a workflow and wait for it to fail, returning the execution ID
+async fn run_failing(server: &TestServer, workflow_id: &str) -> String {
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .json(&json!({ "email": "user@example.com" }))
+        .await;
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    response.header("x-execution-id").to_str().unwrap().to_string()
+}
+
+/// The error workflow execution queued for a failed execution, once it has finished
+async fn error_execution(state: &swisspipe::AppState, failed_execution_id: &str) -> Option<workflow_executions::Model> {
+    let service = ExecutionService::new(state.db.clone());
+    for _ in 0..50 {
+        let queued = workflow_executions::Entity::find()
+            .filter(workflow_executions::Column::FailedExecutionId.eq(failed_execution_id))
+            .one(&*state.db)
+            .await
+            .unwrap();
+        if let Some(execution) = queued {
+            return service.wait_for_execution(&execution.id, Duration::from_secs(10)).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    None
+}
+
+#[tokio::test]
+async fn test_failed_execution_starts_error_workflow() {
+    let (server, state) = setup().await;
+    let alerts_id = create_workflow(&server, "Alerts", PASS, None).await;
+    let sync_id = create_workflow(&server, "CRM sync", FAIL, Some(&alerts_id)).await;
+
+    let workflow: Value = server.get(&format!("/api/admin/v1/workflows/{sync_id}")).await.json();
+    assert_eq!(workflow["error_workflow_id"], alerts_id);
+
+    state.worker_pool.start().await.unwrap();
+    let failed_id = run_failing(&server, &sync_id).await;
+
+    let alert = error_execution(&state, &failed_id).await.expect("error workflow was not started");
+    assert_eq!(alert.workflow_id, alerts_id);
+    assert_eq!(alert.status, "completed");
+
+    let report: Value = serde_json::from_str(alert.output_data.as_deref().unwrap()).unwrap();
+    assert_eq!(report["execution_id"], failed_id);
+    assert_eq!(report["workflow_id"], sync_id);
+    assert_eq!(report["workflow_name"], "CRM sync");
+    assert_eq!(report["node_name"], "Sync contact");
+    assert!(report["error"].as_str().unwrap().contains("CRM unavailable"), "{report}");
+
+    let details: Value = server.get(&format!("/api/admin/v1/executions/{}", alert.id)).await.json();
+    assert_eq!(details["failed_execution_id"], failed_id);
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_failing_error_workflow_does_not_start_another() {
+    let (server, state) = setup().await;
+    let last_resort_id = create_workflow(&server, "Last resort", PASS, None).await;
+    let alerts_id = create_workflow(&server, "Alerts", FAIL, Some(&last_resort_id)).await;
+    let sync_id = create_workflow(&server, "CRM sync", FAIL, Some(&alerts_id)).await;
+    state.worker_pool.start().await.unwrap();
+
+    let failed_id = run_failing(&server, &sync_id).await;
+    let alert = error_execution(&state, &failed_id).await.expect("error workflow was not started");
+    assert_eq!(alert.status, "failed");
+
+    let last_resort_runs = workflow_executions::Entity::find()
+        .filter(workflow_executions::Column::WorkflowId.eq(&last_resort_id))
+        .all(&*state.db)
+        .await
+        .unwrap();
+    assert!(last_resort_runs.is_empty());
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_failure_is_reported_once_and_not_to_disabled_error_workflow() {
+    let (server, state) = setup().await;
+    let alerts_id = create_workflow(&server, "Alerts", PASS, None).await;
+    let sync_id = create_workflow(&server, "CRM sync", FAIL, Some(&alerts_id)).await;
+    let service = ExecutionService::new(state.db.clone());
+    state.worker_pool.start().await.unwrap();
+
+    let failed_id = run_failing(&server, &sync_id).await;
+    error_execution(&state, &failed_id).await.expect("error workflow was not started");
+    assert_eq!(service.start_error_workflow(&failed_id).await.unwrap(), None);
+
+    let alerts = entities::Entity::find_by_id(&alerts_id).one(&*state.db).await.unwrap().unwrap();
+    let mut alerts: entities::ActiveModel = alerts.into();
+    alerts.enabled = Set(false);
+    alerts.update(&*state.db).await.unwrap();
+
+    let failed_id = run_failing(&server, &sync_id).await;
+    assert_eq!(service.start_error_workflow(&failed_id).await.unwrap(), None);
+    let alert_runs = workflow_executions::Entity::find()
+        .filter(workflow_executions::Column::WorkflowId.eq(&alerts_id))
+        .all(&*state.db)
+        .await
+        .unwrap();
+    assert_eq!(alert_runs.len(), 1);
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_error_workflow_is_validated_on_save() {
+    let (server, _state) = setup().await;
+
+    let trigger_id = Uuid::new_v4().to_string();
+    let response = server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({
+            "name": "CRM sync",
+            "error_workflow_id": Uuid::new_v4().to_string(),
+            "nodes": [{ "id": trigger_id, "name": "Trigger", "node_type": { "Trigger": { "methods": ["Post"] } } }],
+            "edges": []
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.json::<Value>()["details"].as_str().unwrap().contains("not found"));
+
+    // A workflow cannot report its own failures to itself
+    let sync_id = create_workflow(&server, "CRM sync", PASS, None).await;
+    let response = server
+        .put(&format!("/api/admin/v1/workflows/{sync_id}"))
+        .json(&json!({ "name": "CRM sync", "error_workflow_id": sync_id, "nodes": [], "edges": [] }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use serde_json::{json, Value};
+use swisspipe::api;
+use swisspipe::database::workflow_executions;
+use uuid::Uuid;
+
+/// Keeps a worker busy for a while, so that jobs pile up behind it
+const SLOW_SCRIPT: &str =
+    "function transformer(event) { const end = Date.now() + 150; while (Date.now() < end) {} return event; }";
+
+async fn setup() -> (TestServer, swisspipe::AppState) {
+    let state = common::test_state().await;
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state)
+}
+
+/// Create a workflow running one slow transformer, with the given scheduling settings
+async fn create_workflow(server: &TestServer, name: &str, settings: Value) -> String {
+    let trigger_id = Uuid::new_v4().to_string();
+    let transformer_id = Uuid::new_v4().to_string();
+    let mut request = json!({
+        "name": name,
+        "nodes": [
+            { "id": trigger_id, "name": "Trigger", "node_type": { "Trigger": { "methods": ["Post"] } } },
+            { "id": transformer_id, "name": "Work", "node_type": { "Transformer": { "script": SLOW_SCRIPT, "limits": null } } }
+        ],
+        "edges": [{ "from_node_id": trigger_id, "to_node_id": transformer_id }]
+    });
+    request.as_object_mut().unwrap().extend(settings.as_object().unwrap().clone());
+
+    let response = server.post("/api/admin/v1/workflows").json(&request).await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+async fn trigger(server: &TestServer, workflow_id: &str, times: usize) {
+    for n in 0..times {
+        server
+            .post(&format!("/api/v1/{workflow_id}/trigger"))
+            .json(&json!({ "n": n }))
+            .await
+            .assert_status(StatusCode::ACCEPTED);
+    }
+}
+
+async fn executions(state: &swisspipe::AppState, workflow_id: &str) -> Vec<workflow_executions::Model> {
+    workflow_executions::Entity::find()
+        .filter(workflow_executions::Column::WorkflowId.eq(workflow_id))
+        .all(&*state.db)
+        .await
+        .unwrap()
+}
+
+async fn completed(state: &swisspipe::AppState, workflow_id: &str) -> usize {
+    executions(state, workflow_id).await.iter().filter(|e| e.status == "completed").count()
+}
+
+async fn wait_for_completed(state: &swisspipe::AppState, workflow_id: &str, count: usize) {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(30);
+    while completed(state, workflow_id).await < count {
+        assert!(tokio::time::Instant::now() < deadline, "executions of {workflow_id} did not complete");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_backlog_on_one_workflow_does_not_hold_up_another() {
+    let (server, state) = setup().await;
+    let busy = create_workflow(&server, "Busy", json!({})).await;
+    let quiet = create_workflow(&server, "Quiet", json!({})).await;
+
+    // The quiet workflow's jobs are queued behind the whole backlog
+    trigger(&server, &busy, 12).await;
+    trigger(&server, &quiet, 2).await;
+    state.worker_pool.start().await.unwrap();
+
+    wait_for_completed(&state, &quiet, 2).await;
+    let busy_completed = completed(&state, &busy).await;
+    assert!(busy_completed <= 6, "quiet workflow waited for the backlog ({busy_completed} of 12 done)");
+
+    wait_for_completed(&state, &busy, 12).await;
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_max_concurrent_executions_leaves_workers_to_other_workflows() {
+    let (server, state) = setup().await;
+    let limited = create_workflow(&server, "Limited", json!({ "max_concurrent_executions": 1 })).await;
+    let other = create_workflow(&server, "Other", json!({})).await;
+
+    trigger(&server, &limited, 3).await;
+    trigger(&server, &other, 4).await;
+    state.worker_pool.start().await.unwrap();
+
+    wait_for_completed(&state, &limited, 3).await;
+    wait_for_completed(&state, &other, 4).await;
+    state.worker_pool.stop().await.unwrap();
+
+    // Executions of the limited workflow ran one after another
+    let mut runs: Vec<(i64, i64)> = executions(&state, &limited)
+        .await
+        .iter()
+        .map(|e| (e.started_at.unwrap(), e.completed_at.unwrap()))
+        .collect();
+    runs.sort();
+    for pair in runs.windows(2) {
+        assert!(pair[1].0 >= pair[0].1, "limited executions overlapped: {runs:?}");
+    }
+
+    // Meanwhile the other workflow used the remaining workers
+    let started_alongside = executions(&state, &other).await.iter().filter(|e| e.started_at.unwrap() < runs[1].0).count();
+    assert!(started_alongside >= 2, "only {started_alongside} other executions ran alongside the first limited one");
+}
+
+#[tokio::test]
+async fn test_scheduling_settings_are_validated_and_returned() {
+    let (server, _state) = setup().await;
+    let workflow_id = create_workflow(&server, "Weighted", json!({ "max_concurrent_executions": 2, "scheduling_weight": 3 })).await;
+
+    let workflow: Value = server.get(&format!("/api/admin/v1/workflows/{workflow_id}")).await.json();
+    assert_eq!(workflow["max_concurrent_executions"], 2);
+    assert_eq!(workflow["scheduling_weight"], 3);
+
+    for (settings, error) in [
+        (json!({ "max_concurrent_executions": 0 }), "max_concurrent_executions must be greater than 0"),
+        (json!({ "scheduling_weight": -1 }), "scheduling_weight must be greater than 0"),
+    ] {
+        let mut request = json!({
+            "name": "Weighted",
+            "nodes": [{ "id": Uuid::new_v4().to_string(), "name": "Trigger", "node_type": { "Trigger": { "methods": ["Post"] } } }],
+            "edges": []
+        });
+        request.as_object_mut().unwrap().extend(settings.as_object().unwrap().clone());
+
+        let response = server.post("/api/admin/v1/workflows").json(&request).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let details = response.json::<Value>()["details"].as_str().unwrap().to_string();
+        assert!(details.contains(error), "{details}");
+    }
+}
@@ -0,0 +1,256 @@
+mod common;
+
+use std::collections::HashMap;
+
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
+use swisspipe::async_execution::ExecutionService;
+use swisspipe::database::workflow_execution_steps;
+use swisspipe::utils::json_path;
+use swisspipe::workflow::{
+    models::{DelayUnit, Edge, HttpMethod, Node, NodeType, Workflow, WorkflowEvent},
+    validation::WorkflowValidator,
+};
+
+fn node(id: &str, node_type: NodeType) -> Node {
+    Node {
+        id: id.to_string(),
+        workflow_id: "wf".to_string(),
+        name: id.to_string(),
+        node_type,
+        input_merge_strategy: None,
+        allowed_host_functions: Vec::new(),
+    }
+}
+
+fn edge(from: &str, to: &str) -> Edge {
+    Edge {
+        id: format!("{from}-{to}"),
+        workflow_id: "wf".to_string(),
+        from_node_id: from.to_string(),
+        to_node_id: to.to_string(),
+        condition_result: None,
+        source_handle_id: None,
+    }
+}
+
+fn transformer(id: &str, script: &str) -> Node {
+    node(id, NodeType::Transformer { script: script.to_string(), limits: None })
+}
+
+fn foreach(selector: &str, concurrency: Option<u32>) -> Node {
+    node(
+        "foreach",
+        NodeType::ForEach { selector: selector.to_string(), concurrency, limits: None },
+    )
+}
+
+fn workflow(workflow_id: &str, nodes: Vec<Node>, edges: Vec<Edge>) -> Workflow {
+    Workflow {
+        id: workflow_id.to_string(),
+        name: "Order fan-out".to_string(),
+        description: None,
+        start_node_id: Some("trigger".to_string()),
+        enabled: true,
+        allowed_host_functions: Vec::new(),
+        nodes,
+        edges,
+    }
+}
+
+/// trigger -> foreach -> double -> aggregate -> summarize
+fn doubling_workflow(workflow_id: &str, selector: &str) -> Workflow {
+    workflow(
+        workflow_id,
+        vec![
+            node("trigger", NodeType::Trigger { methods: vec![HttpMethod::Post], verification: None, api_key: None, ip_filter: None, rate_limit: None }),
+            foreach(selector, Some(2)),
+            transformer("double", "function transformer(event) { event.data = { sku: event.data.sku, qty: event.data.qty * 2 }; return event; }"),
+            node("aggregate", NodeType::Aggregate {}),
+            transformer("summarize", "function transformer(event) { event.data = { lines: event.data }; return event; }"),
+        ],
+        vec![
+            edge("trigger", "foreach"),
+            edge("foreach", "double"),
+            edge("double", "aggregate"),
+            edge("aggregate", "summarize"),
+        ],
+    )
+}
+
+async fn run(
+    build: impl FnOnce(&str) -> Workflow,
+    data: serde_json::Value,
+) -> (swisspipe::AppState, String, swisspipe::workflow::errors::Result<WorkflowEvent>) {
+    let state = common::test_state().await;
+    let workflow_id = common::create_trigger_workflow(&state.db).await;
+    let execution_id = ExecutionService::new(state.db.clone())
+        .create_execution(workflow_id.clone(), serde_json::json!({}), HashMap::new(), None)
+        .await
+        .unwrap();
+
+    let workflow = build(&workflow_id);
+    common::insert_nodes(&state.db, &workflow).await;
+
+    let event = WorkflowEvent { data, ..WorkflowEvent::default() };
+    let result = state.engine.execute_workflow(&workflow, event, &execution_id).await;
+    (state, execution_id, result)
+}
+
+fn orders() -> serde_json::Value {
+    serde_json::json!({
+        "orders": [
+            { "sku": "a", "qty": 1 },
+            { "sku": "b", "qty": 2 },
+            { "sku": "c", "qty": 3 }
+        ]
+    })
+}
+
+#[tokio::test]
+async fn test_foreach_aggregates_results_in_item_order() {
+    let (state, execution_id, result) = run(|id| doubling_workflow(id, "$.data.orders"), orders()).await;
+
+    let output = result.unwrap();
+    assert_eq!(
+        output.data["lines"],
+        serde_json::json!([
+            { "sku": "a", "qty": 2 },
+            { "sku": "b", "qty": 4 },
+            { "sku": "c", "qty": 6 }
+        ])
+    );
+
+    // Each body step records the item it ran for
+    let steps = workflow_execution_steps::Entity::find()
+        .filter(workflow_execution_steps::Column::ExecutionId.eq(&execution_id))
+        .filter(workflow_execution_steps::Column::NodeId.eq("double"))
+        .all(&*state.db)
+        .await
+        .unwrap();
+    let mut item_indexes: Vec<_> = steps.iter().map(|s| s.item_index).collect();
+    item_indexes.sort();
+    assert_eq!(item_indexes, vec![Some(0), Some(1), Some(2)]);
+}
+
+#[tokio::test]
+async fn test_foreach_with_javascript_selector_and_empty_array() {
+    let select = "function select(event) { return event.data.orders.filter(o => o.qty > 1); }";
+    let (_state, _execution_id, result) = run(|id| doubling_workflow(id, select), orders()).await;
+    let output = result.unwrap();
+    assert_eq!(output.data["lines"].as_array().unwrap().len(), 2);
+    assert_eq!(output.data["lines"][0]["sku"], "b");
+
+    let (_state, _execution_id, result) =
+        run(|id| doubling_workflow(id, "$.data.orders"), serde_json::json!({ "orders": [] })).await;
+    assert_eq!(result.unwrap().data["lines"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_foreach_fails_on_non_array_and_failing_item() {
+    let (_state, _execution_id, result) =
+        run(|id| doubling_workflow(id, "$.data.orders"), serde_json::json!({ "orders": "none" })).await;
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("must return an array, got string"), "{err}");
+
+    let (_state, _execution_id, result) = run(|id| doubling_workflow(id, "$.data"), orders()).await;
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("must return an array, got object"), "{err}");
+
+    let failing = |id: &str| {
+        let mut workflow = doubling_workflow(id, "$.data.orders");
+        workflow.nodes[2] = transformer(
+            "double",
+            "function transformer(event) { if (event.data.sku === 'b') { throw new Error('bad sku'); } return event; }",
+        );
+        workflow
+    };
+    let (_state, _execution_id, result) = run(failing, orders()).await;
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("ForEach item 1 failed"), "{err}");
+}
+
+#[tokio::test]
+async fn test_nested_foreach() {
+    let build = |id: &str| {
+        workflow(
+            id,
+            vec![
+                node("trigger", NodeType::Trigger { methods: vec![HttpMethod::Post], verification: None, api_key: None, ip_filter: None, rate_limit: None }),
+                foreach("$.data.groups", None),
+                node(
+                    "inner",
+                    NodeType::ForEach { selector: "$.data".to_string(), concurrency: None, limits: None },
+                ),
+                transformer("square", "function transformer(event) { event.data = event.data * event.data; return event; }"),
+                node("inner_aggregate", NodeType::Aggregate {}),
+                node("aggregate", NodeType::Aggregate {}),
+            ],
+            vec![
+                edge("trigger", "foreach"),
+                edge("foreach", "inner"),
+                edge("inner", "square"),
+                edge("square", "inner_aggregate"),
+                edge("inner_aggregate", "aggregate"),
+            ],
+        )
+    };
+
+    let (_state, _execution_id, result) =
+        run(build, serde_json::json!({ "groups": [[1, 2], [3]] })).await;
+    assert_eq!(result.unwrap().data, serde_json::json!([[1, 4], [9]]));
+}
+
+#[test]
+fn test_validator_checks_foreach_bodies() {
+    let valid = doubling_workflow("wf", "$.data.orders");
+    WorkflowValidator::validate_workflow("wf", "trigger", &valid.nodes, &valid.edges).unwrap();
+
+    let validate = |workflow: &Workflow| {
+        WorkflowValidator::validate_workflow("wf", "trigger", &workflow.nodes, &workflow.edges)
+            .unwrap_err()
+            .to_string()
+    };
+
+    // ForEach without an Aggregate
+    let mut workflow = doubling_workflow("wf", "$.data.orders");
+    workflow.nodes.retain(|n| n.id != "aggregate");
+    workflow.edges.retain(|e| e.from_node_id != "aggregate" && e.to_node_id != "aggregate");
+    workflow.edges.push(edge("double", "summarize"));
+    assert!(validate(&workflow).contains("must lead to an Aggregate"));
+
+    // Aggregate without a ForEach
+    let mut workflow = doubling_workflow("wf", "$.data.orders");
+    workflow.nodes[1] = transformer("foreach", "function transformer(event) { return event; }");
+    assert!(validate(&workflow).contains("not reached from a ForEach"));
+
+    // Edge into the body from outside the ForEach
+    let mut workflow = doubling_workflow("wf", "$.data.orders");
+    workflow.edges.push(edge("trigger", "double"));
+    assert!(validate(&workflow).contains("must come from inside it"));
+
+    // Delays cannot run per item
+    let mut workflow = doubling_workflow("wf", "$.data.orders");
+    workflow.nodes[2] = node(
+        "double",
+        NodeType::Delay { duration: 1, unit: DelayUnit::Seconds },
+    );
+    assert!(validate(&workflow).contains("cannot run inside ForEach"));
+
+    let mut workflow = doubling_workflow("wf", "$.data.orders");
+    workflow.nodes[1] = foreach("$.data.orders", Some(0));
+    assert!(validate(&workflow).contains("concurrency must be at least 1"));
+}
+
+#[test]
+fn test_json_path_resolve() {
+    let value = serde_json::json!({ "data": { "items": [{ "id": 1 }, { "id": 2 }], "odd key": true } });
+
+    assert_eq!(json_path::resolve(&value, "$").unwrap(), &value);
+    assert_eq!(json_path::resolve(&value, "$.data.items[1].id").unwrap(), 2);
+    assert_eq!(json_path::resolve(&value, "$['data'][\"odd key\"]").unwrap(), true);
+    assert!(json_path::resolve(&value, "$.data.missing.deeper").unwrap().is_null());
+
+    assert!(json_path::resolve(&value, "data.items").is_err());
+    assert!(json_path::resolve(&value, "$.data.items[x]").is_err());
+    assert!(json_path::resolve(&value, "$.data.items[0").is_err());
+}
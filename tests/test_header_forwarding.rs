@@ -0,0 +1,175 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::{extract::State, http::{HeaderMap, StatusCode}, routing::post, Json, Router};
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use swisspipe::api;
+use uuid::Uuid;
+
+type Received = Arc<Mutex<Vec<HeaderMap>>>;
+
+/// Serve an API that records the headers of every request
+async fn start_api() -> (String, Received) {
+    let received = Received::default();
+    let app = Router::new()
+        .route(
+            "/contacts",
+            post(|State(received): State<Received>, headers: HeaderMap| async move {
+                received.lock().unwrap().push(headers);
+                Json(json!({ "ok": true }))
+            }),
+        )
+        .with_state(received.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/contacts", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, received)
+}
+
+async fn setup() -> (TestServer, swisspipe::AppState) {
+    let state = common::test_state().await;
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state)
+}
+
+/// Create a workflow posting to `url` with the given node headers and forwarding policy
+async fn create_workflow(server: &TestServer, url: &str, headers: Value, forward_headers: Option<Value>) -> axum_test::TestResponse {
+    let trigger_id = Uuid::new_v4().to_string();
+    let request_id = Uuid::new_v4().to_string();
+    let mut http_request = json!({
+        "url": url,
+        "method": "Post",
+        "timeout_seconds": 5,
+        "failure_action": "Stop",
+        "retry_config": { "max_attempts": 1, "initial_delay_ms": 1, "max_delay_ms": 1, "backoff_multiplier": 1.0 },
+        "headers": headers,
+        "loop_config": null
+    });
+    if let Some(forward_headers) = forward_headers {
+        http_request["forward_headers"] = forward_headers;
+    }
+
+    server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({
+            "name": "CRM sync",
+            "nodes": [
+                { "id": trigger_id, "name": "Trigger", "node_type": { "Trigger": { "methods": ["Post"] } } },
+                { "id": request_id, "name": "Create contact", "node_type": { "HttpRequest": http_request } }
+            ],
+            "edges": [{ "from_node_id": trigger_id, "to_node_id": request_id }]
+        }))
+        .await
+}
+
+/// Trigger the workflow as a caller sending its own credentials, and return what the API received
+async fn run(server: &TestServer, state: &swisspipe::AppState, workflow_id: &str, received: &Received) -> HeaderMap {
+    state.worker_pool.start().await.unwrap();
+    server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .add_header("Authorization", "Bearer caller-token")
+        .add_header("X-Segment-Token", "caller-segment-token")
+        .add_header("X-Request-Id", "req_1")
+        .json(&json!({ "email": "ada@example.com" }))
+        .await
+        .assert_status_ok();
+    state.worker_pool.stop().await.unwrap();
+    received.lock().unwrap().pop().unwrap()
+}
+
+#[tokio::test]
+async fn test_trigger_headers_are_not_forwarded_by_default() {
+    let (server, state) = setup().await;
+    let (url, received) = start_api().await;
+    let response = create_workflow(
+        &server,
+        &url,
+        json!({ "X-Source": "swisspipe", "X-Correlation-Id": "{{ event.headers.[x-request-id] }}" }),
+        None,
+    )
+    .await;
+    response.assert_status(StatusCode::CREATED);
+
+    let headers = run(&server, &state, response.json::<Value>()["id"].as_str().unwrap(), &received).await;
+    assert_eq!(headers["x-source"], "swisspipe");
+    assert_eq!(headers["x-correlation-id"], "req_1");
+    for name in ["authorization", "x-segment-token", "x-request-id"] {
+        assert!(!headers.contains_key(name), "{name} was forwarded");
+    }
+}
+
+#[tokio::test]
+async fn test_allowlisted_headers_are_forwarded() {
+    let (server, state) = setup().await;
+    let (url, received) = start_api().await;
+    let response = create_workflow(
+        &server,
+        &url,
+        json!({ "authorization": "Bearer node-token" }),
+        Some(json!({ "Allow": ["X-REQUEST-ID", "Authorization"] })),
+    )
+    .await;
+    response.assert_status(StatusCode::CREATED);
+
+    let headers = run(&server, &state, response.json::<Value>()["id"].as_str().unwrap(), &received).await;
+    assert_eq!(headers["x-request-id"], "req_1");
+    // The node's own header wins over the forwarded one
+    assert_eq!(headers["authorization"], "Bearer node-token");
+    assert!(!headers.contains_key("x-segment-token"));
+}
+
+#[tokio::test]
+async fn test_all_headers_are_forwarded_when_configured() {
+    let (server, state) = setup().await;
+    let (url, received) = start_api().await;
+    let response = create_workflow(&server, &url, json!({ "Authorization": "Bearer node-token" }), Some(json!("All"))).await;
+    response.assert_status(StatusCode::CREATED);
+
+    let headers = run(&server, &state, response.json::<Value>()["id"].as_str().unwrap(), &received).await;
+    assert_eq!(headers["x-segment-token"], "caller-segment-token");
+    assert_eq!(headers["x-request-id"], "req_1");
+    assert_eq!(headers.get_all("authorization").iter().collect::<Vec<_>>(), ["Bearer node-token"]);
+}
+
+#[tokio::test]
+async fn test_invalid_forwarded_header_is_rejected() {
+    let (server, _state) = setup().await;
+    let response = create_workflow(&server, "https://crm.example.com", json!({}), Some(json!({ "Allow": ["X Request Id"] }))).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let details = response.json::<Value>()["details"].as_str().unwrap().to_string();
+    assert!(details.contains("forwards an invalid header 'X Request Id'"), "{details}");
+}
+
+#[tokio::test]
+async fn test_forwarding_is_rejected_for_looping_nodes() {
+    let (server, _state) = setup().await;
+    let trigger_id = Uuid::new_v4().to_string();
+    let request_id = Uuid::new_v4().to_string();
+    let response = server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({
+            "name": "CRM poll",
+            "nodes": [
+                { "id": trigger_id, "name": "Trigger", "node_type": { "Trigger": { "methods": ["Post"] } } },
+                { "id": request_id, "name": "Poll contact", "node_type": { "HttpRequest": {
+                    "url": "https://crm.example.com/contacts",
+                    "method": "Post",
+                    "timeout_seconds": 5,
+                    "failure_action": "Stop",
+                    "retry_config": { "max_attempts": 1, "initial_delay_ms": 1, "max_delay_ms": 1, "backoff_multiplier": 1.0 },
+                    "headers": {},
+                    "forward_headers": "All",
+                    "loop_config": { "max_iterations": 2, "interval_seconds": 1, "backoff_strategy": { "Fixed": 1 }, "termination_condition": null }
+                } } }
+            ],
+            "edges": [{ "from_node_id": trigger_id, "to_node_id": request_id }]
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let details = response.json::<Value>()["details"].as_str().unwrap().to_string();
+    assert!(details.contains("forward_headers together with loop_config"), "{details}");
+}
@@ -0,0 +1,225 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::{body::Bytes, extract::State, http::{HeaderMap, StatusCode, Uri}, routing::{any, post}, Json, Router};
+use axum_test::TestServer;
+use chrono::TimeZone;
+use serde_json::{json, Value};
+use swisspipe::api;
+use swisspipe::utils::http_auth::{self, AwsCredentials};
+use swisspipe::variables::CreateVariableRequest;
+use uuid::Uuid;
+
+/// A request received by the stand-in token endpoint or API
+#[derive(Debug, Clone)]
+struct Received {
+    query: Option<String>,
+    authorization: Option<String>,
+    body: String,
+}
+
+type Log = Arc<Mutex<Vec<Received>>>;
+
+fn record(log: &Log, uri: &Uri, headers: &HeaderMap, body: &Bytes) {
+    log.lock().unwrap().push(Received {
+        query: uri.query().map(str::to_string),
+        authorization: headers.get("authorization").map(|v| v.to_str().unwrap().to_string()),
+        body: String::from_utf8(body.to_vec()).unwrap(),
+    });
+}
+
+/// Serve `/token`, issuing `tok_<n>` tokens, and `/contacts`; returns the base URL and both logs
+async fn start_api() -> (String, Log, Log) {
+    let tokens: Log = Arc::default();
+    let requests: Log = Arc::default();
+    let app = Router::new()
+        .route(
+            "/token",
+            post(|State((tokens, _)): State<(Log, Log)>, uri: Uri, headers: HeaderMap, body: Bytes| async move {
+                record(&tokens, &uri, &headers, &body);
+                let issued = tokens.lock().unwrap().len();
+                Json(json!({ "access_token": format!("tok_{issued}"), "token_type": "Bearer", "expires_in": 3600 }))
+            }),
+        )
+        .route(
+            "/contacts",
+            any(|State((_, requests)): State<(Log, Log)>, uri: Uri, headers: HeaderMap, body: Bytes| async move {
+                record(&requests, &uri, &headers, &body);
+                Json(json!({ "ok": true }))
+            }),
+        )
+        .with_state((tokens.clone(), requests.clone()));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, tokens, requests)
+}
+
+async fn setup() -> (TestServer, swisspipe::AppState) {
+    let state = common::test_state().await;
+    for (name, value) in [("CRM_CLIENT_ID", "client"), ("CRM_CLIENT_SECRET", "s3cret"), ("CRM_USER", "ada"), ("CRM_KEY", "key_123")] {
+        state
+            .variable_service
+            .create_variable(CreateVariableRequest {
+                name: name.to_string(),
+                value_type: "secret".to_string(),
+                value: value.to_string(),
+                description: None,
+            })
+            .await
+            .unwrap();
+    }
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state)
+}
+
+/// Create a workflow posting to `url` with the given auth and node settings
+async fn create_workflow(server: &TestServer, url: &str, auth: Value, settings: Value) -> axum_test::TestResponse {
+    let trigger_id = Uuid::new_v4().to_string();
+    let request_id = Uuid::new_v4().to_string();
+    let mut http_request = json!({
+        "url": url,
+        "method": "Post",
+        "timeout_seconds": 5,
+        "failure_action": "Stop",
+        "retry_config": { "max_attempts": 1, "initial_delay_ms": 1, "max_delay_ms": 1, "backoff_multiplier": 1.0 },
+        "headers": {},
+        "loop_config": null,
+        "auth": auth
+    });
+    http_request.as_object_mut().unwrap().extend(settings.as_object().unwrap().clone());
+
+    server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({
+            "name": "CRM sync",
+            "nodes": [
+                { "id": trigger_id, "name": "Trigger", "node_type": { "Trigger": { "methods": ["Post"] } } },
+                { "id": request_id, "name": "Create contact", "node_type": { "HttpRequest": http_request } }
+            ],
+            "edges": [{ "from_node_id": trigger_id, "to_node_id": request_id }]
+        }))
+        .await
+}
+
+async fn run(server: &TestServer, workflow_id: &str) {
+    server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .json(&json!({ "email": "ada@example.com" }))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_oauth2_token_is_shared_across_executions() {
+    let (server, state) = setup().await;
+    let (base, tokens, requests) = start_api().await;
+    let auth = json!({ "OAuth2ClientCredentials": {
+        "token_url": format!("{base}/token"),
+        "client_id_variable": "CRM_CLIENT_ID",
+        "client_secret_variable": "CRM_CLIENT_SECRET",
+        "scopes": ["contacts.read", "contacts.write"]
+    }});
+    let response = create_workflow(&server, &format!("{base}/contacts"), auth, json!({})).await;
+    response.assert_status(StatusCode::CREATED);
+    let workflow_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    state.worker_pool.start().await.unwrap();
+
+    for _ in 0..3 {
+        run(&server, &workflow_id).await;
+    }
+
+    let tokens = tokens.lock().unwrap().clone();
+    assert_eq!(tokens.len(), 1, "{tokens:?}");
+    assert_eq!(tokens[0].authorization.as_deref(), Some("Basic Y2xpZW50OnMzY3JldA=="));
+    assert_eq!(tokens[0].body, "grant_type=client_credentials&scope=contacts.read+contacts.write");
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 3);
+    assert!(requests.iter().all(|r| r.authorization.as_deref() == Some("Bearer tok_1")), "{requests:?}");
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_basic_and_api_key_auth() {
+    let (server, state) = setup().await;
+    let (base, _, requests) = start_api().await;
+    let url = format!("{base}/contacts");
+
+    // The configured credentials replace a hand-written Authorization header
+    let basic = create_workflow(
+        &server,
+        &url,
+        json!({ "Basic": { "username_variable": "CRM_USER", "password_variable": "CRM_CLIENT_SECRET" } }),
+        json!({ "headers": { "Authorization": "Bearer stale" } }),
+    )
+    .await;
+    basic.assert_status(StatusCode::CREATED);
+    let query_key = create_workflow(&server, &url, json!({ "ApiKey": { "key_variable": "CRM_KEY", "query_param": "key" } }), json!({})).await;
+    query_key.assert_status(StatusCode::CREATED);
+    state.worker_pool.start().await.unwrap();
+
+    run(&server, basic.json::<Value>()["id"].as_str().unwrap()).await;
+    run(&server, query_key.json::<Value>()["id"].as_str().unwrap()).await;
+
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests[0].authorization.as_deref(), Some("Basic YWRhOnMzY3JldA=="));
+    assert_eq!(requests[1].query.as_deref(), Some("key=key_123"));
+    assert_eq!(requests[1].authorization, None);
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_invalid_auth_is_rejected() {
+    let (server, _state) = setup().await;
+    let url = "https://crm.example.com/contacts";
+    let loop_config = json!({
+        "loop_config": { "max_iterations": 2, "interval_seconds": 1, "backoff_strategy": { "Fixed": 1 }, "termination_condition": null }
+    });
+
+    for (auth, settings, error) in [
+        (json!({ "Bearer": { "token_variable": "CRM_KEY" } }), loop_config, "auth or forward_headers together with loop_config"),
+        (json!({ "Bearer": { "token_variable": " " } }), json!({}), "auth requires every credential variable to be named"),
+        (json!({ "ApiKey": { "key_variable": "CRM_KEY", "header": "X API Key" } }), json!({}), "auth has an invalid header 'X API Key'"),
+        (
+            json!({ "OAuth2ClientCredentials": { "token_url": "ftp://auth", "client_id_variable": "A", "client_secret_variable": "B" } }),
+            json!({}),
+            "auth has an invalid token_url 'ftp://auth'",
+        ),
+    ] {
+        let response = create_workflow(&server, url, auth, settings).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let details = response.json::<Value>()["details"].as_str().unwrap().to_string();
+        assert!(details.contains(error), "{details}");
+    }
+}
+
+/// The GET example from the AWS Signature Version 4 documentation
+#[test]
+fn test_aws_sigv4_signature() {
+    let mut request = reqwest::Client::new()
+        .get("https://iam.amazonaws.com/?Action=ListUsers&Version=2010-05-08")
+        .header("content-type", "application/x-www-form-urlencoded; charset=utf-8")
+        .build()
+        .unwrap();
+    let credentials = AwsCredentials {
+        access_key_id: "AKIDEXAMPLE".to_string(),
+        secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+        session_token: None,
+        region: "us-east-1".to_string(),
+        service: "iam".to_string(),
+    };
+    http_auth::sign_aws_v4(&mut request, &credentials, chrono::Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap()).unwrap();
+
+    assert_eq!(request.headers()["x-amz-date"], "20150830T123600Z");
+    assert_eq!(
+        request.headers()["authorization"],
+        "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, \
+         SignedHeaders=content-type;host;x-amz-date, \
+         Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+    );
+}
@@ -1,6 +1,6 @@
 use swisspipe::database::establish_connection;
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use uuid::Uuid;
@@ -116,6 +116,8 @@ async fn test_http_loop_scheduler_blocking_behavior() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -222,6 +224,8 @@ async fn test_http_loop_data_preservation() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -311,6 +315,8 @@ async fn test_http_loop_termination_condition() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -413,6 +419,8 @@ async fn test_concurrent_http_loops() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -422,6 +430,8 @@ async fn test_concurrent_http_loops() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -0,0 +1,239 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::{body::Bytes, extract::State, http::{HeaderMap, StatusCode, Uri}, routing::any, Json, Router};
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use swisspipe::api;
+use swisspipe::utils::json_path;
+use uuid::Uuid;
+
+/// A request received by the stand-in third-party API
+#[derive(Debug, Clone)]
+struct Received {
+    query: Option<String>,
+    content_type: Option<String>,
+    body: String,
+}
+
+/// Serve an API that records every request and answers `{"id": "crm_1", "status": "created"}`
+async fn start_api() -> (String, Arc<Mutex<Vec<Received>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let app = Router::new()
+        .route(
+            "/contacts",
+            any(|State(received): State<Arc<Mutex<Vec<Received>>>>, uri: Uri, headers: HeaderMap, body: Bytes| async move {
+                received.lock().unwrap().push(Received {
+                    query: uri.query().map(str::to_string),
+                    content_type: headers.get("content-type").map(|v| v.to_str().unwrap().to_string()),
+                    body: String::from_utf8(body.to_vec()).unwrap(),
+                });
+                Json(json!({ "id": "crm_1", "status": "created" }))
+            }),
+        )
+        .route("/missing", any(|| async { (StatusCode::NOT_FOUND, [("x-request-id", "req_1")], "Not found") }))
+        .route("/logo", any(|| async { ([("content-type", "image/png")], vec![0x89u8, b'P', b'N', b'G', 0xff]) }))
+        .route("/empty", any(|| async { StatusCode::NO_CONTENT }))
+        .with_state(received.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/contacts", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, received)
+}
+
+async fn setup() -> (TestServer, swisspipe::AppState) {
+    let state = common::test_state().await;
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state)
+}
+
+/// Create a workflow calling the API with an HttpRequest node built from `settings`
+async fn create_workflow(server: &TestServer, url: &str, method: &str, settings: Value) -> axum_test::TestResponse {
+    let trigger_id = Uuid::new_v4().to_string();
+    let request_id = Uuid::new_v4().to_string();
+    let mut http_request = json!({
+        "url": url,
+        "method": method,
+        "timeout_seconds": 5,
+        "failure_action": "Stop",
+        "retry_config": { "max_attempts": 1, "initial_delay_ms": 1, "max_delay_ms": 1, "backoff_multiplier": 1.0 },
+        "headers": {},
+        "loop_config": null
+    });
+    http_request.as_object_mut().unwrap().extend(settings.as_object().unwrap().clone());
+
+    server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({
+            "name": "CRM sync",
+            "nodes": [
+                { "id": trigger_id, "name": "Trigger", "node_type": { "Trigger": { "methods": ["Post"] } } },
+                { "id": request_id, "name": "Create contact", "node_type": { "HttpRequest": http_request } }
+            ],
+            "edges": [{ "from_node_id": trigger_id, "to_node_id": request_id }]
+        }))
+        .await
+}
+
+async fn run(server: &TestServer, workflow_id: &str, input: Value) -> Value {
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .json(&input)
+        .await;
+    response.assert_status_ok();
+    response.json()
+}
+
+#[tokio::test]
+async fn test_json_body_template_and_pointer_mapping() {
+    let (server, state) = setup().await;
+    let (url, received) = start_api().await;
+    let response = create_workflow(&server, &url, "Post", json!({
+        "body": { "Json": "{\"email\": {{json event.data.user.email}}, \"source\": \"swisspipe\"}" },
+        "query_params": { "tenant": "{{ event.data.tenant }}" },
+        "response_mapping": { "Pointer": "/crm_response" }
+    }))
+    .await;
+    response.assert_status(StatusCode::CREATED);
+    let workflow_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    state.worker_pool.start().await.unwrap();
+
+    let output = run(&server, &workflow_id, json!({ "user": { "email": "ada@example.com" }, "tenant": "acme" })).await;
+
+    let request = received.lock().unwrap()[0].clone();
+    assert_eq!(request.query.as_deref(), Some("tenant=acme"));
+    assert_eq!(request.content_type.as_deref(), Some("application/json"));
+    assert_eq!(serde_json::from_str::<Value>(&request.body).unwrap(), json!({ "email": "ada@example.com", "source": "swisspipe" }));
+
+    // The response is added to the event instead of replacing it
+    assert_eq!(output["user"]["email"], "ada@example.com");
+    assert_eq!(output["crm_response"], json!({ "id": "crm_1", "status": "created" }));
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_form_body_and_template_mapping() {
+    let (server, state) = setup().await;
+    let (url, received) = start_api().await;
+    let response = create_workflow(&server, &url, "Post", json!({
+        "body": { "Form": { "email": "{{ event.data.email }}", "plan": "pro" } },
+        "response_mapping": { "Template": "{\"order_id\": {{ event.data.order_id }}, \"contact_id\": \"{{ response.id }}\"}" }
+    }))
+    .await;
+    response.assert_status(StatusCode::CREATED);
+    let workflow_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    state.worker_pool.start().await.unwrap();
+
+    let output = run(&server, &workflow_id, json!({ "email": "ada@example.com", "order_id": 42 })).await;
+
+    let request = received.lock().unwrap()[0].clone();
+    assert_eq!(request.content_type.as_deref(), Some("application/x-www-form-urlencoded"));
+    assert_eq!(request.body, "email=ada%40example.com&plan=pro");
+    assert_eq!(output, json!({ "order_id": 42, "contact_id": "crm_1" }));
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_get_query_params_replace_event_fields() {
+    let (server, state) = setup().await;
+    let (url, received) = start_api().await;
+    let response = create_workflow(&server, &url, "Get", json!({
+        "query_params": { "email": "{{ event.data.email }}" },
+        "body": { "Raw": { "template": "ignored", "content_type": "text/plain" } }
+    }))
+    .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.json::<Value>()["details"].as_str().unwrap().contains("cannot send a body with a GET request"));
+
+    let response = create_workflow(&server, &url, "Get", json!({ "query_params": { "email": "{{ event.data.email }}" } })).await;
+    response.assert_status(StatusCode::CREATED);
+    let workflow_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    state.worker_pool.start().await.unwrap();
+
+    run(&server, &workflow_id, json!({ "email": "ada@example.com", "password": "hunter2" })).await;
+    assert_eq!(received.lock().unwrap()[0].query.as_deref(), Some("email=ada%40example.com"));
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_accepted_error_status_is_returned_in_full() {
+    let (server, state) = setup().await;
+    let (url, _) = start_api().await;
+    let url = url.replace("/contacts", "/missing");
+    let response = create_workflow(&server, &url, "Get", json!({
+        "response_format": "Full",
+        "success_status_codes": ["2xx", "404"],
+        "response_mapping": { "Pointer": "/lookup" }
+    }))
+    .await;
+    response.assert_status(StatusCode::CREATED);
+    let workflow_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    state.worker_pool.start().await.unwrap();
+
+    let output = run(&server, &workflow_id, json!({ "email": "ada@example.com" })).await;
+
+    assert_eq!(output["email"], "ada@example.com");
+    let lookup = &output["lookup"];
+    assert_eq!(lookup["status"], 404);
+    assert_eq!(lookup["body"], "Not found");
+    assert_eq!(lookup["body_encoding"], "text");
+    assert_eq!(lookup["headers"]["x-request-id"], "req_1");
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_binary_and_empty_bodies() {
+    let (server, state) = setup().await;
+    let (url, _) = start_api().await;
+
+    let response = create_workflow(&server, &url.replace("/contacts", "/logo"), "Get", json!({ "response_mapping": { "Pointer": "/logo" } })).await;
+    response.assert_status(StatusCode::CREATED);
+    let logo_workflow = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let response = create_workflow(&server, &url.replace("/contacts", "/empty"), "Post", json!({})).await;
+    response.assert_status(StatusCode::CREATED);
+    let empty_workflow = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    state.worker_pool.start().await.unwrap();
+
+    let output = run(&server, &logo_workflow, json!({ "id": 1 })).await;
+    assert_eq!(output, json!({ "id": 1, "logo": "iVBOR/8=" }));
+
+    // A 204 keeps the data the node received
+    let output = run(&server, &empty_workflow, json!({ "id": 2 })).await;
+    assert_eq!(output, json!({ "id": 2 }));
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_invalid_success_status_codes_are_rejected() {
+    let (server, _state) = setup().await;
+    let (url, _) = start_api().await;
+    for pattern in ["6xx", "404-400", "abc"] {
+        let response = create_workflow(&server, &url, "Get", json!({ "success_status_codes": [pattern] })).await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let details = response.json::<Value>()["details"].as_str().unwrap().to_string();
+        assert!(details.contains(&format!("has an invalid success status code '{pattern}'")), "{details}");
+    }
+}
+
+#[test]
+fn test_set_pointer() {
+    let mut data = json!({ "order": { "id": 42 }, "tags": ["a"] });
+    json_path::set_pointer(&mut data, "/order/crm/id", json!("crm_1")).unwrap();
+    json_path::set_pointer(&mut data, "/tags/-", json!("b")).unwrap();
+    json_path::set_pointer(&mut data, "/a~1b", json!(true)).unwrap();
+    assert_eq!(data, json!({ "order": { "id": 42, "crm": { "id": "crm_1" } }, "tags": ["a", "b"], "a/b": true }));
+
+    assert!(json_path::set_pointer(&mut data, "/tags/5", json!(1)).is_err());
+    assert!(json_path::set_pointer(&mut data, "crm", json!(1)).is_err());
+
+    json_path::set_pointer(&mut data, "", json!({ "replaced": true })).unwrap();
+    assert_eq!(data, json!({ "replaced": true }));
+}
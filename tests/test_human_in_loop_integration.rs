@@ -8,7 +8,7 @@ use swisspipe::{
 };
 use sea_orm::{ActiveModelTrait, Set, EntityTrait, ColumnTrait, QueryFilter, DatabaseConnection};
 use serde_json::json;
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::{HashMap, HashSet}, sync::Arc};
 use uuid::Uuid;
 use chrono::Utc;
 
@@ -31,9 +31,12 @@ async fn create_test_hil_workflow(db: &DatabaseConnection, workflow_name: &str)
         description: Set(Some("Test HIL workflow".to_string())),
         start_node_id: Set(Some(trigger_node_id.clone())),
         enabled: Set(true),
+        allowed_host_functions: Set(None),
+        error_workflow_id: Set(None),
+        max_concurrent_executions: Set(None),
+        scheduling_weight: Set(None),
         created_at: Set(Utc::now().timestamp_micros()),
         updated_at: Set(Utc::now().timestamp_micros()),
-        ..Default::default()
     };
     workflow.insert(db).await.unwrap();
 
@@ -138,6 +141,8 @@ async fn test_hil_task_creation_and_database_persistence() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -296,7 +301,7 @@ async fn test_hil_task_query_operations() {
             timeout_action: Set(Some("denied".to_string())),
             required_fields: Set(Some(json!(["decision"]))),
             metadata: Set(Some(json!({"query_test": true}))),
-            response_data: Set(response_data.map(|v| v)),
+            response_data: Set(response_data),
             response_received_at: Set(if status == "completed" {
                 Some(Utc::now().timestamp_micros())
             } else {
@@ -0,0 +1,156 @@
+mod common;
+
+use std::sync::Arc;
+
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use swisspipe::api;
+use swisspipe::api::ingestion_limits::IngestionLimiter;
+use uuid::Uuid;
+
+async fn setup() -> (TestServer, swisspipe::AppState) {
+    let state = common::test_state().await;
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state)
+}
+
+async fn create_workflow(server: &TestServer, rate_limit: Value) -> String {
+    let response = server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({
+            "name": "Noisy client",
+            "nodes": [{
+                "id": Uuid::new_v4().to_string(),
+                "name": "Trigger",
+                "node_type": { "Trigger": { "methods": ["Post"], "rate_limit": rate_limit } }
+            }],
+            "edges": []
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+fn retry_after(response: &axum_test::TestResponse) -> u64 {
+    response.header(header::RETRY_AFTER).to_str().unwrap().parse().unwrap()
+}
+
+#[tokio::test]
+async fn test_rate_limit_returns_429_with_retry_after() {
+    let (server, _state) = setup().await;
+    let workflow_id = create_workflow(&server, json!({ "requests_per_minute": 60, "burst": 2 })).await;
+
+    for _ in 0..2 {
+        server.post(&format!("/api/v1/{workflow_id}/trigger")).json(&json!({})).await.assert_status(StatusCode::ACCEPTED);
+    }
+    let response = server.post(&format!("/api/v1/{workflow_id}/trigger")).json(&json!({})).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(retry_after(&response), 1);
+    assert_eq!(response.json::<Value>()["error"], "rate_limited");
+
+    // Other workflows keep their own buckets
+    let other_id = create_workflow(&server, json!({ "requests_per_minute": 60, "burst": 1 })).await;
+    server.post(&format!("/api/v1/{other_id}/trigger")).json(&json!({})).await.assert_status(StatusCode::ACCEPTED);
+
+    let limits: Value = server.get("/api/admin/v1/executions/rate-limits").await.json();
+    let state = &limits["workflows"][&workflow_id];
+    assert_eq!(state["burst"], 2);
+    assert_eq!(state["available_requests"], 0);
+    assert_eq!(state["rate_limited_requests"], 1);
+    assert_eq!(limits["workflows"][&other_id]["rate_limited_requests"], 0);
+}
+
+#[tokio::test]
+async fn test_daily_quota_caps_bulk_and_single_triggers() {
+    let (server, _state) = setup().await;
+    let workflow_id = create_workflow(&server, json!({ "daily_quota": 2 })).await;
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/ndjson"))
+        .bytes("{\"n\": 1}\n{\"n\": 2}\n{\"n\": 3}\n".as_bytes().to_vec().into())
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+    let summary: Value = response.json();
+    assert_eq!(summary["accepted"], 2);
+    assert_eq!(summary["rejected_lines"][0]["line"], 3);
+    assert_eq!(summary["rejected_lines"][0]["error"], "Daily execution quota exceeded");
+
+    let response = server.post(&format!("/api/v1/{workflow_id}/trigger")).json(&json!({})).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.json::<Value>()["error"], "quota_exceeded");
+    assert!((1..=86_400).contains(&retry_after(&response)));
+
+    let limits: Value = server.get("/api/admin/v1/executions/rate-limits").await.json();
+    assert_eq!(limits["workflows"][&workflow_id]["executions_today"], 2);
+    assert_eq!(limits["workflows"][&workflow_id]["quota_exceeded_requests"], 1);
+}
+
+#[tokio::test]
+async fn test_daily_quota_counts_executions_from_before_a_restart() {
+    let (server, state) = setup().await;
+    let workflow_id = create_workflow(&server, json!({ "daily_quota": 2 })).await;
+    server.post(&format!("/api/v1/{workflow_id}/trigger")).json(&json!({})).await.assert_status(StatusCode::ACCEPTED);
+
+    let restarted = swisspipe::AppState { ingestion_limiter: Arc::new(IngestionLimiter::new()), ..state };
+    let server = TestServer::new(api::create_router().with_state(restarted)).unwrap();
+
+    server.post(&format!("/api/v1/{workflow_id}/trigger")).json(&json!({})).await.assert_status(StatusCode::ACCEPTED);
+    server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .json(&json!({}))
+        .await
+        .assert_status(StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_segment_routes_share_the_workflow_limits() {
+    let (server, _state) = setup().await;
+    let workflow_id = create_workflow(&server, json!({ "requests_per_minute": 1, "daily_quota": 3 })).await;
+    let authorization = HeaderValue::from_str(&format!("Bearer {workflow_id}")).unwrap();
+
+    let response = server
+        .post("/api/v1/batch")
+        .add_header(header::AUTHORIZATION, authorization.clone())
+        .json(&json!({ "batch": [{ "type": "track" }, { "type": "track" }, { "type": "track" }, { "type": "track" }] }))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["details"]["failed_events"], json!([3]));
+    assert_eq!(body["details"]["validation_errors"][0], "Event 3: daily execution quota exceeded");
+
+    let response = server
+        .post("/api/v1/track")
+        .add_header(header::AUTHORIZATION, authorization)
+        .json(&json!({ "event": "Signed Up" }))
+        .await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert!(retry_after(&response) > 0);
+}
+
+#[tokio::test]
+async fn test_invalid_rate_limit_is_rejected_on_save() {
+    let (server, _state) = setup().await;
+
+    for (rate_limit, error) in [
+        (json!({}), "set requests_per_minute, daily_quota or both"),
+        (json!({ "daily_quota": 0 }), "daily_quota must be greater than 0"),
+        (json!({ "burst": 5 }), "burst requires requests_per_minute"),
+    ] {
+        let response = server
+            .post("/api/admin/v1/workflows")
+            .json(&json!({
+                "name": "Noisy client",
+                "nodes": [{
+                    "id": Uuid::new_v4().to_string(),
+                    "name": "Trigger",
+                    "node_type": { "Trigger": { "methods": ["Post"], "rate_limit": rate_limit } }
+                }],
+                "edges": []
+            }))
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let details = response.json::<Value>()["details"].as_str().unwrap().to_string();
+        assert!(details.contains(error), "{details}");
+    }
+}
@@ -0,0 +1,143 @@
+use swisspipe::utils::javascript::{JavaScriptExecutor, JavaScriptLimits, ScriptOptions};
+use swisspipe::utils::javascript_console::{ConsoleCapture, ConsoleLevel};
+use swisspipe::workflow::errors::JavaScriptError;
+use swisspipe::workflow::models::WorkflowEvent;
+use std::collections::{HashMap, HashSet};
+
+fn create_event(data: serde_json::Value) -> WorkflowEvent {
+    WorkflowEvent {
+        data,
+        metadata: HashMap::new(),
+        headers: HashMap::new(),
+        condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
+        hil_task: None,
+        sources: Vec::new(),
+    }
+}
+
+fn create_executor() -> JavaScriptExecutor {
+    JavaScriptExecutor::with_limits(JavaScriptLimits::default())
+}
+
+#[tokio::test]
+async fn test_console_calls_are_captured_with_levels() {
+    let js_executor = create_executor();
+    let console = ConsoleCapture::new();
+
+    let script = r#"
+        function transformer(event) {
+            console.log('received', event.data.id);
+            console.info('payload', event.data);
+            console.debug([1, 'two']);
+            console.warn('missing field:', undefined, null, true);
+            console.error(new TypeError('bad value'));
+            return event;
+        }
+    "#;
+
+    let options = ScriptOptions {
+        console: Some(&console),
+        ..ScriptOptions::default()
+    };
+    js_executor
+        .execute_transformer_with_options(script, create_event(serde_json::json!({"id": 7})), options)
+        .await
+        .expect("Transformer should succeed");
+
+    let log = console.log();
+    let entries: Vec<(ConsoleLevel, &str)> = log.entries.iter().map(|e| (e.level, e.message.as_str())).collect();
+    assert_eq!(
+        entries,
+        vec![
+            (ConsoleLevel::Log, "received 7"),
+            (ConsoleLevel::Info, r#"payload {"id":7}"#),
+            (ConsoleLevel::Debug, r#"[1,"two"]"#),
+            (ConsoleLevel::Warn, "missing field: undefined null true"),
+            (ConsoleLevel::Error, "TypeError: bad value"),
+        ]
+    );
+    assert_eq!(log.dropped, 0);
+}
+
+#[tokio::test]
+async fn test_console_output_survives_script_failure() {
+    let js_executor = create_executor();
+    let console = ConsoleCapture::new();
+
+    let script = r#"
+        function condition(event) {
+            console.log('checking', event.data.status);
+            throw new Error('unexpected status');
+        }
+    "#;
+
+    let options = ScriptOptions {
+        console: Some(&console),
+        ..ScriptOptions::default()
+    };
+    let result = js_executor
+        .execute_condition_with_options(script, &create_event(serde_json::json!({"status": "odd"})), options)
+        .await;
+    assert!(matches!(result, Err(JavaScriptError::ExecutionError(_))), "got {result:?}");
+
+    let log = console.log();
+    assert_eq!(log.entries.len(), 1);
+    assert_eq!(log.entries[0].message, "checking odd");
+}
+
+#[tokio::test]
+async fn test_console_output_is_capped() {
+    let js_executor = create_executor();
+    let console = ConsoleCapture::new();
+
+    let script = r#"
+        function transformer(event) {
+            console.log('x'.repeat(5000));
+            for (let i = 0; i < 200; i++) {
+                console.log('line ' + i);
+            }
+            return event;
+        }
+    "#;
+
+    let options = ScriptOptions {
+        console: Some(&console),
+        ..ScriptOptions::default()
+    };
+    js_executor
+        .execute_transformer_with_options(script, create_event(serde_json::json!({})), options)
+        .await
+        .expect("Transformer should succeed");
+
+    let log = console.log();
+    assert!(log.entries[0].truncated);
+    assert_eq!(log.entries[0].message.len(), 2048);
+    assert!(!log.entries[1].truncated);
+    assert_eq!(log.entries.len(), 100);
+    assert_eq!(log.dropped, 101);
+
+    let json = serde_json::to_value(&log).unwrap();
+    assert_eq!(json["entries"][0]["level"], "log");
+    assert_eq!(json["entries"][0]["truncated"], true);
+    assert!(json["entries"][1].get("truncated").is_none());
+}
+
+#[tokio::test]
+async fn test_console_is_available_without_capture() {
+    let js_executor = create_executor();
+
+    let script = r#"
+        function condition(event) {
+            const cyclic = {};
+            cyclic.self = cyclic;
+            console.log(cyclic);
+            console.warn(Symbol('tag'));
+            return true;
+        }
+    "#;
+
+    let result = js_executor.execute_condition(script, &create_event(serde_json::json!({}))).await;
+    assert!(result.unwrap());
+}
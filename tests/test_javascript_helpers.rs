@@ -0,0 +1,196 @@
+use swisspipe::utils::javascript::{JavaScriptExecutor, JavaScriptLimits, ScriptOptions};
+use swisspipe::workflow::errors::JavaScriptError;
+use swisspipe::workflow::models::{HostFunction, WorkflowEvent};
+use std::collections::{HashMap, HashSet};
+
+fn create_event(data: serde_json::Value) -> WorkflowEvent {
+    WorkflowEvent {
+        data,
+        metadata: HashMap::new(),
+        headers: HashMap::new(),
+        condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
+        hil_task: None,
+        sources: Vec::new(),
+    }
+}
+
+async fn transform(script: &str, data: serde_json::Value) -> serde_json::Value {
+    JavaScriptExecutor::with_limits(JavaScriptLimits::default())
+        .execute_transformer(script, create_event(data))
+        .await
+        .expect("Transformer should succeed")
+        .data
+}
+
+#[tokio::test]
+async fn test_hashing_helpers() {
+    let script = r#"
+        function transformer(event) {
+            event.data.sha256 = swisspipe.sha256(event.data.email);
+            event.data.hmac = swisspipe.hmacSha256('key', 'The quick brown fox jumps over the lazy dog');
+            return event;
+        }
+    "#;
+
+    let data = transform(script, serde_json::json!({"email": "abc"})).await;
+    assert_eq!(data["sha256"], "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    assert_eq!(data["hmac"], "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8");
+}
+
+#[tokio::test]
+async fn test_uuid_helpers() {
+    let script = r#"
+        function transformer(event) {
+            event.data.v4 = swisspipe.uuid();
+            event.data.v7 = swisspipe.uuidv7();
+            event.data.unique = swisspipe.uuid() !== swisspipe.uuid();
+            return event;
+        }
+    "#;
+
+    let data = transform(script, serde_json::json!({})).await;
+    let v4 = uuid::Uuid::parse_str(data["v4"].as_str().unwrap()).unwrap();
+    let v7 = uuid::Uuid::parse_str(data["v7"].as_str().unwrap()).unwrap();
+    assert_eq!(v4.get_version_num(), 4);
+    assert_eq!(v7.get_version_num(), 7);
+    assert_eq!(data["unique"], true);
+}
+
+#[tokio::test]
+async fn test_base64_helpers() {
+    let script = r#"
+        function transformer(event) {
+            event.data.encoded = swisspipe.base64Encode('user:päss');
+            event.data.url_safe = swisspipe.base64Encode('??>>', true);
+            event.data.decoded = swisspipe.base64Decode(event.data.encoded);
+            event.data.url_safe_decoded = swisspipe.base64Decode(event.data.url_safe, true);
+            try {
+                swisspipe.base64Decode('not base64!');
+                event.data.invalid = 'accepted';
+            } catch (e) {
+                event.data.invalid = e.name;
+            }
+            return event;
+        }
+    "#;
+
+    let data = transform(script, serde_json::json!({})).await;
+    assert_eq!(data["encoded"], "dXNlcjpww6Rzcw==");
+    assert_eq!(data["url_safe"], "Pz8-Pg");
+    assert_eq!(data["decoded"], "user:päss");
+    assert_eq!(data["url_safe_decoded"], "??>>");
+    assert_eq!(data["invalid"], "TypeError");
+}
+
+#[tokio::test]
+async fn test_parse_url_helper() {
+    let script = r#"
+        function transformer(event) {
+            const url = swisspipe.parseUrl(event.data.url);
+            event.data.parsed = url;
+            try {
+                swisspipe.parseUrl('not a url');
+            } catch (e) {
+                event.data.invalid = e.name;
+            }
+            return event;
+        }
+    "#;
+
+    let data = transform(
+        script,
+        serde_json::json!({"url": "https://shop.example.com:8443/cart/items?utm_source=mail&q=a%20b#top"}),
+    ).await;
+
+    let parsed = &data["parsed"];
+    assert_eq!(parsed["protocol"], "https:");
+    assert_eq!(parsed["hostname"], "shop.example.com");
+    assert_eq!(parsed["port"], "8443");
+    assert_eq!(parsed["host"], "shop.example.com:8443");
+    assert_eq!(parsed["pathname"], "/cart/items");
+    assert_eq!(parsed["search"], "?utm_source=mail&q=a%20b");
+    assert_eq!(parsed["hash"], "#top");
+    assert_eq!(parsed["query"]["utm_source"], "mail");
+    assert_eq!(parsed["query"]["q"], "a b");
+    assert_eq!(data["invalid"], "TypeError");
+}
+
+#[tokio::test]
+async fn test_format_date_helper() {
+    let script = r#"
+        function transformer(event) {
+            const ts = event.data.ts;
+            event.data.default = swisspipe.formatDate(ts);
+            event.data.iso_week = swisspipe.formatDate(ts, '%G-W%V');
+            event.data.tokyo = swisspipe.formatDate(ts, '%Y-%m-%d %H:%M %Z', 'Asia/Tokyo');
+            event.data.from_string = swisspipe.formatDate('2024-12-30T23:30:00-05:00', '%Y-%m-%d', 'UTC');
+            event.data.from_date = swisspipe.formatDate(new Date(ts), '%A');
+            try {
+                swisspipe.formatDate(ts, '%Y', 'Mars/Olympus');
+            } catch (e) {
+                event.data.bad_timezone = e.name;
+            }
+            try {
+                swisspipe.formatDate(ts, '%Q');
+            } catch (e) {
+                event.data.bad_format = e.name;
+            }
+            return event;
+        }
+    "#;
+
+    // 2024-12-30T12:00:00Z is a Monday in ISO week 1 of 2025
+    let data = transform(script, serde_json::json!({"ts": 1735560000000_i64})).await;
+    assert_eq!(data["default"], "2024-12-30T12:00:00.000+00:00");
+    assert_eq!(data["iso_week"], "2025-W01");
+    assert_eq!(data["tokyo"], "2024-12-30 21:00 JST");
+    assert_eq!(data["from_string"], "2024-12-31");
+    assert_eq!(data["from_date"], "Monday");
+    assert_eq!(data["bad_timezone"], "RangeError");
+    assert_eq!(data["bad_format"], "RangeError");
+}
+
+#[tokio::test]
+async fn test_env_requires_opt_in_and_is_read_only() {
+    let js_executor = JavaScriptExecutor::with_limits(JavaScriptLimits::default());
+    let variables = HashMap::from([("API_HOST".to_string(), "api.example.com".to_string())]);
+
+    let script = r#"
+        'use strict';
+        function transformer(event) {
+            event.data.host = swisspipe.env('API_HOST');
+            event.data.missing = swisspipe.env('MISSING') === undefined;
+            try {
+                swisspipe.env = () => 'hijacked';
+            } catch (e) {
+                event.data.reassign = e.name;
+            }
+            return event;
+        }
+    "#;
+
+    let options = ScriptOptions {
+        host_functions: &[HostFunction::Env],
+        variables: Some(&variables),
+        ..ScriptOptions::default()
+    };
+    let data = js_executor
+        .execute_transformer_with_options(script, create_event(serde_json::json!({})), options)
+        .await
+        .expect("Transformer should succeed")
+        .data;
+    assert_eq!(data["host"], "api.example.com");
+    assert_eq!(data["missing"], true);
+    assert_eq!(data["reassign"], "TypeError");
+
+    let options = ScriptOptions {
+        variables: Some(&variables),
+        ..ScriptOptions::default()
+    };
+    let result = js_executor
+        .execute_transformer_with_options(script, create_event(serde_json::json!({})), options)
+        .await;
+    assert!(matches!(result, Err(JavaScriptError::ExecutionError(_))), "got {result:?}");
+}
@@ -0,0 +1,150 @@
+use swisspipe::utils::javascript::{JavaScriptExecutor, JavaScriptLimits};
+use swisspipe::workflow::errors::JavaScriptError;
+use swisspipe::workflow::models::{ScriptLimits, WorkflowEvent};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+fn create_event() -> WorkflowEvent {
+    WorkflowEvent {
+        data: serde_json::json!({"value": 1}),
+        metadata: HashMap::new(),
+        headers: HashMap::new(),
+        condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
+        hil_task: None,
+        sources: Vec::new(),
+    }
+}
+
+fn create_executor(timeout_ms: u64) -> JavaScriptExecutor {
+    JavaScriptExecutor::with_limits(JavaScriptLimits {
+        max_execution_time: Duration::from_millis(timeout_ms),
+        ..JavaScriptLimits::default()
+    })
+}
+
+#[tokio::test]
+async fn test_long_running_transformer_is_interrupted() {
+    let js_executor = create_executor(200);
+
+    let script = r#"
+        function transformer(event) {
+            let i = 0;
+            while (1) { i++; }
+            return event;
+        }
+    "#;
+
+    let start = Instant::now();
+    let result = js_executor.execute_transformer(script, create_event()).await;
+
+    assert!(matches!(result, Err(JavaScriptError::Timeout)), "expected timeout, got {result:?}");
+    assert!(start.elapsed() < Duration::from_secs(3), "script was not aborted promptly");
+}
+
+#[tokio::test]
+async fn test_long_running_condition_is_interrupted() {
+    let js_executor = create_executor(200);
+
+    let script = r#"
+        function condition(event) {
+            let i = 0;
+            do { i++; } while (i >= 0);
+            return true;
+        }
+    "#;
+
+    let result = js_executor.execute_condition(script, &create_event()).await;
+    assert!(matches!(result, Err(JavaScriptError::Timeout)), "expected timeout, got {result:?}");
+}
+
+#[tokio::test]
+async fn test_memory_limit_exceeded() {
+    let js_executor = JavaScriptExecutor::with_limits(JavaScriptLimits {
+        memory_limit_bytes: 8 * 1024 * 1024,
+        ..JavaScriptLimits::default()
+    });
+
+    let script = r#"
+        function transformer(event) {
+            const items = [];
+            for (let i = 0; i < 10000000; i++) {
+                items.push({ index: i, label: "item-" + i });
+            }
+            event.data.count = items.length;
+            return event;
+        }
+    "#;
+
+    let result = js_executor.execute_transformer(script, create_event()).await;
+    assert!(
+        matches!(result, Err(JavaScriptError::MemoryLimitExceeded)),
+        "expected memory limit error, got {result:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_stack_limit_exceeded() {
+    let js_executor = JavaScriptExecutor::new().expect("Failed to create JavaScript executor");
+
+    let script = r#"
+        function recurse(n) { return recurse(n + 1) + 1; }
+        function condition(event) {
+            return recurse(0) > 0;
+        }
+    "#;
+
+    let result = js_executor.execute_condition(script, &create_event()).await;
+    assert!(
+        matches!(result, Err(JavaScriptError::StackOverflow)),
+        "expected stack overflow, got {result:?}"
+    );
+}
+
+#[tokio::test]
+async fn test_per_node_limits_override_global_limits() {
+    // Generous global limit, tight per-node override
+    let js_executor = create_executor(30_000);
+
+    let script = r#"
+        function condition(event) {
+            while (1) {}
+            return true;
+        }
+    "#;
+
+    let overrides = ScriptLimits {
+        timeout_ms: Some(100),
+        ..ScriptLimits::default()
+    };
+
+    let start = Instant::now();
+    let result = js_executor
+        .execute_condition_with_limits(script, &create_event(), Some(&overrides))
+        .await;
+
+    assert!(matches!(result, Err(JavaScriptError::Timeout)), "expected timeout, got {result:?}");
+    assert!(start.elapsed() < Duration::from_secs(3), "per-node timeout was not applied");
+}
+
+#[tokio::test]
+async fn test_script_within_limits_succeeds() {
+    let js_executor = create_executor(1000);
+
+    let script = r#"
+        function transformer(event) {
+            let total = 0;
+            for (let i = 0; i < 1000; i++) { total += i; }
+            event.data.total = total;
+            return event;
+        }
+    "#;
+
+    let result = js_executor
+        .execute_transformer(script, create_event())
+        .await
+        .expect("Transformer within limits should succeed");
+
+    assert_eq!(result.data["total"], 499500);
+}
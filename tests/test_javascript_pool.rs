@@ -0,0 +1,195 @@
+use swisspipe::utils::javascript::{JavaScriptExecutor, JavaScriptLimits};
+use swisspipe::utils::javascript_pool::JavaScriptPoolConfig;
+use swisspipe::workflow::errors::JavaScriptError;
+use swisspipe::workflow::models::WorkflowEvent;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+fn create_event(value: i64) -> WorkflowEvent {
+    WorkflowEvent {
+        data: serde_json::json!({"value": value}),
+        metadata: HashMap::new(),
+        headers: HashMap::new(),
+        condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
+        hil_task: None,
+        sources: Vec::new(),
+    }
+}
+
+fn create_executor(runtime_pool_size: usize) -> JavaScriptExecutor {
+    JavaScriptExecutor::with_config(
+        JavaScriptLimits::default(),
+        JavaScriptPoolConfig {
+            runtime_pool_size,
+            script_cache_size: 16,
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_runtimes_and_bytecode_are_reused() {
+    let js_executor = create_executor(2);
+
+    let script = r#"
+        function transformer(event) {
+            event.data.doubled = event.data.value * 2;
+            return event;
+        }
+    "#;
+
+    for value in 0..5 {
+        let result = js_executor
+            .execute_transformer(script, create_event(value))
+            .await
+            .expect("Transformer should succeed");
+        assert_eq!(result.data["doubled"], value * 2);
+    }
+
+    let stats = js_executor.get_stats();
+    assert_eq!(stats.pool_size, 2);
+    assert_eq!(stats.runtime_hits, 5);
+    assert_eq!(stats.runtime_misses, 0);
+    assert_eq!(stats.cached_scripts, 1);
+    assert_eq!(stats.script_cache_misses, 1);
+    assert_eq!(stats.script_cache_hits, 4);
+    assert!((stats.script_cache_hit_rate - 0.8).abs() < f64::EPSILON);
+}
+
+#[tokio::test]
+async fn test_empty_pool_creates_runtimes_on_demand() {
+    let js_executor = create_executor(0);
+
+    let script = "function condition(event) { return event.data.value > 1; }";
+    assert!(js_executor.execute_condition(script, &create_event(2)).await.unwrap());
+    assert!(!js_executor.execute_condition(script, &create_event(0)).await.unwrap());
+
+    let stats = js_executor.get_stats();
+    assert_eq!(stats.runtime_hits, 0);
+    assert_eq!(stats.runtime_misses, 2);
+    assert_eq!(stats.idle_runtimes, 0);
+}
+
+#[tokio::test]
+async fn test_global_state_does_not_leak_between_events() {
+    let js_executor = create_executor(1);
+
+    let script = r#"
+        var seen = (typeof seen === 'undefined') ? 0 : seen;
+        function transformer(event) {
+            seen += 1;
+            globalThis.leaked = event.data.value;
+            event.data.seen = seen;
+            event.data.previous = (typeof previous === 'undefined') ? null : previous;
+            return event;
+        }
+        var previous = globalThis.leaked;
+    "#;
+
+    for value in 0..3 {
+        let result = js_executor
+            .execute_transformer(script, create_event(value))
+            .await
+            .expect("Transformer should succeed");
+        assert_eq!(result.data["seen"], 1, "state leaked from a previous event");
+        assert!(result.data["previous"].is_null(), "globals leaked from a previous event");
+    }
+}
+
+#[tokio::test]
+async fn test_different_scripts_do_not_share_functions() {
+    let js_executor = create_executor(1);
+
+    let first = "function condition(event) { return true; }";
+    let second = "const condition = (event) => false;";
+
+    assert!(js_executor.execute_condition(first, &create_event(1)).await.unwrap());
+    assert!(!js_executor.execute_condition(second, &create_event(1)).await.unwrap());
+    assert!(js_executor.execute_condition(first, &create_event(1)).await.unwrap());
+
+    assert_eq!(js_executor.get_stats().cached_scripts, 2);
+}
+
+#[tokio::test]
+async fn test_aborted_runtime_is_not_returned_to_pool() {
+    let js_executor = JavaScriptExecutor::with_config(
+        JavaScriptLimits {
+            max_execution_time: Duration::from_millis(100),
+            ..JavaScriptLimits::default()
+        },
+        JavaScriptPoolConfig {
+            runtime_pool_size: 1,
+            script_cache_size: 16,
+        },
+    );
+
+    let script = "function condition(event) { while (1) {} }";
+    let result = js_executor.execute_condition(script, &create_event(1)).await;
+    assert!(matches!(result, Err(JavaScriptError::Timeout)));
+    assert_eq!(js_executor.get_stats().idle_runtimes, 0);
+
+    // The next execution gets a fresh runtime and still works
+    let script = "function condition(event) { return event.data.value === 1; }";
+    assert!(js_executor.execute_condition(script, &create_event(1)).await.unwrap());
+    assert_eq!(js_executor.get_stats().idle_runtimes, 1);
+}
+
+#[tokio::test]
+async fn test_syntax_errors_are_reported() {
+    let js_executor = create_executor(1);
+
+    let result = js_executor
+        .execute_transformer("function transformer(event) { return event", create_event(1))
+        .await;
+    assert!(matches!(result, Err(JavaScriptError::ExecutionError(_))), "got {result:?}");
+    assert_eq!(js_executor.get_stats().cached_scripts, 0);
+
+    // A script cannot close the function it runs in to declare globals
+    let result = js_executor
+        .execute_transformer("function transformer(event) { return event; }\n})(); let planted = 1; (function () {", create_event(1))
+        .await;
+    assert!(matches!(result, Err(JavaScriptError::ExecutionError(_))), "got {result:?}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_pool_size_caps_concurrent_sandboxes() {
+    let js_executor = std::sync::Arc::new(create_executor(1));
+
+    let script = "function condition(event) { let total = 0; for (let i = 0; i < 100000; i++) { total += i; } return total > event.data.value; }";
+    let tasks: Vec<_> = (0..8)
+        .map(|value| {
+            let js_executor = js_executor.clone();
+            tokio::spawn(async move { js_executor.execute_condition(script, &create_event(value)).await })
+        })
+        .collect();
+    for task in tasks {
+        assert!(task.await.unwrap().unwrap());
+    }
+
+    // Callers waited for the one sandbox instead of creating more
+    let stats = js_executor.get_stats();
+    assert_eq!(stats.runtime_hits, 8);
+    assert_eq!(stats.runtime_misses, 0);
+    assert_eq!(stats.idle_runtimes, 1);
+}
+
+#[tokio::test]
+async fn test_sandbox_with_tampered_globals_is_discarded() {
+    let js_executor = create_executor(1);
+
+    let tamper = [
+        "Object.defineProperty(globalThis, 'stash', { value: event.data.value, configurable: false });",
+        "Object.setPrototypeOf(globalThis, { stash: event.data.value });",
+        "Object.preventExtensions(globalThis);",
+    ];
+    for (run, tamper) in tamper.iter().enumerate() {
+        let script = format!("function condition(event) {{ {tamper} return true; }}");
+        assert!(js_executor.execute_condition(&script, &create_event(42)).await.unwrap());
+        assert_eq!(js_executor.get_stats().runtime_misses, run as u64, "sandbox was not replaced");
+
+        let script = "function condition(event) { globalThis.added = 1; return typeof stash === 'undefined' && added === 1; }";
+        assert!(js_executor.execute_condition(script, &create_event(1)).await.unwrap(), "{tamper}");
+    }
+    assert_eq!(js_executor.get_stats().runtime_misses, 3);
+}
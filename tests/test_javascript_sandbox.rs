@@ -0,0 +1,243 @@
+use swisspipe::utils::javascript::{JavaScriptExecutor, JavaScriptLimits, ScriptOptions};
+use swisspipe::workflow::errors::JavaScriptError;
+use swisspipe::workflow::models::{HostFunction, WorkflowEvent};
+use std::collections::{HashMap, HashSet};
+
+fn create_event(data: serde_json::Value) -> WorkflowEvent {
+    WorkflowEvent {
+        data,
+        metadata: HashMap::new(),
+        headers: HashMap::new(),
+        condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
+        hil_task: None,
+        sources: Vec::new(),
+    }
+}
+
+fn create_executor() -> JavaScriptExecutor {
+    JavaScriptExecutor::with_limits(JavaScriptLimits::default())
+}
+
+/// Run `attempt` inside a strict-mode condition and report whether it completed without throwing
+async fn escape_succeeds(js_executor: &JavaScriptExecutor, attempt: &str) -> bool {
+    let script = format!(
+        r#"
+        'use strict';
+        function condition(event) {{
+            try {{
+                {attempt}
+                return true;
+            }} catch (e) {{
+                return false;
+            }}
+        }}
+        "#
+    );
+
+    js_executor
+        .execute_condition(&script, &create_event(serde_json::json!({})))
+        .await
+        .expect("Condition should run")
+}
+
+#[tokio::test]
+async fn test_dynamic_code_evaluation_is_blocked() {
+    let js_executor = create_executor();
+
+    let attempts = [
+        "eval('1 + 1');",
+        "(0, eval)('1 + 1');",
+        "globalThis['ev' + 'al']('1 + 1');",
+        "Function('return 1')();",
+        "new Function('return this')();",
+        "(function () {}).constructor('return 1')();",
+        "(() => {}).constructor('return 1')();",
+        "Object.getPrototypeOf(function* () {}).constructor('yield 1')().next();",
+        "Object.getPrototypeOf(async function () {}).constructor('return 1')();",
+        "Object.getPrototypeOf(async function* () {}).constructor('yield 1')();",
+        "Reflect.construct(Function, ['return 1'])();",
+        "[].map.constructor('return 1')();",
+        "''.constructor.constructor('return 1')();",
+    ];
+
+    for attempt in attempts {
+        assert!(!escape_succeeds(&js_executor, attempt).await, "escape succeeded: {attempt}");
+    }
+}
+
+#[tokio::test]
+async fn test_intrinsics_cannot_be_modified() {
+    let js_executor = create_executor();
+
+    let attempts = [
+        "Object.prototype.polluted = true;",
+        "({}).__proto__.polluted = true;",
+        "Array.prototype.push = function () {};",
+        "JSON.stringify = function () { return '{}'; };",
+        "Math.random = function () { return 4; };",
+        "Object.defineProperty(Object.prototype, 'polluted', { value: true });",
+        "Object.setPrototypeOf(Object.prototype, {});",
+        "Object.prototype.toString = function () { return 'x'; };",
+        "Error.prototype.name = 'Hijacked';",
+        "JSON = {};",
+        "swisspipe.log = function () {};",
+    ];
+
+    for attempt in attempts {
+        assert!(!escape_succeeds(&js_executor, attempt).await, "modification succeeded: {attempt}");
+    }
+}
+
+#[tokio::test]
+async fn test_sloppy_mode_pollution_has_no_effect() {
+    let js_executor = create_executor();
+
+    let script = r#"
+        function transformer(event) {
+            Object.prototype.polluted = true;
+            Array.prototype.polluted = true;
+            event.data.polluted = ({}).polluted === true || [].polluted === true;
+            return event;
+        }
+    "#;
+
+    let result = js_executor
+        .execute_transformer(script, create_event(serde_json::json!({})))
+        .await
+        .expect("Transformer should succeed");
+    assert_eq!(result.data["polluted"], false);
+}
+
+#[tokio::test]
+async fn test_module_system_is_unavailable() {
+    let js_executor = create_executor();
+
+    let static_import = r#"
+        import fs from 'fs';
+        function condition(event) { return true; }
+    "#;
+    let result = js_executor.execute_condition(static_import, &create_event(serde_json::json!({}))).await;
+    assert!(matches!(result, Err(JavaScriptError::ExecutionError(_))), "got {result:?}");
+
+    assert!(!escape_succeeds(&js_executor, "require('fs');").await);
+    assert!(!escape_succeeds(&js_executor, "process.exit(1);").await);
+    assert!(!escape_succeeds(&js_executor, "std.loadFile('/etc/passwd');").await);
+    assert!(!escape_succeeds(&js_executor, "os.exec(['ls']);").await);
+}
+
+#[tokio::test]
+async fn test_dynamic_import_is_rejected() {
+    let js_executor = create_executor();
+
+    let script = r#"
+        var outcome = 'pending';
+        import('fs').then(() => { outcome = 'loaded'; }, () => { outcome = 'rejected'; });
+        function condition(event) { return outcome !== 'loaded'; }
+    "#;
+
+    let result = js_executor.execute_condition(script, &create_event(serde_json::json!({}))).await;
+    assert!(!matches!(result, Ok(false)), "dynamic import loaded a module");
+}
+
+#[tokio::test]
+async fn test_legitimate_scripts_previously_rejected_now_run() {
+    let js_executor = create_executor();
+
+    let script = r#"
+        function transformer(event) {
+            const module = event.data.module;
+            event.data.label = String.fromCharCode(72, 105) + ' ' + module;
+            event.data.imported = 'import' in event.data;
+            event.data.exports = ['evaluation', 'required'].join(',');
+            event.data.escaped = 'A\x42';
+            return event;
+        }
+    "#;
+
+    let result = js_executor
+        .execute_transformer(script, create_event(serde_json::json!({"module": "billing", "import": 1})))
+        .await
+        .expect("Transformer should succeed");
+
+    assert_eq!(result.data["label"], "Hi billing");
+    assert_eq!(result.data["imported"], true);
+    assert_eq!(result.data["exports"], "evaluation,required");
+    assert_eq!(result.data["escaped"], "AB");
+}
+
+#[tokio::test]
+async fn test_common_overrides_still_work_on_instances() {
+    let js_executor = create_executor();
+
+    let script = r#"
+        'use strict';
+        class ValidationError extends Error {
+            constructor(message) {
+                super(message);
+                this.name = 'ValidationError';
+            }
+        }
+
+        function transformer(event) {
+            const point = { x: 1, y: 2 };
+            point.toString = function () { return this.x + ',' + this.y; };
+
+            const error = new ValidationError('bad input');
+            event.data.point = String(point);
+            event.data.error = error.toString();
+            event.data.is_error = error instanceof Error;
+            event.data.is_function = transformer instanceof Function;
+            event.data.constructor = 'field named constructor';
+            return event;
+        }
+    "#;
+
+    let result = js_executor
+        .execute_transformer(script, create_event(serde_json::json!({})))
+        .await
+        .expect("Transformer should succeed");
+
+    assert_eq!(result.data["point"], "1,2");
+    assert_eq!(result.data["error"], "ValidationError: bad input");
+    assert_eq!(result.data["is_error"], true);
+    assert_eq!(result.data["is_function"], true);
+    assert_eq!(result.data["constructor"], "field named constructor");
+}
+
+#[tokio::test]
+async fn test_host_functions_require_opt_in() {
+    let js_executor = create_executor();
+    let event = create_event(serde_json::json!({}));
+
+    let script = r#"
+        function condition(event) {
+            swisspipe.log('hello from a condition');
+            return true;
+        }
+    "#;
+
+    let result = js_executor.execute_condition(script, &event).await;
+    match result {
+        Err(JavaScriptError::ExecutionError(message)) => {
+            assert!(message.contains("swisspipe.log"), "unexpected error: {message}");
+        }
+        other => panic!("expected host function error, got {other:?}"),
+    }
+
+    let options = ScriptOptions {
+        host_functions: &[HostFunction::Log],
+        ..ScriptOptions::default()
+    };
+    let result = js_executor.execute_condition_with_options(script, &event, options).await;
+    assert!(result.unwrap());
+}
+
+#[tokio::test]
+async fn test_host_function_allowlist_is_deserialized_from_names() {
+    let allowlist: Vec<HostFunction> = serde_json::from_str(r#"["log"]"#).unwrap();
+    assert_eq!(allowlist, vec![HostFunction::Log]);
+
+    assert!(serde_json::from_str::<Vec<HostFunction>>(r#"["exec"]"#).is_err());
+}
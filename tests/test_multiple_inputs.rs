@@ -6,7 +6,7 @@ use swisspipe::{
     },
 };
 use sea_orm::{ActiveModelTrait, Set, DatabaseConnection};
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::{HashMap, HashSet}, sync::Arc};
 
 // Helper function to create a test execution record to satisfy foreign key constraints
 async fn create_test_execution(db: &DatabaseConnection, execution_id: &str) -> Result<(), Box<dyn std::error::Error>> {
@@ -35,7 +35,11 @@ async fn create_test_execution(db: &DatabaseConnection, execution_id: &str) -> R
         started_at: Set(None),
         completed_at: Set(None),
         created_at: Set(chrono::Utc::now().timestamp_micros()),
-        updated_at: Set(chrono::Utc::now().timestamp_micros())
+        updated_at: Set(chrono::Utc::now().timestamp_micros()),
+        parent_execution_id: Set(None),
+        parent_node_id: Set(None),
+        failed_execution_id: Set(None),
+        http_response: Set(None),
     };
     execution.insert(db).await?;
     Ok(())
@@ -66,6 +70,8 @@ async fn test_input_synchronization_wait_for_all() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -89,6 +95,8 @@ async fn test_input_synchronization_wait_for_all() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -167,6 +175,8 @@ async fn test_input_synchronization_first_wins() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -176,6 +186,8 @@ async fn test_input_synchronization_first_wins() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -185,6 +197,8 @@ async fn test_input_synchronization_first_wins() {
         metadata: HashMap::new(), 
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -233,6 +247,8 @@ async fn test_timeout_based_strategy() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -274,6 +290,8 @@ fn test_merge_strategies_logic() {
         },
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -287,6 +305,8 @@ fn test_merge_strategies_logic() {
         },
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
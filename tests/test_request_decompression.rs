@@ -0,0 +1,130 @@
+mod common;
+
+use axum::http::{header, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use flate2::{write::{GzEncoder, ZlibEncoder}, Compression};
+use sea_orm::{EntityTrait, PaginatorTrait};
+use std::io::Write;
+use swisspipe::{api, database::workflow_executions};
+
+fn gzip(body: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// HTTP `deflate` is the zlib format (RFC 9110), not raw DEFLATE
+fn deflate(body: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body).unwrap();
+    encoder.finish().unwrap()
+}
+
+async fn setup() -> (TestServer, swisspipe::AppState, String) {
+    let state = common::test_state().await;
+    let workflow_id = common::create_trigger_workflow(&state.db).await;
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state, workflow_id)
+}
+
+#[tokio::test]
+async fn test_gzip_segment_batch_is_decompressed() {
+    let (server, state, workflow_id) = setup().await;
+
+    let body = serde_json::json!({
+        "batch": [
+            { "type": "track", "event": "Signed Up", "userId": "u1" },
+            { "type": "identify", "userId": "u1", "traits": { "plan": "pro" } },
+        ]
+    });
+
+    let response = server
+        .post("/api/v1/batch")
+        .add_header(header::AUTHORIZATION, HeaderValue::from_str(&format!("Bearer {workflow_id}")).unwrap())
+        .add_header(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        .add_header(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"))
+        .bytes(gzip(body.to_string().as_bytes()).into())
+        .await;
+
+    response.assert_status_ok();
+    let result: serde_json::Value = response.json();
+    assert_eq!(result["success"], true, "unexpected response: {result}");
+
+    let executions = workflow_executions::Entity::find().count(&*state.db).await.unwrap();
+    assert_eq!(executions, 2);
+}
+
+#[tokio::test]
+async fn test_deflate_trigger_is_decompressed() {
+    let (server, state, workflow_id) = setup().await;
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_header(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        .add_header(header::CONTENT_ENCODING, HeaderValue::from_static("deflate"))
+        .bytes(deflate(br#"{"order_id": 42}"#).into())
+        .await;
+
+    response.assert_status(StatusCode::ACCEPTED);
+    let executions = workflow_executions::Entity::find().count(&*state.db).await.unwrap();
+    assert_eq!(executions, 1);
+}
+
+#[tokio::test]
+async fn test_decompressed_size_is_limited() {
+    let (server, state, workflow_id) = setup().await;
+
+    // ~3 MB of JSON compresses to a few KB but exceeds the 2 MB default limit once inflated
+    let body = format!(r#"{{"padding": "{}"}}"#, "0".repeat(3 * 1024 * 1024));
+    let compressed = gzip(body.as_bytes());
+    assert!(compressed.len() < 64 * 1024);
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_header(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        .add_header(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"))
+        .bytes(compressed.into())
+        .await;
+
+    response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    let executions = workflow_executions::Entity::find().count(&*state.db).await.unwrap();
+    assert_eq!(executions, 0);
+}
+
+#[tokio::test]
+async fn test_decompressed_bulk_size_is_limited() {
+    let (server, state, workflow_id) = setup().await;
+
+    // Streamed bulk bodies are counted as they inflate, not only when buffered
+    let line = format!("{{\"padding\": \"{}\"}}\n", "0".repeat(1024));
+    let lines = 3 * 1024;
+    let compressed = gzip(line.repeat(lines).as_bytes());
+    assert!(compressed.len() < 64 * 1024);
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/ndjson"))
+        .add_header(header::CONTENT_TYPE, HeaderValue::from_static("application/x-ndjson"))
+        .add_header(header::CONTENT_ENCODING, HeaderValue::from_static("gzip"))
+        .bytes(compressed.into())
+        .await;
+
+    response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    let summary: serde_json::Value = response.json();
+    assert_eq!(summary["status"], "incomplete");
+    let executions = workflow_executions::Entity::find().count(&*state.db).await.unwrap();
+    assert!(executions < lines as u64);
+}
+
+#[tokio::test]
+async fn test_unsupported_encoding_is_rejected() {
+    let (server, _state, workflow_id) = setup().await;
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_header(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        .add_header(header::CONTENT_ENCODING, HeaderValue::from_static("br"))
+        .bytes(b"not really brotli".to_vec().into())
+        .await;
+
+    response.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use swisspipe::api;
+use swisspipe::async_execution::ExecutionService;
+use uuid::Uuid;
+
+async fn setup() -> (TestServer, swisspipe::AppState) {
+    let state = common::test_state().await;
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state)
+}
+
+/// Create a workflow running `steps` in a chain after its trigger
+async fn create_workflow(server: &TestServer, steps: Vec<(&str, Value)>) -> String {
+    let trigger_id = Uuid::new_v4().to_string();
+    let mut nodes = vec![json!({
+        "id": trigger_id,
+        "name": "Trigger",
+        "node_type": { "Trigger": { "methods": ["Post"] } }
+    })];
+    let mut edges = Vec::new();
+    let mut previous = trigger_id;
+    for (name, node_type) in steps {
+        let id = Uuid::new_v4().to_string();
+        nodes.push(json!({ "id": id, "name": name, "node_type": node_type }));
+        edges.push(json!({ "from_node_id": previous, "to_node_id": id }));
+        previous = id;
+    }
+
+    let response = server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({ "name": "Webhook", "nodes": nodes, "edges": edges }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+fn respond(status_code: u16, headers: Value, body_template: &str) -> Value {
+    json!({ "Respond": { "status_code": status_code, "headers": headers, "body_template": body_template } })
+}
+
+fn transformer(script: &str) -> Value {
+    json!({ "Transformer": { "script": script, "limits": null } })
+}
+
+#[tokio::test]
+async fn test_respond_node_answers_handshake() {
+    let (server, state) = setup().await;
+    let workflow_id = create_workflow(
+        &server,
+        vec![("Verify URL", respond(200, json!({ "Content-Type": "text/plain" }), "{{ event.data.challenge }}"))],
+    )
+    .await;
+    state.worker_pool.start().await.unwrap();
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .json(&json!({ "type": "url_verification", "challenge": "3eZbrw1aBm2rZgRNFdxV2595E9CY3gmdALWMmHkvFXO7tYXAYM8P" }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "text/plain");
+    assert_eq!(response.text(), "3eZbrw1aBm2rZgRNFdxV2595E9CY3gmdALWMmHkvFXO7tYXAYM8P");
+    assert!(!response.header("x-execution-id").is_empty());
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_workflow_continues_after_respond_node() {
+    let (server, state) = setup().await;
+    let workflow_id = create_workflow(
+        &server,
+        vec![
+            ("Acknowledge", respond(201, json!({ "X-Order-Id": "{{ event.data.order_id }}" }), "{\"accepted\": {{ event.data.order_id }}}")),
+            ("Process", transformer("function transformer(event) { event.data.processed = true; return event; }")),
+        ],
+    )
+    .await;
+    state.worker_pool.start().await.unwrap();
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .json(&json!({ "order_id": 42 }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    assert_eq!(response.header("content-type"), "application/json");
+    assert_eq!(response.header("x-order-id"), "42");
+    assert_eq!(response.json::<Value>(), json!({ "accepted": 42 }));
+
+    // The nodes after the Respond node still run
+    let execution_id = response.header("x-execution-id").to_str().unwrap().to_string();
+    let execution = ExecutionService::new(state.db.clone())
+        .wait_for_execution(&execution_id, Duration::from_secs(10))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(execution.status, "completed");
+    let output: Value = serde_json::from_str(execution.output_data.as_deref().unwrap()).unwrap();
+    assert_eq!(output["processed"], true);
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_first_respond_node_wins() {
+    let (server, state) = setup().await;
+    let workflow_id = create_workflow(
+        &server,
+        vec![
+            ("First", respond(200, json!({}), "first")),
+            ("Second", respond(500, json!({}), "second")),
+        ],
+    )
+    .await;
+    state.worker_pool.start().await.unwrap();
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .json(&json!({}))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.text(), "first");
+
+    let execution_id = response.header("x-execution-id").to_str().unwrap().to_string();
+    let execution = ExecutionService::new(state.db.clone())
+        .wait_for_execution(&execution_id, Duration::from_secs(10))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(execution.status, "completed");
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[test]
+fn test_validator_checks_respond_nodes() {
+    use swisspipe::workflow::{
+        models::{Edge, HttpMethod, Node, NodeType},
+        validation::WorkflowValidator,
+    };
+
+    let node = |id: &str, node_type: NodeType| Node {
+        id: id.to_string(),
+        workflow_id: "wf".to_string(),
+        name: id.to_string(),
+        node_type,
+        input_merge_strategy: None,
+        allowed_host_functions: Vec::new(),
+    };
+    let validate = |status_code: u16, header: &str| {
+        let nodes = vec![
+            node("trigger", NodeType::Trigger { methods: vec![HttpMethod::Post], verification: None, api_key: None, ip_filter: None, rate_limit: None }),
+            node(
+                "respond",
+                NodeType::Respond {
+                    status_code,
+                    headers: HashMap::from([(header.to_string(), "value".to_string())]),
+                    body_template: String::new(),
+                },
+            ),
+        ];
+        let edges = vec![Edge {
+            id: "trigger-respond".to_string(),
+            workflow_id: "wf".to_string(),
+            from_node_id: "trigger".to_string(),
+            to_node_id: "respond".to_string(),
+            condition_result: None,
+            source_handle_id: None,
+        }];
+        WorkflowValidator::validate_workflow("wf", "trigger", &nodes, &edges)
+    };
+
+    validate(204, "X-Request-Id").unwrap();
+    assert!(validate(99, "X-Request-Id").unwrap_err().to_string().contains("status_code must be between 100 and 599"));
+    assert!(validate(200, "Bad Header").unwrap_err().to_string().contains("invalid header name 'Bad Header'"));
+}
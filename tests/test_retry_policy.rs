@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::post, Json, Router};
+use chrono::TimeZone;
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use serde_json::json;
+use swisspipe::utils::http_client::{AppExecutor, RequestContent};
+use swisspipe::utils::retry::{self, AttemptError, FailureKind, RetryPolicy};
+use swisspipe::workflow::errors::SwissPipeError;
+use swisspipe::workflow::models::{HttpMethod, RetryCondition, RetryConfig, RetryJitter, WorkflowEvent};
+
+fn config() -> RetryConfig {
+    RetryConfig { max_attempts: 5, initial_delay_ms: 100, max_delay_ms: 300, backoff_multiplier: 2.0, ..RetryConfig::default() }
+}
+
+fn failure(kind: FailureKind, retry_after: Option<Duration>) -> AttemptError {
+    AttemptError { kind, retry_after, error: SwissPipeError::Generic("failed".to_string()) }
+}
+
+/// A status to answer with and its `Retry-After` header
+type Response = (StatusCode, Option<&'static str>);
+
+/// Serve `/flaky`, answering with `responses` in turn and then 200; returns the URL and a request counter
+async fn start_api(responses: Vec<Response>) -> (String, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let responses = Arc::new(responses);
+    let app = Router::new()
+        .route(
+            "/flaky",
+            post(|State((calls, responses)): State<(Arc<AtomicUsize>, Arc<Vec<Response>>)>| async move {
+                let call = calls.fetch_add(1, Ordering::SeqCst);
+                match responses.get(call) {
+                    Some((status, Some(retry_after))) => (*status, [("retry-after", *retry_after)]).into_response(),
+                    Some((status, None)) => status.into_response(),
+                    None => Json(json!({ "ok": true })).into_response(),
+                }
+            }),
+        )
+        .with_state((calls.clone(), responses));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/flaky", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, calls)
+}
+
+async fn call(url: &str, retry_config: &RetryConfig) -> swisspipe::workflow::errors::Result<()> {
+    AppExecutor::new()
+        .execute_http_request(url, &HttpMethod::Post, 5, retry_config, WorkflowEvent::default(), &HashMap::new(), &RequestContent::default())
+        .await
+        .map(|_| ())
+}
+
+#[test]
+fn test_failure_classes_are_retried_as_configured() {
+    let config = RetryConfig { retry_status_codes: vec!["409".to_string()], ..config() };
+    let policy = RetryPolicy::new(&config);
+    for kind in [FailureKind::Connect, FailureKind::Timeout, FailureKind::Status(503), FailureKind::Status(429), FailureKind::Status(409)] {
+        assert!(policy.is_retryable(kind), "{kind:?}");
+    }
+    for kind in [FailureKind::Status(400), FailureKind::Status(404), FailureKind::Other] {
+        assert!(!policy.is_retryable(kind), "{kind:?}");
+    }
+
+    let config = RetryConfig { retry_on: vec![RetryCondition::TooManyRequests], ..config };
+    let policy = RetryPolicy::new(&config);
+    assert!(policy.is_retryable(FailureKind::Status(429)));
+    assert!(!policy.is_retryable(FailureKind::Status(503)));
+    assert!(!policy.is_retryable(FailureKind::Timeout));
+}
+
+#[test]
+fn test_backoff_jitter_and_retry_after() {
+    let config = config();
+    let mut policy = RetryPolicy::new(&config);
+    let server_error = failure(FailureKind::Status(503), None);
+    let delays: Vec<_> = (1..=5).map(|attempt| policy.next_delay(attempt, &server_error)).collect();
+    let ms = |ms| Some(Duration::from_millis(ms));
+    assert_eq!(delays, [ms(100), ms(200), ms(300), ms(300), None]);
+
+    // Retry-After wins over a shorter backoff, even above max_delay_ms
+    let mut policy = RetryPolicy::new(&config);
+    assert_eq!(policy.next_delay(1, &failure(FailureKind::Status(429), Some(Duration::from_secs(2)))), Some(Duration::from_secs(2)));
+    assert_eq!(policy.next_delay(2, &failure(FailureKind::Status(400), None)), None);
+
+    // Without a total retry budget, very long waits are capped
+    let mut policy = RetryPolicy::new(&config);
+    assert_eq!(policy.next_delay(1, &failure(FailureKind::Status(429), Some(Duration::from_secs(864_000)))), Some(retry::MAX_RETRY_AFTER));
+
+    // Waits that would run past the total retry budget give up instead
+    let capped = RetryConfig { max_retry_duration_ms: Some(1000), ..config.clone() };
+    let mut policy = RetryPolicy::new(&capped);
+    assert_eq!(policy.next_delay(1, &failure(FailureKind::Status(429), Some(Duration::from_secs(2)))), None);
+
+    for jitter in [RetryJitter::Full, RetryJitter::Decorrelated] {
+        let jittered = RetryConfig { jitter, max_attempts: 50, ..config.clone() };
+        let mut policy = RetryPolicy::new(&jittered);
+        let low = if jitter == RetryJitter::Full { 0 } else { 100 };
+        for attempt in 1..50 {
+            let delay = policy.next_delay(attempt, &server_error).unwrap();
+            assert!((low..=300).contains(&(delay.as_millis() as u64)), "{jitter:?}: {delay:?}");
+        }
+    }
+}
+
+#[test]
+fn test_retry_after_header() {
+    let now = chrono::Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+    let headers = |value: &str| HeaderMap::from_iter([(RETRY_AFTER, HeaderValue::from_str(value).unwrap())]);
+    assert_eq!(retry::retry_after(&headers("120"), now), Some(Duration::from_secs(120)));
+    assert_eq!(retry::retry_after(&headers("Wed, 21 Oct 2015 07:28:30 GMT"), now), Some(Duration::from_secs(30)));
+    assert_eq!(retry::retry_after(&headers("Wed, 21 Oct 2015 07:00:00 GMT"), now), Some(Duration::ZERO));
+    assert_eq!(retry::retry_after(&headers("soon"), now), None);
+    assert_eq!(retry::retry_after(&HeaderMap::new(), now), None);
+
+    // Only 429 and 503 responses are taken at their word
+    let error = || SwissPipeError::Generic("failed".to_string());
+    assert_eq!(AttemptError::<SwissPipeError>::status(503, &headers("120"), error()).retry_after, Some(Duration::from_secs(120)));
+    assert_eq!(AttemptError::<SwissPipeError>::status(500, &headers("120"), error()).retry_after, None);
+}
+
+#[tokio::test]
+async fn test_client_errors_are_not_retried() {
+    let (url, calls) = start_api(vec![(StatusCode::BAD_REQUEST, None); 3]).await;
+    let error = call(&url, &config()).await.unwrap_err().to_string();
+    assert!(error.contains("after 1 attempts") && error.contains("400"), "{error}");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_server_errors_are_retried_after_retry_after() {
+    let (url, calls) = start_api(vec![(StatusCode::SERVICE_UNAVAILABLE, Some("1")), (StatusCode::INTERNAL_SERVER_ERROR, None)]).await;
+    let started = Instant::now();
+    call(&url, &RetryConfig { initial_delay_ms: 1, max_delay_ms: 1, ..config() }).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert!(started.elapsed() >= Duration::from_secs(1), "Retry-After was ignored");
+}
+
+#[tokio::test]
+async fn test_retry_after_beyond_budget_fails_at_once() {
+    let (url, calls) = start_api(vec![(StatusCode::TOO_MANY_REQUESTS, Some("30"))]).await;
+    let started = Instant::now();
+    let result = call(&url, &RetryConfig { max_retry_duration_ms: Some(5000), ..config() }).await;
+    assert!(result.unwrap_err().to_string().contains("429"));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
@@ -0,0 +1,122 @@
+use axum::http::Extensions;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use sea_orm::{ActiveModelBehavior, ActiveModelTrait, Set};
+use swisspipe::api::segment::middleware::{
+    parse_authorization_header, resolve_workflow_from_request, resolve_write_key, SegmentAuth, SegmentAuthError,
+};
+use swisspipe::api::segment::write_keys::validate_write_key_alias;
+use swisspipe::database::{entities, establish_connection, segment_write_keys};
+
+const WORKFLOW_ID: &str = "0192c3a0-7b1e-7c4d-9e2f-1a2b3c4d5e6f";
+
+async fn setup_db_with_alias(write_key: &str) -> sea_orm::DatabaseConnection {
+    let db = establish_connection("sqlite::memory:").await.unwrap();
+
+    let now = chrono::Utc::now().timestamp_micros();
+    entities::ActiveModel {
+        id: Set(WORKFLOW_ID.to_string()),
+        name: Set("Segment workflow".to_string()),
+        description: Set(None),
+        start_node_id: Set(None),
+        enabled: Set(true),
+        allowed_host_functions: Set(None),
+        error_workflow_id: Set(None),
+        max_concurrent_executions: Set(None),
+        scheduling_weight: Set(None),
+        created_at: Set(now),
+        updated_at: Set(now),
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    segment_write_keys::ActiveModel {
+        write_key: Set(write_key.to_string()),
+        workflow_id: Set(WORKFLOW_ID.to_string()),
+        description: Set(Some("analytics.js on the marketing site".to_string())),
+        ..segment_write_keys::ActiveModel::new()
+    }
+    .insert(&db)
+    .await
+    .unwrap();
+
+    db
+}
+
+#[test]
+fn test_parse_bearer_and_basic_authorization() {
+    assert_eq!(parse_authorization_header(&format!("Bearer {WORKFLOW_ID}")).unwrap(), WORKFLOW_ID);
+
+    // Segment SDKs send base64(writeKey:) with an empty password
+    let basic = STANDARD.encode("wk_marketing:");
+    assert_eq!(parse_authorization_header(&format!("Basic {basic}")).unwrap(), "wk_marketing");
+
+    let basic_without_colon = STANDARD.encode("wk_marketing");
+    assert_eq!(parse_authorization_header(&format!("basic {basic_without_colon}")).unwrap(), "wk_marketing");
+
+    let basic_with_password = STANDARD.encode("wk_marketing:ignored");
+    assert_eq!(parse_authorization_header(&format!("Basic {basic_with_password}")).unwrap(), "wk_marketing");
+}
+
+#[test]
+fn test_parse_rejects_malformed_authorization() {
+    let empty_username = STANDARD.encode(":secret");
+    let invalid = [
+        "Bearer".to_string(),
+        "Bearer ".to_string(),
+        "Digest abc".to_string(),
+        "Basic not-base64!".to_string(),
+        format!("Basic {empty_username}"),
+    ];
+
+    for header in invalid {
+        assert!(
+            matches!(parse_authorization_header(&header), Err(SegmentAuthError::InvalidFormat { .. })),
+            "accepted: {header}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_resolve_write_key_alias_and_workflow_id() {
+    let db = setup_db_with_alias("wk_marketing").await;
+
+    assert_eq!(resolve_write_key(&db, WORKFLOW_ID, "test").await.unwrap(), WORKFLOW_ID);
+    assert_eq!(resolve_write_key(&db, "wk_marketing", "test").await.unwrap(), WORKFLOW_ID);
+    assert!(matches!(
+        resolve_write_key(&db, "wk_unknown", "test").await,
+        Err(SegmentAuthError::UnknownWriteKey { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_resolve_from_header_or_body_write_key() {
+    let db = setup_db_with_alias("wk_marketing").await;
+
+    // Header write key (stored by the middleware) wins over the body
+    let mut extensions = Extensions::new();
+    extensions.insert(SegmentAuth { write_key: "wk_marketing".to_string() });
+    let body = serde_json::json!({ "writeKey": "wk_unknown", "batch": [] });
+    assert_eq!(resolve_workflow_from_request(&db, &extensions, &body).await.unwrap(), WORKFLOW_ID);
+
+    let body = serde_json::json!({ "writeKey": "wk_marketing", "batch": [] });
+    assert_eq!(resolve_workflow_from_request(&db, &Extensions::new(), &body).await.unwrap(), WORKFLOW_ID);
+
+    let body = serde_json::json!({ "batch": [] });
+    assert!(matches!(
+        resolve_workflow_from_request(&db, &Extensions::new(), &body).await,
+        Err(SegmentAuthError::Missing)
+    ));
+}
+
+#[test]
+fn test_write_key_alias_validation() {
+    assert!(validate_write_key_alias("wk_marketing-site.prod").is_ok());
+
+    assert!(validate_write_key_alias("").is_err());
+    assert!(validate_write_key_alias("has:colon").is_err());
+    assert!(validate_write_key_alias("has space").is_err());
+    assert!(validate_write_key_alias(&"k".repeat(129)).is_err());
+    assert!(validate_write_key_alias(WORKFLOW_ID).is_err());
+    assert!(validate_write_key_alias("0192c3a07b1e7c4d9e2f1a2b3c4d5e6f").is_err());
+}
@@ -9,6 +9,8 @@ async fn test_source_tracking_in_linear_workflow() {
         metadata: std::collections::HashMap::new(),
         headers: std::collections::HashMap::new(),
         condition_results: std::collections::HashMap::new(),
+        switch_results: std::collections::HashMap::new(),
+        failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -67,6 +69,8 @@ async fn test_source_serialization() {
         metadata: std::collections::HashMap::new(),
         headers: std::collections::HashMap::new(),
         condition_results: std::collections::HashMap::new(),
+        switch_results: std::collections::HashMap::new(),
+        failed_nodes: std::collections::HashSet::new(),
         hil_task: None,
         sources: vec![
             NodeSource {
@@ -0,0 +1,207 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::{json, Value};
+use swisspipe::api;
+use swisspipe::async_execution::ExecutionService;
+use uuid::Uuid;
+
+async fn setup() -> (TestServer, swisspipe::AppState) {
+    let state = common::test_state().await;
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state)
+}
+
+/// Nodes and edges for a trigger followed by `steps` in a chain
+fn chain(steps: Vec<(&str, Value)>) -> (Value, Value) {
+    let trigger_id = Uuid::new_v4().to_string();
+    let mut nodes = vec![json!({
+        "id": trigger_id,
+        "name": "Trigger",
+        "node_type": { "Trigger": { "methods": ["Post"] } }
+    })];
+    let mut edges = Vec::new();
+    let mut previous = trigger_id;
+    for (name, node_type) in steps {
+        let id = Uuid::new_v4().to_string();
+        nodes.push(json!({ "id": id, "name": name, "node_type": node_type }));
+        edges.push(json!({ "from_node_id": previous, "to_node_id": id }));
+        previous = id;
+    }
+    (Value::Array(nodes), Value::Array(edges))
+}
+
+async fn create_workflow(server: &TestServer, name: &str, steps: Vec<(&str, Value)>) -> String {
+    let (nodes, edges) = chain(steps);
+    let response = server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({ "name": name, "nodes": nodes, "edges": edges }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+fn transformer(script: &str) -> Value {
+    json!({ "Transformer": { "script": script, "limits": null } })
+}
+
+fn sub_workflow(workflow_id: &str, wait: bool) -> Value {
+    json!({ "SubWorkflow": { "workflow_id": workflow_id, "wait": wait, "timeout_seconds": 10 } })
+}
+
+async fn enrichment_workflow(server: &TestServer) -> String {
+    let script = "function transformer(event) { event.data.tier = event.data.spend > 100 ? 'gold' : 'basic'; return event; }";
+    create_workflow(server, "Enrich user", vec![("Enrich", transformer(script))]).await
+}
+
+#[tokio::test]
+async fn test_waiting_sub_workflow_returns_child_output() {
+    let (server, state) = setup().await;
+    let child_id = enrichment_workflow(&server).await;
+    let parent_id = create_workflow(&server, "Signup", vec![("Enrich user", sub_workflow(&child_id, true))]).await;
+    state.worker_pool.start().await.unwrap();
+
+    let response = server
+        .post(&format!("/api/v1/{parent_id}/trigger"))
+        .add_query_param("wait", "true")
+        .json(&json!({ "spend": 250 }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["tier"], "gold");
+
+    // The parent lists its child, and the child points back at the parent
+    let parent_execution_id = response.header("x-execution-id").to_str().unwrap().to_string();
+    let parent: Value = server.get(&format!("/api/admin/v1/executions/{parent_execution_id}")).await.json();
+    let children = parent["child_executions"].as_array().unwrap();
+    assert_eq!(children.len(), 1);
+    assert_eq!(children[0]["workflow_id"], child_id);
+    assert_eq!(children[0]["status"], "completed");
+
+    let child: Value = server
+        .get(&format!("/api/admin/v1/executions/{}", children[0]["id"].as_str().unwrap()))
+        .await
+        .json();
+    assert_eq!(child["parent_execution_id"], parent_execution_id);
+    assert_eq!(child["output_data"]["tier"], "gold");
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_fire_and_forget_sub_workflow_is_queued() {
+    let (server, state) = setup().await;
+    let child_id = enrichment_workflow(&server).await;
+    let parent_id = create_workflow(&server, "Signup", vec![("Enrich user", sub_workflow(&child_id, false))]).await;
+    state.worker_pool.start().await.unwrap();
+
+    let response = server
+        .post(&format!("/api/v1/{parent_id}/trigger"))
+        .add_query_param("wait", "true")
+        .json(&json!({ "spend": 5 }))
+        .await;
+    response.assert_status_ok();
+    // The parent continues with its own data
+    assert!(response.json::<Value>().get("tier").is_none());
+
+    let parent_execution_id = response.header("x-execution-id").to_str().unwrap().to_string();
+    let service = ExecutionService::new(state.db.clone());
+    let children = service.get_child_executions(&parent_execution_id).await.unwrap();
+    assert_eq!(children.len(), 1);
+
+    let child = service.wait_for_execution(&children[0].id, Duration::from_secs(10)).await.unwrap().unwrap();
+    assert_eq!(child.status, "completed");
+    let output: Value = serde_json::from_str(child.output_data.as_deref().unwrap()).unwrap();
+    assert_eq!(output["tier"], "basic");
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_failing_sub_workflow_fails_parent() {
+    let (server, state) = setup().await;
+    let script = "function transformer(event) { throw new Error('CRM unavailable'); }";
+    let child_id = create_workflow(&server, "Enrich user", vec![("Enrich", transformer(script))]).await;
+    let parent_id = create_workflow(&server, "Signup", vec![("Enrich user", sub_workflow(&child_id, true))]).await;
+    state.worker_pool.start().await.unwrap();
+
+    let response = server
+        .post(&format!("/api/v1/{parent_id}/trigger"))
+        .add_query_param("wait", "true")
+        .json(&json!({}))
+        .await;
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    let error = response.json::<Value>()["error"].as_str().unwrap().to_string();
+    assert!(error.contains("Sub-workflow 'Enrich user'"), "{error}");
+
+    let parent_execution_id = response.header("x-execution-id").to_str().unwrap().to_string();
+    let children = ExecutionService::new(state.db.clone())
+        .get_child_executions(&parent_execution_id)
+        .await
+        .unwrap();
+    assert_eq!(children[0].status, "failed");
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_sub_workflow_cycles_are_rejected_on_save() {
+    let (server, _state) = setup().await;
+    let first_id = create_workflow(&server, "First", vec![]).await;
+    let second_id = create_workflow(&server, "Second", vec![("Call first", sub_workflow(&first_id, true))]).await;
+
+    // Make First call Second, closing the loop
+    let first: Value = server.get(&format!("/api/admin/v1/workflows/{first_id}")).await.json();
+    let start_node_id = first["start_node_id"].as_str().unwrap();
+    let node_id = Uuid::new_v4().to_string();
+    let response = server
+        .put(&format!("/api/admin/v1/workflows/{first_id}"))
+        .json(&json!({
+            "name": "First",
+            "nodes": [{ "id": node_id, "name": "Call second", "node_type": sub_workflow(&second_id, true) }],
+            "edges": [{ "from_node_id": start_node_id, "to_node_id": node_id }]
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+
+    // Unknown targets are rejected too
+    let (nodes, edges) = chain(vec![("Call missing", sub_workflow(&Uuid::new_v4().to_string(), true))]);
+    let response = server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({ "name": "Broken", "nodes": nodes, "edges": edges }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.json::<Value>()["details"].as_str().unwrap().contains("not found"));
+}
+
+#[test]
+fn test_validator_follows_sub_workflow_references() {
+    use std::collections::HashMap;
+    use swisspipe::workflow::{models::{Node, NodeType}, validation::WorkflowValidator};
+
+    let calls = |target: &str| Node {
+        id: "call".to_string(),
+        workflow_id: "a".to_string(),
+        name: "Call".to_string(),
+        node_type: NodeType::SubWorkflow { workflow_id: target.to_string(), wait: true, timeout_seconds: None },
+        input_merge_strategy: None,
+        allowed_host_functions: Vec::new(),
+    };
+
+    // a -> b -> c is fine
+    let references = HashMap::from([("b".to_string(), vec!["c".to_string()])]);
+    WorkflowValidator::validate_sub_workflow_references("a", &[calls("b")], &references).unwrap();
+
+    // a -> b -> c -> a is not
+    let references = HashMap::from([
+        ("b".to_string(), vec!["c".to_string()]),
+        ("c".to_string(), vec!["a".to_string()]),
+    ]);
+    let err = WorkflowValidator::validate_sub_workflow_references("a", &[calls("b")], &references).unwrap_err();
+    assert!(err.to_string().contains("a -> b -> c -> a"), "{err}");
+
+    // A workflow may not call itself
+    assert!(WorkflowValidator::validate_sub_workflow_references("a", &[calls("a")], &HashMap::new()).is_err());
+}
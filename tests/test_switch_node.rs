@@ -0,0 +1,152 @@
+mod common;
+
+use std::collections::HashMap;
+
+use swisspipe::async_execution::ExecutionService;
+use swisspipe::workflow::{
+    models::{Edge, HttpMethod, Node, NodeType, Workflow, WorkflowEvent},
+    validation::WorkflowValidator,
+};
+
+const ROUTE_BY_TYPE: &str = "function route(event) { return event.data.type; }";
+
+fn node(id: &str, node_type: NodeType) -> Node {
+    Node {
+        id: id.to_string(),
+        workflow_id: "wf".to_string(),
+        name: id.to_string(),
+        node_type,
+        input_merge_strategy: None,
+        allowed_host_functions: Vec::new(),
+    }
+}
+
+fn edge(from: &str, to: &str, handle: Option<&str>) -> Edge {
+    Edge {
+        id: format!("{from}-{to}"),
+        workflow_id: "wf".to_string(),
+        from_node_id: from.to_string(),
+        to_node_id: to.to_string(),
+        condition_result: None,
+        source_handle_id: handle.map(str::to_string),
+    }
+}
+
+/// Tag the event with the branch that handled it
+fn branch(id: &str) -> Node {
+    let script = format!("function transformer(event) {{ event.data.branch = '{id}'; return event; }}");
+    node(id, NodeType::Transformer { script, limits: None })
+}
+
+fn switch_workflow(workflow_id: &str) -> Workflow {
+    let nodes = vec![
+        node("trigger", NodeType::Trigger { methods: vec![HttpMethod::Post], verification: None, api_key: None, ip_filter: None, rate_limit: None }),
+        node(
+            "switch",
+            NodeType::Switch {
+                script: ROUTE_BY_TYPE.to_string(),
+                cases: vec!["track".to_string(), "identify".to_string(), "page".to_string()],
+                limits: None,
+            },
+        ),
+        branch("track"),
+        branch("identify"),
+        branch("fallback"),
+    ];
+    let edges = vec![
+        edge("trigger", "switch", None),
+        edge("switch", "track", Some("track")),
+        // Two cases may share a branch
+        edge("switch", "identify", Some("identify")),
+        edge("switch", "identify", Some("page")),
+        edge("switch", "fallback", Some("default")),
+    ];
+
+    Workflow {
+        id: workflow_id.to_string(),
+        name: "Segment router".to_string(),
+        description: None,
+        start_node_id: Some("trigger".to_string()),
+        enabled: true,
+        allowed_host_functions: Vec::new(),
+        nodes,
+        edges,
+    }
+}
+
+async fn run(event_type: serde_json::Value) -> WorkflowEvent {
+    let state = common::test_state().await;
+    let workflow_id = common::create_trigger_workflow(&state.db).await;
+    let execution_id = ExecutionService::new(state.db.clone())
+        .create_execution(workflow_id.clone(), serde_json::json!({}), HashMap::new(), None)
+        .await
+        .unwrap();
+
+    let event = WorkflowEvent {
+        data: serde_json::json!({ "type": event_type }),
+        ..WorkflowEvent::default()
+    };
+    state
+        .engine
+        .execute_workflow(&switch_workflow(&workflow_id), event, &execution_id)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_switch_routes_to_matching_case() {
+    let output = run(serde_json::json!("track")).await;
+    assert_eq!(output.data["branch"], "track");
+    assert_eq!(output.switch_results["switch"], "track");
+
+    let output = run(serde_json::json!("page")).await;
+    assert_eq!(output.data["branch"], "identify");
+    assert_eq!(output.switch_results["switch"], "page");
+}
+
+#[tokio::test]
+async fn test_switch_falls_back_to_default_branch() {
+    for event_type in [serde_json::json!("alias"), serde_json::Value::Null] {
+        let output = run(event_type).await;
+        assert_eq!(output.data["branch"], "fallback");
+        assert_eq!(output.switch_results["switch"], "default");
+    }
+}
+
+#[test]
+fn test_validator_accepts_switch_edges() {
+    let workflow = switch_workflow("wf");
+    WorkflowValidator::validate_workflow("wf", "trigger", &workflow.nodes, &workflow.edges).unwrap();
+    assert!(WorkflowValidator::validate_condition_completeness(&workflow.nodes, &workflow.edges).is_empty());
+}
+
+#[test]
+fn test_validator_rejects_invalid_switch_edges() {
+    let mut workflow = switch_workflow("wf");
+    workflow.edges.push(edge("switch", "fallback", Some("screen")));
+    let err = WorkflowValidator::validate_workflow("wf", "trigger", &workflow.nodes, &workflow.edges).unwrap_err();
+    assert!(err.to_string().contains("unknown case 'screen'"), "{err}");
+
+    let mut workflow = switch_workflow("wf");
+    workflow.edges[1].condition_result = Some(true);
+    assert!(WorkflowValidator::validate_workflow("wf", "trigger", &workflow.nodes, &workflow.edges).is_err());
+
+    let mut workflow = switch_workflow("wf");
+    workflow.nodes[1].node_type = NodeType::Switch {
+        script: ROUTE_BY_TYPE.to_string(),
+        cases: vec!["track".to_string(), "track".to_string()],
+        limits: None,
+    };
+    assert!(WorkflowValidator::validate_workflow("wf", "trigger", &workflow.nodes, &workflow.edges).is_err());
+}
+
+#[test]
+fn test_validator_warns_about_missing_default_edge() {
+    let mut workflow = switch_workflow("wf");
+    workflow.edges.retain(|e| e.source_handle_id.as_deref() != Some("default"));
+    workflow.nodes.retain(|n| n.id != "fallback");
+
+    let warnings = WorkflowValidator::validate_condition_completeness(&workflow.nodes, &workflow.edges);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("no default edge"));
+}
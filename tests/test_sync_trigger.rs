@@ -0,0 +1,142 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use sea_orm::{ActiveModelTrait, EntityTrait, Set};
+use swisspipe::api::{self, ingestion::parse_wait_timeout};
+use swisspipe::database::workflow_executions::{self, ExecutionStatus};
+
+async fn setup() -> (TestServer, swisspipe::AppState, String) {
+    let state = common::test_state().await;
+    let workflow_id = common::create_trigger_workflow(&state.db).await;
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    (server, state, workflow_id)
+}
+
+#[test]
+fn test_parse_wait_timeout() {
+    assert_eq!(parse_wait_timeout("10s").unwrap(), Duration::from_secs(10));
+    assert_eq!(parse_wait_timeout("500ms").unwrap(), Duration::from_millis(500));
+    assert_eq!(parse_wait_timeout("2m").unwrap(), Duration::from_secs(120));
+    assert_eq!(parse_wait_timeout("15").unwrap(), Duration::from_secs(15));
+
+    assert!(parse_wait_timeout("0s").is_err());
+    assert!(parse_wait_timeout("10h").is_err());
+    assert!(parse_wait_timeout("soon").is_err());
+    assert!(parse_wait_timeout("1000m").is_err());
+}
+
+#[tokio::test]
+async fn test_wait_returns_workflow_output() {
+    let (server, state, workflow_id) = setup().await;
+    state.worker_pool.start().await.unwrap();
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .add_query_param("timeout", "10s")
+        .json(&serde_json::json!({ "order_id": 42 }))
+        .await;
+
+    response.assert_status_ok();
+    let output: serde_json::Value = response.json();
+    assert_eq!(output["order_id"], 42);
+
+    let execution_id = response.header("x-execution-id");
+    let execution = workflow_executions::Entity::find_by_id(execution_id.to_str().unwrap())
+        .one(&*state.db)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(execution.status, ExecutionStatus::Completed.to_string());
+
+    state.worker_pool.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_wait_falls_back_to_accepted_on_timeout() {
+    // No workers are running, so the execution stays pending
+    let (server, _state, workflow_id) = setup().await;
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .add_query_param("timeout", "100ms")
+        .json(&serde_json::json!({ "order_id": 42 }))
+        .await;
+
+    response.assert_status(StatusCode::ACCEPTED);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["status"], "accepted");
+    assert!(body["execution_id"].is_string());
+}
+
+#[tokio::test]
+async fn test_wait_reports_failed_execution() {
+    let (server, state, workflow_id) = setup().await;
+
+    // Stand in for a worker that fails whatever execution gets queued
+    let db = state.db.clone();
+    let worker = tokio::spawn(async move {
+        loop {
+            if let Some(execution) = workflow_executions::Entity::find().one(&*db).await.unwrap() {
+                let mut execution: workflow_executions::ActiveModel = execution.into();
+                execution.status = Set(ExecutionStatus::Failed.to_string());
+                execution.error_message = Set(Some("Transformer threw".to_string()));
+                execution.update(&*db).await.unwrap();
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .json(&serde_json::json!({}))
+        .await;
+    worker.await.unwrap();
+
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["status"], "failed");
+    assert_eq!(body["error"], "Transformer threw");
+}
+
+#[tokio::test]
+async fn test_invalid_timeout_is_rejected_before_queueing() {
+    let (server, state, workflow_id) = setup().await;
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_query_param("wait", "true")
+        .add_query_param("timeout", "forever")
+        .json(&serde_json::json!({}))
+        .await;
+
+    response.assert_status(StatusCode::BAD_REQUEST);
+    let executions = workflow_executions::Entity::find().all(&*state.db).await.unwrap();
+    assert!(executions.is_empty());
+}
+
+#[tokio::test]
+async fn test_get_trigger_strips_wait_params_without_wait() {
+    let (server, state, workflow_id) = setup().await;
+
+    for params in [vec![("wait", "false"), ("timeout", "5s")], vec![("timeout", "5s")]] {
+        let response = server
+            .get(&format!("/api/v1/{workflow_id}/trigger"))
+            .add_query_params(params.into_iter().chain([("order_id", "42")]).collect::<Vec<_>>())
+            .await;
+        response.assert_status(StatusCode::ACCEPTED);
+    }
+
+    let executions = workflow_executions::Entity::find().all(&*state.db).await.unwrap();
+    assert_eq!(executions.len(), 2);
+    for execution in executions {
+        let input: serde_json::Value = serde_json::from_str(&execution.input_data.unwrap()).unwrap();
+        assert_eq!(input["data"], serde_json::json!({ "order_id": "42" }));
+    }
+}
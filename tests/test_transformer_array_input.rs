@@ -1,6 +1,6 @@
 use swisspipe::utils::javascript::JavaScriptExecutor;
 use swisspipe::workflow::models::WorkflowEvent;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[tokio::test]
 async fn test_transformer_with_array_input() {
@@ -15,6 +15,8 @@ async fn test_transformer_with_array_input() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -64,6 +66,8 @@ async fn test_transformer_with_single_input() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -121,6 +125,8 @@ async fn test_transformer_return_data_only() {
         },
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -166,6 +172,8 @@ async fn test_transformer_array_access_patterns() {
         metadata: HashMap::new(),
         headers: HashMap::new(),
         condition_results: HashMap::new(),
+        switch_results: HashMap::new(),
+        failed_nodes: HashSet::new(),
         hil_task: None,
         sources: Vec::new(),
     };
@@ -0,0 +1,218 @@
+mod common;
+
+use std::net::SocketAddr;
+
+use axum::http::{header::AUTHORIZATION, HeaderName, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use sea_orm::EntityTrait;
+use serde_json::{json, Value};
+use swisspipe::api;
+use swisspipe::database::workflow_executions;
+use swisspipe::variables::CreateVariableRequest;
+use swisspipe::workflow::models::TriggerIpFilter;
+use uuid::Uuid;
+
+const API_KEY: &str = "sk_live_123";
+
+/// A server that sees real peer addresses, as `main` serves the router
+async fn setup() -> (TestServer, swisspipe::AppState) {
+    let state = common::test_state().await;
+    state
+        .variable_service
+        .create_variable(CreateVariableRequest {
+            name: "PARTNER_API_KEY".to_string(),
+            value_type: "secret".to_string(),
+            value: API_KEY.to_string(),
+            description: None,
+        })
+        .await
+        .unwrap();
+
+    let app = api::create_router().with_state(state.clone());
+    let server = TestServer::new(app.into_make_service_with_connect_info::<SocketAddr>()).unwrap();
+    (server, state)
+}
+
+async fn create_workflow(server: &TestServer, trigger: Value) -> String {
+    let response = server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({
+            "name": "Partner webhook",
+            "nodes": [{ "id": Uuid::new_v4().to_string(), "name": "Trigger", "node_type": { "Trigger": trigger } }],
+            "edges": []
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    response.json::<Value>()["id"].as_str().unwrap().to_string()
+}
+
+async fn executions(state: &swisspipe::AppState) -> Vec<Value> {
+    workflow_executions::Entity::find()
+        .all(&*state.db)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|execution| serde_json::from_str(execution.input_data.as_deref().unwrap()).unwrap())
+        .collect()
+}
+
+fn api_key_header(value: &str) -> (HeaderName, HeaderValue) {
+    (HeaderName::from_static("x-api-key"), HeaderValue::from_str(value).unwrap())
+}
+
+#[tokio::test]
+async fn test_api_key_header() {
+    let (server, state) = setup().await;
+    let workflow_id = create_workflow(&server, json!({
+        "methods": ["Post"],
+        "api_key": { "secret_variable": "PARTNER_API_KEY" }
+    }))
+    .await;
+
+    let response = server.post(&format!("/api/v1/{workflow_id}/trigger")).json(&json!({})).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+
+    let (name, value) = api_key_header("sk_live_456");
+    let response = server.post(&format!("/api/v1/{workflow_id}/trigger")).add_header(name, value).json(&json!({})).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert!(executions(&state).await.is_empty());
+
+    let (name, value) = api_key_header(API_KEY);
+    let response = server.post(&format!("/api/v1/{workflow_id}/trigger")).add_header(name, value).json(&json!({ "n": 1 })).await;
+    response.assert_status(StatusCode::ACCEPTED);
+
+    // The key is not recorded with the execution
+    let input = &executions(&state).await[0];
+    assert_eq!(input["data"], json!({ "n": 1 }));
+    assert!(input["headers"].get("x-api-key").is_none(), "{input}");
+}
+
+#[tokio::test]
+async fn test_api_key_query_param() {
+    let (server, state) = setup().await;
+    let workflow_id = create_workflow(&server, json!({
+        "methods": ["Get"],
+        "api_key": { "secret_variable": "PARTNER_API_KEY", "query_param": "api_key" }
+    }))
+    .await;
+
+    let response = server.get(&format!("/api/v1/{workflow_id}/trigger?order_id=42&api_key=wrong")).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+
+    let response = server.get(&format!("/api/v1/{workflow_id}/trigger?order_id=42&api_key={API_KEY}")).await;
+    response.assert_status(StatusCode::ACCEPTED);
+    assert_eq!(executions(&state).await[0]["data"], json!({ "order_id": "42" }));
+}
+
+#[tokio::test]
+async fn test_ip_filter() {
+    let (server, state) = setup().await;
+    let private_only = create_workflow(&server, json!({ "methods": ["Post"], "ip_filter": { "allow": ["10.0.0.0/8"] } })).await;
+    let loopback = create_workflow(&server, json!({ "methods": ["Post"], "ip_filter": { "allow": ["127.0.0.0/8", "::1"] } })).await;
+    let denied = create_workflow(&server, json!({ "methods": ["Post"], "ip_filter": { "deny": ["127.0.0.1"] } })).await;
+
+    let response = server.post(&format!("/api/v1/{private_only}/trigger")).json(&json!({})).await;
+    response.assert_status(StatusCode::FORBIDDEN);
+    let response = server.post(&format!("/api/v1/{denied}/ndjson")).bytes(b"{}\n".to_vec().into()).await;
+    response.assert_status(StatusCode::FORBIDDEN);
+    assert!(executions(&state).await.is_empty());
+
+    let response = server.post(&format!("/api/v1/{loopback}/trigger")).json(&json!({})).await;
+    response.assert_status(StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn test_segment_endpoints_enforce_access() {
+    let (server, state) = setup().await;
+    let keyed = create_workflow(&server, json!({
+        "methods": ["Post"],
+        "api_key": { "secret_variable": "PARTNER_API_KEY" }
+    }))
+    .await;
+    let filtered = create_workflow(&server, json!({ "methods": ["Post"], "ip_filter": { "allow": ["10.0.0.0/8"] } })).await;
+    let bearer = |workflow_id: &str| HeaderValue::from_str(&format!("Bearer {workflow_id}")).unwrap();
+    let event = json!({ "type": "track", "event": "Signed Up", "userId": "u1" });
+
+    for (workflow_id, error_type) in [(&keyed, "unauthorized"), (&filtered, "forbidden")] {
+        let track: Value = server.post("/api/v1/track").add_header(AUTHORIZATION, bearer(workflow_id)).json(&event).await.json();
+        assert_eq!(track["details"]["error_type"], error_type, "{track}");
+
+        let batch: Value = server
+            .post("/api/v1/batch")
+            .add_header(AUTHORIZATION, bearer(workflow_id))
+            .json(&json!({ "batch": [event.clone()] }))
+            .await
+            .json();
+        assert_eq!(batch["details"]["error_type"], error_type, "{batch}");
+    }
+    assert!(executions(&state).await.is_empty());
+
+    let (name, value) = api_key_header(API_KEY);
+    let track: Value = server.post("/api/v1/track").add_header(AUTHORIZATION, bearer(&keyed)).add_header(name, value).json(&event).await.json();
+    assert_eq!(track["success"], true, "{track}");
+    assert!(executions(&state).await[0]["headers"].get("x-api-key").is_none());
+}
+
+#[tokio::test]
+async fn test_rejections_are_counted_per_workflow() {
+    let (server, _state) = setup().await;
+    let keyed = create_workflow(&server, json!({
+        "methods": ["Post"],
+        "api_key": { "secret_variable": "PARTNER_API_KEY" }
+    }))
+    .await;
+    let filtered = create_workflow(&server, json!({ "methods": ["Post"], "ip_filter": { "allow": ["10.0.0.0/8"] } })).await;
+
+    for _ in 0..2 {
+        server.post(&format!("/api/v1/{keyed}/trigger")).json(&json!({})).await.assert_status(StatusCode::UNAUTHORIZED);
+    }
+    server.post(&format!("/api/v1/{filtered}/trigger")).json(&json!({})).await.assert_status(StatusCode::FORBIDDEN);
+
+    let stats: Value = server.get("/api/admin/v1/executions/stats").await.json();
+    let rejections = &stats["trigger_rejections"];
+    assert_eq!(rejections["total"], json!({ "ip_denied": 1, "invalid_api_key": 2, "invalid_signature": 0 }));
+    assert_eq!(rejections["workflows"][&keyed]["invalid_api_key"], 2);
+    assert_eq!(rejections["workflows"][&filtered]["ip_denied"], 1);
+}
+
+#[tokio::test]
+async fn test_invalid_access_settings_are_rejected_on_save() {
+    let (server, _state) = setup().await;
+
+    for (trigger, error) in [
+        (json!({ "methods": ["Post"], "ip_filter": { "allow": ["10.0.0.0/33"] } }), "'10.0.0.0/33' is not an IP address or CIDR network"),
+        (json!({ "methods": ["Post"], "api_key": { "secret_variable": "" } }), "API key requires a secret_variable"),
+        (json!({ "methods": ["Post"], "api_key": { "secret_variable": "K", "header": "Bad Header" } }), "invalid API key header"),
+    ] {
+        let response = server
+            .post("/api/admin/v1/workflows")
+            .json(&json!({
+                "name": "Partner webhook",
+                "nodes": [{ "id": Uuid::new_v4().to_string(), "name": "Trigger", "node_type": { "Trigger": trigger } }],
+                "edges": []
+            }))
+            .await;
+        response.assert_status(StatusCode::BAD_REQUEST);
+        let details = response.json::<Value>()["details"].as_str().unwrap().to_string();
+        assert!(details.contains(error), "{details}");
+    }
+}
+
+#[test]
+fn test_ip_filter_matching() {
+    let filter = TriggerIpFilter {
+        allow: vec!["192.168.0.0/16".to_string(), "2001:db8::/32".to_string()],
+        deny: vec!["192.168.1.0/24".to_string()],
+    };
+    filter.validate().unwrap();
+
+    assert!(filter.allows("192.168.2.7".parse().unwrap()));
+    assert!(filter.allows("2001:db8::1".parse().unwrap()));
+    assert!(!filter.allows("192.168.1.7".parse().unwrap()));
+    assert!(!filter.allows("10.0.0.1".parse().unwrap()));
+
+    // Without an allow list everything that is not denied may call
+    let deny_only = TriggerIpFilter { allow: Vec::new(), deny: vec!["10.0.0.1".to_string()] };
+    assert!(deny_only.allows("10.0.0.2".parse().unwrap()));
+    assert!(!deny_only.allows("10.0.0.1".parse().unwrap()));
+}
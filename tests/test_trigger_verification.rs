@@ -0,0 +1,244 @@
+mod common;
+
+use std::collections::HashMap;
+
+use axum::http::{header, HeaderName, HeaderValue, StatusCode};
+use axum_test::TestServer;
+use sea_orm::{EntityTrait, PaginatorTrait};
+use serde_json::{json, Value};
+use swisspipe::api;
+use swisspipe::database::workflow_executions;
+use swisspipe::utils::javascript_helpers::hmac_sha256_hex;
+use swisspipe::utils::webhook_signature::verify;
+use swisspipe::variables::CreateVariableRequest;
+use swisspipe::workflow::models::TriggerVerification;
+use uuid::Uuid;
+
+const SECRET: &str = "whsec_test";
+
+async fn setup(verification: Value) -> (TestServer, swisspipe::AppState, String) {
+    let state = common::test_state().await;
+    state
+        .variable_service
+        .create_variable(CreateVariableRequest {
+            name: "WEBHOOK_SECRET".to_string(),
+            value_type: "secret".to_string(),
+            value: SECRET.to_string(),
+            description: None,
+        })
+        .await
+        .unwrap();
+
+    let server = TestServer::new(api::create_router().with_state(state.clone())).unwrap();
+    let response = server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({
+            "name": "Webhook",
+            "nodes": [{
+                "id": Uuid::new_v4().to_string(),
+                "name": "Trigger",
+                "node_type": { "Trigger": { "methods": ["Get", "Post"], "verification": verification } }
+            }],
+            "edges": []
+        }))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let workflow_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    (server, state, workflow_id)
+}
+
+async fn execution_count(state: &swisspipe::AppState) -> u64 {
+    workflow_executions::Entity::find().count(&*state.db).await.unwrap()
+}
+
+fn header_value(value: &str) -> HeaderValue {
+    HeaderValue::from_str(value).unwrap()
+}
+
+fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+#[tokio::test]
+async fn test_github_signed_trigger() {
+    let (server, state, workflow_id) = setup(json!({ "GitHub": { "secret_variable": "WEBHOOK_SECRET" } })).await;
+    let body = br#"{"action": "opened"}"#.to_vec();
+
+    let unsigned = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_header(header::CONTENT_TYPE, header_value("application/json"))
+        .bytes(body.clone().into())
+        .await;
+    unsigned.assert_status(StatusCode::UNAUTHORIZED);
+
+    let forged = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_header(header::CONTENT_TYPE, header_value("application/json"))
+        .add_header(HeaderName::from_static("x-hub-signature-256"), header_value(&format!("sha256={}", hmac_sha256_hex("wrong", r#"{"action": "opened"}"#))))
+        .bytes(body.clone().into())
+        .await;
+    forged.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(execution_count(&state).await, 0);
+
+    let signature = format!("sha256={}", hmac_sha256_hex(SECRET, r#"{"action": "opened"}"#));
+    let signed = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_header(header::CONTENT_TYPE, header_value("application/json"))
+        .add_header(HeaderName::from_static("x-hub-signature-256"), header_value(&signature))
+        .bytes(body.into())
+        .await;
+    signed.assert_status(StatusCode::ACCEPTED);
+    assert_eq!(execution_count(&state).await, 1);
+}
+
+#[tokio::test]
+async fn test_hmac_signed_get_and_ndjson_triggers() {
+    let (server, state, workflow_id) = setup(json!({
+        "Hmac": { "secret_variable": "WEBHOOK_SECRET", "header": "X-Signature" }
+    }))
+    .await;
+
+    // GET triggers sign the raw query string
+    let response = server
+        .get(&format!("/api/v1/{workflow_id}/trigger?order_id=42"))
+        .add_header(HeaderName::from_static("x-signature"), header_value(&hmac_sha256_hex(SECRET, "order_id=42")))
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+
+    let response = server
+        .get(&format!("/api/v1/{workflow_id}/trigger?order_id=43"))
+        .add_header(HeaderName::from_static("x-signature"), header_value(&hmac_sha256_hex(SECRET, "order_id=42")))
+        .await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+
+    let body = "{\"n\": 1}\n{\"n\": 2}\n";
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/ndjson"))
+        .bytes(body.as_bytes().to_vec().into())
+        .await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/ndjson"))
+        .add_header(HeaderName::from_static("x-signature"), header_value(&hmac_sha256_hex(SECRET, body)))
+        .bytes(body.as_bytes().to_vec().into())
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+    assert_eq!(response.json::<Value>()["accepted"], 2);
+
+    assert_eq!(execution_count(&state).await, 3);
+}
+
+#[tokio::test]
+async fn test_segment_calls_to_signed_trigger_are_rejected() {
+    let (server, state, workflow_id) = setup(json!({ "GitHub": { "secret_variable": "WEBHOOK_SECRET" } })).await;
+    let authorization = header_value(&format!("Bearer {workflow_id}"));
+
+    let track = server
+        .post("/api/v1/track")
+        .add_header(header::AUTHORIZATION, authorization.clone())
+        .json(&json!({ "type": "track", "event": "Signed Up", "userId": "u1" }))
+        .await;
+    let track: Value = track.json();
+    assert_eq!(track["success"], false);
+    assert_eq!(track["details"]["error_type"], "unauthorized");
+
+    let batch = server
+        .post("/api/v1/batch")
+        .add_header(header::AUTHORIZATION, authorization)
+        .json(&json!({ "batch": [{ "type": "track", "event": "Signed Up", "userId": "u1" }] }))
+        .await;
+    let batch: Value = batch.json();
+    assert_eq!(batch["success"], false);
+    assert_eq!(batch["details"]["error_type"], "unauthorized");
+
+    assert_eq!(execution_count(&state).await, 0);
+}
+
+#[tokio::test]
+async fn test_missing_secret_variable_rejects_requests() {
+    let (server, state, workflow_id) = setup(json!({ "GitHub": { "secret_variable": "MISSING_SECRET" } })).await;
+
+    let response = server
+        .post(&format!("/api/v1/{workflow_id}/trigger"))
+        .add_header(HeaderName::from_static("x-hub-signature-256"), header_value(&format!("sha256={}", hmac_sha256_hex(SECRET, "{}"))))
+        .json(&json!({}))
+        .await;
+    response.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(execution_count(&state).await, 0);
+}
+
+#[tokio::test]
+async fn test_invalid_verification_is_rejected_on_save() {
+    let state = common::test_state().await;
+    let server = TestServer::new(api::create_router().with_state(state)).unwrap();
+
+    let response = server
+        .post("/api/admin/v1/workflows")
+        .json(&json!({
+            "name": "Webhook",
+            "nodes": [{
+                "id": Uuid::new_v4().to_string(),
+                "name": "Trigger",
+                "node_type": { "Trigger": {
+                    "methods": ["Post"],
+                    "verification": { "Hmac": { "secret_variable": "WEBHOOK_SECRET", "header": "Bad Header" } }
+                } }
+            }],
+            "edges": []
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert!(response.json::<Value>()["details"].as_str().unwrap().contains("invalid signature header"));
+}
+
+#[test]
+fn test_stripe_signatures() {
+    let stripe = TriggerVerification::Stripe { secret_variable: "S".to_string(), tolerance_seconds: None };
+    let body = br#"{"id": "evt_1"}"#;
+    let now = 1_700_000_000;
+    let signature = hmac_sha256_hex(SECRET, &format!("{now}.{{\"id\": \"evt_1\"}}"));
+
+    // Any v1 signature may match while secrets are rolled
+    let header = format!("t={now},v1={},v1={signature},v0=ignored", hmac_sha256_hex("old", "x"));
+    verify(&stripe, SECRET, &headers(&[("stripe-signature", &header)]), body, now + 10).unwrap();
+
+    let stale = verify(&stripe, SECRET, &headers(&[("stripe-signature", &header)]), body, now + 301).unwrap_err();
+    assert!(stale.contains("300s"), "{stale}");
+
+    let tampered = verify(&stripe, SECRET, &headers(&[("stripe-signature", &header)]), b"{}", now).unwrap_err();
+    assert!(tampered.contains("no matching v1 signature"), "{tampered}");
+
+    let missing = verify(&stripe, SECRET, &HashMap::new(), body, now).unwrap_err();
+    assert!(missing.contains("Missing stripe-signature header"), "{missing}");
+}
+
+#[test]
+fn test_slack_signatures() {
+    let slack = TriggerVerification::Slack { secret_variable: "S".to_string(), tolerance_seconds: Some(60) };
+    let body = b"token=abc&challenge=xyz";
+    let timestamp = "1700000000";
+    let signature = format!("v0={}", hmac_sha256_hex(SECRET, "v0:1700000000:token=abc&challenge=xyz"));
+    let request = headers(&[("x-slack-request-timestamp", timestamp), ("x-slack-signature", &signature)]);
+
+    verify(&slack, SECRET, &request, body, 1_700_000_030).unwrap();
+    assert!(verify(&slack, SECRET, &request, body, 1_700_000_061).is_err());
+    assert!(verify(&slack, "other", &request, body, 1_700_000_000).is_err());
+}
+
+#[test]
+fn test_hmac_signatures() {
+    let hmac = TriggerVerification::Hmac {
+        secret_variable: "S".to_string(),
+        header: "X-Signature".to_string(),
+        prefix: Some("sha256=".to_string()),
+    };
+    let signature = format!("sha256={}", hmac_sha256_hex(SECRET, "payload"));
+
+    verify(&hmac, SECRET, &headers(&[("x-signature", &signature)]), b"payload", 0).unwrap();
+    let unprefixed = hmac_sha256_hex(SECRET, "payload");
+    let err = verify(&hmac, SECRET, &headers(&[("x-signature", &unprefixed)]), b"payload", 0).unwrap_err();
+    assert!(err.contains("does not start with 'sha256='"), "{err}");
+    let err = verify(&hmac, SECRET, &headers(&[("x-signature", "sha256=zz")]), b"payload", 0).unwrap_err();
+    assert!(err.contains("not valid hex"), "{err}");
+}